@@ -0,0 +1,61 @@
+//! Execution speed of a long running loop with and without the block cache,
+//! run with `cargo bench -p armv6-m --bench block_cache`
+
+use std::time::{Duration, Instant};
+
+use armv6_m::abi::Runtime;
+use armv6_m::{thumb, Armv6M};
+
+const STEPS: usize = 2_000_000;
+
+fn measure(name: &str, enabled: bool) -> Duration {
+    // NOTE: A checksum over a RAM buffer, the kind of loop firmware spends its time in
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    ldr r0, =0x20000000",
+        "    movs r1, #0",
+        "outer:",
+        "    movs r2, #64",
+        "    mov r3, r0",
+        "inner:",
+        "    ldr r4, [r3]",
+        "    adds r1, r1, r4",
+        "    str r1, [r3]",
+        "    adds r3, #4",
+        "    subs r2, #1",
+        "    bne inner",
+        "    b outer",
+    );
+
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine.set_block_cache(enabled);
+
+    let start = Instant::now();
+    for _ in 0..STEPS {
+        machine.step().unwrap();
+    }
+    let elapsed = start.elapsed();
+
+    let stats = machine.block_cache_stats();
+    println!(
+        "{name:<10} {:>8.1} ns/step, {:>6.1} M steps/s ({} hits, {} misses)",
+        elapsed.as_nanos() as f64 / STEPS as f64,
+        STEPS as f64 / elapsed.as_secs_f64() / 1e6,
+        stats.hits,
+        stats.misses,
+    );
+    elapsed
+}
+
+fn main() {
+    let uncached = measure("decode", false);
+    let cached = measure("cache", true);
+
+    println!(
+        "cache is {:.1}x faster",
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+}
@@ -0,0 +1,59 @@
+//! Throughput of the table driven decoder against the reference nom parsers,
+//! run with `cargo bench -p armv6-m --bench decoder`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use armv6_m::decoder::Decoder;
+use armv6_m::instructions::parse_instruction;
+
+const ROUNDS: usize = 20;
+
+fn measure(
+    name: &str,
+    halfwords: &[[u16; 2]],
+    mut decode: impl FnMut(u16, u16) -> bool,
+) -> Duration {
+    let start = Instant::now();
+    let mut decoded = 0;
+    for _ in 0..ROUNDS {
+        for [first, second] in halfwords {
+            decoded += usize::from(decode(black_box(*first), black_box(*second)));
+        }
+    }
+    let elapsed = start.elapsed();
+
+    let count = ROUNDS * halfwords.len();
+    println!(
+        "{name:<10} {:>8.1} ns/instruction, {:>6.1} M instructions/s ({decoded} decoded)",
+        elapsed.as_nanos() as f64 / count as f64,
+        count as f64 / elapsed.as_secs_f64() / 1e6,
+    );
+    elapsed
+}
+
+fn main() {
+    let start = Instant::now();
+    let decoder = Decoder::new();
+    println!("table built in {:?}", start.elapsed());
+
+    // NOTE: Every halfword, followed by the second halfword of a BL
+    let halfwords = (0..=u16::MAX)
+        .map(|first| [first, 0xF800])
+        .collect::<Vec<_>>();
+
+    let reference = measure("nom", &halfwords, |first, second| {
+        let mut bytes = [0; 4];
+        bytes[..2].copy_from_slice(&first.to_be_bytes());
+        bytes[2..].copy_from_slice(&second.to_be_bytes());
+        parse_instruction(&bytes).is_ok()
+    });
+    let table = measure("table", &halfwords, |first, second| {
+        decoder.decode(first, second).is_some()
+    });
+
+    println!(
+        "table is {:.1}x faster",
+        reference.as_secs_f64() / table.as_secs_f64()
+    );
+}
@@ -1,16 +1,46 @@
-use std::rc::Rc;
+use crate::watchpoint::WatchKind;
 
 pub trait MemoryMutation<R: Runtime> {
     fn apply(&self, on: &mut R);
     fn rollback(&self, on: &mut R);
 }
 
+/// A decoded instruction that a runtime can execute
+pub trait Execute<R: Runtime> {
+    fn execute(&self, on: &mut R) -> Result<(), R::Error>;
+}
+
+/// Why the runtime gave control back to the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// A `BKPT` instruction or a breakpoint of the core was reached at this address,
+    /// it is not executed
+    Breakpoint(u32),
+    /// The core sleeps (`WFI`/`WFE`) and nothing can wake it up
+    Sleeping,
+    /// A peripheral put the system in System OFF, it stays off until a peripheral wakes it up
+    /// with a reset
+    SystemOff,
+    /// A data access matched a watchpoint, the instruction doing it completed
+    Watchpoint {
+        kind: WatchKind,
+        /// Address of the instruction doing the access
+        pc: u32,
+        address: u32,
+        /// Value before the access, `None` for peripheral registers that cannot be peeked
+        old: Option<u32>,
+        /// Value read or written
+        new: u32,
+    },
+}
+
 pub trait Runtime
 where
     Self: Sized,
 {
     type Error: std::error::Error;
     type Mutation: MemoryMutation<Self>;
+    type Memory;
     // type Register;
 
     fn init() -> Self;
@@ -19,17 +49,24 @@ where
     fn load_hex(&mut self, hex: &str) -> Result<&mut Self, Self::Error>;
     fn load_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self, Self::Error>;
 
+    /// Save the whole machine state to a file, to resume from it with `load_snapshot`
+    fn save_snapshot(&self, path: &str) -> Result<(), Self::Error>;
+    fn load_snapshot(&mut self, path: &str) -> Result<&mut Self, Self::Error>;
+
     // NOTE: Useless ?
     // fn get_register(&self, register: usize) -> Self::Register;
     // fn set_register(&mut self, register: usize, data: Self::Register) -> Result<&mut Self, Self::Error>;
 
-    fn get_memory(&self) -> Rc<[u8]>;
-    fn get_mutations_history(&self) -> Rc<[Self::Mutation]>;
+    fn get_memory(&self) -> &Self::Memory;
+    fn get_mutations_history(&self) -> &[Self::Mutation];
 
-    fn get_memory_at_mutation(&self, idx: usize) -> Vec<u8>;
+    /// Memory as it was after the first `idx` mutations of the history
+    fn get_memory_at_mutation(&self, idx: usize) -> Result<Self::Memory, Self::Error>;
 
-    fn step(&mut self) -> Self;
-    fn run(&mut self) -> Self;
+    /// Execute a single instruction
+    fn step(&mut self) -> Result<Option<StopReason>, Self::Error>;
+    /// Step until something stops the execution
+    fn run(&mut self) -> Result<StopReason, Self::Error>;
 }
 
 pub trait RuntimeExtras
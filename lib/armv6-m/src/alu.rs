@@ -0,0 +1,69 @@
+//! Helpers from the Armv6-M pseudocode library.
+//! <https://developer.arm.com/documentation/ddi0419/c/Appendix-D--Pseudocode-Definition/Pseudocode-details-of-operations-on-integers-and-logical-operations>
+
+/// `AddWithCarry()`, returns the result with the carry and overflow flags
+pub fn add_with_carry(x: u32, y: u32, carry_in: bool) -> (u32, bool, bool) {
+    let unsigned_sum = u64::from(x) + u64::from(y) + u64::from(carry_in);
+    let signed_sum = i64::from(x as i32) + i64::from(y as i32) + i64::from(carry_in);
+    let result = unsigned_sum as u32;
+
+    (
+        result,
+        u64::from(result) != unsigned_sum,
+        i64::from(result as i32) != signed_sum,
+    )
+}
+
+/// `LSL_C()` extended to any shift amount, as used by the register forms
+pub fn lsl_c(x: u32, shift: u32, carry_in: bool) -> (u32, bool) {
+    match shift {
+        0 => (x, carry_in),
+        1..=31 => (x << shift, x >> (32 - shift) & 1 == 1),
+        32 => (0, x & 1 == 1),
+        _ => (0, false),
+    }
+}
+
+/// `LSR_C()` extended to any shift amount, as used by the register forms
+pub fn lsr_c(x: u32, shift: u32, carry_in: bool) -> (u32, bool) {
+    match shift {
+        0 => (x, carry_in),
+        1..=31 => (x >> shift, x >> (shift - 1) & 1 == 1),
+        32 => (0, x >> 31 == 1),
+        _ => (0, false),
+    }
+}
+
+/// `ASR_C()` extended to any shift amount, as used by the register forms
+pub fn asr_c(x: u32, shift: u32, carry_in: bool) -> (u32, bool) {
+    match shift {
+        0 => (x, carry_in),
+        1..=31 => ((x as i32 >> shift) as u32, x >> (shift - 1) & 1 == 1),
+        _ => ((x as i32 >> 31) as u32, x >> 31 == 1),
+    }
+}
+
+/// `ROR_C()` extended to any shift amount, as used by the register forms
+pub fn ror_c(x: u32, shift: u32, carry_in: bool) -> (u32, bool) {
+    if shift == 0 {
+        return (x, carry_in);
+    }
+
+    let result = x.rotate_right(shift % 32);
+    (result, result >> 31 == 1)
+}
+
+/// `DecodeImmShift()` for LSR and ASR, where an immediate of 0 encodes a shift by 32
+pub fn decode_imm_shift(imm5: u8) -> u32 {
+    if imm5 == 0 {
+        32
+    } else {
+        u32::from(imm5)
+    }
+}
+
+/// `SignExtend()` of the `bits` low bits of `value`
+pub fn sign_extend(value: u32, bits: u32) -> u32 {
+    let shift = 32 - bits;
+    ((value << shift) as i32 >> shift) as u32
+}
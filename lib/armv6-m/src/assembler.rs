@@ -0,0 +1,1043 @@
+//! Two pass assembler for the ARMv6-M Thumb instructions, in the Unified Assembler Language.
+//!
+//! Labels end with `:` and comments start with `@` or `//`. The `.word`, `.short`, `.hword`,
+//! `.byte`, `.space`, `.align`, `.balign`, `.equ`, `.set`, `.ltorg` and `.pool` directives are
+//! supported, section and symbol directives like `.thumb` or `.global` are ignored.
+//! `ldr rt, =value` loads a constant from the next literal pool, placed by `.ltorg` or at the end.
+
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+use crate::symbols::Symbols;
+
+const SP: u8 = 13;
+const LR: u8 = 14;
+const PC: u8 = 15;
+
+/// Machine code of an assembled source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    /// Address of the first byte
+    pub origin: u32,
+    pub bytes: Vec<u8>,
+    pub labels: BTreeMap<String, u32>,
+}
+
+impl Program {
+    /// Code as little endian halfwords, an odd trailing byte is zero extended
+    pub fn halfwords(&self) -> Vec<u16> {
+        self.bytes
+            .chunks(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair.get(1).copied().unwrap_or_default()]))
+            .collect()
+    }
+
+    pub fn label(&self, name: &str) -> Option<u32> {
+        self.labels.get(name).copied()
+    }
+
+    /// Labels as symbols, to disassemble the program
+    pub fn symbols(&self) -> Symbols {
+        let mut symbols = Symbols::new();
+        for (name, address) in &self.labels {
+            symbols.insert(*address, name.as_str());
+        }
+        symbols
+    }
+}
+
+/// Assemble `source` to be loaded at `origin`
+pub fn assemble(source: &str, origin: u32) -> Result<Program, Error> {
+    let mut assembler = Assembler {
+        origin,
+        address: origin,
+        statements: Vec::new(),
+        labels: BTreeMap::new(),
+        constants: BTreeMap::new(),
+        literals: Vec::new(),
+    };
+
+    let mut lines = 0;
+    for (index, text) in source.lines().enumerate() {
+        lines = index + 1;
+        assembler
+            .statement(lines, text)
+            .map_err(|reason| Error::Assembly {
+                line: lines,
+                reason,
+            })?;
+    }
+    assembler.flush_literals(lines);
+
+    assembler.emit()
+}
+
+/// Where a statement is placed and what it produces
+struct Statement<'a> {
+    /// Source line, from 1
+    line: usize,
+    address: u32,
+    kind: Kind<'a>,
+    /// Address of the constant loaded by `ldr rt, =value`
+    literal: Option<u32>,
+}
+
+enum Kind<'a> {
+    Instruction {
+        mnemonic: String,
+        operands: Vec<&'a str>,
+    },
+    Data {
+        size: u8,
+        values: Vec<&'a str>,
+    },
+    /// Zero bytes
+    Fill(u32),
+    /// Alignment of the code, with `nop` instructions
+    Padding(u32),
+    /// Words of a literal pool
+    Pool(Vec<&'a str>),
+}
+
+struct Assembler<'a> {
+    origin: u32,
+    address: u32,
+    statements: Vec<Statement<'a>>,
+    labels: BTreeMap<String, u32>,
+    constants: BTreeMap<String, i64>,
+    /// Statements loading a constant from the next literal pool, with the value
+    literals: Vec<(usize, &'a str)>,
+}
+
+impl<'a> Assembler<'a> {
+    /// First pass, place the statement of a line and define its labels
+    fn statement(&mut self, line: usize, text: &'a str) -> Result<(), String> {
+        let mut text = strip_comment(text).trim();
+        while let Some((label, rest)) = split_label(text) {
+            if self.labels.contains_key(label) || self.constants.contains_key(label) {
+                return Err(format!("label `{label}` is already defined"));
+            }
+            self.labels.insert(label.to_owned(), self.address);
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let mnemonic = mnemonic.to_ascii_lowercase();
+        let operands = split_operands(operands);
+
+        let (kind, size) = match mnemonic.as_str() {
+            ".word" | ".long" | ".4byte" => data(4, operands),
+            ".short" | ".hword" | ".2byte" => data(2, operands),
+            ".byte" => data(1, operands),
+            ".space" | ".skip" | ".zero" => {
+                let [size] = count(&operands)?;
+                let size = u32::try_from(self.constant(size)?)
+                    .ok()
+                    .ok_or_else(|| "negative size".to_owned())?;
+                (Kind::Fill(size), size)
+            }
+            ".align" | ".p2align" | ".balign" => {
+                let [alignment] = count(&operands)?;
+                let alignment = self.constant(alignment)?;
+                let alignment = match mnemonic.as_str() {
+                    ".balign" => alignment,
+                    _ if (0..32).contains(&alignment) => 1 << alignment,
+                    _ => return Err(format!("invalid alignment {alignment}")),
+                };
+                let alignment = u32::try_from(alignment)
+                    .ok()
+                    .filter(|alignment| alignment.is_power_of_two())
+                    .ok_or_else(|| format!("invalid alignment {alignment}"))?;
+                let padding = self.address.next_multiple_of(alignment) - self.address;
+                (Kind::Padding(padding), padding)
+            }
+            ".equ" | ".set" => {
+                let [name, value] = count(&operands)?;
+                let value = self.constant(value)?;
+                self.constants.insert(name.to_owned(), value);
+                return Ok(());
+            }
+            ".ltorg" | ".pool" => {
+                self.flush_literals(line);
+                return Ok(());
+            }
+            ".syntax" | ".thumb" | ".thumb_func" | ".code" | ".text" | ".section" | ".global"
+            | ".globl" | ".type" | ".size" | ".cpu" | ".arch" | ".fpu" => return Ok(()),
+            directive if directive.starts_with('.') => {
+                return Err(format!("unknown directive `{directive}`"));
+            }
+            _ => {
+                if mnemonic == "ldr" {
+                    if let Some(value) = operands.get(1).and_then(|value| value.strip_prefix('=')) {
+                        self.literals.push((self.statements.len(), value.trim()));
+                    }
+                }
+                let size = instruction_size(&mnemonic);
+                let kind = Kind::Instruction { mnemonic, operands };
+                (kind, size)
+            }
+        };
+
+        self.push(line, kind, size);
+        Ok(())
+    }
+
+    fn push(&mut self, line: usize, kind: Kind<'a>, size: u32) {
+        self.statements.push(Statement {
+            line,
+            address: self.address,
+            kind,
+            literal: None,
+        });
+        self.address = self.address.wrapping_add(size);
+    }
+
+    /// Place the pending literals in a word aligned pool, equal values share a word
+    fn flush_literals(&mut self, line: usize) {
+        if self.literals.is_empty() {
+            return;
+        }
+
+        let padding = self.address.next_multiple_of(4) - self.address;
+        if padding > 0 {
+            self.push(line, Kind::Fill(padding), padding);
+        }
+
+        let mut pool: Vec<&str> = Vec::new();
+        for (statement, value) in std::mem::take(&mut self.literals) {
+            let index = pool
+                .iter()
+                .position(|other| *other == value)
+                .unwrap_or_else(|| {
+                    pool.push(value);
+                    pool.len() - 1
+                });
+            self.statements[statement].literal = Some(self.address + 4 * index as u32);
+        }
+        let size = 4 * pool.len() as u32;
+        self.push(line, Kind::Pool(pool), size);
+    }
+
+    /// Value needed by the first pass, it can only use what is already defined
+    fn constant(&self, expression: &str) -> Result<i64, String> {
+        self.scope(self.address).evaluate(expression)
+    }
+
+    fn scope(&self, address: u32) -> Scope<'_> {
+        Scope {
+            address,
+            labels: &self.labels,
+            constants: &self.constants,
+        }
+    }
+
+    /// Second pass, encode the statements now that every label is known
+    fn emit(&self) -> Result<Program, Error> {
+        let mut bytes = Vec::with_capacity((self.address - self.origin) as usize);
+        for statement in &self.statements {
+            let encoder = Encoder {
+                scope: self.scope(statement.address),
+                literal: statement.literal,
+            };
+            let error = |reason| Error::Assembly {
+                line: statement.line,
+                reason,
+            };
+
+            match &statement.kind {
+                Kind::Instruction { mnemonic, operands } => {
+                    let halfwords = encoder.encode(mnemonic, operands).map_err(error)?;
+                    for halfword in halfwords {
+                        bytes.extend_from_slice(&halfword.to_le_bytes());
+                    }
+                }
+                Kind::Data { size, values } => {
+                    for value in values {
+                        let value = encoder
+                            .scope
+                            .evaluate(value)
+                            .and_then(|value| fit_data(value, *size))
+                            .map_err(error)?;
+                        bytes.extend_from_slice(&value.to_le_bytes()[..usize::from(*size)]);
+                    }
+                }
+                Kind::Fill(size) => bytes.resize(bytes.len() + *size as usize, 0),
+                Kind::Padding(size) => {
+                    if size % 2 == 1 {
+                        bytes.push(0);
+                    }
+                    for _ in 0..size / 2 {
+                        bytes.extend_from_slice(&NOP.to_le_bytes());
+                    }
+                }
+                Kind::Pool(values) => {
+                    for value in values {
+                        let value = encoder
+                            .scope
+                            .evaluate(value)
+                            .and_then(|value| fit_data(value, 4))
+                            .map_err(error)?;
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+            }
+        }
+
+        Ok(Program {
+            origin: self.origin,
+            bytes,
+            labels: self.labels.clone(),
+        })
+    }
+}
+
+fn data(size: u8, values: Vec<&str>) -> (Kind<'_>, u32) {
+    let length = u32::from(size) * values.len() as u32;
+    (Kind::Data { size, values }, length)
+}
+
+/// Data values can be given signed or unsigned
+fn fit_data(value: i64, size: u8) -> Result<u32, String> {
+    let bits = 8 * u32::from(size);
+    if value >= -(1 << (bits - 1)) && value < 1 << bits {
+        Ok(value as u32)
+    } else {
+        Err(format!("value {value} does not fit in {size} bytes"))
+    }
+}
+
+fn instruction_size(mnemonic: &str) -> u32 {
+    match mnemonic {
+        "bl" | "mrs" | "msr" | "dmb" | "dsb" | "isb" | "udf.w" => 4,
+        _ => 2,
+    }
+}
+
+fn strip_comment(text: &str) -> &str {
+    let end = [text.find('@'), text.find("//")]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || matches!(first, '_' | '.' | '$'))
+        && chars.all(|char| char.is_ascii_alphanumeric() || matches!(char, '_' | '.' | '$'))
+}
+
+/// `label: rest`
+fn split_label(text: &str) -> Option<(&str, &str)> {
+    let (label, rest) = text.split_once(':')?;
+    is_identifier(label).then_some((label, rest))
+}
+
+/// Commas inside of `[]` and `{}` do not separate operands
+fn split_operands(text: &str) -> Vec<&str> {
+    let mut operands = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, char) in text.char_indices() {
+        match char {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                operands.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = text[start..].trim();
+    if !last.is_empty() || !operands.is_empty() {
+        operands.push(last);
+    }
+    operands
+}
+
+fn count<'a, const N: usize>(operands: &[&'a str]) -> Result<[&'a str; N], String> {
+    operands
+        .try_into()
+        .ok()
+        .ok_or_else(|| format!("expected {N} operands, found {}", operands.len()))
+}
+
+/// Symbols visible from a statement
+struct Scope<'a> {
+    /// Value of `.`
+    address: u32,
+    labels: &'a BTreeMap<String, u32>,
+    constants: &'a BTreeMap<String, i64>,
+}
+
+impl Scope<'_> {
+    /// Sum and differences of numbers, symbols and `.`
+    fn evaluate(&self, expression: &str) -> Result<i64, String> {
+        let mut value = 0i64;
+        let mut sign = 1;
+        let mut rest = expression.trim();
+        loop {
+            if let Some(after) = rest.strip_prefix('-') {
+                sign = -sign;
+                rest = after.trim_start();
+                continue;
+            }
+            if let Some(after) = rest.strip_prefix('+') {
+                rest = after.trim_start();
+                continue;
+            }
+
+            let end = rest.find(['+', '-']).unwrap_or(rest.len());
+            value = value.wrapping_add(sign * self.term(rest[..end].trim())?);
+            rest = rest[end..].trim_start();
+            if rest.is_empty() {
+                return Ok(value);
+            }
+            sign = 1;
+        }
+    }
+
+    fn term(&self, term: &str) -> Result<i64, String> {
+        if term == "." {
+            return Ok(i64::from(self.address));
+        }
+        if term.starts_with(|char: char| char.is_ascii_digit()) {
+            return parse_number(term).ok_or_else(|| format!("invalid number `{term}`"));
+        }
+        if term.is_empty() {
+            return Err("missing value".to_owned());
+        }
+
+        self.constants
+            .get(term)
+            .copied()
+            .or_else(|| self.labels.get(term).map(|address| i64::from(*address)))
+            .ok_or_else(|| format!("undefined symbol `{term}`"))
+    }
+}
+
+fn parse_number(text: &str) -> Option<i64> {
+    let lower = text.to_ascii_lowercase();
+    let value = if let Some(digits) = lower.strip_prefix("0x") {
+        u64::from_str_radix(digits, 16)
+    } else if let Some(digits) = lower.strip_prefix("0b") {
+        u64::from_str_radix(digits, 2)
+    } else {
+        lower.parse()
+    };
+    value.ok().and_then(|value| i64::try_from(value).ok())
+}
+
+fn register(text: &str) -> Result<u8, String> {
+    let name = text.trim().to_ascii_lowercase();
+    let register = match name.as_str() {
+        "sp" => SP,
+        "lr" => LR,
+        "pc" => PC,
+        "ip" => 12,
+        "fp" => 11,
+        "sl" => 10,
+        "sb" => 9,
+        _ => name
+            .strip_prefix('r')
+            .and_then(|number| number.parse().ok())
+            .filter(|number| *number < 16)
+            .ok_or_else(|| format!("invalid register `{text}`"))?,
+    };
+    Ok(register)
+}
+
+fn is_register(text: &str) -> bool {
+    register(text).is_ok()
+}
+
+/// r0 to r7
+fn low(register: u8) -> Result<u16, String> {
+    if register < 8 {
+        Ok(u16::from(register))
+    } else {
+        Err(format!("r{register} is not a low register"))
+    }
+}
+
+fn low_register(text: &str) -> Result<u16, String> {
+    low(register(text)?)
+}
+
+/// `{r0, r2-r4, lr}`, bit n is set for register n
+fn register_list(text: &str) -> Result<u16, String> {
+    let inner = text
+        .strip_prefix('{')
+        .and_then(|text| text.strip_suffix('}'))
+        .ok_or_else(|| format!("invalid register list `{text}`"))?;
+
+    let mut list = 0u16;
+    for item in inner.split(',') {
+        let (first, last) = match item.split_once('-') {
+            Some((first, last)) => (register(first)?, register(last)?),
+            None => (register(item)?, register(item)?),
+        };
+        if first > last {
+            return Err(format!("invalid register range `{}`", item.trim()));
+        }
+        for register in first..=last {
+            list |= 1 << register;
+        }
+    }
+    Ok(list)
+}
+
+/// `[rn]`, `[rn, #imm]` or `[rn, rm]`
+fn memory(text: &str) -> Result<(u8, Option<&str>), String> {
+    let inner = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .ok_or_else(|| format!("invalid memory operand `{text}`"))?;
+    match inner.split_once(',') {
+        Some((base, offset)) => Ok((register(base)?, Some(offset.trim()))),
+        None => Ok((register(inner)?, None)),
+    }
+}
+
+/// An unsigned field of `bits` bits
+fn unsigned(value: i64, bits: u32) -> Result<u16, String> {
+    if (0..1 << bits).contains(&value) {
+        Ok(value as u16)
+    } else {
+        Err(format!("immediate {value} does not fit in {bits} bits"))
+    }
+}
+
+/// An unsigned field of `bits` bits holding `value / scale`
+fn scaled(value: i64, scale: i64, bits: u32) -> Result<u16, String> {
+    if value % scale != 0 {
+        return Err(format!("immediate {value} is not a multiple of {scale}"));
+    }
+    let maximum = ((1 << bits) - 1) * scale;
+    if !(0..=maximum).contains(&value) {
+        return Err(format!("immediate {value} is out of range 0..={maximum}"));
+    }
+    unsigned(value / scale, bits)
+}
+
+/// Branch offset halved, as a signed field of `bits` bits
+fn branch_offset(offset: i64, bits: u32) -> Result<u32, String> {
+    let limit = 1i64 << bits;
+    if offset % 2 != 0 {
+        return Err(format!("branch offset {offset} is odd"));
+    }
+    if !(-limit..limit).contains(&offset) {
+        return Err(format!(
+            "branch offset {offset} is out of range {}..{limit}",
+            -limit
+        ));
+    }
+    Ok((offset >> 1) as u32 & ((1 << bits) - 1))
+}
+
+fn condition(name: &str) -> Option<u16> {
+    let condition = match name {
+        "eq" => 0,
+        "ne" => 1,
+        "cs" | "hs" => 2,
+        "cc" | "lo" => 3,
+        "mi" => 4,
+        "pl" => 5,
+        "vs" => 6,
+        "vc" => 7,
+        "hi" => 8,
+        "ls" => 9,
+        "ge" => 10,
+        "lt" => 11,
+        "gt" => 12,
+        "le" => 13,
+        _ => return None,
+    };
+    Some(condition)
+}
+
+/// Opcode of the data processing instructions of the form `op rdn, rm`
+fn data_processing(mnemonic: &str) -> Option<u16> {
+    let opcode = match mnemonic {
+        "ands" | "and" => 0b0000,
+        "eors" | "eor" => 0b0001,
+        "adcs" | "adc" => 0b0101,
+        "sbcs" | "sbc" => 0b0110,
+        "rors" | "ror" => 0b0111,
+        "tst" => 0b1000,
+        "cmn" => 0b1011,
+        "orrs" | "orr" => 0b1100,
+        "bics" | "bic" => 0b1110,
+        "mvns" | "mvn" => 0b1111,
+        _ => return None,
+    };
+    Some(opcode)
+}
+
+fn special_register(text: &str) -> Result<u16, String> {
+    let name = text.trim().to_ascii_lowercase();
+    let sysm = match name.strip_suffix("_nzcvq").unwrap_or(&name) {
+        "apsr" => 0,
+        "iapsr" => 1,
+        "eapsr" => 2,
+        "xpsr" | "psr" => 3,
+        "ipsr" => 5,
+        "epsr" => 6,
+        "iepsr" => 7,
+        "msp" => 8,
+        "psp" => 9,
+        "primask" => 16,
+        "control" => 20,
+        _ => return Err(format!("invalid special register `{text}`")),
+    };
+    Ok(sysm)
+}
+
+const NOP: u16 = 0xBF00;
+
+/// Encode one instruction of the second pass
+struct Encoder<'a> {
+    scope: Scope<'a>,
+    literal: Option<u32>,
+}
+
+impl Encoder<'_> {
+    /// `#value`, the `#` is optional
+    fn immediate(&self, text: &str) -> Result<i64, String> {
+        self.scope.evaluate(text.strip_prefix('#').unwrap_or(text))
+    }
+
+    /// Offset of `target` from the PC of this instruction
+    fn pc_offset(&self, target: &str) -> Result<i64, String> {
+        Ok(self.scope.evaluate(target)? - i64::from(self.scope.address) - 4)
+    }
+
+    /// Offset of `target` from `Align(PC, 4)`, used by literal loads and ADR
+    fn aligned_pc_offset(&self, target: i64) -> i64 {
+        target - i64::from((self.scope.address + 4) & !0b11)
+    }
+
+    fn encode(&self, mnemonic: &str, operands: &[&str]) -> Result<Vec<u16>, String> {
+        let mnemonic = mnemonic.strip_suffix(".n").unwrap_or(mnemonic);
+        let halfword = match mnemonic {
+            "adds" | "add" => self.add(mnemonic == "adds", operands)?,
+            "subs" | "sub" => self.sub(operands)?,
+            "movs" | "mov" => self.mov(mnemonic == "movs", operands)?,
+            "cmp" => self.cmp(operands)?,
+            "lsls" | "lsl" => self.shift(0b00, 0b0010, operands)?,
+            "lsrs" | "lsr" => self.shift(0b01, 0b0011, operands)?,
+            "asrs" | "asr" => self.shift(0b10, 0b0100, operands)?,
+            "muls" | "mul" => Self::mul(operands)?,
+            "negs" | "neg" => {
+                let [rd, rn] = count(operands)?;
+                0x4240 | low_register(rn)? << 3 | low_register(rd)?
+            }
+            "rsbs" | "rsb" => {
+                let [rd, rn, zero] = count(operands)?;
+                if self.immediate(zero)? != 0 {
+                    return Err("rsbs only subtracts from #0".to_owned());
+                }
+                0x4240 | low_register(rn)? << 3 | low_register(rd)?
+            }
+            "adr" => {
+                let [rd, target] = count(operands)?;
+                let offset = self.aligned_pc_offset(self.scope.evaluate(target)?);
+                0xA000 | low_register(rd)? << 8 | scaled(offset, 4, 8)?
+            }
+            "ldr" | "ldrb" | "ldrh" | "ldrsb" | "ldrsh" | "str" | "strb" | "strh" => {
+                self.load_store(mnemonic, operands)?
+            }
+            "ldm" | "ldmia" | "ldmfd" => Self::multiple(true, operands)?,
+            "stm" | "stmia" | "stmea" => Self::multiple(false, operands)?,
+            "push" | "pop" => {
+                let [list] = count(operands)?;
+                let list = register_list(list)?;
+                let (extra, opcode) = match mnemonic {
+                    "push" => (LR, 0xB400),
+                    _ => (PC, 0xBC00),
+                };
+                if list & !(0xFF | 1 << extra) != 0 {
+                    return Err(format!("{mnemonic} only takes low registers and r{extra}"));
+                }
+                opcode | (list >> extra & 1) << 8 | list & 0xFF
+            }
+            "b" | "bal" => {
+                let [target] = count(operands)?;
+                0xE000 | branch_offset(self.pc_offset(target)?, 11)? as u16
+            }
+            "bl" => {
+                let [target] = count(operands)?;
+                let offset = branch_offset(self.pc_offset(target)?, 24)?;
+                let s = offset >> 23 & 1;
+                let j1 = (!(offset >> 22) ^ s) & 1;
+                let j2 = (!(offset >> 21) ^ s) & 1;
+                let first = 0xF000 | s << 10 | offset >> 11 & 0x3FF;
+                let second = 0xD000 | j1 << 13 | j2 << 11 | offset & 0x7FF;
+                return Ok(vec![first as u16, second as u16]);
+            }
+            "bx" | "blx" => {
+                let [rm] = count(operands)?;
+                let opcode = if mnemonic == "bx" { 0x4700 } else { 0x4780 };
+                opcode | u16::from(register(rm)?) << 3
+            }
+            "svc" | "swi" | "bkpt" | "udf" => {
+                let [value] = count(operands)?;
+                let opcode = match mnemonic {
+                    "bkpt" => 0xBE00,
+                    "udf" => 0xDE00,
+                    _ => 0xDF00,
+                };
+                opcode | unsigned(self.immediate(value)?, 8)?
+            }
+            "udf.w" => {
+                let [value] = count(operands)?;
+                let value = unsigned(self.immediate(value)?, 16)?;
+                return Ok(vec![0xF7F0 | value >> 12, 0xA000 | value & 0xFFF]);
+            }
+            "cpsid" | "cpsie" => {
+                let [flags] = count(operands)?;
+                if !flags.eq_ignore_ascii_case("i") {
+                    return Err(format!("{mnemonic} only masks `i`"));
+                }
+                if mnemonic == "cpsid" {
+                    0xB672
+                } else {
+                    0xB662
+                }
+            }
+            "dmb" | "dsb" | "isb" => {
+                let option = match operands {
+                    [] => 0xF,
+                    [option] if option.eq_ignore_ascii_case("sy") => 0xF,
+                    [option] => unsigned(self.immediate(option)?, 4)?,
+                    _ => return Err(format!("expected 1 operand, found {}", operands.len())),
+                };
+                let opcode = match mnemonic {
+                    "dsb" => 0x8F40,
+                    "dmb" => 0x8F50,
+                    _ => 0x8F60,
+                };
+                return Ok(vec![0xF3BF, opcode | option]);
+            }
+            "mrs" => {
+                let [rd, special] = count(operands)?;
+                let rd = u16::from(register(rd)?);
+                return Ok(vec![0xF3EF, 0x8000 | rd << 8 | special_register(special)?]);
+            }
+            "msr" => {
+                let [special, rn] = count(operands)?;
+                let rn = u16::from(register(rn)?);
+                return Ok(vec![0xF380 | rn, 0x8800 | special_register(special)?]);
+            }
+            "nop" | "yield" | "wfe" | "wfi" | "sev" => {
+                count::<0>(operands)?;
+                match mnemonic {
+                    "yield" => 0xBF10,
+                    "wfe" => 0xBF20,
+                    "wfi" => 0xBF30,
+                    "sev" => 0xBF40,
+                    _ => NOP,
+                }
+            }
+            "sxth" | "sxtb" | "uxth" | "uxtb" | "rev" | "rev16" | "revsh" => {
+                let [rd, rm] = count(operands)?;
+                let opcode = match mnemonic {
+                    "sxth" => 0xB200,
+                    "sxtb" => 0xB240,
+                    "uxth" => 0xB280,
+                    "uxtb" => 0xB2C0,
+                    "rev" => 0xBA00,
+                    "rev16" => 0xBA40,
+                    _ => 0xBAC0,
+                };
+                opcode | low_register(rm)? << 3 | low_register(rd)?
+            }
+            _ => {
+                if let Some(opcode) = data_processing(mnemonic) {
+                    let (rdn, rm) = match operands {
+                        [rdn, rm] => (*rdn, *rm),
+                        [rd, rn, rm] if register(rd)? == register(rn)? => (*rd, *rm),
+                        _ => return Err(format!("expected 2 operands, found {}", operands.len())),
+                    };
+                    0x4000 | opcode << 6 | low_register(rm)? << 3 | low_register(rdn)?
+                } else if let Some(cond) = mnemonic.strip_prefix('b').and_then(condition) {
+                    let [target] = count(operands)?;
+                    0xD000 | cond << 8 | branch_offset(self.pc_offset(target)?, 8)? as u16
+                } else {
+                    return Err(format!("unknown instruction `{mnemonic}`"));
+                }
+            }
+        };
+        Ok(vec![halfword])
+    }
+
+    /// ADD (immediate), ADD (register), ADD (SP plus immediate) and ADD (SP plus register).
+    /// The non flag setting form only exists for high registers, low registers use ADDS.
+    fn add(&self, flags: bool, operands: &[&str]) -> Result<u16, String> {
+        match operands {
+            [rdn, value] if !is_register(value) => {
+                let (rdn, value) = (register(rdn)?, self.immediate(value)?);
+                if rdn == SP {
+                    Ok(0xB000 | scaled(value, 4, 7)?)
+                } else {
+                    Ok(0x3000 | low(rdn)? << 8 | unsigned(value, 8)?)
+                }
+            }
+            [rd, rn, value] if !is_register(value) => {
+                let (rd, rn, value) = (register(rd)?, register(rn)?, self.immediate(value)?);
+                match (rd, rn) {
+                    (SP, SP) => Ok(0xB000 | scaled(value, 4, 7)?),
+                    (_, SP) => Ok(0xA800 | low(rd)? << 8 | scaled(value, 4, 8)?),
+                    (_, PC) => Ok(0xA000 | low(rd)? << 8 | scaled(value, 4, 8)?),
+                    _ if (0..8).contains(&value) => {
+                        Ok(0x1C00 | unsigned(value, 3)? << 6 | low(rn)? << 3 | low(rd)?)
+                    }
+                    _ if rd == rn => Ok(0x3000 | low(rd)? << 8 | unsigned(value, 8)?),
+                    _ => Err(format!("immediate {value} does not fit in 3 bits")),
+                }
+            }
+            [rdn, rm] => {
+                let (rdn, rm) = (register(rdn)?, register(rm)?);
+                if flags {
+                    Ok(0x1800 | low(rm)? << 6 | low(rdn)? << 3 | low(rdn)?)
+                } else {
+                    Ok(add_high(rdn, rm))
+                }
+            }
+            [rd, rn, rm] => {
+                let (rd, rn, rm) = (register(rd)?, register(rn)?, register(rm)?);
+                if rd < 8 && rn < 8 && rm < 8 && (flags || rd != rn) {
+                    Ok(0x1800 | u16::from(rm) << 6 | u16::from(rn) << 3 | u16::from(rd))
+                } else if flags {
+                    Err("adds only takes low registers".to_owned())
+                } else if rd == rn {
+                    Ok(add_high(rd, rm))
+                } else if rd == rm {
+                    Ok(add_high(rd, rn))
+                } else {
+                    Err("add of high registers needs the destination as an operand".to_owned())
+                }
+            }
+            _ => Err(format!(
+                "expected 2 or 3 operands, found {}",
+                operands.len()
+            )),
+        }
+    }
+
+    /// SUB (immediate), SUB (register) and SUB (SP minus immediate)
+    fn sub(&self, operands: &[&str]) -> Result<u16, String> {
+        match operands {
+            [rdn, value] if !is_register(value) => {
+                let (rdn, value) = (register(rdn)?, self.immediate(value)?);
+                if rdn == SP {
+                    Ok(0xB080 | scaled(value, 4, 7)?)
+                } else {
+                    Ok(0x3800 | low(rdn)? << 8 | unsigned(value, 8)?)
+                }
+            }
+            [rd, rn, value] if !is_register(value) => {
+                let (rd, rn, value) = (register(rd)?, register(rn)?, self.immediate(value)?);
+                match (rd, rn) {
+                    (SP, SP) => Ok(0xB080 | scaled(value, 4, 7)?),
+                    _ if (0..8).contains(&value) => {
+                        Ok(0x1E00 | unsigned(value, 3)? << 6 | low(rn)? << 3 | low(rd)?)
+                    }
+                    _ if rd == rn => Ok(0x3800 | low(rd)? << 8 | unsigned(value, 8)?),
+                    _ => Err(format!("immediate {value} does not fit in 3 bits")),
+                }
+            }
+            [rdn, rm] => {
+                let (rdn, rm) = (low_register(rdn)?, low_register(rm)?);
+                Ok(0x1A00 | rm << 6 | rdn << 3 | rdn)
+            }
+            [rd, rn, rm] => {
+                let (rd, rn, rm) = (low_register(rd)?, low_register(rn)?, low_register(rm)?);
+                Ok(0x1A00 | rm << 6 | rn << 3 | rd)
+            }
+            _ => Err(format!(
+                "expected 2 or 3 operands, found {}",
+                operands.len()
+            )),
+        }
+    }
+
+    /// MOV (immediate) and MOV (register), MOVS between registers is a LSLS by 0
+    fn mov(&self, flags: bool, operands: &[&str]) -> Result<u16, String> {
+        let [rd, source] = count(operands)?;
+        let rd = register(rd)?;
+        if !is_register(source) {
+            return Ok(0x2000 | low(rd)? << 8 | unsigned(self.immediate(source)?, 8)?);
+        }
+
+        let rm = register(source)?;
+        if flags {
+            Ok(low(rm)? << 3 | low(rd)?)
+        } else {
+            Ok(0x4600 | u16::from(rd & 0b1000) << 4 | u16::from(rm) << 3 | u16::from(rd & 0b111))
+        }
+    }
+
+    fn cmp(&self, operands: &[&str]) -> Result<u16, String> {
+        let [rn, source] = count(operands)?;
+        let rn = register(rn)?;
+        if !is_register(source) {
+            return Ok(0x2800 | low(rn)? << 8 | unsigned(self.immediate(source)?, 8)?);
+        }
+
+        let rm = register(source)?;
+        if rn < 8 && rm < 8 {
+            Ok(0x4280 | u16::from(rm) << 3 | u16::from(rn))
+        } else {
+            Ok(0x4500 | u16::from(rn & 0b1000) << 4 | u16::from(rm) << 3 | u16::from(rn & 0b111))
+        }
+    }
+
+    /// LSL, LSR and ASR, by an immediate or by a register
+    fn shift(&self, kind: u16, opcode: u16, operands: &[&str]) -> Result<u16, String> {
+        let (rd, rm, amount) = match operands {
+            [rdn, amount] => (*rdn, *rdn, *amount),
+            [rd, rm, amount] => (*rd, *rm, *amount),
+            _ => {
+                return Err(format!(
+                    "expected 2 or 3 operands, found {}",
+                    operands.len()
+                ))
+            }
+        };
+        let (rd, rm) = (low_register(rd)?, low_register(rm)?);
+
+        if is_register(amount) {
+            if rd != rm {
+                return Err(
+                    "shift by a register needs the destination as the first operand".to_owned(),
+                );
+            }
+            return Ok(0x4000 | opcode << 6 | low_register(amount)? << 3 | rd);
+        }
+
+        // NOTE: LSR and ASR encode a shift by 32 as 0, LSL by 0 is a MOVS
+        let amount = self.immediate(amount)?;
+        let amount = match (kind, amount) {
+            (0b00, 0..=31) | (_, 1..=31) => amount,
+            (_, 32) if kind != 0b00 => 0,
+            _ => return Err(format!("invalid shift amount {amount}")),
+        };
+        Ok(kind << 11 | (amount as u16) << 6 | rm << 3 | rd)
+    }
+
+    /// `muls rdm, rn` or `muls rd, rn, rm` with `rd` one of the operands
+    fn mul(operands: &[&str]) -> Result<u16, String> {
+        let (rdm, rn) = match operands {
+            [rdm, rn] => (low_register(rdm)?, low_register(rn)?),
+            [rd, rn, rm] => {
+                let (rd, rn, rm) = (low_register(rd)?, low_register(rn)?, low_register(rm)?);
+                if rd == rm {
+                    (rd, rn)
+                } else if rd == rn {
+                    (rd, rm)
+                } else {
+                    return Err("muls needs the destination as an operand".to_owned());
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "expected 2 or 3 operands, found {}",
+                    operands.len()
+                ))
+            }
+        };
+        Ok(0x4340 | rn << 3 | rdm)
+    }
+
+    fn load_store(&self, mnemonic: &str, operands: &[&str]) -> Result<u16, String> {
+        let [rt, address] = count(operands)?;
+        let rt = low_register(rt)?;
+
+        // NOTE: Literal loads, from a pool or a label
+        if !address.starts_with('[') {
+            if mnemonic != "ldr" {
+                return Err(format!("{mnemonic} cannot load a literal"));
+            }
+            let target = match address.strip_prefix('=') {
+                Some(_) => self
+                    .literal
+                    .ok_or_else(|| "no literal pool".to_owned())?
+                    .into(),
+                None => self.scope.evaluate(address)?,
+            };
+            let offset = self.aligned_pc_offset(target);
+            return Ok(0x4800 | rt << 8 | scaled(offset, 4, 8)?);
+        }
+
+        let (rn, offset) = memory(address)?;
+        if let Some(rm) = offset.filter(|offset| is_register(offset)) {
+            let opcode = match mnemonic {
+                "str" => 0b000,
+                "strh" => 0b001,
+                "strb" => 0b010,
+                "ldrsb" => 0b011,
+                "ldr" => 0b100,
+                "ldrh" => 0b101,
+                "ldrb" => 0b110,
+                _ => 0b111,
+            };
+            return Ok(0x5000 | opcode << 9 | low_register(rm)? << 6 | low(rn)? << 3 | rt);
+        }
+
+        let offset = match offset {
+            Some(offset) => self.immediate(offset)?,
+            None => 0,
+        };
+        match (mnemonic, rn) {
+            ("ldr", PC) => Ok(0x4800 | rt << 8 | scaled(offset, 4, 8)?),
+            ("ldr", SP) => Ok(0x9800 | rt << 8 | scaled(offset, 4, 8)?),
+            ("str", SP) => Ok(0x9000 | rt << 8 | scaled(offset, 4, 8)?),
+            ("str", _) => Ok(0x6000 | scaled(offset, 4, 5)? << 6 | low(rn)? << 3 | rt),
+            ("ldr", _) => Ok(0x6800 | scaled(offset, 4, 5)? << 6 | low(rn)? << 3 | rt),
+            ("strb", _) => Ok(0x7000 | scaled(offset, 1, 5)? << 6 | low(rn)? << 3 | rt),
+            ("ldrb", _) => Ok(0x7800 | scaled(offset, 1, 5)? << 6 | low(rn)? << 3 | rt),
+            ("strh", _) => Ok(0x8000 | scaled(offset, 2, 5)? << 6 | low(rn)? << 3 | rt),
+            ("ldrh", _) => Ok(0x8800 | scaled(offset, 2, 5)? << 6 | low(rn)? << 3 | rt),
+            _ => Err(format!("{mnemonic} only has a register offset form")),
+        }
+    }
+
+    /// LDM and STM, the base register is written back unless LDM loads it
+    fn multiple(load: bool, operands: &[&str]) -> Result<u16, String> {
+        let [base, list] = count(operands)?;
+        let (base, writeback) = match base.strip_suffix('!') {
+            Some(base) => (base, true),
+            None => (base, false),
+        };
+        let rn = low_register(base)?;
+        let list = register_list(list)?;
+        if list & 0xFF00 != 0 {
+            return Err("only low registers can be transferred".to_owned());
+        }
+
+        if load {
+            if writeback == (list >> rn & 1 == 1) {
+                return Err("ldm writes back the base register unless it is in the list".to_owned());
+            }
+            Ok(0xC800 | rn << 8 | list)
+        } else {
+            if !writeback {
+                return Err("stm always writes back the base register".to_owned());
+            }
+            Ok(0xC000 | rn << 8 | list)
+        }
+    }
+}
+
+/// ADD (register) T2, any register can be used
+fn add_high(rdn: u8, rm: u8) -> u16 {
+    0x4400 | u16::from(rdn & 0b1000) << 4 | u16::from(rm) << 3 | u16::from(rdn & 0b111)
+}
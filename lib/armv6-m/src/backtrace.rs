@@ -0,0 +1,311 @@
+//! Call stack of the core, unwound through exception frames and the caller frames.
+//!
+//! The caller of a frame is found with, in order, the `.debug_frame` entry of its address,
+//! the `.ARM.exidx` entry of its function, or a scan of the function prologue for the
+//! `PUSH` and `SUB SP` instructions executed so far. A return address that is an EXC_RETURN
+//! value unwinds the exception frame stacked when the exception was taken.
+
+use std::fmt;
+
+use crate::debug_info::{FrameRule, Location};
+use crate::decoder;
+use crate::instructions::add::Add;
+use crate::instructions::sub::Sub;
+use crate::instructions::Instruction;
+use crate::structure::{Exception, EXC_RETURN_THREAD_PROCESS};
+use crate::Armv6M;
+
+/// Frames beyond this depth are not unwound, the stack is most likely corrupted
+pub const MAX_FRAMES: usize = 64;
+
+/// Registers stacked on exception entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionFrame {
+    /// Exception which stacked the frame
+    pub exception: Option<Exception>,
+    /// Address of the frame
+    pub address: u32,
+    pub r0: u32,
+    pub r1: u32,
+    pub r2: u32,
+    pub r3: u32,
+    pub r12: u32,
+    pub lr: u32,
+    pub pc: u32,
+    pub xpsr: u32,
+}
+
+impl ExceptionFrame {
+    /// Stacked registers with their names, in stacking order
+    pub fn registers(&self) -> [(&'static str, u32); 8] {
+        [
+            ("r0", self.r0),
+            ("r1", self.r1),
+            ("r2", self.r2),
+            ("r3", self.r3),
+            ("r12", self.r12),
+            ("lr", self.lr),
+            ("pc", self.pc),
+            ("xpsr", self.xpsr),
+        ]
+    }
+}
+
+/// How the caller of a frame was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unwind {
+    DebugFrame,
+    Exidx,
+    PrologueScan,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub pc: u32,
+    pub sp: u32,
+    /// Symbol of the function and offset of the pc in it
+    pub function: Option<(String, u32)>,
+    /// Source line of the pc, or of the call for the callers
+    pub location: Option<Location>,
+    /// Registers stacked when an exception interrupted the frame
+    pub exception: Option<ExceptionFrame>,
+    /// How the caller was found, `None` for the outermost frame
+    pub unwind: Option<Unwind>,
+}
+
+/// Frames from the innermost one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Backtrace {
+    pub frames: Vec<Frame>,
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (n, frame) in self.frames.iter().enumerate() {
+            write!(f, "#{n:<2} {:#010x}", frame.pc)?;
+            match &frame.function {
+                Some((name, 0)) => write!(f, " in {name}")?,
+                Some((name, offset)) => write!(f, " in {name}+{offset:#x}")?,
+                None => {}
+            }
+            if let Some(location) = &frame.location {
+                write!(f, " at {location}")?;
+            }
+            writeln!(f)?;
+
+            if let Some(stacked) = &frame.exception {
+                let exception = stacked
+                    .exception
+                    .map_or("exception".to_owned(), |exception| {
+                        format!("{exception:?} exception")
+                    });
+                write!(f, "    <{exception}>")?;
+                for (name, value) in stacked.registers() {
+                    write!(f, " {name} {value:#010x}")?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Registers known while unwinding, callee-saved ones are restored from the stack
+struct State {
+    registers: [Option<u32>; 16],
+    /// Exception number of the code running in the frame
+    exception_number: u32,
+}
+
+impl State {
+    fn get(&self, register: u8) -> Option<u32> {
+        self.registers[usize::from(register & 0xF)]
+    }
+}
+
+pub(crate) fn unwind(core: &Armv6M) -> Backtrace {
+    let mut registers = [None; 16];
+    for (n, register) in registers.iter_mut().enumerate().take(13) {
+        *register = Some(core.registers[n]);
+    }
+    registers[13] = Some(core.get_sp());
+    registers[14] = Some(core.lr);
+    registers[15] = Some(core.pc);
+    let mut state = State {
+        registers,
+        exception_number: core.ipsr.exception_number(),
+    };
+
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut exception = None;
+    let mut method = None;
+    while frames.len() < MAX_FRAMES {
+        let (Some(pc), Some(sp)) = (state.get(15), state.get(13)) else {
+            break;
+        };
+        // NOTE: The pc of a caller is the return address, the call is the instruction before it
+        let call = if frames.is_empty() || exception.is_some() {
+            pc
+        } else {
+            pc.wrapping_sub(2)
+        };
+        if let Some(callee) = frames.last_mut() {
+            callee.unwind = method.take();
+        }
+        frames.push(Frame {
+            pc,
+            sp,
+            function: core
+                .symbols
+                .lookup(call)
+                .map(|(name, offset)| (name.to_owned(), offset + pc - call)),
+            location: core.debug_info.location(call).cloned(),
+            exception: exception.take(),
+            unwind: None,
+        });
+
+        let Some((rule, found)) = frame_rule(core, pc) else {
+            break;
+        };
+        let Some(return_address) = apply(core, &mut state, &rule) else {
+            break;
+        };
+        method = Some(found);
+
+        if return_address >> 28 == 0xF && state.exception_number != 0 {
+            let Some(stacked) = unstack(core, &mut state, return_address) else {
+                break;
+            };
+            exception = Some(stacked);
+        } else if return_address == u32::MAX
+            || state.get(13).is_none_or(|caller| caller < sp)
+            || state.get(13) == Some(sp) && return_address & !1 == pc
+        {
+            // NOTE: The reset value of LR, a stack going the wrong way or a frame returning to itself
+            break;
+        } else {
+            state.registers[15] = Some(return_address & !1);
+        }
+    }
+
+    Backtrace { frames }
+}
+
+/// Rule of the function at `pc`, from the first unwinding information describing it
+pub(crate) fn frame_rule(core: &Armv6M, pc: u32) -> Option<(FrameRule, Unwind)> {
+    if let Some(rule) = core.debug_info.debug_frame_rule(pc) {
+        return Some((rule, Unwind::DebugFrame));
+    }
+
+    let start = core.symbols.lookup(pc).map(|(_, offset)| pc - offset);
+    // NOTE: An entry covers the code up to the next one, it only describes the function starting with it
+    if let Some((entry, rule)) = core.debug_info.exidx_rule(pc) {
+        if start.is_none_or(|start| start <= entry) {
+            if let Some(rule) = rule {
+                return Some((rule.clone(), Unwind::Exidx));
+            }
+        }
+    }
+
+    Some((scan_prologue(core, start?, pc), Unwind::PrologueScan))
+}
+
+/// Follow the stack adjustments from the start of the function to `pc`
+fn scan_prologue(core: &Armv6M, start: u32, pc: u32) -> FrameRule {
+    let mut rule = FrameRule {
+        cfa_register: 13,
+        ..FrameRule::default()
+    };
+    let mut address = start;
+    while address < pc {
+        let first = core.memory.read_u16(address);
+        let second = core.memory.read_u16(address.wrapping_add(2));
+        let Some((instruction, size)) = decoder::decode(first, second) else {
+            break;
+        };
+        match instruction {
+            Instruction::Push(push) => {
+                let list = u16::from(push.register_list) | u16::from(push.m) << 14;
+                let count = list.count_ones() as i32;
+                rule.cfa_offset += 4 * count;
+                for (k, register) in (0..16)
+                    .filter(|register| list >> register & 1 == 1)
+                    .enumerate()
+                {
+                    rule.saved.push((register, 4 * k as i32 - rule.cfa_offset));
+                }
+            }
+            Instruction::Sub(Sub::SpMinusImmediateT1 { imm7 }) => {
+                rule.cfa_offset += i32::from(imm7) << 2;
+            }
+            Instruction::Add(Add::SpPlusImmediateT2 { imm7 }) => {
+                rule.cfa_offset -= i32::from(imm7) << 2;
+            }
+            _ => {}
+        }
+        address = address.wrapping_add(size);
+    }
+    rule
+}
+
+/// Restore the registers of the caller, returns the return address
+fn apply(core: &Armv6M, state: &mut State, rule: &FrameRule) -> Option<u32> {
+    let cfa = state
+        .get(rule.cfa_register)?
+        .wrapping_add_signed(rule.cfa_offset);
+
+    let mut caller = state.registers;
+    for (register, offset) in &rule.saved {
+        caller[usize::from(*register & 0xF)] =
+            Some(core.memory.read_u32(cfa.wrapping_add_signed(*offset)));
+    }
+    let saved = |register| rule.saved.iter().any(|(saved, _)| *saved == register);
+    let return_address = if saved(15) {
+        caller[15]
+    } else if saved(14) {
+        caller[14]
+    } else {
+        state.get(14)
+    }?;
+
+    // NOTE: LR held the return address, the one of the caller is lost unless the caller saved it
+    caller[13] = Some(cfa);
+    caller[14] = None;
+    state.registers = caller;
+    Some(return_address)
+}
+
+/// Pop the exception frame for `exc_return`, like an exception return
+fn unstack(core: &Armv6M, state: &mut State, exc_return: u32) -> Option<ExceptionFrame> {
+    // NOTE: The process stack is only known for the innermost exception, later ones use the main stack
+    let address = if exc_return == EXC_RETURN_THREAD_PROCESS {
+        core.sp_process
+    } else {
+        state.get(13)?
+    };
+    let word = |n: u32| core.memory.read_u32(address.wrapping_add(4 * n));
+    let stacked = ExceptionFrame {
+        exception: Exception::from_number(state.exception_number),
+        address,
+        r0: word(0),
+        r1: word(1),
+        r2: word(2),
+        r3: word(3),
+        r12: word(4),
+        lr: word(5),
+        pc: word(6),
+        xpsr: word(7),
+    };
+
+    for (register, (_, value)) in [0, 1, 2, 3, 12, 14, 15]
+        .into_iter()
+        .zip(stacked.registers())
+    {
+        state.registers[register] = Some(value);
+    }
+    state.registers[15] = Some(stacked.pc & !1);
+    // NOTE: Bit 9 of the stacked xPSR records the realignment of the frame
+    state.registers[13] = Some(address.wrapping_add(0x20 + (stacked.xpsr >> 9 & 1) * 4));
+    state.exception_number = stacked.xpsr & 0x3F;
+    Some(stacked)
+}
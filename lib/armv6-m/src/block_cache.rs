@@ -0,0 +1,258 @@
+//! Cache of predecoded basic blocks, so loops are not fetched and decoded on every iteration.
+//!
+//! Blocks are keyed by the address of their first instruction and indexed by the pages they
+//! cover, any write to one of these pages drops them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use crate::decoder::decode;
+use crate::instructions::add::Add;
+use crate::instructions::mov::Mov;
+use crate::instructions::Instruction;
+use crate::memory::{Memory, PAGE_SHIFT};
+
+/// Longest block decoded at once, straight line code is split in blocks of this length
+pub const MAX_BLOCK_LENGTH: usize = 64;
+
+/// Counters of the block cache, they survive the invalidations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Blocks found in the cache when the execution jumped to them
+    pub hits: u64,
+    /// Blocks decoded because they were not in the cache
+    pub misses: u64,
+    /// Blocks dropped because their memory was written
+    pub invalidations: u64,
+}
+
+impl CacheStats {
+    /// Ratio of the block lookups served by the cache
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Instructions decoded from `start` up to the first one that may branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: u32,
+    /// Instructions with their size in bytes. Empty when the first instruction is undefined,
+    /// an undefined instruction after the first one ends the block before it.
+    pub instructions: Vec<(Instruction, u32)>,
+}
+
+impl Block {
+    fn decode(memory: &Memory, start: u32) -> Self {
+        let mut instructions = Vec::new();
+        let mut address = start;
+        while instructions.len() < MAX_BLOCK_LENGTH {
+            let first = memory.read_u16(address);
+            let second = memory.read_u16(address.wrapping_add(2));
+            let Some((instruction, size)) = decode(first, second) else {
+                break;
+            };
+            instructions.push((instruction, size));
+            address = address.wrapping_add(size);
+            if ends_block(&instruction) {
+                break;
+            }
+        }
+        Self {
+            start,
+            instructions,
+        }
+    }
+
+    /// Address after the last byte of the block, an empty block covers its undefined halfword
+    pub fn end(&self) -> u32 {
+        let size = self
+            .instructions
+            .iter()
+            .map(|(_, size)| size)
+            .sum::<u32>()
+            .max(2);
+        self.start.wrapping_add(size)
+    }
+
+    /// Pages holding the instructions of the block
+    fn pages(&self) -> impl Iterator<Item = u32> {
+        let first = self.start >> PAGE_SHIFT;
+        let last = self.end().wrapping_sub(1) >> PAGE_SHIFT;
+        first..=last.max(first)
+    }
+}
+
+/// Instructions after which the next one may not be the next in memory.
+/// Exceptions are checked before every instruction so they do not need to end the block.
+fn ends_block(instruction: &Instruction) -> bool {
+    match instruction {
+        Instruction::B(_)
+        | Instruction::Bl(_)
+        | Instruction::Blx(_)
+        | Instruction::Bx(_)
+        | Instruction::Bkpt(_)
+        | Instruction::Svc(_)
+        | Instruction::Udf(_) => true,
+        Instruction::Pop(pop) => pop.p == 1,
+        Instruction::Add(Add::RegisterT2 { rdn, dn, .. }) => dn << 3 | rdn == 15,
+        Instruction::Add(Add::SpPlusRegisterT1 { dm, rdm }) => dm << 3 | rdm == 15,
+        Instruction::Mov(Mov::RegisterT1 { d, rd, .. }) => d << 3 | rd == 15,
+        _ => false,
+    }
+}
+
+/// Position of the execution in a cached block
+#[derive(Debug, Clone)]
+struct Cursor {
+    block: Rc<Block>,
+    /// Index of the next instruction
+    index: usize,
+    /// Address of the next instruction
+    address: u32,
+}
+
+/// Decoded blocks of the code executed so far
+#[derive(Debug, Clone)]
+pub struct BlockCache {
+    enabled: bool,
+    blocks: HashMap<u32, Rc<Block>>,
+    /// Start of the blocks covering each page number
+    pages: BTreeMap<u32, Vec<u32>>,
+    cursor: Option<Cursor>,
+    stats: CacheStats,
+}
+
+impl BlockCache {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            blocks: HashMap::new(),
+            pages: BTreeMap::new(),
+            cursor: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// A disabled cache decodes every instruction from the memory
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Number of blocks in the cache
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Instruction at `address` and its size in bytes, `None` when it is undefined
+    pub fn fetch(&mut self, memory: &Memory, address: u32) -> Option<(Instruction, u32)> {
+        if !self.enabled {
+            return decode(
+                memory.read_u16(address),
+                memory.read_u16(address.wrapping_add(2)),
+            );
+        }
+
+        let cursor = match self.cursor.take() {
+            Some(cursor)
+                if cursor.address == address && cursor.index < cursor.block.instructions.len() =>
+            {
+                cursor
+            }
+            _ => Cursor {
+                block: self.lookup(memory, address),
+                index: 0,
+                address,
+            },
+        };
+
+        let (instruction, size) = *cursor.block.instructions.get(cursor.index)?;
+        self.cursor = Some(Cursor {
+            index: cursor.index + 1,
+            address: address.wrapping_add(size),
+            ..cursor
+        });
+        Some((instruction, size))
+    }
+
+    fn lookup(&mut self, memory: &Memory, address: u32) -> Rc<Block> {
+        if let Some(block) = self.blocks.get(&address) {
+            self.stats.hits += 1;
+            return Rc::clone(block);
+        }
+
+        self.stats.misses += 1;
+        let block = Rc::new(Block::decode(memory, address));
+        for page in block.pages() {
+            self.pages.entry(page).or_default().push(address);
+        }
+        self.blocks.insert(address, Rc::clone(&block));
+        block
+    }
+
+    /// Drop the blocks covering the pages of a write of `size` bytes at `address`
+    pub fn invalidate(&mut self, address: u32, size: u32) {
+        if self.pages.is_empty() {
+            return;
+        }
+
+        let first = address >> PAGE_SHIFT;
+        let last = address.wrapping_add(size.max(1) - 1) >> PAGE_SHIFT;
+        for page in first..=last.max(first) {
+            let Some(starts) = self.pages.remove(&page) else {
+                continue;
+            };
+            // NOTE: Blocks spanning two pages stay listed in the other page, it only costs
+            // a spurious invalidation of the block decoded later at the same address
+            for start in starts {
+                if self.blocks.remove(&start).is_some() {
+                    self.stats.invalidations += 1;
+                }
+            }
+        }
+
+        if self
+            .cursor
+            .as_ref()
+            .is_some_and(|cursor| !self.blocks.contains_key(&cursor.block.start))
+        {
+            self.cursor = None;
+        }
+    }
+
+    /// Drop every block, when the whole memory is replaced
+    pub fn clear(&mut self) {
+        self.stats.invalidations += self.blocks.len() as u64;
+        self.blocks.clear();
+        self.pages.clear();
+        self.cursor = None;
+    }
+}
+
+impl Default for BlockCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,96 @@
+//! Breakpoints of the core, with an optional condition and an ignore count, checked by `step`.
+
+use crate::expression::{Environment, Expression, Variable};
+use crate::structure::RegisterId;
+use crate::Armv6M;
+
+/// Stops the core before the instruction at `address`.
+///
+/// When it is reached the breakpoint counts a hit, then evaluates its condition.
+/// If the condition holds the first `ignore_count` times are skipped, the core stops after that.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub address: u32,
+    pub condition: Option<Expression>,
+    /// Times the condition must hold before the core stops, decremented on each of them
+    pub ignore_count: u32,
+    /// Times the address was reached, the `hits` variable of the condition
+    pub hits: u32,
+}
+
+impl Breakpoint {
+    pub fn new(address: u32) -> Self {
+        Self {
+            address: address & !1,
+            condition: None,
+            ignore_count: 0,
+            hits: 0,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Expression) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn with_ignore_count(mut self, ignore_count: u32) -> Self {
+        self.ignore_count = ignore_count;
+        self
+    }
+
+    /// Count a hit and tell whether the core must stop
+    pub(crate) fn reached(&mut self, core: &Armv6M) -> bool {
+        self.hits = self.hits.saturating_add(1);
+        if !self.holds(core) {
+            false
+        } else if self.ignore_count > 0 {
+            self.ignore_count -= 1;
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Whether the condition holds, without counting a hit
+    pub(crate) fn holds(&self, core: &Armv6M) -> bool {
+        self.condition.as_ref().is_none_or(|condition| {
+            condition.is_true(&Context {
+                core,
+                hits: self.hits,
+            })
+        })
+    }
+}
+
+/// Variables of a condition, memory is read without side effects on the peripherals
+struct Context<'a> {
+    core: &'a Armv6M,
+    hits: u32,
+}
+
+impl Environment for Context<'_> {
+    fn variable(&self, variable: Variable) -> u32 {
+        let apsr = self.core.get_apsr();
+        match variable {
+            Variable::Register(13) => self.core.get_sp(),
+            Variable::Register(14) => self.core.get_lr(),
+            Variable::Register(15) => self.core.get_pc(),
+            Variable::Register(n) => self.core.read_register(RegisterId::ALL[usize::from(n)]),
+            Variable::Msp => self.core.read_register(RegisterId::SpMain),
+            Variable::Psp => self.core.read_register(RegisterId::SpProcess),
+            Variable::Xpsr => self.core.get_xpsr(),
+            Variable::Apsr => apsr.bits(),
+            Variable::Primask => self.core.read_register(RegisterId::Primask),
+            Variable::Control => self.core.read_register(RegisterId::Control),
+            Variable::N => u32::from(apsr.n()),
+            Variable::Z => u32::from(apsr.z()),
+            Variable::C => u32::from(apsr.c()),
+            Variable::V => u32::from(apsr.v()),
+            Variable::Hits => self.hits,
+        }
+    }
+
+    fn read(&self, address: u32, size: u8) -> u32 {
+        self.core.peek(address, size).unwrap_or(0)
+    }
+}
@@ -0,0 +1,361 @@
+//! Debug information of the loaded ELF file: the DWARF line table, variables and call frame
+//! information, and the ARM exception index table.
+//! <https://github.com/ARM-software/abi-aa/blob/main/ehabi32/ehabi32.rst>
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use elf::abi::SHT_ARM_EXIDX;
+use elf::endian::LittleEndian;
+use elf::ElfBytes;
+use gimli::{
+    BaseAddresses, CfaRule, DebugFrame, EndianSlice, RegisterRule, UnwindContext, UnwindSection,
+};
+
+use crate::error::Error;
+use crate::variables::{self, Function, Variable};
+
+/// Source line of an instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Where a function saved the registers of its caller
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameRule {
+    /// The Canonical Frame Address, the SP of the caller, is this register plus `cfa_offset`
+    pub cfa_register: u8,
+    pub cfa_offset: i32,
+    /// Registers stored on the stack, at an offset from the CFA
+    pub saved: Vec<(u8, i32)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugInfo {
+    /// Location of the instructions from each address, `None` past the end of a sequence
+    lines: BTreeMap<u32, Option<Location>>,
+    globals: Vec<Variable>,
+    /// Functions with code, by start address
+    functions: Vec<Function>,
+    debug_frame: Vec<u8>,
+    /// Rules of the exception index entries by start address, `None` when they cannot unwind
+    exidx: BTreeMap<u32, Option<FrameRule>>,
+}
+
+impl DebugInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sections missing from the file are left empty
+    pub fn from_elf(bytes: &[u8]) -> Result<Self, Error> {
+        let file = ElfBytes::<LittleEndian>::minimal_parse(bytes)?;
+        let section = |name: &str| -> Result<&[u8], Error> {
+            match file.section_header_by_name(name)? {
+                Some(header) => Ok(file.section_data(&header)?.0),
+                None => Ok(&[]),
+            }
+        };
+
+        let dwarf = gimli::Dwarf::load(|id| {
+            section(id.name()).map(|data| EndianSlice::new(data, gimli::LittleEndian))
+        })?;
+
+        let mut exidx = BTreeMap::new();
+        for header in file.section_headers().into_iter().flatten() {
+            if header.sh_type == SHT_ARM_EXIDX {
+                let (data, _) = file.section_data(&header)?;
+                let extab = file
+                    .section_header_by_name(".ARM.extab")?
+                    .map(|extab| {
+                        Ok::<_, Error>((extab.sh_addr as u32, file.section_data(&extab)?.0))
+                    })
+                    .transpose()?;
+                parse_exidx(&mut exidx, header.sh_addr as u32, data, extab);
+            }
+        }
+
+        let (globals, functions) = variables::parse(&dwarf)?;
+        Ok(Self {
+            lines: parse_lines(&dwarf)?,
+            globals,
+            functions,
+            debug_frame: section(".debug_frame")?.to_vec(),
+            exidx,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+            && self.globals.is_empty()
+            && self.functions.is_empty()
+            && self.debug_frame.is_empty()
+            && self.exidx.is_empty()
+    }
+
+    /// Source line of the instruction at `address`
+    pub fn location(&self, address: u32) -> Option<&Location> {
+        self.lines
+            .range(..=address)
+            .next_back()
+            .and_then(|(_, location)| location.as_ref())
+    }
+
+    /// First instruction of a source line. The file matches a path ending with it.
+    pub fn address_of(&self, file: &str, line: u32) -> Option<u32> {
+        let matches = |path: &str| {
+            path == file
+                || path
+                    .strip_suffix(file)
+                    .is_some_and(|directory| directory.ends_with('/'))
+        };
+        self.lines
+            .iter()
+            .find(|(_, location)| {
+                location
+                    .as_ref()
+                    .is_some_and(|location| location.line == line && matches(&location.file))
+            })
+            .map(|(address, _)| *address)
+    }
+
+    /// Variables with a static location
+    pub fn globals(&self) -> &[Variable] {
+        &self.globals
+    }
+
+    /// Function whose code covers `address`
+    pub fn function(&self, address: u32) -> Option<&Function> {
+        self.functions
+            .iter()
+            .find(|function| function.contains(address))
+    }
+
+    /// Rule of the `.debug_frame` entry covering `address`
+    pub fn debug_frame_rule(&self, address: u32) -> Option<FrameRule> {
+        let mut section = DebugFrame::new(&self.debug_frame, gimli::LittleEndian);
+        section.set_address_size(4);
+        let mut context = UnwindContext::new();
+        let row = section
+            .unwind_info_for_address(
+                &BaseAddresses::default(),
+                &mut context,
+                u64::from(address),
+                DebugFrame::cie_from_offset,
+            )
+            .ok()?;
+
+        let CfaRule::RegisterAndOffset { register, offset } = *row.cfa() else {
+            return None;
+        };
+        let saved = row
+            .registers()
+            .filter_map(|(register, rule)| match rule {
+                RegisterRule::Offset(offset) => Some((register.0 as u8, *offset as i32)),
+                _ => None,
+            })
+            .collect();
+        Some(FrameRule {
+            cfa_register: register.0 as u8,
+            cfa_offset: offset as i32,
+            saved,
+        })
+    }
+
+    /// Start address and rule of the exception index entry covering `address`.
+    /// An entry covers everything up to the next one, the caller checks it describes the right function.
+    pub fn exidx_rule(&self, address: u32) -> Option<(u32, Option<&FrameRule>)> {
+        self.exidx
+            .range(..=address)
+            .next_back()
+            .map(|(start, rule)| (*start, rule.as_ref()))
+    }
+}
+
+fn parse_lines(
+    dwarf: &gimli::Dwarf<EndianSlice<'_, gimli::LittleEndian>>,
+) -> Result<BTreeMap<u32, Option<Location>>, Error> {
+    let mut lines = BTreeMap::new();
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let Some(program) = unit.line_program.clone() else {
+            continue;
+        };
+
+        let mut rows = program.rows();
+        while let Some((header, row)) = rows.next_row()? {
+            let address = row.address() as u32;
+            if row.end_sequence() {
+                lines.entry(address).or_insert(None);
+                continue;
+            }
+            let Some(file) = row.file(header) else {
+                continue;
+            };
+
+            // NOTE: Directory 0 is the compilation directory, it is left out to keep the paths short
+            let mut path = dwarf
+                .attr_string(&unit, file.path_name())?
+                .to_string_lossy()
+                .into_owned();
+            if file.directory_index() != 0 {
+                if let Some(directory) = file.directory(header) {
+                    let directory = dwarf.attr_string(&unit, directory)?;
+                    path = format!("{}/{path}", directory.to_string_lossy());
+                }
+            }
+            let line = row.line().map_or(0, |line| line.get() as u32);
+            lines.insert(address, Some(Location { file: path, line }));
+        }
+    }
+    Ok(lines)
+}
+
+/// Entries of a `.ARM.exidx` section loaded at `base`, the `.ARM.extab` holds the longer ones
+fn parse_exidx(
+    exidx: &mut BTreeMap<u32, Option<FrameRule>>,
+    base: u32,
+    data: &[u8],
+    extab: Option<(u32, &[u8])>,
+) {
+    let word = |bytes: &[u8], offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+    };
+
+    for (i, entry) in data.as_chunks::<8>().0.iter().enumerate() {
+        let place = base.wrapping_add(8 * i as u32);
+        let start = prel31(
+            u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]),
+            place,
+        );
+        let content = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+
+        let rule = match content {
+            // NOTE: EXIDX_CANTUNWIND
+            1 => None,
+            _ if content >> 31 == 1 => {
+                compact_opcodes(content, &[]).and_then(|opcodes| decode_opcodes(&opcodes))
+            }
+            _ => extab.and_then(|(extab_base, extab)| {
+                let offset =
+                    prel31(content, place.wrapping_add(4)).wrapping_sub(extab_base) as usize;
+                let first = word(extab, offset)?;
+                let count = if first >> 24 & 0xF == 0 {
+                    0
+                } else {
+                    (first >> 16 & 0xFF) as usize
+                };
+                let more = (1..=count)
+                    .map(|n| word(extab, offset + 4 * n))
+                    .collect::<Option<Vec<_>>>()?;
+                compact_opcodes(first, &more).and_then(|opcodes| decode_opcodes(&opcodes))
+            }),
+        };
+        exidx.insert(start, rule);
+    }
+}
+
+/// Sign extended 31 bits offset from `place`
+fn prel31(word: u32, place: u32) -> u32 {
+    place.wrapping_add((((word << 1) as i32) >> 1) as u32)
+}
+
+/// Unwinding opcodes of the compact model, `None` for the generic personality routines
+fn compact_opcodes(first: u32, more: &[u32]) -> Option<Vec<u8>> {
+    if first >> 31 == 0 {
+        return None;
+    }
+    let [_, b1, b2, b3] = first.to_be_bytes();
+    let mut opcodes = match first >> 24 & 0xF {
+        0 => vec![b1, b2, b3],
+        1 | 2 => vec![b2, b3],
+        _ => return None,
+    };
+    opcodes.extend(more.iter().flat_map(|word| word.to_be_bytes()));
+    Some(opcodes)
+}
+
+/// Turn the opcodes, which move a virtual SP over the saved registers, into the rule they describe.
+/// Only the core registers opcodes are supported.
+fn decode_opcodes(opcodes: &[u8]) -> Option<FrameRule> {
+    let mut rule = FrameRule {
+        cfa_register: 13,
+        ..FrameRule::default()
+    };
+    // Offset of the virtual SP from the register of the CFA
+    let mut vsp = 0i32;
+    let pop = |rule: &mut FrameRule, vsp: &mut i32, mask: u16| {
+        for register in (0..16).filter(|register| mask >> register & 1 == 1) {
+            rule.saved.push((register, *vsp));
+            *vsp += 4;
+        }
+    };
+
+    let mut bytes = opcodes.iter().copied();
+    while let Some(opcode) = bytes.next() {
+        match opcode {
+            0x00..=0x3F => vsp += (i32::from(opcode) << 2) + 4,
+            0x40..=0x7F => vsp -= (i32::from(opcode & 0x3F) << 2) + 4,
+            0x80..=0x8F => {
+                let mask = u16::from(opcode & 0xF) << 8 | u16::from(bytes.next()?);
+                // NOTE: `0x80 0x00` refuses to unwind
+                if mask == 0 {
+                    return None;
+                }
+                pop(&mut rule, &mut vsp, mask << 4);
+            }
+            // NOTE: Registers saved before the SP is set from another register cannot be found back
+            0x90..=0x9F if opcode & 0xF != 13 && opcode & 0xF != 15 && rule.saved.is_empty() => {
+                rule.cfa_register = opcode & 0xF;
+                vsp = 0;
+            }
+            0xA0..=0xAF => {
+                let high = (1u16 << ((opcode & 0x7) + 5)) - 1;
+                let mut mask = high & !0xF;
+                if opcode & 0x8 != 0 {
+                    mask |= 1 << 14;
+                }
+                pop(&mut rule, &mut vsp, mask);
+            }
+            0xB0 => break,
+            0xB1 => {
+                let mask = bytes.next()?;
+                if mask == 0 || mask & 0xF0 != 0 {
+                    return None;
+                }
+                pop(&mut rule, &mut vsp, u16::from(mask));
+            }
+            0xB2 => {
+                let mut value = 0u32;
+                let mut shift = 0;
+                loop {
+                    let byte = bytes.next()?;
+                    value |= u32::from(byte & 0x7F) << shift;
+                    shift += 7;
+                    if byte & 0x80 == 0 || shift > 28 {
+                        break;
+                    }
+                }
+                vsp += 0x204 + (value << 2) as i32;
+            }
+            _ => return None,
+        }
+    }
+
+    rule.cfa_offset = vsp;
+    for (_, offset) in &mut rule.saved {
+        *offset -= vsp;
+    }
+    Some(rule)
+}
@@ -0,0 +1,90 @@
+//! Table driven decoding of the instructions executed by the core.
+//! The nom parsers of `instructions` stay the reference, the table is built from them.
+
+use std::sync::OnceLock;
+
+use crate::instructions::bl::Bl;
+use crate::instructions::{is_32bits, parse_instruction, Instruction};
+
+/// What a first halfword decodes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entry {
+    Undefined,
+    /// First halfword of a 32 bits instruction, which needs the second halfword
+    Wide,
+    Narrow(Instruction),
+}
+
+/// Lookup table of the 65536 halfwords
+pub struct Decoder {
+    table: Box<[Entry]>,
+}
+
+impl Decoder {
+    /// Decode every halfword with the reference parsers
+    pub fn new() -> Self {
+        let table = (0..=u16::MAX)
+            .map(|halfword| {
+                if is_32bits(halfword) {
+                    return Entry::Wide;
+                }
+                match parse_instruction(&halfword.to_be_bytes()) {
+                    Ok((_, instruction)) => Entry::Narrow(instruction),
+                    Err(_) => Entry::Undefined,
+                }
+            })
+            .collect();
+        Self { table }
+    }
+
+    /// Decoder shared by every core, built on first use
+    pub fn global() -> &'static Self {
+        static DECODER: OnceLock<Decoder> = OnceLock::new();
+        DECODER.get_or_init(Self::new)
+    }
+
+    pub fn entry(&self, halfword: u16) -> Entry {
+        self.table[usize::from(halfword)]
+    }
+
+    /// Decode the instruction starting with `first`, `second` is only used by 32 bits instructions.
+    /// Returns the instruction and its size in bytes, `None` when it is undefined.
+    pub fn decode(&self, first: u16, second: u16) -> Option<(Instruction, u32)> {
+        match self.entry(first) {
+            Entry::Narrow(instruction) => Some((instruction, 2)),
+            Entry::Undefined => None,
+            Entry::Wide => decode_wide(first, second).map(|instruction| (instruction, 4)),
+        }
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode with the shared decoder
+pub fn decode(first: u16, second: u16) -> Option<(Instruction, u32)> {
+    Decoder::global().decode(first, second)
+}
+
+/// BL is the only frequent 32 bits instruction, the others go through the reference parsers
+fn decode_wide(first: u16, second: u16) -> Option<Instruction> {
+    if first >> 11 == 0b11110 && second >> 14 == 0b11 && second >> 12 & 1 == 1 {
+        return Some(Instruction::Bl(Bl {
+            s: (first >> 10 & 1) as u8,
+            imm10: first & 0x3FF,
+            j1: (second >> 13 & 1) as u8,
+            j2: (second >> 11 & 1) as u8,
+            imm11: second & 0x7FF,
+        }));
+    }
+
+    let mut bytes = [0; 4];
+    bytes[..2].copy_from_slice(&first.to_be_bytes());
+    bytes[2..].copy_from_slice(&second.to_be_bytes());
+    parse_instruction(&bytes)
+        .ok()
+        .map(|(_, instruction)| instruction)
+}
@@ -0,0 +1,181 @@
+//! Unified Assembler Language rendering of the decoded instructions, in the `objdump -d` style.
+
+use std::fmt;
+
+use crate::decoder::decode;
+use crate::instructions::{is_32bits, Instruction};
+use crate::memory::Memory;
+use crate::symbols::Symbols;
+
+/// Render an instruction as its mnemonic and operands separated by a tab
+pub trait Disassemble {
+    fn disassemble(&self, context: &Context<'_>) -> String;
+}
+
+/// Where the instruction lives, to resolve PC relative operands
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Context<'a> {
+    /// Address of the instruction, PC relative operands stay relative without it
+    pub address: Option<u32>,
+    pub symbols: Option<&'a Symbols>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(address: u32, symbols: Option<&'a Symbols>) -> Self {
+        Self {
+            address: Some(address),
+            symbols,
+        }
+    }
+
+    /// `address <symbol+offset>`
+    pub fn label(&self, address: u32) -> String {
+        match self.symbols.and_then(|symbols| symbols.label(address)) {
+            Some(label) => format!("{address:x} <{label}>"),
+            None => format!("{address:x}"),
+        }
+    }
+
+    /// Branch target, `offset` is relative to the PC (the instruction address plus 4)
+    pub fn branch(&self, offset: u32) -> String {
+        match self.address {
+            Some(address) => self.label(address.wrapping_add(4).wrapping_add(offset)),
+            None => format!(".{:+}", 4i32.wrapping_add(offset as i32)),
+        }
+    }
+
+    /// Address of a literal, `offset` is relative to `Align(PC, 4)`
+    pub fn literal_address(&self, offset: u32) -> Option<u32> {
+        self.address
+            .map(|address| (address.wrapping_add(4) & !0b11).wrapping_add(offset))
+    }
+
+    /// Comment giving the address of a literal
+    pub fn literal(&self, offset: u32) -> String {
+        self.literal_address(offset)
+            .map(|address| format!("\t@ ({})", self.label(address)))
+            .unwrap_or_default()
+    }
+}
+
+pub fn register(n: u8) -> &'static str {
+    const NAMES: [&str; 16] = [
+        "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "sp",
+        "lr", "pc",
+    ];
+    NAMES[usize::from(n & 0xF)]
+}
+
+/// `{r4, r5, lr}`, bit n of `list` selects register n
+pub fn register_list(list: u16) -> String {
+    let registers = (0..16)
+        .filter(|n| list >> n & 1 == 1)
+        .map(register)
+        .collect::<Vec<_>>();
+    format!("{{{}}}", registers.join(", "))
+}
+
+/// Condition suffix of a conditional branch
+pub fn condition(cond: u8) -> &'static str {
+    const NAMES: [&str; 15] = [
+        "eq", "ne", "cs", "cc", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "",
+    ];
+    NAMES.get(usize::from(cond)).copied().unwrap_or_default()
+}
+
+/// Option of the DMB, DSB and ISB barriers, only `sy` is architecturally defined
+pub fn barrier_option(option: u8) -> String {
+    match option {
+        0xF => "sy".to_owned(),
+        _ => format!("#{option}"),
+    }
+}
+
+/// Special register names of MRS and MSR
+pub fn special_register(sysm: u8) -> &'static str {
+    match sysm {
+        0 => "APSR",
+        1 => "IAPSR",
+        2 => "EAPSR",
+        3 => "PSR",
+        5 => "IPSR",
+        6 => "EPSR",
+        7 => "IEPSR",
+        8 => "MSP",
+        9 => "PSP",
+        16 => "PRIMASK",
+        20 => "CONTROL",
+        _ => "<unknown>",
+    }
+}
+
+impl fmt::Display for Instruction {
+    /// PC relative operands are rendered relative to the instruction (`.+4`)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.disassemble(&Context::default()))
+    }
+}
+
+/// One line of a listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub address: u32,
+    pub halfwords: Vec<u16>,
+    /// `None` when the halfwords are not a valid instruction
+    pub instruction: Option<Instruction>,
+    pub text: String,
+    /// Symbol starting at this address
+    pub symbol: Option<String>,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(symbol) = &self.symbol {
+            writeln!(f, "\n{:08x} <{symbol}>:", self.address)?;
+        }
+        let raw = self
+            .halfwords
+            .iter()
+            .map(|halfword| format!("{halfword:04x} "))
+            .collect::<String>();
+        write!(f, "{:>8x}:\t{raw:<10}\t{}", self.address, self.text)
+    }
+}
+
+/// Decode and render the instruction at `address`
+pub fn disassemble_at(memory: &Memory, address: u32, symbols: Option<&Symbols>) -> Line {
+    let first = memory.read_u16(address);
+    let second = memory.read_u16(address.wrapping_add(2));
+    let mut halfwords = vec![first];
+    if is_32bits(first) {
+        halfwords.push(second);
+    }
+
+    let instruction = decode(first, second).map(|(instruction, _)| instruction);
+    let text = match &instruction {
+        Some(instruction) => instruction.disassemble(&Context::new(address, symbols)),
+        None => format!("\t@ <UNDEFINED> instruction: {:#06x}", first),
+    };
+
+    Line {
+        address,
+        halfwords,
+        instruction,
+        text,
+        symbol: symbols
+            .and_then(|symbols| symbols.get(address))
+            .map(str::to_owned),
+    }
+}
+
+/// Disassemble `start..end` like `objdump -d`, literal pools are decoded as instructions
+pub fn disassemble(memory: &Memory, start: u32, end: u32, symbols: Option<&Symbols>) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut address = start;
+    while address < end {
+        let line = disassemble_at(memory, address, symbols);
+        address = address.wrapping_add(2 * line.halfwords.len() as u32);
+        lines.push(line);
+    }
+    lines
+}
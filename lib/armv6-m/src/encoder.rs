@@ -0,0 +1,30 @@
+//! Inverse of the decoder, from the instruction fields back to the halfwords.
+
+use crate::error::Error;
+
+/// Encode an instruction as the halfwords it is decoded from, first halfword first
+pub trait Encode {
+    /// Fails when a field does not fit, or when the fields select another instruction
+    /// (the "SEE" notes of the encodings in the reference manual)
+    fn encode(&self) -> Result<Vec<u16>, Error>;
+}
+
+/// Concatenate `(value, width)` fields given most significant first,
+/// like the encoding diagrams of the reference manual
+pub fn halfwords(fields: &[(u32, u32)]) -> Result<Vec<u16>, Error> {
+    let mut bits = 0u32;
+    let mut length = 0;
+    for &(value, width) in fields {
+        if value >> width != 0 {
+            return Err(Error::Encoding("a field does not fit in its bits"));
+        }
+        bits = bits << width | value;
+        length += width;
+    }
+
+    match length {
+        16 => Ok(vec![bits as u16]),
+        32 => Ok(vec![(bits >> 16) as u16, bits as u16]),
+        _ => Err(Error::Encoding("the fields are not 16 or 32 bits long")),
+    }
+}
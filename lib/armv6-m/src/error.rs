@@ -1,4 +1,40 @@
 use thiserror::Error;
 
+use crate::structure::Exception;
+
 #[derive(Debug, Error)]
-pub enum Error {}
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid ELF file: {0}")]
+    Elf(#[from] elf::ParseError),
+    #[error("invalid Intel HEX file at line {line}: {reason}")]
+    Hex { line: usize, reason: &'static str },
+    #[error("invalid assembly at line {line}: {reason}")]
+    Assembly { line: usize, reason: String },
+    #[error("cannot encode the instruction: {0}")]
+    Encoding(&'static str),
+    /// Raised by instructions and turned into an exception entry by `step`,
+    /// it is never returned to the caller
+    #[error("{0:?} exception")]
+    Fault(Exception),
+    /// A HardFault happened while handling a HardFault or a NMI
+    #[error("core locked up at {0:#010x}")]
+    Lockup(u32),
+    #[error("mutation {index} is outside of the history, which has {len} mutations")]
+    MutationOutOfRange { index: usize, len: usize },
+    #[error("no mutation left to rollback")]
+    EmptyHistory,
+    #[error("invalid snapshot: {0}")]
+    Snapshot(&'static str),
+    #[error("cannot encode or decode the state: {0}")]
+    Bincode(#[from] bincode::Error),
+    #[error("peripheral {0} does not match the snapshot")]
+    SnapshotPeripheral(String),
+    #[error("invalid trace: {0}")]
+    Trace(&'static str),
+    #[error("invalid DWARF information: {0}")]
+    Dwarf(#[from] gimli::Error),
+    #[error("invalid expression at column {column}: {reason}")]
+    Expression { column: usize, reason: &'static str },
+}
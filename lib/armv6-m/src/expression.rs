@@ -0,0 +1,366 @@
+//! Small expression language over registers, flags and memory, for breakpoint conditions.
+//!
+//! Values are 32 bits and wrap around, comparisons are unsigned and evaluate to 0 or 1,
+//! a division by zero gives 0. Operators have the C precedence:
+//! unary `- ~ !`, `* / %`, `+ -`, `<< >>`, `< <= > >=`, `== !=`, `&`, `^`, `|`, `&&`, `||`.
+//!
+//! Operands are numbers (`42`, `0x2A`, `0b101010`), the registers `r0`-`r12`, `sp`, `lr`,
+//! `pc`, `msp`, `psp`, `xpsr`, `apsr`, `primask` and `control`, the flags `n`, `z`, `c`
+//! and `v`, `hits` (times the breakpoint was reached, this one included) and memory reads:
+//! `[address]` for a word, `u16[address]` and `u8[address]` for a halfword and a byte.
+
+use std::fmt;
+
+use crate::error::Error;
+
+/// Values an expression can refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    /// R0-R15, R13 is the current SP
+    Register(u8),
+    Msp,
+    Psp,
+    Xpsr,
+    Apsr,
+    Primask,
+    Control,
+    N,
+    Z,
+    C,
+    V,
+    Hits,
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Self> {
+        let variable = match name {
+            "sp" => Variable::Register(13),
+            "lr" => Variable::Register(14),
+            "pc" => Variable::Register(15),
+            "msp" => Variable::Msp,
+            "psp" => Variable::Psp,
+            "xpsr" => Variable::Xpsr,
+            "apsr" => Variable::Apsr,
+            "primask" => Variable::Primask,
+            "control" => Variable::Control,
+            "n" => Variable::N,
+            "z" => Variable::Z,
+            "c" => Variable::C,
+            "v" => Variable::V,
+            "hits" => Variable::Hits,
+            _ => {
+                let n = name.strip_prefix('r')?.parse::<u8>().ok()?;
+                return (n <= 15).then_some(Variable::Register(n));
+            }
+        };
+        Some(variable)
+    }
+}
+
+/// Where the variables and the memory of an expression are read
+pub trait Environment {
+    fn variable(&self, variable: Variable) -> u32;
+    /// Read `size` bytes (1, 2 or 4) at `address`
+    fn read(&self, address: u32, size: u8) -> u32;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unary {
+    Negate,
+    Not,
+    LogicalNot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Binary {
+    Multiply,
+    Divide,
+    Remainder,
+    Add,
+    Subtract,
+    ShiftLeft,
+    ShiftRight,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+    And,
+    Xor,
+    Or,
+    LogicalAnd,
+    LogicalOr,
+}
+
+impl Binary {
+    /// Operators by increasing precedence, with their tokens
+    const LEVELS: [&'static [(&'static str, Binary)]; 10] = [
+        &[("||", Binary::LogicalOr)],
+        &[("&&", Binary::LogicalAnd)],
+        &[("|", Binary::Or)],
+        &[("^", Binary::Xor)],
+        &[("&", Binary::And)],
+        &[("==", Binary::Equal), ("!=", Binary::NotEqual)],
+        &[
+            ("<=", Binary::LessOrEqual),
+            (">=", Binary::GreaterOrEqual),
+            ("<", Binary::Less),
+            (">", Binary::Greater),
+        ],
+        &[("<<", Binary::ShiftLeft), (">>", Binary::ShiftRight)],
+        &[("+", Binary::Add), ("-", Binary::Subtract)],
+        &[
+            ("*", Binary::Multiply),
+            ("/", Binary::Divide),
+            ("%", Binary::Remainder),
+        ],
+    ];
+
+    fn apply(self, left: u32, right: u32) -> u32 {
+        match self {
+            Binary::Multiply => left.wrapping_mul(right),
+            Binary::Divide => left.checked_div(right).unwrap_or_default(),
+            Binary::Remainder => left.checked_rem(right).unwrap_or_default(),
+            Binary::Add => left.wrapping_add(right),
+            Binary::Subtract => left.wrapping_sub(right),
+            Binary::ShiftLeft => left.checked_shl(right).unwrap_or_default(),
+            Binary::ShiftRight => left.checked_shr(right).unwrap_or_default(),
+            Binary::Less => u32::from(left < right),
+            Binary::LessOrEqual => u32::from(left <= right),
+            Binary::Greater => u32::from(left > right),
+            Binary::GreaterOrEqual => u32::from(left >= right),
+            Binary::Equal => u32::from(left == right),
+            Binary::NotEqual => u32::from(left != right),
+            Binary::And => left & right,
+            Binary::Xor => left ^ right,
+            Binary::Or => left | right,
+            Binary::LogicalAnd => u32::from(left != 0 && right != 0),
+            Binary::LogicalOr => u32::from(left != 0 || right != 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Number(u32),
+    Variable(Variable),
+    Memory { size: u8, address: Box<Node> },
+    Unary(Unary, Box<Node>),
+    Binary(Binary, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, environment: &dyn Environment) -> u32 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Variable(variable) => environment.variable(*variable),
+            Node::Memory { size, address } => {
+                environment.read(address.evaluate(environment), *size)
+            }
+            Node::Unary(operator, operand) => {
+                let value = operand.evaluate(environment);
+                match operator {
+                    Unary::Negate => value.wrapping_neg(),
+                    Unary::Not => !value,
+                    Unary::LogicalNot => u32::from(value == 0),
+                }
+            }
+            // NOTE: `&&` and `||` short-circuit, so guarded memory reads are not evaluated
+            Node::Binary(Binary::LogicalAnd, left, right) => {
+                u32::from(left.evaluate(environment) != 0 && right.evaluate(environment) != 0)
+            }
+            Node::Binary(Binary::LogicalOr, left, right) => {
+                u32::from(left.evaluate(environment) != 0 || right.evaluate(environment) != 0)
+            }
+            Node::Binary(operator, left, right) => {
+                operator.apply(left.evaluate(environment), right.evaluate(environment))
+            }
+        }
+    }
+}
+
+/// A parsed expression, with its source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+        };
+        let root = parser.expression(0)?;
+        parser.skip_spaces();
+        if parser.position < source.len() {
+            return Err(parser.error("unexpected characters after the expression"));
+        }
+
+        Ok(Self {
+            source: source.trim().to_owned(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn evaluate(&self, environment: &dyn Environment) -> u32 {
+        self.root.evaluate(environment)
+    }
+
+    /// Whether the expression evaluates to a non zero value
+    pub fn is_true(&self, environment: &dyn Environment) -> bool {
+        self.evaluate(environment) != 0
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Recursive descent parser, one level per precedence
+struct Parser<'a> {
+    source: &'a str,
+    /// Byte offset of the next character
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &'static str) -> Error {
+        Error::Expression {
+            column: self.position + 1,
+            reason,
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `token` when it comes next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_spaces();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str, reason: &'static str) -> Result<(), Error> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(reason))
+        }
+    }
+
+    fn expression(&mut self, level: usize) -> Result<Node, Error> {
+        let Some(operators) = Binary::LEVELS.get(level) else {
+            return self.unary();
+        };
+
+        let mut left = self.expression(level + 1)?;
+        'operators: loop {
+            for (token, operator) in *operators {
+                // NOTE: `|` and `&` must not eat the first character of `||` and `&&`
+                let doubled = matches!(*token, "|" | "&")
+                    && self.rest().trim_start().starts_with(&token.repeat(2));
+                if !doubled && self.eat(token) {
+                    let right = self.expression(level + 1)?;
+                    left = Node::Binary(*operator, Box::new(left), Box::new(right));
+                    continue 'operators;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, Error> {
+        let operator = if self.eat("-") {
+            Unary::Negate
+        } else if self.eat("~") {
+            Unary::Not
+        } else if self.rest().trim_start().starts_with("!=") {
+            return self.primary();
+        } else if self.eat("!") {
+            Unary::LogicalNot
+        } else {
+            return self.primary();
+        };
+        Ok(Node::Unary(operator, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Node, Error> {
+        self.skip_spaces();
+        if self.eat("(") {
+            let node = self.expression(0)?;
+            self.expect(")", "missing closing parenthesis")?;
+            return Ok(node);
+        }
+        if self.eat("[") {
+            return self.memory(4);
+        }
+
+        let start = self.position;
+        let length = self
+            .rest()
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(self.rest().len());
+        if length == 0 {
+            return Err(self.error("expected a number, a variable or a memory read"));
+        }
+        let word = self.source[start..start + length].to_ascii_lowercase();
+        self.position += length;
+
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            let value = parse_number(&word).ok_or(Error::Expression {
+                column: start + 1,
+                reason: "invalid number",
+            })?;
+            return Ok(Node::Number(value));
+        }
+        match word.as_str() {
+            "u8" | "u16" if self.eat("[") => self.memory(if word == "u8" { 1 } else { 2 }),
+            _ => Variable::from_name(&word)
+                .map(Node::Variable)
+                .ok_or(Error::Expression {
+                    column: start + 1,
+                    reason: "unknown variable",
+                }),
+        }
+    }
+
+    /// Memory read, after the opening bracket
+    fn memory(&mut self, size: u8) -> Result<Node, Error> {
+        let address = self.expression(0)?;
+        self.expect("]", "missing closing bracket")?;
+        Ok(Node::Memory {
+            size,
+            address: Box::new(address),
+        })
+    }
+}
+
+fn parse_number(word: &str) -> Option<u32> {
+    if let Some(hexadecimal) = word.strip_prefix("0x") {
+        u32::from_str_radix(hexadecimal, 16).ok()
+    } else if let Some(binary) = word.strip_prefix("0b") {
+        u32::from_str_radix(binary, 2).ok()
+    } else {
+        word.parse().ok()
+    }
+}
@@ -0,0 +1,162 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+/// Sent by GDB out of packets to interrupt the target (Ctrl-C)
+const INTERRUPT: u8 = 0x03;
+
+/// Something received from GDB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incoming {
+    Packet(Vec<u8>),
+    Interrupt,
+}
+
+/// Packet framing of the Remote Serial Protocol, `$data#checksum`
+/// <https://sourceware.org/gdb/current/onlinedocs/gdb.html/Overview.html>
+pub struct Connection {
+    stream: TcpStream,
+    /// Acknowledgments are disabled by `QStartNoAckMode`
+    no_ack: bool,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            no_ack: false,
+        }
+    }
+
+    pub fn disable_ack(&mut self) {
+        self.no_ack = true;
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Wait for the next packet or interrupt, `None` once GDB disconnected
+    pub fn receive(&mut self) -> io::Result<Option<Incoming>> {
+        loop {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(INTERRUPT) => return Ok(Some(Incoming::Interrupt)),
+                Some(b'$') => {}
+                // NOTE: Acknowledgments of our packets and noise between packets
+                Some(_) => continue,
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(byte) => data.push(byte),
+                }
+            }
+            let mut checksum = [0; 2];
+            self.stream.read_exact(&mut checksum)?;
+
+            let expected = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+            if self.no_ack {
+                return Ok(Some(Incoming::Packet(data)));
+            }
+            if expected == Some(checksum_of(&data)) {
+                self.stream.write_all(b"+")?;
+                return Ok(Some(Incoming::Packet(data)));
+            }
+            self.stream.write_all(b"-")?;
+        }
+    }
+
+    /// Whether GDB asked to interrupt the target, without blocking
+    pub fn poll_interrupt(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut byte = [0];
+        let received = match self.stream.read(&mut byte) {
+            Ok(1) => byte[0] == INTERRUPT,
+            Ok(_) => false,
+            Err(error) if error.kind() == ErrorKind::WouldBlock => false,
+            Err(error) => {
+                self.stream.set_nonblocking(false)?;
+                return Err(error);
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        Ok(received)
+    }
+
+    /// Send a packet, special characters of `data` are escaped
+    pub fn send(&mut self, data: &[u8]) -> io::Result<()> {
+        let mut packet = Vec::with_capacity(data.len() + 4);
+        packet.push(b'$');
+        for byte in data {
+            if matches!(byte, b'$' | b'#' | b'}' | b'*') {
+                packet.extend_from_slice(&[b'}', byte ^ 0x20]);
+            } else {
+                packet.push(*byte);
+            }
+        }
+        let checksum = checksum_of(&packet[1..]);
+        packet.extend_from_slice(format!("#{checksum:02x}").as_bytes());
+
+        loop {
+            self.stream.write_all(&packet)?;
+            if self.no_ack || self.read_byte()? != Some(b'-') {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// Binary data of `X` packets, `}` escapes the next byte
+pub fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(data.len());
+    let mut escaped = false;
+    for byte in data {
+        if escaped {
+            bytes.push(byte ^ 0x20);
+            escaped = false;
+        } else if *byte == b'}' {
+            escaped = true;
+        } else {
+            bytes.push(*byte);
+        }
+    }
+    bytes
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn decode_hex(digits: &[u8]) -> Option<Vec<u8>> {
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect()
+}
+
+pub fn parse_hex(digits: &[u8]) -> Option<u32> {
+    std::str::from_utf8(digits)
+        .ok()
+        .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+}
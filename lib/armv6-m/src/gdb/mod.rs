@@ -0,0 +1,684 @@
+//! GDB Remote Serial Protocol server, so `target remote localhost:<port>` can debug the core.
+//! <https://sourceware.org/gdb/current/onlinedocs/gdb.html/Remote-Protocol.html>
+
+use std::collections::BTreeMap;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use connection::{decode_hex, encode_hex, parse_hex, unescape, Connection, Incoming};
+
+use crate::abi::{Runtime, RuntimeExtras, StopReason};
+use crate::breakpoint::Breakpoint;
+use crate::error::Error;
+use crate::expression::Expression;
+use crate::mutation::{MemoryChange, Mutation, RegisterChange};
+use crate::structure::RegisterId;
+use crate::watchpoint::{WatchKind, Watchpoint};
+use crate::Armv6M;
+
+mod connection;
+
+/// Registers described to GDB, `target.xml` gives their names and numbers
+const TARGET_XML: &str = include_str!("target.xml");
+const REGISTER_COUNT: usize = 21;
+
+/// Largest packet accepted from GDB, memory reads are capped to fit in a reply
+const PACKET_SIZE: usize = 0x4000;
+
+/// Steps executed between two checks for a Ctrl-C from GDB
+const INTERRUPT_POLL_INTERVAL: usize = 4096;
+
+const SIGINT: u8 = 2;
+const SIGTRAP: u8 = 5;
+const SIGSEGV: u8 = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakpointKind {
+    Software,
+    Hardware,
+}
+
+/// Why the target stopped, as reported to GDB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    Signal(u8),
+    Breakpoint(BreakpointKind),
+    Watchpoint(WatchKind, u32),
+    /// Reverse execution reached the start of the history
+    HistoryStart,
+}
+
+impl Stop {
+    fn reply(&self) -> Vec<u8> {
+        match self {
+            Stop::Signal(signal) => format!("S{signal:02x}"),
+            Stop::Breakpoint(BreakpointKind::Software) => format!("T{SIGTRAP:02x}swbreak:;"),
+            Stop::Breakpoint(BreakpointKind::Hardware) => format!("T{SIGTRAP:02x}hwbreak:;"),
+            Stop::Watchpoint(kind, address) => {
+                let name = match kind {
+                    WatchKind::Write => "watch",
+                    WatchKind::Read => "rwatch",
+                    WatchKind::Access => "awatch",
+                };
+                format!("T{SIGTRAP:02x}{name}:{address:08x};")
+            }
+            Stop::HistoryStart => format!("T{SIGTRAP:02x}replaylog:begin;"),
+        }
+        .into_bytes()
+    }
+}
+
+/// What to do once a packet has been handled
+enum Action {
+    Reply(Vec<u8>),
+    /// Reply, then close the connection
+    Detach(Vec<u8>),
+    /// Close the connection without replying
+    Kill,
+}
+
+/// Debug session over the GDB Remote Serial Protocol.
+///
+/// Registers and memory written by GDB are recorded as mutations, like the steps of the core.
+/// Peripheral registers are read through `Peripheral::peek` so GDB has no side effects on them,
+/// a read stops short at the first register that cannot be peeked. Writes only reach the memory.
+///
+/// Reverse execution (`reverse-stepi`, `reverse-continue`) rolls the mutations history back
+/// and stops at the breakpoints of GDB and at the ones of the core whose condition holds.
+/// Peripherals states are not rolled back, GDB is warned about it on the first reverse execution.
+///
+/// Watchpoints are the ones of the core, they are shared with the Rust API.
+/// Breakpoints with a condition or an ignore count are the ones of the core too,
+/// they are set with `monitor break <address> if <condition>` as GDB evaluates its own ones.
+pub struct GdbServer<'a> {
+    vm: &'a mut Armv6M,
+    breakpoints: BTreeMap<u32, BreakpointKind>,
+    last_stop: Stop,
+    warned_peripherals: bool,
+}
+
+impl<'a> GdbServer<'a> {
+    pub fn new(vm: &'a mut Armv6M) -> Self {
+        Self {
+            vm,
+            breakpoints: BTreeMap::new(),
+            last_stop: Stop::Signal(SIGTRAP),
+            warned_peripherals: false,
+        }
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (u32, BreakpointKind)> + '_ {
+        self.breakpoints
+            .iter()
+            .map(|(address, kind)| (*address, *kind))
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        self.vm.watchpoints()
+    }
+
+    /// Wait for GDB on `address`, then serve it until it detaches or kills the target
+    pub fn listen(&mut self, address: impl ToSocketAddrs) -> Result<(), Error> {
+        let listener = TcpListener::bind(address)?;
+        let (stream, _) = listener.accept()?;
+        self.serve(stream)
+    }
+
+    /// Serve an already connected GDB until it detaches or kills the target
+    pub fn serve(&mut self, stream: TcpStream) -> Result<(), Error> {
+        stream.set_nodelay(true)?;
+        let mut connection = Connection::new(stream);
+
+        while let Some(incoming) = connection.receive()? {
+            let packet = match incoming {
+                Incoming::Packet(packet) => packet,
+                // NOTE: The target is already stopped
+                Incoming::Interrupt => {
+                    self.last_stop = Stop::Signal(SIGINT);
+                    connection.send(&self.last_stop.reply())?;
+                    continue;
+                }
+            };
+
+            match self.handle(&packet, &mut connection)? {
+                Action::Reply(reply) => connection.send(&reply)?,
+                Action::Detach(reply) => {
+                    connection.send(&reply)?;
+                    return Ok(());
+                }
+                Action::Kill => return Ok(()),
+            }
+            if packet == b"QStartNoAckMode" {
+                connection.disable_ack();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle(&mut self, packet: &[u8], connection: &mut Connection) -> Result<Action, Error> {
+        let ok = || Action::Reply(b"OK".to_vec());
+        let error = || Action::Reply(b"E01".to_vec());
+        let (command, arguments) = packet.split_first().unwrap_or((&0, &[]));
+
+        let action = match command {
+            b'?' => Action::Reply(self.last_stop.reply()),
+            b'g' => Action::Reply(
+                encode_hex(
+                    &(0..REGISTER_COUNT)
+                        .filter_map(|n| self.read_gdb_register(n))
+                        .flat_map(u32::to_le_bytes)
+                        .collect::<Vec<_>>(),
+                )
+                .into_bytes(),
+            ),
+            b'G' => match decode_hex(arguments) {
+                Some(bytes) => {
+                    let values = bytes
+                        .as_chunks::<4>()
+                        .0
+                        .iter()
+                        .take(REGISTER_COUNT)
+                        .enumerate()
+                        .flat_map(|(n, value)| {
+                            self.gdb_register_targets(n, u32::from_le_bytes(*value))
+                        })
+                        .collect::<Vec<_>>();
+                    self.write_registers(&values)?;
+                    ok()
+                }
+                None => error(),
+            },
+            b'p' => match parse_hex(arguments).and_then(|n| self.read_gdb_register(n as usize)) {
+                Some(value) => Action::Reply(encode_hex(&value.to_le_bytes()).into_bytes()),
+                None => error(),
+            },
+            b'P' => {
+                let register = arguments
+                    .splitn(2, |byte| *byte == b'=')
+                    .collect::<Vec<_>>();
+                match register[..] {
+                    [n, value] => match (parse_hex(n), decode_hex(value)) {
+                        (Some(n), Some(value)) if value.len() == 4 => {
+                            let value =
+                                u32::from_le_bytes([value[0], value[1], value[2], value[3]]);
+                            let values = self.gdb_register_targets(n as usize, value);
+                            self.write_registers(&values)?;
+                            ok()
+                        }
+                        _ => error(),
+                    },
+                    _ => error(),
+                }
+            }
+            b'm' => match parse_range(arguments) {
+                Some((address, length)) => {
+                    let bytes = self.read_memory(address, length.min(PACKET_SIZE as u32 / 2));
+                    if bytes.is_empty() && length != 0 {
+                        error()
+                    } else {
+                        Action::Reply(encode_hex(&bytes).into_bytes())
+                    }
+                }
+                None => error(),
+            },
+            b'M' | b'X' => {
+                let split = arguments.iter().position(|byte| *byte == b':');
+                let (range, data) = split.map_or((arguments, &[][..]), |split| {
+                    (&arguments[..split], &arguments[split + 1..])
+                });
+                let bytes = if *command == b'M' {
+                    decode_hex(data)
+                } else {
+                    Some(unescape(data))
+                };
+                match (parse_range(range), bytes) {
+                    (Some((address, length)), Some(bytes)) if bytes.len() == length as usize => {
+                        self.write_memory(address, &bytes)?;
+                        ok()
+                    }
+                    _ => error(),
+                }
+            }
+            b's' | b'c' => {
+                if let Some(address) = parse_hex(arguments) {
+                    self.write_registers(&[(RegisterId::Pc, address)])?;
+                }
+                self.resume(connection, *command == b's')?
+            }
+            b'b' => match arguments {
+                b"s" => self.reverse(connection, true)?,
+                b"c" => self.reverse(connection, false)?,
+                _ => Action::Reply(Vec::new()),
+            },
+            b'Z' | b'z' => self.update_point(*command == b'Z', arguments),
+            b'D' => Action::Detach(b"OK".to_vec()),
+            b'k' => Action::Kill,
+            b'H' | b'T' => ok(),
+            b'q' | b'Q' | b'v' => self.handle_query(packet, connection)?,
+            _ => Action::Reply(Vec::new()),
+        };
+
+        Ok(action)
+    }
+
+    /// Packets made of a name, general queries and `v` packets
+    fn handle_query(
+        &mut self,
+        packet: &[u8],
+        connection: &mut Connection,
+    ) -> Result<Action, Error> {
+        let ok = || Action::Reply(b"OK".to_vec());
+        let reply = |reply: &str| Action::Reply(reply.as_bytes().to_vec());
+
+        let action = if packet.starts_with(b"qSupported") {
+            reply(&format!(
+                "PacketSize={PACKET_SIZE:x};qXfer:features:read+;swbreak+;hwbreak+;QStartNoAckMode+;vContSupported+;ReverseStep+;ReverseContinue+"
+            ))
+        } else if packet == b"QStartNoAckMode" || packet.starts_with(b"qSymbol") {
+            ok()
+        } else if let Some(range) = packet.strip_prefix(b"qXfer:features:read:target.xml:") {
+            match parse_range(range) {
+                Some((offset, length)) => {
+                    let xml = TARGET_XML.as_bytes();
+                    let start = (offset as usize).min(xml.len());
+                    let end = start.saturating_add(length as usize).min(xml.len());
+                    let marker = if end == xml.len() { b'l' } else { b'm' };
+                    Action::Reply([&[marker], &xml[start..end]].concat())
+                }
+                None => reply("E01"),
+            }
+        } else if packet == b"qAttached" {
+            reply("1")
+        } else if packet == b"qC" {
+            reply("QC1")
+        } else if packet == b"qfThreadInfo" {
+            reply("m1")
+        } else if packet == b"qsThreadInfo" {
+            reply("l")
+        } else if let Some(command) = packet.strip_prefix(b"qRcmd,") {
+            match decode_hex(command).map(String::from_utf8) {
+                Some(Ok(command)) => self.monitor(&command, connection)?,
+                _ => reply("E01"),
+            }
+        } else if packet == b"vCont?" {
+            reply("vCont;c;C;s;S")
+        } else if let Some(actions) = packet.strip_prefix(b"vCont;") {
+            // NOTE: There is a single thread, the first action applies to it
+            match actions.first() {
+                Some(b's' | b'S') => self.resume(connection, true)?,
+                Some(b'c' | b'C') => self.resume(connection, false)?,
+                _ => reply("E01"),
+            }
+        } else if packet.starts_with(b"vKill") {
+            Action::Detach(b"OK".to_vec())
+        } else {
+            Action::Reply(Vec::new())
+        };
+
+        Ok(action)
+    }
+
+    /// `monitor` commands:
+    /// - `reset` restarts from the reset vector
+    /// - `break <address> [if <condition>]` sets a breakpoint of the core, see `Expression`
+    /// - `ignore <address> <count>` skips the next `count` times its condition holds
+    /// - `delete <address>` removes a breakpoint of the core
+    /// - `breakpoints` lists them
+    /// - `backtrace` unwinds the stack of the core, exception frames included
+    /// - `print <variable>` shows a local of the current function or a global, from the DWARF types
+    /// - `locals` shows the parameters and locals of the current function
+    fn monitor(&mut self, command: &str, connection: &mut Connection) -> Result<Action, Error> {
+        let (name, arguments) = command
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((command.trim(), ""));
+        let address = |argument: &str| {
+            let argument = argument.trim();
+            match argument.strip_prefix("0x") {
+                Some(hexadecimal) => u32::from_str_radix(hexadecimal, 16).ok(),
+                None => argument.parse().ok(),
+            }
+        };
+
+        let output = match name {
+            "reset" => {
+                self.vm.reset();
+                self.last_stop = Stop::Signal(SIGTRAP);
+                String::new()
+            }
+            "break" => {
+                let (location, condition) = arguments
+                    .split_once(" if ")
+                    .map_or((arguments, None), |(location, condition)| {
+                        (location, Some(condition))
+                    });
+                let Some(address) = address(location) else {
+                    return Ok(Action::Reply(b"E01".to_vec()));
+                };
+                let mut breakpoint = Breakpoint::new(address);
+                if let Some(condition) = condition {
+                    match Expression::parse(condition) {
+                        Ok(condition) => breakpoint = breakpoint.with_condition(condition),
+                        Err(error) => {
+                            connection.send(&output_packet(&format!("{error}\n")))?;
+                            return Ok(Action::Reply(b"E01".to_vec()));
+                        }
+                    }
+                }
+                self.vm.add_breakpoint(breakpoint);
+                String::new()
+            }
+            "ignore" => {
+                let (location, count) = arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+                let breakpoint =
+                    address(location).and_then(|address| self.vm.breakpoint_mut(address));
+                match (breakpoint, count.trim().parse()) {
+                    (Some(breakpoint), Ok(count)) => breakpoint.ignore_count = count,
+                    _ => return Ok(Action::Reply(b"E01".to_vec())),
+                }
+                String::new()
+            }
+            "delete" => {
+                match address(arguments).and_then(|address| self.vm.remove_breakpoint(address)) {
+                    Some(_) => String::new(),
+                    None => return Ok(Action::Reply(b"E01".to_vec())),
+                }
+            }
+            "backtrace" => self.vm.backtrace().to_string(),
+            "print" => match self.vm.variable(arguments.trim()) {
+                Some(value) => format!("{value}\n"),
+                None => return Ok(Action::Reply(b"E01".to_vec())),
+            },
+            "locals" => self
+                .vm
+                .locals()
+                .iter()
+                .map(|value| format!("{value}\n"))
+                .collect(),
+            "breakpoints" => self
+                .vm
+                .breakpoints()
+                .map(|breakpoint| {
+                    let condition = breakpoint
+                        .condition
+                        .as_ref()
+                        .map_or(String::new(), |condition| format!(" if {condition}"));
+                    format!(
+                        "{:#010x}{condition}, hits {}, ignore {}\n",
+                        breakpoint.address, breakpoint.hits, breakpoint.ignore_count
+                    )
+                })
+                .collect(),
+            _ => return Ok(Action::Reply(Vec::new())),
+        };
+
+        if !output.is_empty() {
+            connection.send(&output_packet(&output))?;
+        }
+        Ok(Action::Reply(b"OK".to_vec()))
+    }
+
+    /// Register `n` of `target.xml`
+    fn read_gdb_register(&self, n: usize) -> Option<u32> {
+        let value = match n {
+            0..=12 => self.vm.read_register(RegisterId::ALL[n]),
+            13 => self.vm.get_sp(),
+            14 => self.vm.get_lr(),
+            15 => self.vm.get_pc(),
+            16 => self.vm.get_xpsr(),
+            17 => self.vm.read_register(RegisterId::SpMain),
+            18 => self.vm.read_register(RegisterId::SpProcess),
+            19 => self.vm.read_register(RegisterId::Primask),
+            20 => self.vm.read_register(RegisterId::Control),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Core registers written when GDB writes its register `n`
+    fn gdb_register_targets(&self, n: usize, value: u32) -> Vec<(RegisterId, u32)> {
+        let register = match n {
+            0..=12 => RegisterId::ALL[n],
+            13 => self.vm.sp_register(),
+            14 => RegisterId::Lr,
+            15 => RegisterId::Pc,
+            16 => {
+                return vec![
+                    (RegisterId::Apsr, value),
+                    (RegisterId::Ipsr, value),
+                    (RegisterId::Epsr, value),
+                ]
+            }
+            17 => RegisterId::SpMain,
+            18 => RegisterId::SpProcess,
+            19 => RegisterId::Primask,
+            20 => RegisterId::Control,
+            _ => return Vec::new(),
+        };
+        vec![(register, value)]
+    }
+
+    fn write_registers(&mut self, values: &[(RegisterId, u32)]) -> Result<(), Error> {
+        let mutation = Mutation {
+            address: self.vm.get_pc(),
+            cycles: 0,
+            registers: values
+                .iter()
+                .map(|(register, new)| RegisterChange {
+                    register: *register,
+                    old: self.vm.read_register(*register),
+                    new: *new,
+                })
+                .collect(),
+            memory: Vec::new(),
+            debugger: true,
+        };
+        self.vm.apply_mutation(mutation)?;
+        Ok(())
+    }
+
+    /// Read `length` bytes like the core would, without side effects.
+    /// Aligned words are read at once, as peripheral registers may not allow narrower accesses.
+    fn read_memory(&self, address: u32, length: u32) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(length as usize);
+        while (bytes.len() as u32) < length {
+            let at = address.wrapping_add(bytes.len() as u32);
+            let size = if at.is_multiple_of(4) && length - bytes.len() as u32 >= 4 {
+                4
+            } else {
+                1
+            };
+            let Some(value) = self.vm.peek(at, size) else {
+                break;
+            };
+            bytes.extend_from_slice(&value.to_le_bytes()[..usize::from(size)]);
+        }
+        bytes
+    }
+
+    fn write_memory(&mut self, address: u32, bytes: &[u8]) -> Result<(), Error> {
+        let memory = self.vm.get_memory();
+        let mutation = Mutation {
+            address: self.vm.get_pc(),
+            cycles: 0,
+            registers: Vec::new(),
+            memory: bytes
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| {
+                    let address = address.wrapping_add(i as u32);
+                    MemoryChange {
+                        address,
+                        size: 1,
+                        old: u32::from(memory.read_u8(address)),
+                        new: u32::from(*byte),
+                    }
+                })
+                .collect(),
+            debugger: true,
+        };
+        self.vm.apply_mutation(mutation)?;
+        Ok(())
+    }
+
+    /// `Z`/`z` packets, `type,address,kind` where kind is the length of watchpoints
+    fn update_point(&mut self, insert: bool, arguments: &[u8]) -> Action {
+        let fields = arguments.split(|byte| *byte == b',').collect::<Vec<_>>();
+        let [kind, address, length] = fields[..] else {
+            return Action::Reply(b"E01".to_vec());
+        };
+        let (Some(address), Some(length)) = (parse_hex(address), parse_hex(length)) else {
+            return Action::Reply(b"E01".to_vec());
+        };
+
+        let breakpoint = match kind {
+            b"0" => Some(BreakpointKind::Software),
+            b"1" => Some(BreakpointKind::Hardware),
+            _ => None,
+        };
+        let watch = match kind {
+            b"2" => Some(WatchKind::Write),
+            b"3" => Some(WatchKind::Read),
+            b"4" => Some(WatchKind::Access),
+            _ => None,
+        };
+
+        match (breakpoint, watch) {
+            (Some(breakpoint), _) => {
+                if insert {
+                    self.breakpoints.insert(address, breakpoint);
+                } else {
+                    self.breakpoints.remove(&address);
+                }
+            }
+            (_, Some(kind)) => {
+                let watchpoint = Watchpoint::new(kind, address, length);
+                if insert {
+                    self.vm.add_watchpoint(watchpoint);
+                } else {
+                    self.vm.remove_watchpoint(&watchpoint);
+                }
+            }
+            (None, None) => return Action::Reply(Vec::new()),
+        }
+        Action::Reply(b"OK".to_vec())
+    }
+
+    /// Write watchpoint hit by a mutation, reads are not recorded in the history
+    fn written_watchpoint(&self, mutation: &Mutation) -> Option<Stop> {
+        mutation.memory.iter().find_map(|change| {
+            self.vm
+                .watchpoints()
+                .iter()
+                .find(|watchpoint| {
+                    watchpoint.kind != WatchKind::Read
+                        && watchpoint.overlaps(change.address, change.size)
+                        && watchpoint.value.is_none_or(|value| value == change.new)
+                })
+                .map(|watchpoint| Stop::Watchpoint(watchpoint.kind, change.address))
+        })
+    }
+
+    /// Roll back one instruction or until a breakpoint, a write to a watchpoint or a Ctrl-C.
+    /// Registers and memory written by GDB are rolled back on the way, they do not count as a step.
+    /// Rolled back mutations leave the history, executing forward records them again.
+    fn reverse(&mut self, connection: &mut Connection, single_step: bool) -> Result<Action, Error> {
+        if !self.warned_peripherals && self.vm.peripherals().next().is_some() {
+            connection.send(&output_packet(
+                "warning: peripherals states are not rolled back\n",
+            ))?;
+            self.warned_peripherals = true;
+        }
+        let mut steps = 0usize;
+
+        let stop = loop {
+            let Some(mutation) = self.vm.get_mutations_history().last() else {
+                break Stop::HistoryStart;
+            };
+            let debugger = mutation.debugger;
+            let watchpoint = self.written_watchpoint(mutation);
+            self.vm.rollback_last_mutation()?;
+
+            if debugger {
+                continue;
+            }
+            if let Some(stop) = watchpoint {
+                break stop;
+            }
+            if single_step {
+                break Stop::Signal(SIGTRAP);
+            }
+            if let Some(stop) = self.reverse_breakpoint() {
+                break stop;
+            }
+
+            steps += 1;
+            if steps.is_multiple_of(INTERRUPT_POLL_INTERVAL) && connection.poll_interrupt()? {
+                break Stop::Signal(SIGINT);
+            }
+        };
+
+        self.last_stop = stop;
+        Ok(Action::Reply(stop.reply()))
+    }
+
+    /// Breakpoint of GDB at the PC, or one of the core whose condition holds.
+    /// Executing backward, the hits and ignore counts of the core breakpoints are left alone.
+    fn reverse_breakpoint(&self) -> Option<Stop> {
+        let pc = self.vm.get_pc();
+        if let Some(kind) = self.breakpoints.get(&pc) {
+            return Some(Stop::Breakpoint(*kind));
+        }
+        self.vm
+            .breakpoints()
+            .any(|breakpoint| breakpoint.address == pc && breakpoint.holds(self.vm))
+            .then_some(Stop::Signal(SIGTRAP))
+    }
+
+    /// Step once or until a breakpoint, a watchpoint, a stop of the core or a Ctrl-C
+    fn resume(&mut self, connection: &mut Connection, single_step: bool) -> Result<Action, Error> {
+        // NOTE: GDB resumes from where the target stopped, a breakpoint there does not stop it again
+        self.vm.resume_over_breakpoint();
+        let mut steps = 0usize;
+
+        let stop = loop {
+            match self.vm.step() {
+                Ok(None) => {}
+                Ok(Some(
+                    StopReason::Breakpoint(_) | StopReason::Sleeping | StopReason::SystemOff,
+                )) => break Stop::Signal(SIGTRAP),
+                Ok(Some(StopReason::Watchpoint { kind, address, .. })) => {
+                    break Stop::Watchpoint(kind, address)
+                }
+                // NOTE: A lockup leaves the core unusable, like a segmentation fault
+                Err(_) => break Stop::Signal(SIGSEGV),
+            }
+
+            if single_step {
+                break Stop::Signal(SIGTRAP);
+            }
+            if let Some(kind) = self.breakpoints.get(&self.vm.get_pc()) {
+                break Stop::Breakpoint(*kind);
+            }
+
+            steps += 1;
+            if steps.is_multiple_of(INTERRUPT_POLL_INTERVAL) && connection.poll_interrupt()? {
+                break Stop::Signal(SIGINT);
+            }
+        };
+
+        self.last_stop = stop;
+        Ok(Action::Reply(stop.reply()))
+    }
+}
+
+/// Console output of a `monitor` command, sent before its final reply
+fn output_packet(text: &str) -> Vec<u8> {
+    format!("O{}", encode_hex(text.as_bytes())).into_bytes()
+}
+
+/// `address,length` in hexadecimal
+fn parse_range(range: &[u8]) -> Option<(u32, u32)> {
+    let split = range.iter().position(|byte| *byte == b',')?;
+    Some((parse_hex(&range[..split])?, parse_hex(&range[split + 1..])?))
+}
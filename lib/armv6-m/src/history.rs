@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::memory::{Memory, Page, PAGE_SIZE};
+use crate::mutation::Mutation;
+
+/// How often the memory is checkpointed and how much memory checkpoints may keep alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointConfig {
+    /// Number of executed instructions between two checkpoints, 0 disables checkpoints
+    pub interval: usize,
+    /// Bytes of pages that checkpoints may keep alive on top of the current memory,
+    /// older checkpoints are thinned out when it is exceeded
+    pub budget: usize,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            interval: 10_000,
+            budget: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Memory as it was after the first `index` mutations
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    index: usize,
+    memory: Memory,
+}
+
+/// Mutations recorded since the program was loaded, with periodic memory checkpoints.
+///
+/// A past memory is rebuilt from the closest checkpoint (or from the current memory),
+/// replaying or rolling back the memory writes in between.
+/// Checkpoints are copy-on-write clones of the memory, they only cost the pages modified since.
+#[derive(Debug, Clone)]
+pub struct History {
+    mutations: Vec<Mutation>,
+    /// Sorted by index, the first one is always the initial memory
+    checkpoints: Vec<Checkpoint>,
+    config: CheckpointConfig,
+    /// Instructions executed since the latest checkpoint
+    steps: usize,
+}
+
+impl History {
+    pub fn new(config: CheckpointConfig, initial: &Memory) -> Self {
+        Self {
+            mutations: Vec::new(),
+            checkpoints: vec![Checkpoint {
+                index: 0,
+                memory: initial.clone(),
+            }],
+            config,
+            steps: 0,
+        }
+    }
+
+    /// Forget every mutation, `initial` becomes the memory at index 0
+    pub fn reset(&mut self, initial: &Memory) {
+        *self = Self::new(self.config, initial);
+    }
+
+    pub fn config(&self) -> CheckpointConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: CheckpointConfig, current: &Memory) {
+        self.config = config;
+        self.enforce_budget(current);
+    }
+
+    pub fn mutations(&self) -> &[Mutation] {
+        &self.mutations
+    }
+
+    pub fn len(&self) -> usize {
+        self.mutations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mutations.is_empty()
+    }
+
+    /// Indexes of the checkpoints currently kept
+    pub fn checkpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.checkpoints.iter().map(|checkpoint| checkpoint.index)
+    }
+
+    /// Record a mutation that was not made by an instruction, it does not count towards the checkpoint interval
+    pub fn push(&mut self, mutation: Mutation) {
+        self.mutations.push(mutation);
+    }
+
+    /// Record an executed instruction, `current` is the memory once its mutation has been applied.
+    /// Instructions without side effects are counted but not recorded.
+    pub fn step(&mut self, mutation: Mutation, current: &Memory) {
+        if !mutation.is_empty() {
+            self.mutations.push(mutation);
+        }
+
+        self.steps += 1;
+        if self.config.interval == 0 || self.steps < self.config.interval {
+            return;
+        }
+        self.steps = 0;
+
+        let index = self.mutations.len();
+        if self.checkpoints.last().map(|checkpoint| checkpoint.index) != Some(index) {
+            self.checkpoints.push(Checkpoint {
+                index,
+                memory: current.clone(),
+            });
+            self.enforce_budget(current);
+        }
+    }
+
+    /// Remove the last mutation, the caller is in charge of rolling it back
+    pub fn pop(&mut self) -> Option<Mutation> {
+        let mutation = self.mutations.pop()?;
+
+        let len = self.mutations.len();
+        self.checkpoints
+            .retain(|checkpoint| checkpoint.index <= len);
+
+        Some(mutation)
+    }
+
+    /// Memory after the first `idx` mutations, `current` being the memory after all of them
+    pub fn memory_at(&self, idx: usize, current: &Memory) -> Option<Memory> {
+        let len = self.mutations.len();
+        if idx > len {
+            return None;
+        }
+
+        // NOTE: Closest checkpoints around idx, the first one is at 0 and the current memory acts as the last one
+        let split = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.index <= idx);
+        let before = &self.checkpoints[split - 1];
+        let (after_index, after_memory) = self
+            .checkpoints
+            .get(split)
+            .map_or((len, current), |checkpoint| {
+                (checkpoint.index, &checkpoint.memory)
+            });
+
+        if idx - before.index <= after_index - idx {
+            let mut memory = before.memory.clone();
+            for mutation in &self.mutations[before.index..idx] {
+                mutation.apply_memory(&mut memory);
+            }
+            Some(memory)
+        } else {
+            let mut memory = after_memory.clone();
+            for mutation in self.mutations[idx..after_index].iter().rev() {
+                mutation.rollback_memory(&mut memory);
+            }
+            Some(memory)
+        }
+    }
+
+    /// Bytes of pages kept alive by checkpoints and not shared with the current memory
+    pub fn retained_bytes(&self, current: &Memory) -> usize {
+        self.retained_pages(current).len() * PAGE_SIZE
+    }
+
+    /// Pages kept alive by checkpoints and not shared with the current memory,
+    /// with the number of checkpoints sharing each of them
+    fn retained_pages(&self, current: &Memory) -> HashMap<*const Page, usize> {
+        let live: HashSet<*const Page> =
+            current.pages().map(|(_, page)| Rc::as_ptr(page)).collect();
+
+        let mut retained = HashMap::new();
+        for checkpoint in &self.checkpoints {
+            for (_, page) in checkpoint.memory.pages() {
+                let page = Rc::as_ptr(page);
+                if !live.contains(&page) {
+                    *retained.entry(page).or_insert(0) += 1;
+                }
+            }
+        }
+        retained
+    }
+
+    /// Drop checkpoints until the budget is met, the initial and latest ones are always kept.
+    ///
+    /// The dropped checkpoint is the one leaving the smallest hole relative to its age,
+    /// so recent history stays dense while old history gets sparser.
+    fn enforce_budget(&mut self, current: &Memory) {
+        let mut retained = self.retained_pages(current);
+
+        while self.checkpoints.len() > 2 && retained.len() * PAGE_SIZE > self.config.budget {
+            let latest = self.checkpoints[self.checkpoints.len() - 1].index;
+            let victim = (1..self.checkpoints.len() - 1)
+                .min_by(|&a, &b| {
+                    let score = |i: usize| {
+                        let hole = self.checkpoints[i + 1].index - self.checkpoints[i - 1].index;
+                        let age = latest - self.checkpoints[i].index;
+                        (hole as u128, age as u128)
+                    };
+                    let ((hole_a, age_a), (hole_b, age_b)) = (score(a), score(b));
+                    // NOTE: hole_a / age_a < hole_b / age_b, oldest first on ties
+                    (hole_a * age_b).cmp(&(hole_b * age_a)).then(a.cmp(&b))
+                })
+                .unwrap_or(1);
+
+            // NOTE: Only the pages no other checkpoint shares are freed
+            for (_, page) in self.checkpoints.remove(victim).memory.pages() {
+                let page = Rc::as_ptr(page);
+                if let Some(owners) = retained.get_mut(&page) {
+                    *owners -= 1;
+                    if *owners == 0 {
+                        retained.remove(&page);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+use crate::alu::add_with_carry;
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/ADC--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adc {
+    pub rm: u8,
+    pub rdn: u8,
+}
+
+pub fn parse_adc(i: &[u8]) -> IResult<&'_ [u8], Adc> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100000101, 10u8),
+        (3u8, 3u8),
+        (rm, rdn),
+        Adc { rm, rdn }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Adc {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (result, carry, overflow) = add_with_carry(on.reg(self.rdn), on.reg(self.rm), on.carry());
+        on.set_reg(self.rdn, result);
+        on.set_nzcv(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl Disassemble for Adc {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("adcs\t{}, {}", register(self.rdn), register(self.rm))
+    }
+}
+
+impl Encode for Adc {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100000101, 10),
+            (self.rm, 3),
+            (self.rdn, 3)
+        )
+    }
+}
@@ -1,9 +1,14 @@
 use nom::{IResult, branch::alt, bits::bits, combinator::map, bits::streaming::{tag, take}, sequence::tuple};
 use nom::error::Error as NomError;
 
-use crate::{parse_bits, abi::MemoryMutation};
+use crate::abi::Execute;
+use crate::alu::add_with_carry;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Add {
     /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/ADD--immediate->
     ImmediateT1 {
@@ -105,21 +110,104 @@ pub fn parse_add(i: &[u8]) -> IResult<&'_ [u8], Add> {
             parse_immediate_t1,
             parse_immediate_t2,
             parse_register_t1,
+            // NOTE: ADD (register) T2 with SP as an operand is ADD (SP plus register),
+            // and its T2 encoding with SP as Rm is the T1 encoding
+            parse_sp_plus_register_t1,
+            parse_sp_plus_register_t2,
             parse_register_t2,
             parse_sp_plus_immediate_t1,
             parse_sp_plus_immediate_t2,
-            parse_sp_plus_register_t1,
-            parse_sp_plus_register_t2,
         ))
     )(i)
 }
 
-impl MemoryMutation<crate::Armv6M> for Add {
-    fn apply(&self, _on: &mut crate::Armv6M) {
-        todo!()
+impl Execute<Armv6M> for Add {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        match *self {
+            Add::ImmediateT1 { imm3, rn, rd } => {
+                let (result, carry, overflow) = add_with_carry(on.reg(rn), u32::from(imm3), false);
+                on.set_reg(rd, result);
+                on.set_nzcv(result, carry, overflow);
+            }
+            Add::ImmediateT2 { imm8, rdn } => {
+                let (result, carry, overflow) = add_with_carry(on.reg(rdn), u32::from(imm8), false);
+                on.set_reg(rdn, result);
+                on.set_nzcv(result, carry, overflow);
+            }
+            Add::RegisterT1 { rd, rn, rm } => {
+                let (result, carry, overflow) = add_with_carry(on.reg(rn), on.reg(rm), false);
+                on.set_reg(rd, result);
+                on.set_nzcv(result, carry, overflow);
+            }
+            Add::RegisterT2 { rdn, rm, dn } => {
+                let d = dn << 3 | rdn;
+                on.set_reg(d, on.reg(d).wrapping_add(on.reg(rm)));
+            }
+            Add::SpPlusImmediateT1 { imm8, rd } => {
+                on.set_reg(rd, on.reg(13).wrapping_add(u32::from(imm8) << 2));
+            }
+            Add::SpPlusImmediateT2 { imm7 } => {
+                on.set_reg(13, on.reg(13).wrapping_add(u32::from(imm7) << 2));
+            }
+            Add::SpPlusRegisterT1 { dm, rdm } => {
+                let d = dm << 3 | rdm;
+                on.set_reg(d, on.reg(13).wrapping_add(on.reg(d)));
+            }
+            Add::SpPlusRegisterT2 { rm } => {
+                on.set_reg(13, on.reg(13).wrapping_add(on.reg(rm)));
+            }
+        }
+        Ok(())
     }
+}
 
-    fn rollback(&self, _on: &mut crate::Armv6M) {
-        todo!()
+impl Disassemble for Add {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Add::ImmediateT1 { imm3, rn, rd } => {
+                format!("adds\t{}, {}, #{imm3}", register(rd), register(rn))
+            }
+            Add::ImmediateT2 { imm8, rdn } => format!("adds\t{}, #{imm8}", register(rdn)),
+            Add::RegisterT1 { rd, rn, rm } => {
+                format!("adds\t{}, {}, {}", register(rd), register(rn), register(rm))
+            }
+            Add::RegisterT2 { rdn, rm, dn } => {
+                format!("add\t{}, {}", register(dn << 3 | rdn), register(rm))
+            }
+            Add::SpPlusImmediateT1 { imm8, rd } => {
+                format!("add\t{}, sp, #{}", register(rd), u32::from(imm8) << 2)
+            }
+            Add::SpPlusImmediateT2 { imm7 } => format!("add\tsp, #{}", u32::from(imm7) << 2),
+            Add::SpPlusRegisterT1 { dm, rdm } => {
+                let rdm = register(dm << 3 | rdm);
+                format!("add\t{rdm}, sp, {rdm}")
+            }
+            Add::SpPlusRegisterT2 { rm } => format!("add\tsp, {}", register(rm)),
+        }
     }
-}
\ No newline at end of file
+}
+
+impl Encode for Add {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Add::ImmediateT1 { imm3, rn, rd } => encode_bits!((0b0001110, 7), (imm3, 3), (rn, 3), (rd, 3)),
+            Add::ImmediateT2 { imm8, rdn } => encode_bits!((0b00110, 5), (rdn, 3), (imm8, 8)),
+            Add::RegisterT1 { rd, rn, rm } => encode_bits!((0b0001100, 7), (rm, 3), (rn, 3), (rd, 3)),
+            Add::RegisterT2 { rdn, rm, dn } => {
+                if (dn << 3 | rdn) == 13 || rm == 13 {
+                    return Err(Error::Encoding("ADD (register) with SP is ADD (SP plus register)"));
+                }
+                encode_bits!((0b01000100, 8), (dn, 1), (rm, 4), (rdn, 3))
+            }
+            Add::SpPlusImmediateT1 { imm8, rd } => encode_bits!((0b10101, 5), (rd, 3), (imm8, 8)),
+            Add::SpPlusImmediateT2 { imm7 } => encode_bits!((0b101100000, 9), (imm7, 7)),
+            Add::SpPlusRegisterT1 { dm, rdm } => encode_bits!((0b01000100, 8), (dm, 1), (0b1101, 4), (rdm, 3)),
+            Add::SpPlusRegisterT2 { rm } => {
+                if rm == 13 {
+                    return Err(Error::Encoding("ADD SP, SP is ADD (SP plus register) T1"));
+                }
+                encode_bits!((0b010001001, 9), (rm, 4), (0b101, 3))
+            }
+        }
+    }
+}
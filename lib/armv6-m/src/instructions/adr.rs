@@ -0,0 +1,54 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/ADR>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adr {
+    pub rd: u8,
+    pub imm8: u8,
+}
+
+pub fn parse_adr(i: &[u8]) -> IResult<&'_ [u8], Adr> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b10100, 5u8),
+        (3u8, 8u8),
+        (rd, imm8),
+        Adr { rd, imm8 }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Adr {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let base = on.reg(15) & !0b11;
+        on.set_reg(self.rd, base.wrapping_add(u32::from(self.imm8) << 2));
+        Ok(())
+    }
+}
+
+impl Disassemble for Adr {
+    fn disassemble(&self, context: &Context<'_>) -> String {
+        let offset = u32::from(self.imm8) << 2;
+        // NOTE: objdump renders ADR as an ADD to the PC, with the resolved address in comment
+        let comment = context
+            .literal_address(offset)
+            .map(|address| format!("\t@ (adr {}, {})", register(self.rd), context.label(address)))
+            .unwrap_or_default();
+        format!("add\t{}, pc, #{offset}{comment}", register(self.rd))
+    }
+}
+
+impl Encode for Adr {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b10100, 5),
+            (self.rd, 3),
+            (self.imm8, 8)
+        )
+    }
+}
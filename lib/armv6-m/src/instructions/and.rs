@@ -0,0 +1,49 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/AND--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct And {
+    pub rm: u8,
+    pub rdn: u8,
+}
+
+pub fn parse_and(i: &[u8]) -> IResult<&'_ [u8], And> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100000000, 10u8),
+        (3u8, 3u8),
+        (rm, rdn),
+        And { rm, rdn }
+    ))(i)
+}
+
+impl Execute<Armv6M> for And {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let result = on.reg(self.rdn) & on.reg(self.rm);
+        on.set_reg(self.rdn, result);
+        on.set_nz(result);
+        Ok(())
+    }
+}
+
+impl Disassemble for And {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("ands\t{}, {}", register(self.rdn), register(self.rm))
+    }
+}
+
+impl Encode for And {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100000000, 10),
+            (self.rm, 3),
+            (self.rdn, 3)
+        )
+    }
+}
@@ -0,0 +1,77 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::alu::{asr_c, decode_imm_shift};
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asr {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/ASR--immediate->
+    ImmediateT1 {
+        imm5: u8,
+        rm: u8,
+        rd: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/ASR--register->
+    RegisterT1 {
+        rm: u8,
+        rdn: u8,
+    },
+}
+
+pub fn parse_asr(i: &[u8]) -> IResult<&'_ [u8], Asr> {
+    let parse_immediate_t1 = parse_bits!(
+        (0b00010, 5u8),
+        (5u8, 3u8, 3u8),
+        (imm5, rm, rd),
+        Asr::ImmediateT1 { imm5, rm, rd }
+    );
+    let parse_register_t1 = parse_bits!(
+        (0b0100000100, 10u8),
+        (3u8, 3u8),
+        (rm, rdn),
+        Asr::RegisterT1 { rm, rdn }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((parse_immediate_t1, parse_register_t1)))(i)
+}
+
+impl Execute<Armv6M> for Asr {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (rd, value, shift) = match *self {
+            Asr::ImmediateT1 { imm5, rm, rd } => (rd, on.reg(rm), decode_imm_shift(imm5)),
+            Asr::RegisterT1 { rm, rdn } => (rdn, on.reg(rdn), on.reg(rm) & 0xFF),
+        };
+
+        let (result, carry) = asr_c(value, shift, on.carry());
+        on.set_reg(rd, result);
+        on.set_nzc(result, carry);
+        Ok(())
+    }
+}
+
+impl Disassemble for Asr {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            // NOTE: A shift by 0 encodes a shift by 32
+            Asr::ImmediateT1 { imm5, rm, rd } => {
+                let shift = if imm5 == 0 { 32 } else { imm5 };
+                format!("asrs\t{}, {}, #{shift}", register(rd), register(rm))
+            }
+            Asr::RegisterT1 { rm, rdn } => format!("asrs\t{}, {}", register(rdn), register(rm)),
+        }
+    }
+}
+
+impl Encode for Asr {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Asr::ImmediateT1 { imm5, rm, rd } => encode_bits!((0b00010, 5), (imm5, 5), (rm, 3), (rd, 3)),
+            Asr::RegisterT1 { rm, rdn } => encode_bits!((0b0100000100, 10), (rm, 3), (rdn, 3)),
+        }
+    }
+}
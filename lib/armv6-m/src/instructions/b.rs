@@ -0,0 +1,106 @@
+use nom::combinator::verify;
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::alu::sign_extend;
+use crate::disassembler::{Context, Disassemble, condition};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::structure::Apsr;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/B>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum B {
+    /// Conditional branch
+    T1 {
+        cond: u8,
+        imm8: u8,
+    },
+    T2 {
+        imm11: u16,
+    },
+}
+
+pub fn parse_b(i: &[u8]) -> IResult<&'_ [u8], B> {
+    // NOTE: Conditions 0b1110 and 0b1111 are UDF and SVC
+    let parse_t1 = verify(
+        parse_bits!(
+            (0b1101, 4u8),
+            (4u8, 8u8),
+            (cond, imm8),
+            B::T1 { cond, imm8 }
+        ),
+        |instruction| matches!(instruction, B::T1 { cond, .. } if *cond < 0b1110),
+    );
+    let parse_t2 = parse_bits!((0b11100, 5u8), 11u8, imm11, B::T2 { imm11 });
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((parse_t1, parse_t2)))(i)
+}
+
+/// `ConditionPassed()`
+/// <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/ARM-Instruction-Set-Encoding/Conditional-execution>
+pub fn condition_passed(cond: u8, apsr: Apsr) -> bool {
+    let passed = match cond >> 1 {
+        0b000 => apsr.z(),
+        0b001 => apsr.c(),
+        0b010 => apsr.n(),
+        0b011 => apsr.v(),
+        0b100 => apsr.c() && !apsr.z(),
+        0b101 => apsr.n() == apsr.v(),
+        0b110 => apsr.n() == apsr.v() && !apsr.z(),
+        _ => true,
+    };
+
+    if cond & 1 == 1 && cond != 0b1111 {
+        !passed
+    } else {
+        passed
+    }
+}
+
+impl Execute<Armv6M> for B {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let offset = match *self {
+            B::T1 { cond, imm8 } => {
+                if !condition_passed(cond, on.get_apsr()) {
+                    return Ok(());
+                }
+                sign_extend(u32::from(imm8) << 1, 9)
+            }
+            B::T2 { imm11 } => sign_extend(u32::from(imm11) << 1, 12),
+        };
+
+        on.branch_write_pc(on.reg(15).wrapping_add(offset));
+        Ok(())
+    }
+}
+
+impl Disassemble for B {
+    fn disassemble(&self, context: &Context<'_>) -> String {
+        match *self {
+            B::T1 { cond, imm8 } => format!(
+                "b{}.n\t{}",
+                condition(cond),
+                context.branch(sign_extend(u32::from(imm8) << 1, 9))
+            ),
+            B::T2 { imm11 } => format!("b.n\t{}", context.branch(sign_extend(u32::from(imm11) << 1, 12))),
+        }
+    }
+}
+
+impl Encode for B {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            B::T1 { cond, imm8 } => {
+                // NOTE: The last two conditions are UDF and SVC
+                if cond >= 0b1110 {
+                    return Err(Error::Encoding("B with condition 0b1110 or 0b1111 is UDF or SVC"));
+                }
+                encode_bits!((0b1101, 4), (cond, 4), (imm8, 8))
+            }
+            B::T2 { imm11 } => encode_bits!((0b11100, 5), (imm11, 11)),
+        }
+    }
+}
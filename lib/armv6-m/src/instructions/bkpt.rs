@@ -0,0 +1,48 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::{Execute, StopReason};
+use crate::disassembler::{Context, Disassemble};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/BKPT>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bkpt {
+    pub imm8: u8,
+}
+
+pub fn parse_bkpt(i: &[u8]) -> IResult<&'_ [u8], Bkpt> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b10111110, 8u8),
+        8u8,
+        imm8,
+        Bkpt { imm8 }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Bkpt {
+    /// The core halts in Debug state before the breakpoint, it is not executed
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let address = on.get_pc();
+        on.branch_write_pc(address);
+        on.stop(StopReason::Breakpoint(address));
+        Ok(())
+    }
+}
+
+impl Disassemble for Bkpt {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("bkpt\t{:#06x}", self.imm8)
+    }
+}
+
+impl Encode for Bkpt {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b10111110, 8),
+            (self.imm8, 8)
+        )
+    }
+}
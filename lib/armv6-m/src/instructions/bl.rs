@@ -0,0 +1,90 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::alu::sign_extend;
+use crate::disassembler::{Context, Disassemble};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/BL>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bl {
+    pub s: u8,
+    pub imm10: u16,
+    pub j1: u8,
+    pub j2: u8,
+    pub imm11: u16,
+}
+
+impl Bl {
+    /// `SignExtend(S:I1:I2:imm10:imm11:'0')` with `I1 = NOT(J1 EOR S)` and `I2 = NOT(J2 EOR S)`
+    pub fn offset(&self) -> u32 {
+        let i1 = u32::from(!(self.j1 ^ self.s) & 1);
+        let i2 = u32::from(!(self.j2 ^ self.s) & 1);
+        let imm = u32::from(self.s) << 24
+            | i1 << 23
+            | i2 << 22
+            | u32::from(self.imm10) << 12
+            | u32::from(self.imm11) << 1;
+
+        sign_extend(imm, 25)
+    }
+}
+
+pub fn parse_bl(i: &[u8]) -> IResult<&'_ [u8], Bl> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((
+            tag(0b11110, 5u8),
+            take(1u8),
+            take(10u8),
+            tag(0b11, 2u8),
+            take(1u8),
+            tag(0b1, 1u8),
+            take(1u8),
+            take(11u8),
+        )),
+        |(_, s, imm10, _, j1, _, j2, imm11)| Bl {
+            s,
+            imm10,
+            j1,
+            j2,
+            imm11,
+        },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Bl {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let next_instruction = on.reg(15);
+        on.set_reg(14, next_instruction | 1);
+        on.branch_write_pc(next_instruction.wrapping_add(self.offset()));
+        Ok(())
+    }
+}
+
+impl Disassemble for Bl {
+    fn disassemble(&self, context: &Context<'_>) -> String {
+        format!("bl\t{}", context.branch(self.offset()))
+    }
+}
+
+impl Encode for Bl {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b11110, 5),
+            (self.s, 1),
+            (self.imm10, 10),
+            (0b11, 2),
+            (self.j1, 1),
+            (0b1, 1),
+            (self.j2, 1),
+            (self.imm11, 11)
+        )
+    }
+}
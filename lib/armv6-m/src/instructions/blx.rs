@@ -0,0 +1,50 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/BLX--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blx {
+    pub rm: u8,
+}
+
+pub fn parse_blx(i: &[u8]) -> IResult<&'_ [u8], Blx> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((tag(0b010001111, 9u8), take(4u8), tag(0b000, 3u8))),
+        |(_, rm, _)| Blx { rm },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Blx {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let target = on.reg(self.rm);
+        let next_instruction = on.get_pc().wrapping_add(2);
+        on.set_reg(14, next_instruction | 1);
+        on.bx_write_pc(target)
+    }
+}
+
+impl Disassemble for Blx {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("blx\t{}", register(self.rm))
+    }
+}
+
+impl Encode for Blx {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b010001111, 9),
+            (self.rm, 4),
+            (0b000, 3)
+        )
+    }
+}
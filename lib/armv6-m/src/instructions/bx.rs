@@ -0,0 +1,47 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/BX>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bx {
+    pub rm: u8,
+}
+
+pub fn parse_bx(i: &[u8]) -> IResult<&'_ [u8], Bx> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((tag(0b010001110, 9u8), take(4u8), tag(0b000, 3u8))),
+        |(_, rm, _)| Bx { rm },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Bx {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        on.bx_write_pc(on.reg(self.rm))
+    }
+}
+
+impl Disassemble for Bx {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("bx\t{}", register(self.rm))
+    }
+}
+
+impl Encode for Bx {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b010001110, 9),
+            (self.rm, 4),
+            (0b000, 3)
+        )
+    }
+}
@@ -0,0 +1,49 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+use crate::alu::add_with_carry;
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/CMN--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cmn {
+    pub rm: u8,
+    pub rn: u8,
+}
+
+pub fn parse_cmn(i: &[u8]) -> IResult<&'_ [u8], Cmn> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100001011, 10u8),
+        (3u8, 3u8),
+        (rm, rn),
+        Cmn { rm, rn }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Cmn {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (result, carry, overflow) = add_with_carry(on.reg(self.rn), on.reg(self.rm), false);
+        on.set_nzcv(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl Disassemble for Cmn {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("cmn\t{}, {}", register(self.rn), register(self.rm))
+    }
+}
+
+impl Encode for Cmn {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100001011, 10),
+            (self.rm, 3),
+            (self.rn, 3)
+        )
+    }
+}
@@ -0,0 +1,93 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::alu::add_with_carry;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/CMP--immediate->
+    ImmediateT1 {
+        rn: u8,
+        imm8: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/CMP--register->
+    RegisterT1 {
+        rm: u8,
+        rn: u8,
+    },
+    RegisterT2 {
+        n: u8,
+        rm: u8,
+        rn: u8,
+    },
+}
+
+pub fn parse_cmp(i: &[u8]) -> IResult<&'_ [u8], Cmp> {
+    // Immediate
+    let parse_immediate_t1 = parse_bits!(
+        (0b00101, 5u8),
+        (3u8, 8u8),
+        (rn, imm8),
+        Cmp::ImmediateT1 { rn, imm8 }
+    );
+    // Register
+    let parse_register_t1 = parse_bits!(
+        (0b0100001010, 10u8),
+        (3u8, 3u8),
+        (rm, rn),
+        Cmp::RegisterT1 { rm, rn }
+    );
+    let parse_register_t2 = parse_bits!(
+        (0b01000101, 8u8),
+        (1u8, 4u8, 3u8),
+        (n, rm, rn),
+        Cmp::RegisterT2 { n, rm, rn }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((
+        parse_immediate_t1,
+        parse_register_t1,
+        parse_register_t2,
+    )))(i)
+}
+
+impl Execute<Armv6M> for Cmp {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (left, right) = match *self {
+            Cmp::ImmediateT1 { rn, imm8 } => (on.reg(rn), u32::from(imm8)),
+            Cmp::RegisterT1 { rm, rn } => (on.reg(rn), on.reg(rm)),
+            Cmp::RegisterT2 { n, rm, rn } => (on.reg(n << 3 | rn), on.reg(rm)),
+        };
+
+        let (result, carry, overflow) = add_with_carry(left, !right, true);
+        on.set_nzcv(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl Disassemble for Cmp {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Cmp::ImmediateT1 { rn, imm8 } => format!("cmp\t{}, #{imm8}", register(rn)),
+            Cmp::RegisterT1 { rm, rn } => format!("cmp\t{}, {}", register(rn), register(rm)),
+            Cmp::RegisterT2 { n, rm, rn } => {
+                format!("cmp\t{}, {}", register(n << 3 | rn), register(rm))
+            }
+        }
+    }
+}
+
+impl Encode for Cmp {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Cmp::ImmediateT1 { rn, imm8 } => encode_bits!((0b00101, 5), (rn, 3), (imm8, 8)),
+            Cmp::RegisterT1 { rm, rn } => encode_bits!((0b0100001010, 10), (rm, 3), (rn, 3)),
+            Cmp::RegisterT2 { n, rm, rn } => encode_bits!((0b01000101, 8), (n, 1), (rm, 4), (rn, 3)),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/CPS>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cps {
+    /// 1 disables interrupts (CPSID), 0 enables them (CPSIE)
+    pub im: u8,
+}
+
+pub fn parse_cps(i: &[u8]) -> IResult<&'_ [u8], Cps> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((tag(0b10110110011, 11u8), take(1u8), tag(0b0010, 4u8))),
+        |(_, im, _)| Cps { im },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Cps {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        if on.is_privileged() {
+            on.primask = u32::from(self.im);
+        }
+        Ok(())
+    }
+}
+
+impl Disassemble for Cps {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        if self.im == 1 {
+            "cpsid\ti".to_owned()
+        } else {
+            "cpsie\ti".to_owned()
+        }
+    }
+}
+
+impl Encode for Cps {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b10110110011, 11),
+            (self.im, 1),
+            (0b0010, 4)
+        )
+    }
+}
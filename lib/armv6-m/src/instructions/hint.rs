@@ -0,0 +1,87 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::{Execute, StopReason};
+use crate::disassembler::{Context, Disassemble};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, Armv6M};
+
+/// Hint instructions share the same encoding, with different `opA` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    /// <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/NOP>
+    Nop,
+    /// <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/YIELD>
+    Yield,
+    /// <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/WFE>
+    Wfe,
+    /// <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/WFI>
+    Wfi,
+    /// <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/SEV>
+    Sev,
+}
+
+pub fn parse_hint(i: &[u8]) -> IResult<&'_ [u8], Hint> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((tag(0b10111111, 8u8), take(4u8), tag(0b0000, 4u8))),
+        |(_, op_a, _): (_, u8, _)| match op_a {
+            0b0001 => Hint::Yield,
+            0b0010 => Hint::Wfe,
+            0b0011 => Hint::Wfi,
+            0b0100 => Hint::Sev,
+            // NOTE: 0b0000 is NOP, unallocated hints execute as NOP too
+            _ => Hint::Nop,
+        },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Hint {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        match self {
+            Hint::Nop | Hint::Yield => {}
+            Hint::Wfe => {
+                if !on.take_event() && !on.wake_up_pending() && !on.sleep() {
+                    on.stop(StopReason::Sleeping);
+                }
+            }
+            Hint::Wfi => {
+                if !on.wake_up_pending() && !on.sleep() {
+                    on.stop(StopReason::Sleeping);
+                }
+            }
+            Hint::Sev => on.send_event(),
+        }
+        Ok(())
+    }
+}
+
+impl Disassemble for Hint {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match self {
+            Hint::Nop => "nop",
+            Hint::Yield => "yield",
+            Hint::Wfe => "wfe",
+            Hint::Wfi => "wfi",
+            Hint::Sev => "sev",
+        }
+        .to_owned()
+    }
+}
+
+impl Encode for Hint {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        let op_a = match self {
+            Hint::Nop => 0b0000,
+            Hint::Yield => 0b0001,
+            Hint::Wfe => 0b0010,
+            Hint::Wfi => 0b0011,
+            Hint::Sev => 0b0100,
+        };
+        encode_bits!((0b10111111, 8), (op_a, 4), (0b0000, 4))
+    }
+}
@@ -0,0 +1,52 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, barrier_option};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/ISB>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Isb {
+    pub option: u8,
+}
+
+pub fn parse_isb(i: &[u8]) -> IResult<&'_ [u8], Isb> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((
+            tag(0b1111001110111111, 16u8),
+            tag(0b100011110110, 12u8),
+            take(4u8),
+        )),
+        |(_, _, option)| Isb { option },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Isb {
+    /// Memory accesses are never reordered by the emulator
+    fn execute(&self, _on: &mut Armv6M) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Disassemble for Isb {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("isb\t{}", barrier_option(self.option))
+    }
+}
+
+impl Encode for Isb {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1111001110111111, 16),
+            (0b100011110110, 12),
+            (self.option, 4)
+        )
+    }
+}
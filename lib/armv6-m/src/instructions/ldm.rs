@@ -0,0 +1,65 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register, register_list};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDM--LDMIA--LDMFD>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ldm {
+    pub rn: u8,
+    pub register_list: u8,
+}
+
+pub fn parse_ldm(i: &[u8]) -> IResult<&'_ [u8], Ldm> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b11001, 5u8),
+        (3u8, 8u8),
+        (rn, register_list),
+        Ldm { rn, register_list }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Ldm {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let mut address = on.reg(self.rn);
+        for register in 0..8 {
+            if self.register_list >> register & 1 == 1 {
+                let value = on.read_memory(address, 4)?;
+                on.set_reg(register, value);
+                address = address.wrapping_add(4);
+            }
+        }
+
+        // NOTE: No writeback when the base register is loaded
+        if self.register_list >> self.rn & 1 == 0 {
+            on.set_reg(self.rn, address);
+        }
+        Ok(())
+    }
+}
+
+impl Disassemble for Ldm {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        // NOTE: The base register is written back unless it is loaded
+        let writeback = if self.register_list >> self.rn & 1 == 1 { "" } else { "!" };
+        format!(
+            "ldmia\t{}{writeback}, {}",
+            register(self.rn),
+            register_list(u16::from(self.register_list))
+        )
+    }
+}
+
+impl Encode for Ldm {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b11001, 5),
+            (self.rn, 3),
+            (self.register_list, 8)
+        )
+    }
+}
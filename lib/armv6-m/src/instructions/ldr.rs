@@ -0,0 +1,122 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ldr {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDR--immediate->
+    ImmediateT1 {
+        imm5: u8,
+        rn: u8,
+        rt: u8,
+    },
+    ImmediateT2 {
+        rt: u8,
+        imm8: u8,
+    },
+    /// Literal: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDR--literal->
+    LiteralT1 {
+        rt: u8,
+        imm8: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDR--register->
+    RegisterT1 {
+        rm: u8,
+        rn: u8,
+        rt: u8,
+    },
+}
+
+pub fn parse_ldr(i: &[u8]) -> IResult<&'_ [u8], Ldr> {
+    // Immediate
+    let parse_immediate_t1 = parse_bits!(
+        (0b01101, 5u8),
+        (5u8, 3u8, 3u8),
+        (imm5, rn, rt),
+        Ldr::ImmediateT1 { imm5, rn, rt }
+    );
+    let parse_immediate_t2 = parse_bits!(
+        (0b10011, 5u8),
+        (3u8, 8u8),
+        (rt, imm8),
+        Ldr::ImmediateT2 { rt, imm8 }
+    );
+    // Literal
+    let parse_literal_t1 = parse_bits!(
+        (0b01001, 5u8),
+        (3u8, 8u8),
+        (rt, imm8),
+        Ldr::LiteralT1 { rt, imm8 }
+    );
+    // Register
+    let parse_register_t1 = parse_bits!(
+        (0b0101100, 7u8),
+        (3u8, 3u8, 3u8),
+        (rm, rn, rt),
+        Ldr::RegisterT1 { rm, rn, rt }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((
+        parse_immediate_t1,
+        parse_immediate_t2,
+        parse_literal_t1,
+        parse_register_t1,
+    )))(i)
+}
+
+impl Execute<Armv6M> for Ldr {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (rt, address) = match *self {
+            Ldr::ImmediateT1 { imm5, rn, rt } => (rt, on.reg(rn).wrapping_add(u32::from(imm5) << 2)),
+            Ldr::ImmediateT2 { rt, imm8 } => (rt, on.reg(13).wrapping_add(u32::from(imm8) << 2)),
+            Ldr::LiteralT1 { rt, imm8 } => (
+                rt,
+                (on.reg(15) & !0b11).wrapping_add(u32::from(imm8) << 2),
+            ),
+            Ldr::RegisterT1 { rm, rn, rt } => (rt, on.reg(rn).wrapping_add(on.reg(rm))),
+        };
+
+        let value = on.read_memory(address, 4)?;
+        on.set_reg(rt, value);
+        Ok(())
+    }
+}
+
+impl Disassemble for Ldr {
+    fn disassemble(&self, context: &Context<'_>) -> String {
+        match *self {
+            Ldr::ImmediateT1 { imm5, rn, rt } => format!(
+                "ldr\t{}, [{}, #{}]",
+                register(rt),
+                register(rn),
+                u32::from(imm5) << 2
+            ),
+            Ldr::ImmediateT2 { rt, imm8 } => {
+                format!("ldr\t{}, [sp, #{}]", register(rt), u32::from(imm8) << 2)
+            }
+            Ldr::LiteralT1 { rt, imm8 } => {
+                let offset = u32::from(imm8) << 2;
+                format!("ldr\t{}, [pc, #{offset}]{}", register(rt), context.literal(offset))
+            }
+            Ldr::RegisterT1 { rm, rn, rt } => {
+                format!("ldr\t{}, [{}, {}]", register(rt), register(rn), register(rm))
+            }
+        }
+    }
+}
+
+impl Encode for Ldr {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Ldr::ImmediateT1 { imm5, rn, rt } => encode_bits!((0b01101, 5), (imm5, 5), (rn, 3), (rt, 3)),
+            Ldr::ImmediateT2 { rt, imm8 } => encode_bits!((0b10011, 5), (rt, 3), (imm8, 8)),
+            Ldr::LiteralT1 { rt, imm8 } => encode_bits!((0b01001, 5), (rt, 3), (imm8, 8)),
+            Ldr::RegisterT1 { rm, rn, rt } => encode_bits!((0b0101100, 7), (rm, 3), (rn, 3), (rt, 3)),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ldrh {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDRH--immediate->
+    ImmediateT1 {
+        imm5: u8,
+        rn: u8,
+        rt: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDRH--register->
+    RegisterT1 {
+        rm: u8,
+        rn: u8,
+        rt: u8,
+    },
+}
+
+pub fn parse_ldrh(i: &[u8]) -> IResult<&'_ [u8], Ldrh> {
+    // Immediate
+    let parse_immediate_t1 = parse_bits!(
+        (0b10001, 5u8),
+        (5u8, 3u8, 3u8),
+        (imm5, rn, rt),
+        Ldrh::ImmediateT1 { imm5, rn, rt }
+    );
+    // Register
+    let parse_register_t1 = parse_bits!(
+        (0b0101101, 7u8),
+        (3u8, 3u8, 3u8),
+        (rm, rn, rt),
+        Ldrh::RegisterT1 { rm, rn, rt }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((
+        parse_immediate_t1,
+        parse_register_t1,
+    )))(i)
+}
+
+impl Execute<Armv6M> for Ldrh {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (rt, address) = match *self {
+            Ldrh::ImmediateT1 { imm5, rn, rt } => (rt, on.reg(rn).wrapping_add(u32::from(imm5) << 1)),
+            Ldrh::RegisterT1 { rm, rn, rt } => (rt, on.reg(rn).wrapping_add(on.reg(rm))),
+        };
+
+        let value = on.read_memory(address, 2)?;
+        on.set_reg(rt, value);
+        Ok(())
+    }
+}
+
+impl Disassemble for Ldrh {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Ldrh::ImmediateT1 { imm5, rn, rt } => {
+                format!("ldrh\t{}, [{}, #{}]", register(rt), register(rn), u32::from(imm5) << 1)
+            }
+            Ldrh::RegisterT1 { rm, rn, rt } => {
+                format!("ldrh\t{}, [{}, {}]", register(rt), register(rn), register(rm))
+            }
+        }
+    }
+}
+
+impl Encode for Ldrh {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Ldrh::ImmediateT1 { imm5, rn, rt } => encode_bits!((0b10001, 5), (imm5, 5), (rn, 3), (rt, 3)),
+            Ldrh::RegisterT1 { rm, rn, rt } => encode_bits!((0b0101101, 7), (rm, 3), (rn, 3), (rt, 3)),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDRSB--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ldrsb {
+    pub rm: u8,
+    pub rn: u8,
+    pub rt: u8,
+}
+
+pub fn parse_ldrsb(i: &[u8]) -> IResult<&'_ [u8], Ldrsb> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0101011, 7u8),
+        (3u8, 3u8, 3u8),
+        (rm, rn, rt),
+        Ldrsb { rm, rn, rt }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Ldrsb {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let address = on.reg(self.rn).wrapping_add(on.reg(self.rm));
+        let value = on.read_memory(address, 1)?;
+        on.set_reg(self.rt, value as u8 as i8 as u32);
+        Ok(())
+    }
+}
+
+impl Disassemble for Ldrsb {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!(
+            "ldrsb\t{}, [{}, {}]",
+            register(self.rt),
+            register(self.rn),
+            register(self.rm)
+        )
+    }
+}
+
+impl Encode for Ldrsb {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0101011, 7),
+            (self.rm, 3),
+            (self.rn, 3),
+            (self.rt, 3)
+        )
+    }
+}
@@ -0,0 +1,56 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LDRSH--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ldrsh {
+    pub rm: u8,
+    pub rn: u8,
+    pub rt: u8,
+}
+
+pub fn parse_ldrsh(i: &[u8]) -> IResult<&'_ [u8], Ldrsh> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0101111, 7u8),
+        (3u8, 3u8, 3u8),
+        (rm, rn, rt),
+        Ldrsh { rm, rn, rt }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Ldrsh {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let address = on.reg(self.rn).wrapping_add(on.reg(self.rm));
+        let value = on.read_memory(address, 2)?;
+        on.set_reg(self.rt, value as u16 as i16 as u32);
+        Ok(())
+    }
+}
+
+impl Disassemble for Ldrsh {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!(
+            "ldrsh\t{}, [{}, {}]",
+            register(self.rt),
+            register(self.rn),
+            register(self.rm)
+        )
+    }
+}
+
+impl Encode for Ldrsh {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0101111, 7),
+            (self.rm, 3),
+            (self.rn, 3),
+            (self.rt, 3)
+        )
+    }
+}
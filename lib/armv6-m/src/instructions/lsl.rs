@@ -0,0 +1,85 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+use nom::combinator::verify;
+
+use crate::abi::Execute;
+use crate::alu::{lsl_c};
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lsl {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LSL--immediate->
+    ImmediateT1 {
+        imm5: u8,
+        rm: u8,
+        rd: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LSL--register->
+    RegisterT1 {
+        rm: u8,
+        rdn: u8,
+    },
+}
+
+pub fn parse_lsl(i: &[u8]) -> IResult<&'_ [u8], Lsl> {
+    // NOTE: A shift by 0 is MOV (register)
+    let parse_immediate_t1 = verify(
+        parse_bits!(
+            (0b00000, 5u8),
+            (5u8, 3u8, 3u8),
+            (imm5, rm, rd),
+            Lsl::ImmediateT1 { imm5, rm, rd }
+        ),
+        |instruction| !matches!(instruction, Lsl::ImmediateT1 { imm5: 0, .. }),
+    );
+    let parse_register_t1 = parse_bits!(
+        (0b0100000010, 10u8),
+        (3u8, 3u8),
+        (rm, rdn),
+        Lsl::RegisterT1 { rm, rdn }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((parse_immediate_t1, parse_register_t1)))(i)
+}
+
+impl Execute<Armv6M> for Lsl {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (rd, value, shift) = match *self {
+            Lsl::ImmediateT1 { imm5, rm, rd } => (rd, on.reg(rm), u32::from(imm5)),
+            Lsl::RegisterT1 { rm, rdn } => (rdn, on.reg(rdn), on.reg(rm) & 0xFF),
+        };
+
+        let (result, carry) = lsl_c(value, shift, on.carry());
+        on.set_reg(rd, result);
+        on.set_nzc(result, carry);
+        Ok(())
+    }
+}
+
+impl Disassemble for Lsl {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Lsl::ImmediateT1 { imm5, rm, rd } => {
+                format!("lsls\t{}, {}, #{imm5}", register(rd), register(rm))
+            }
+            Lsl::RegisterT1 { rm, rdn } => format!("lsls\t{}, {}", register(rdn), register(rm)),
+        }
+    }
+}
+
+impl Encode for Lsl {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Lsl::ImmediateT1 { imm5, rm, rd } => {
+                if imm5 == 0 {
+                    return Err(Error::Encoding("LSL #0 is MOV (register) T2"));
+                }
+                encode_bits!((0b00000, 5), (imm5, 5), (rm, 3), (rd, 3))
+            }
+            Lsl::RegisterT1 { rm, rdn } => encode_bits!((0b0100000010, 10), (rm, 3), (rdn, 3)),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::alu::{lsr_c, decode_imm_shift};
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lsr {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LSR--immediate->
+    ImmediateT1 {
+        imm5: u8,
+        rm: u8,
+        rd: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/LSR--register->
+    RegisterT1 {
+        rm: u8,
+        rdn: u8,
+    },
+}
+
+pub fn parse_lsr(i: &[u8]) -> IResult<&'_ [u8], Lsr> {
+    let parse_immediate_t1 = parse_bits!(
+        (0b00001, 5u8),
+        (5u8, 3u8, 3u8),
+        (imm5, rm, rd),
+        Lsr::ImmediateT1 { imm5, rm, rd }
+    );
+    let parse_register_t1 = parse_bits!(
+        (0b0100000011, 10u8),
+        (3u8, 3u8),
+        (rm, rdn),
+        Lsr::RegisterT1 { rm, rdn }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((parse_immediate_t1, parse_register_t1)))(i)
+}
+
+impl Execute<Armv6M> for Lsr {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (rd, value, shift) = match *self {
+            Lsr::ImmediateT1 { imm5, rm, rd } => (rd, on.reg(rm), decode_imm_shift(imm5)),
+            Lsr::RegisterT1 { rm, rdn } => (rdn, on.reg(rdn), on.reg(rm) & 0xFF),
+        };
+
+        let (result, carry) = lsr_c(value, shift, on.carry());
+        on.set_reg(rd, result);
+        on.set_nzc(result, carry);
+        Ok(())
+    }
+}
+
+impl Disassemble for Lsr {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            // NOTE: A shift by 0 encodes a shift by 32
+            Lsr::ImmediateT1 { imm5, rm, rd } => {
+                let shift = if imm5 == 0 { 32 } else { imm5 };
+                format!("lsrs\t{}, {}, #{shift}", register(rd), register(rm))
+            }
+            Lsr::RegisterT1 { rm, rdn } => format!("lsrs\t{}, {}", register(rdn), register(rm)),
+        }
+    }
+}
+
+impl Encode for Lsr {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Lsr::ImmediateT1 { imm5, rm, rd } => encode_bits!((0b00001, 5), (imm5, 5), (rm, 3), (rd, 3)),
+            Lsr::RegisterT1 { rm, rdn } => encode_bits!((0b0100000011, 10), (rm, 3), (rdn, 3)),
+        }
+    }
+}
@@ -1,7 +1,340 @@
+use nom::branch::alt;
+use nom::combinator::map;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::Armv6M;
+
 macro_rules! export_mod {
     ($($i:ident),+) => {
         $(pub mod $i;)+
     };
 }
 
-export_mod!(add, pop, push);
+export_mod!(
+    adc, add, adr, and, asr, b, bic, bkpt, bl, blx, bx, cmn, cmp, cps, dmb, dsb, eor, hint, isb,
+    ldm, ldr, ldrb, ldrh, ldrsb, ldrsh, lsl, lsr, mov, mrs, msr, mul, mvn, orr, pop, push, rev,
+    rev16, revsh, ror, rsb, sbc, stm, str, strb, strh, sub, svc, sxtb, sxth, tst, udf, uxtb, uxth
+);
+
+/// Every Armv6-M Thumb instruction, as decoded from its encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Adc(adc::Adc),
+    Add(add::Add),
+    Adr(adr::Adr),
+    And(and::And),
+    Asr(asr::Asr),
+    B(b::B),
+    Bic(bic::Bic),
+    Bkpt(bkpt::Bkpt),
+    Bl(bl::Bl),
+    Blx(blx::Blx),
+    Bx(bx::Bx),
+    Cmn(cmn::Cmn),
+    Cmp(cmp::Cmp),
+    Cps(cps::Cps),
+    Dmb(dmb::Dmb),
+    Dsb(dsb::Dsb),
+    Eor(eor::Eor),
+    Hint(hint::Hint),
+    Isb(isb::Isb),
+    Ldm(ldm::Ldm),
+    Ldr(ldr::Ldr),
+    Ldrb(ldrb::Ldrb),
+    Ldrh(ldrh::Ldrh),
+    Ldrsb(ldrsb::Ldrsb),
+    Ldrsh(ldrsh::Ldrsh),
+    Lsl(lsl::Lsl),
+    Lsr(lsr::Lsr),
+    Mov(mov::Mov),
+    Mrs(mrs::Mrs),
+    Msr(msr::Msr),
+    Mul(mul::Mul),
+    Mvn(mvn::Mvn),
+    Orr(orr::Orr),
+    Pop(pop::Pop),
+    Push(push::Push),
+    Rev(rev::Rev),
+    Rev16(rev16::Rev16),
+    Revsh(revsh::Revsh),
+    Ror(ror::Ror),
+    Rsb(rsb::Rsb),
+    Sbc(sbc::Sbc),
+    Stm(stm::Stm),
+    Str(str::Str),
+    Strb(strb::Strb),
+    Strh(strh::Strh),
+    Sub(sub::Sub),
+    Svc(svc::Svc),
+    Sxtb(sxtb::Sxtb),
+    Sxth(sxth::Sxth),
+    Tst(tst::Tst),
+    Udf(udf::Udf),
+    Uxtb(uxtb::Uxtb),
+    Uxth(uxth::Uxth),
+}
+
+/// Halfwords starting with `0b11101`, `0b11110` or `0b11111` are the first half of a 32 bits instruction
+pub fn is_32bits(halfword: u16) -> bool {
+    halfword >> 11 >= 0b11101
+}
+
+/// Decode the instruction at the start of `i`.
+/// Halfwords are read most significant bit first, like in the reference manual,
+/// so each halfword fetched from memory (little endian) must be given in big endian.
+/// This is the reference decoder, the core executes through the tables of `decoder::Decoder`.
+pub fn parse_instruction(i: &[u8]) -> IResult<&'_ [u8], Instruction> {
+    if i.len() >= 2 && is_32bits(u16::from_be_bytes([i[0], i[1]])) {
+        return alt((
+            map(bl::parse_bl, Instruction::Bl),
+            map(msr::parse_msr, Instruction::Msr),
+            map(mrs::parse_mrs, Instruction::Mrs),
+            map(dmb::parse_dmb, Instruction::Dmb),
+            map(dsb::parse_dsb, Instruction::Dsb),
+            map(isb::parse_isb, Instruction::Isb),
+            map(udf::parse_udf, Instruction::Udf),
+        ))(i);
+    }
+
+    // NOTE: Order matters where encodings overlap, MOV (register) T2 is LSL #0
+    // and UDF and SVC use the last conditions of B T1
+    alt((
+        alt((
+            map(mov::parse_mov, Instruction::Mov),
+            map(lsl::parse_lsl, Instruction::Lsl),
+            map(lsr::parse_lsr, Instruction::Lsr),
+            map(asr::parse_asr, Instruction::Asr),
+            map(add::parse_add, Instruction::Add),
+            map(sub::parse_sub, Instruction::Sub),
+            map(cmp::parse_cmp, Instruction::Cmp),
+            map(and::parse_and, Instruction::And),
+            map(eor::parse_eor, Instruction::Eor),
+            map(adc::parse_adc, Instruction::Adc),
+            map(sbc::parse_sbc, Instruction::Sbc),
+            map(ror::parse_ror, Instruction::Ror),
+            map(tst::parse_tst, Instruction::Tst),
+            map(rsb::parse_rsb, Instruction::Rsb),
+            map(cmn::parse_cmn, Instruction::Cmn),
+            map(orr::parse_orr, Instruction::Orr),
+            map(mul::parse_mul, Instruction::Mul),
+            map(bic::parse_bic, Instruction::Bic),
+            map(mvn::parse_mvn, Instruction::Mvn),
+        )),
+        alt((
+            map(bx::parse_bx, Instruction::Bx),
+            map(blx::parse_blx, Instruction::Blx),
+            map(ldr::parse_ldr, Instruction::Ldr),
+            map(str::parse_str, Instruction::Str),
+            map(ldrb::parse_ldrb, Instruction::Ldrb),
+            map(strb::parse_strb, Instruction::Strb),
+            map(ldrh::parse_ldrh, Instruction::Ldrh),
+            map(strh::parse_strh, Instruction::Strh),
+            map(ldrsb::parse_ldrsb, Instruction::Ldrsb),
+            map(ldrsh::parse_ldrsh, Instruction::Ldrsh),
+            map(adr::parse_adr, Instruction::Adr),
+            map(ldm::parse_ldm, Instruction::Ldm),
+            map(stm::parse_stm, Instruction::Stm),
+        )),
+        alt((
+            map(push::parse_push, Instruction::Push),
+            map(pop::parse_pop, Instruction::Pop),
+            map(sxth::parse_sxth, Instruction::Sxth),
+            map(sxtb::parse_sxtb, Instruction::Sxtb),
+            map(uxth::parse_uxth, Instruction::Uxth),
+            map(uxtb::parse_uxtb, Instruction::Uxtb),
+            map(rev::parse_rev, Instruction::Rev),
+            map(rev16::parse_rev16, Instruction::Rev16),
+            map(revsh::parse_revsh, Instruction::Revsh),
+            map(cps::parse_cps, Instruction::Cps),
+            map(bkpt::parse_bkpt, Instruction::Bkpt),
+            map(hint::parse_hint, Instruction::Hint),
+            map(udf::parse_udf, Instruction::Udf),
+            map(svc::parse_svc, Instruction::Svc),
+            map(b::parse_b, Instruction::B),
+        )),
+    ))(i)
+}
+
+impl Execute<Armv6M> for Instruction {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        match self {
+            Instruction::Adc(instruction) => instruction.execute(on),
+            Instruction::Add(instruction) => instruction.execute(on),
+            Instruction::Adr(instruction) => instruction.execute(on),
+            Instruction::And(instruction) => instruction.execute(on),
+            Instruction::Asr(instruction) => instruction.execute(on),
+            Instruction::B(instruction) => instruction.execute(on),
+            Instruction::Bic(instruction) => instruction.execute(on),
+            Instruction::Bkpt(instruction) => instruction.execute(on),
+            Instruction::Bl(instruction) => instruction.execute(on),
+            Instruction::Blx(instruction) => instruction.execute(on),
+            Instruction::Bx(instruction) => instruction.execute(on),
+            Instruction::Cmn(instruction) => instruction.execute(on),
+            Instruction::Cmp(instruction) => instruction.execute(on),
+            Instruction::Cps(instruction) => instruction.execute(on),
+            Instruction::Dmb(instruction) => instruction.execute(on),
+            Instruction::Dsb(instruction) => instruction.execute(on),
+            Instruction::Eor(instruction) => instruction.execute(on),
+            Instruction::Hint(instruction) => instruction.execute(on),
+            Instruction::Isb(instruction) => instruction.execute(on),
+            Instruction::Ldm(instruction) => instruction.execute(on),
+            Instruction::Ldr(instruction) => instruction.execute(on),
+            Instruction::Ldrb(instruction) => instruction.execute(on),
+            Instruction::Ldrh(instruction) => instruction.execute(on),
+            Instruction::Ldrsb(instruction) => instruction.execute(on),
+            Instruction::Ldrsh(instruction) => instruction.execute(on),
+            Instruction::Lsl(instruction) => instruction.execute(on),
+            Instruction::Lsr(instruction) => instruction.execute(on),
+            Instruction::Mov(instruction) => instruction.execute(on),
+            Instruction::Mrs(instruction) => instruction.execute(on),
+            Instruction::Msr(instruction) => instruction.execute(on),
+            Instruction::Mul(instruction) => instruction.execute(on),
+            Instruction::Mvn(instruction) => instruction.execute(on),
+            Instruction::Orr(instruction) => instruction.execute(on),
+            Instruction::Pop(instruction) => instruction.execute(on),
+            Instruction::Push(instruction) => instruction.execute(on),
+            Instruction::Rev(instruction) => instruction.execute(on),
+            Instruction::Rev16(instruction) => instruction.execute(on),
+            Instruction::Revsh(instruction) => instruction.execute(on),
+            Instruction::Ror(instruction) => instruction.execute(on),
+            Instruction::Rsb(instruction) => instruction.execute(on),
+            Instruction::Sbc(instruction) => instruction.execute(on),
+            Instruction::Stm(instruction) => instruction.execute(on),
+            Instruction::Str(instruction) => instruction.execute(on),
+            Instruction::Strb(instruction) => instruction.execute(on),
+            Instruction::Strh(instruction) => instruction.execute(on),
+            Instruction::Sub(instruction) => instruction.execute(on),
+            Instruction::Svc(instruction) => instruction.execute(on),
+            Instruction::Sxtb(instruction) => instruction.execute(on),
+            Instruction::Sxth(instruction) => instruction.execute(on),
+            Instruction::Tst(instruction) => instruction.execute(on),
+            Instruction::Udf(instruction) => instruction.execute(on),
+            Instruction::Uxtb(instruction) => instruction.execute(on),
+            Instruction::Uxth(instruction) => instruction.execute(on),
+        }
+    }
+}
+
+impl Disassemble for Instruction {
+    fn disassemble(&self, context: &Context<'_>) -> String {
+        match self {
+            Instruction::Adc(instruction) => instruction.disassemble(context),
+            Instruction::Add(instruction) => instruction.disassemble(context),
+            Instruction::Adr(instruction) => instruction.disassemble(context),
+            Instruction::And(instruction) => instruction.disassemble(context),
+            Instruction::Asr(instruction) => instruction.disassemble(context),
+            Instruction::B(instruction) => instruction.disassemble(context),
+            Instruction::Bic(instruction) => instruction.disassemble(context),
+            Instruction::Bkpt(instruction) => instruction.disassemble(context),
+            Instruction::Bl(instruction) => instruction.disassemble(context),
+            Instruction::Blx(instruction) => instruction.disassemble(context),
+            Instruction::Bx(instruction) => instruction.disassemble(context),
+            Instruction::Cmn(instruction) => instruction.disassemble(context),
+            Instruction::Cmp(instruction) => instruction.disassemble(context),
+            Instruction::Cps(instruction) => instruction.disassemble(context),
+            Instruction::Dmb(instruction) => instruction.disassemble(context),
+            Instruction::Dsb(instruction) => instruction.disassemble(context),
+            Instruction::Eor(instruction) => instruction.disassemble(context),
+            Instruction::Hint(instruction) => instruction.disassemble(context),
+            Instruction::Isb(instruction) => instruction.disassemble(context),
+            Instruction::Ldm(instruction) => instruction.disassemble(context),
+            Instruction::Ldr(instruction) => instruction.disassemble(context),
+            Instruction::Ldrb(instruction) => instruction.disassemble(context),
+            Instruction::Ldrh(instruction) => instruction.disassemble(context),
+            Instruction::Ldrsb(instruction) => instruction.disassemble(context),
+            Instruction::Ldrsh(instruction) => instruction.disassemble(context),
+            Instruction::Lsl(instruction) => instruction.disassemble(context),
+            Instruction::Lsr(instruction) => instruction.disassemble(context),
+            Instruction::Mov(instruction) => instruction.disassemble(context),
+            Instruction::Mrs(instruction) => instruction.disassemble(context),
+            Instruction::Msr(instruction) => instruction.disassemble(context),
+            Instruction::Mul(instruction) => instruction.disassemble(context),
+            Instruction::Mvn(instruction) => instruction.disassemble(context),
+            Instruction::Orr(instruction) => instruction.disassemble(context),
+            Instruction::Pop(instruction) => instruction.disassemble(context),
+            Instruction::Push(instruction) => instruction.disassemble(context),
+            Instruction::Rev(instruction) => instruction.disassemble(context),
+            Instruction::Rev16(instruction) => instruction.disassemble(context),
+            Instruction::Revsh(instruction) => instruction.disassemble(context),
+            Instruction::Ror(instruction) => instruction.disassemble(context),
+            Instruction::Rsb(instruction) => instruction.disassemble(context),
+            Instruction::Sbc(instruction) => instruction.disassemble(context),
+            Instruction::Stm(instruction) => instruction.disassemble(context),
+            Instruction::Str(instruction) => instruction.disassemble(context),
+            Instruction::Strb(instruction) => instruction.disassemble(context),
+            Instruction::Strh(instruction) => instruction.disassemble(context),
+            Instruction::Sub(instruction) => instruction.disassemble(context),
+            Instruction::Svc(instruction) => instruction.disassemble(context),
+            Instruction::Sxtb(instruction) => instruction.disassemble(context),
+            Instruction::Sxth(instruction) => instruction.disassemble(context),
+            Instruction::Tst(instruction) => instruction.disassemble(context),
+            Instruction::Udf(instruction) => instruction.disassemble(context),
+            Instruction::Uxtb(instruction) => instruction.disassemble(context),
+            Instruction::Uxth(instruction) => instruction.disassemble(context),
+        }
+    }
+}
+
+impl Encode for Instruction {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match self {
+            Instruction::Adc(instruction) => instruction.encode(),
+            Instruction::Add(instruction) => instruction.encode(),
+            Instruction::Adr(instruction) => instruction.encode(),
+            Instruction::And(instruction) => instruction.encode(),
+            Instruction::Asr(instruction) => instruction.encode(),
+            Instruction::B(instruction) => instruction.encode(),
+            Instruction::Bic(instruction) => instruction.encode(),
+            Instruction::Bkpt(instruction) => instruction.encode(),
+            Instruction::Bl(instruction) => instruction.encode(),
+            Instruction::Blx(instruction) => instruction.encode(),
+            Instruction::Bx(instruction) => instruction.encode(),
+            Instruction::Cmn(instruction) => instruction.encode(),
+            Instruction::Cmp(instruction) => instruction.encode(),
+            Instruction::Cps(instruction) => instruction.encode(),
+            Instruction::Dmb(instruction) => instruction.encode(),
+            Instruction::Dsb(instruction) => instruction.encode(),
+            Instruction::Eor(instruction) => instruction.encode(),
+            Instruction::Hint(instruction) => instruction.encode(),
+            Instruction::Isb(instruction) => instruction.encode(),
+            Instruction::Ldm(instruction) => instruction.encode(),
+            Instruction::Ldr(instruction) => instruction.encode(),
+            Instruction::Ldrb(instruction) => instruction.encode(),
+            Instruction::Ldrh(instruction) => instruction.encode(),
+            Instruction::Ldrsb(instruction) => instruction.encode(),
+            Instruction::Ldrsh(instruction) => instruction.encode(),
+            Instruction::Lsl(instruction) => instruction.encode(),
+            Instruction::Lsr(instruction) => instruction.encode(),
+            Instruction::Mov(instruction) => instruction.encode(),
+            Instruction::Mrs(instruction) => instruction.encode(),
+            Instruction::Msr(instruction) => instruction.encode(),
+            Instruction::Mul(instruction) => instruction.encode(),
+            Instruction::Mvn(instruction) => instruction.encode(),
+            Instruction::Orr(instruction) => instruction.encode(),
+            Instruction::Pop(instruction) => instruction.encode(),
+            Instruction::Push(instruction) => instruction.encode(),
+            Instruction::Rev(instruction) => instruction.encode(),
+            Instruction::Rev16(instruction) => instruction.encode(),
+            Instruction::Revsh(instruction) => instruction.encode(),
+            Instruction::Ror(instruction) => instruction.encode(),
+            Instruction::Rsb(instruction) => instruction.encode(),
+            Instruction::Sbc(instruction) => instruction.encode(),
+            Instruction::Stm(instruction) => instruction.encode(),
+            Instruction::Str(instruction) => instruction.encode(),
+            Instruction::Strb(instruction) => instruction.encode(),
+            Instruction::Strh(instruction) => instruction.encode(),
+            Instruction::Sub(instruction) => instruction.encode(),
+            Instruction::Svc(instruction) => instruction.encode(),
+            Instruction::Sxtb(instruction) => instruction.encode(),
+            Instruction::Sxth(instruction) => instruction.encode(),
+            Instruction::Tst(instruction) => instruction.encode(),
+            Instruction::Udf(instruction) => instruction.encode(),
+            Instruction::Uxtb(instruction) => instruction.encode(),
+            Instruction::Uxth(instruction) => instruction.encode(),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mov {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/MOV--immediate->
+    ImmediateT1 {
+        rd: u8,
+        imm8: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/MOV--register->
+    RegisterT1 {
+        d: u8,
+        rm: u8,
+        rd: u8,
+    },
+    RegisterT2 {
+        rm: u8,
+        rd: u8,
+    },
+}
+
+pub fn parse_mov(i: &[u8]) -> IResult<&'_ [u8], Mov> {
+    // Immediate
+    let parse_immediate_t1 = parse_bits!(
+        (0b00100, 5u8),
+        (3u8, 8u8),
+        (rd, imm8),
+        Mov::ImmediateT1 { rd, imm8 }
+    );
+    // Register
+    let parse_register_t1 = parse_bits!(
+        (0b01000110, 8u8),
+        (1u8, 4u8, 3u8),
+        (d, rm, rd),
+        Mov::RegisterT1 { d, rm, rd }
+    );
+    let parse_register_t2 = parse_bits!(
+        (0b0000000000, 10u8),
+        (3u8, 3u8),
+        (rm, rd),
+        Mov::RegisterT2 { rm, rd }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((
+        parse_immediate_t1,
+        parse_register_t1,
+        parse_register_t2,
+    )))(i)
+}
+
+impl Execute<Armv6M> for Mov {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        match *self {
+            Mov::ImmediateT1 { rd, imm8 } => {
+                let result = u32::from(imm8);
+                on.set_reg(rd, result);
+                on.set_nz(result);
+            }
+            Mov::RegisterT1 { d, rm, rd } => on.set_reg(d << 3 | rd, on.reg(rm)),
+            Mov::RegisterT2 { rm, rd } => {
+                let result = on.reg(rm);
+                on.set_reg(rd, result);
+                on.set_nz(result);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Disassemble for Mov {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Mov::ImmediateT1 { rd, imm8 } => format!("movs\t{}, #{imm8}", register(rd)),
+            Mov::RegisterT1 { d, rm, rd } => {
+                format!("mov\t{}, {}", register(d << 3 | rd), register(rm))
+            }
+            Mov::RegisterT2 { rm, rd } => format!("movs\t{}, {}", register(rd), register(rm)),
+        }
+    }
+}
+
+impl Encode for Mov {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Mov::ImmediateT1 { rd, imm8 } => encode_bits!((0b00100, 5), (rd, 3), (imm8, 8)),
+            Mov::RegisterT1 { d, rm, rd } => encode_bits!((0b01000110, 8), (d, 1), (rm, 4), (rd, 3)),
+            Mov::RegisterT2 { rm, rd } => encode_bits!((0b0000000000, 10), (rm, 3), (rd, 3)),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register, special_register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::structure::RegisterId;
+use crate::{encode_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/MRS>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mrs {
+    pub rd: u8,
+    pub sysm: u8,
+}
+
+pub fn parse_mrs(i: &[u8]) -> IResult<&'_ [u8], Mrs> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((
+            tag(0b1111001111101111, 16u8),
+            tag(0b1000, 4u8),
+            take(4u8),
+            take(8u8),
+        )),
+        |(_, _, rd, sysm)| Mrs { rd, sysm },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Mrs {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let value = match self.sysm {
+            // NOTE: Combinations of APSR and IPSR, the EPSR always reads as zero
+            0..=7 => {
+                let apsr = if self.sysm & 0b100 == 0 { on.apsr.bits() } else { 0 };
+                let ipsr = if self.sysm & 0b001 == 1 { on.ipsr.bits() } else { 0 };
+                apsr | ipsr
+            }
+            8 => on.read_register(RegisterId::SpMain),
+            9 => on.read_register(RegisterId::SpProcess),
+            16 => on.read_register(RegisterId::Primask),
+            20 => on.read_register(RegisterId::Control),
+            _ => 0,
+        };
+
+        on.set_reg(self.rd, value);
+        Ok(())
+    }
+}
+
+impl Disassemble for Mrs {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("mrs\t{}, {}", register(self.rd), special_register(self.sysm))
+    }
+}
+
+impl Encode for Mrs {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1111001111101111, 16),
+            (0b1000, 4),
+            (self.rd, 4),
+            (self.sysm, 8)
+        )
+    }
+}
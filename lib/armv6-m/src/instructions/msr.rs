@@ -0,0 +1,76 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register, special_register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::structure::{Apsr, RegisterId};
+use crate::{encode_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/MSR--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Msr {
+    pub rn: u8,
+    pub sysm: u8,
+}
+
+pub fn parse_msr(i: &[u8]) -> IResult<&'_ [u8], Msr> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(map(
+        tuple((
+            tag(0b111100111000, 12u8),
+            take(4u8),
+            tag(0b10001000, 8u8),
+            take(8u8),
+        )),
+        |(_, rn, _, sysm)| Msr { rn, sysm },
+    ))(i)
+}
+
+impl Execute<Armv6M> for Msr {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let value = on.reg(self.rn);
+
+        match self.sysm {
+            // NOTE: Only the APSR flags are writable
+            0..=7 if self.sysm & 0b100 == 0 => on.apsr = Apsr::from_bits(value),
+            8 if on.is_privileged() => on.write_register(RegisterId::SpMain, value),
+            9 if on.is_privileged() => on.write_register(RegisterId::SpProcess, value),
+            16 if on.is_privileged() => on.write_register(RegisterId::Primask, value),
+            20 if on.is_privileged() => {
+                // NOTE: SPSEL can only be changed in Thread mode
+                let spsel = if on.is_handler_mode() {
+                    on.control & 0b10
+                } else {
+                    value & 0b10
+                };
+                on.write_register(RegisterId::Control, spsel | value & 1);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Disassemble for Msr {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        // NOTE: The APSR flags written are part of the name
+        let flags = if self.sysm <= 3 { "_nzcvq" } else { "" };
+        format!("msr\t{}{flags}, {}", special_register(self.sysm), register(self.rn))
+    }
+}
+
+impl Encode for Msr {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b111100111000, 12),
+            (self.rn, 4),
+            (0b10001000, 8),
+            (self.sysm, 8)
+        )
+    }
+}
@@ -0,0 +1,49 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/MUL>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mul {
+    pub rn: u8,
+    pub rdm: u8,
+}
+
+pub fn parse_mul(i: &[u8]) -> IResult<&'_ [u8], Mul> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100001101, 10u8),
+        (3u8, 3u8),
+        (rn, rdm),
+        Mul { rn, rdm }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Mul {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let result = on.reg(self.rn).wrapping_mul(on.reg(self.rdm));
+        on.set_reg(self.rdm, result);
+        on.set_nz(result);
+        Ok(())
+    }
+}
+
+impl Disassemble for Mul {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("muls\t{}, {}", register(self.rdm), register(self.rn))
+    }
+}
+
+impl Encode for Mul {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100001101, 10),
+            (self.rn, 3),
+            (self.rdm, 3)
+        )
+    }
+}
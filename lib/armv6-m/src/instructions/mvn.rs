@@ -0,0 +1,49 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/MVN--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mvn {
+    pub rm: u8,
+    pub rd: u8,
+}
+
+pub fn parse_mvn(i: &[u8]) -> IResult<&'_ [u8], Mvn> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100001111, 10u8),
+        (3u8, 3u8),
+        (rm, rd),
+        Mvn { rm, rd }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Mvn {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let result = !on.reg(self.rm);
+        on.set_reg(self.rd, result);
+        on.set_nz(result);
+        Ok(())
+    }
+}
+
+impl Disassemble for Mvn {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("mvns\t{}, {}", register(self.rd), register(self.rm))
+    }
+}
+
+impl Encode for Mvn {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100001111, 10),
+            (self.rm, 3),
+            (self.rd, 3)
+        )
+    }
+}
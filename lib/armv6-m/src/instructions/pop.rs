@@ -1,11 +1,14 @@
-use nom::{IResult, bits};
 use nom::error::Error as NomError;
+use nom::{bits, IResult};
 
-use crate::abi::MemoryMutation;
-use crate::{parse_bits, Armv6M};
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register_list};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
 
 // Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/POP>
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Pop {
     pub p: u8,
     pub register_list: u8
@@ -22,12 +25,42 @@ pub fn parse_pop(i: &[u8]) -> IResult<&'_ [u8], Pop> {
     )(i)
 }
 
-impl MemoryMutation<Armv6M> for Pop {
-    fn apply(&self, _on: &mut Armv6M) {
-        todo!()
+impl Execute<Armv6M> for Pop {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let mut address = on.get_sp();
+
+        for i in 0..8 {
+            if self.register_list >> i & 1 == 1 {
+                let value = on.read_memory(address, 4)?;
+                on.set_reg(i, value);
+                address = address.wrapping_add(4);
+            }
+        }
+
+        if self.p == 1 {
+            let pc = on.read_memory(address, 4)?;
+            on.set_sp(address.wrapping_add(4));
+            on.bx_write_pc(pc)
+        } else {
+            on.set_sp(address);
+            Ok(())
+        }
     }
+}
 
-    fn rollback(&self, _on: &mut Armv6M) {
-        todo!()
+impl Disassemble for Pop {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        let list = u16::from(self.p) << 15 | u16::from(self.register_list);
+        format!("pop\t{}", register_list(list))
+    }
+}
+
+impl Encode for Pop {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1011110, 7),
+            (self.p, 1),
+            (self.register_list, 8)
+        )
     }
 }
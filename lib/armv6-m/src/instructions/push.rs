@@ -1,11 +1,14 @@
 use nom::error::Error as NomError;
 use nom::{bits, IResult};
 
-use crate::abi::MemoryMutation;
-use crate::{parse_bits, Armv6M};
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register_list};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
 
 // Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/PUSH>
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Push {
     pub m: u8,
     pub register_list: u8,
@@ -20,19 +23,40 @@ pub fn parse_push(i: &[u8]) -> IResult<&'_ [u8], Push> {
     ))(i)
 }
 
-impl MemoryMutation<Armv6M> for Push {
-    fn apply(&self, on: &mut Armv6M) {
-        let address = on.sp;
+impl Execute<Armv6M> for Push {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let count = self.register_list.count_ones() + u32::from(self.m);
+        let start = on.get_sp().wrapping_sub(4 * count);
+        let mut address = start;
 
-        for i in 0..7 {
+        for i in 0..8 {
             if self.register_list >> i & 1 == 1 {
-                // on.registers[i] = on.memory[address];
-                todo!()
+                on.write_memory(address, 4, on.reg(i))?;
+                address = address.wrapping_add(4);
             }
         }
+        if self.m == 1 {
+            on.write_memory(address, 4, on.get_lr())?;
+        }
+
+        on.set_sp(start);
+        Ok(())
+    }
+}
+
+impl Disassemble for Push {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        let list = u16::from(self.m) << 14 | u16::from(self.register_list);
+        format!("push\t{}", register_list(list))
     }
+}
 
-    fn rollback(&self, _on: &mut Armv6M) {
-        todo!()
+impl Encode for Push {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1011010, 7),
+            (self.m, 1),
+            (self.register_list, 8)
+        )
     }
 }
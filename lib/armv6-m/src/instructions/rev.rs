@@ -0,0 +1,47 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/REV>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rev {
+    pub rm: u8,
+    pub rd: u8,
+}
+
+pub fn parse_rev(i: &[u8]) -> IResult<&'_ [u8], Rev> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b1011101000, 10u8),
+        (3u8, 3u8),
+        (rm, rd),
+        Rev { rm, rd }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Rev {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        on.set_reg(self.rd, on.reg(self.rm).swap_bytes());
+        Ok(())
+    }
+}
+
+impl Disassemble for Rev {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("rev\t{}, {}", register(self.rd), register(self.rm))
+    }
+}
+
+impl Encode for Rev {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1011101000, 10),
+            (self.rm, 3),
+            (self.rd, 3)
+        )
+    }
+}
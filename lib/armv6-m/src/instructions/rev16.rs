@@ -0,0 +1,48 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/REV16>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rev16 {
+    pub rm: u8,
+    pub rd: u8,
+}
+
+pub fn parse_rev16(i: &[u8]) -> IResult<&'_ [u8], Rev16> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b1011101001, 10u8),
+        (3u8, 3u8),
+        (rm, rd),
+        Rev16 { rm, rd }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Rev16 {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let value = on.reg(self.rm);
+        on.set_reg(self.rd, (value & 0xFF00_FF00) >> 8 | (value & 0x00FF_00FF) << 8);
+        Ok(())
+    }
+}
+
+impl Disassemble for Rev16 {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("rev16\t{}, {}", register(self.rd), register(self.rm))
+    }
+}
+
+impl Encode for Rev16 {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1011101001, 10),
+            (self.rm, 3),
+            (self.rd, 3)
+        )
+    }
+}
@@ -0,0 +1,48 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/REVSH>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Revsh {
+    pub rm: u8,
+    pub rd: u8,
+}
+
+pub fn parse_revsh(i: &[u8]) -> IResult<&'_ [u8], Revsh> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b1011101011, 10u8),
+        (3u8, 3u8),
+        (rm, rd),
+        Revsh { rm, rd }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Revsh {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let value = (on.reg(self.rm) as u16).swap_bytes();
+        on.set_reg(self.rd, value as i16 as u32);
+        Ok(())
+    }
+}
+
+impl Disassemble for Revsh {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("revsh\t{}, {}", register(self.rd), register(self.rm))
+    }
+}
+
+impl Encode for Revsh {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1011101011, 10),
+            (self.rm, 3),
+            (self.rd, 3)
+        )
+    }
+}
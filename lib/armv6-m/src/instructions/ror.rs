@@ -0,0 +1,51 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+use crate::alu::ror_c;
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/ROR--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ror {
+    pub rm: u8,
+    pub rdn: u8,
+}
+
+pub fn parse_ror(i: &[u8]) -> IResult<&'_ [u8], Ror> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100000111, 10u8),
+        (3u8, 3u8),
+        (rm, rdn),
+        Ror { rm, rdn }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Ror {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let shift = on.reg(self.rm) & 0xFF;
+        let (result, carry) = ror_c(on.reg(self.rdn), shift, on.carry());
+        on.set_reg(self.rdn, result);
+        on.set_nzc(result, carry);
+        Ok(())
+    }
+}
+
+impl Disassemble for Ror {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("rors\t{}, {}", register(self.rdn), register(self.rm))
+    }
+}
+
+impl Encode for Ror {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100000111, 10),
+            (self.rm, 3),
+            (self.rdn, 3)
+        )
+    }
+}
@@ -0,0 +1,51 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+use crate::alu::add_with_carry;
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/RSB--immediate->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rsb {
+    pub rn: u8,
+    pub rd: u8,
+}
+
+pub fn parse_rsb(i: &[u8]) -> IResult<&'_ [u8], Rsb> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100001001, 10u8),
+        (3u8, 3u8),
+        (rn, rd),
+        Rsb { rn, rd }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Rsb {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        // NOTE: The only immediate is #0, also known as NEG
+        let (result, carry, overflow) = add_with_carry(!on.reg(self.rn), 0, true);
+        on.set_reg(self.rd, result);
+        on.set_nzcv(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl Disassemble for Rsb {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("negs\t{}, {}", register(self.rd), register(self.rn))
+    }
+}
+
+impl Encode for Rsb {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100001001, 10),
+            (self.rn, 3),
+            (self.rd, 3)
+        )
+    }
+}
@@ -0,0 +1,59 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register, register_list};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/STM--STMIA--STMEA>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stm {
+    pub rn: u8,
+    pub register_list: u8,
+}
+
+pub fn parse_stm(i: &[u8]) -> IResult<&'_ [u8], Stm> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b11000, 5u8),
+        (3u8, 8u8),
+        (rn, register_list),
+        Stm { rn, register_list }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Stm {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let mut address = on.reg(self.rn);
+        for register in 0..8 {
+            if self.register_list >> register & 1 == 1 {
+                on.write_memory(address, 4, on.reg(register))?;
+                address = address.wrapping_add(4);
+            }
+        }
+
+        on.set_reg(self.rn, address);
+        Ok(())
+    }
+}
+
+impl Disassemble for Stm {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!(
+            "stmia\t{}!, {}",
+            register(self.rn),
+            register_list(u16::from(self.register_list))
+        )
+    }
+}
+
+impl Encode for Stm {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b11000, 5),
+            (self.rn, 3),
+            (self.register_list, 8)
+        )
+    }
+}
@@ -0,0 +1,99 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Str {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/STR--immediate->
+    ImmediateT1 {
+        imm5: u8,
+        rn: u8,
+        rt: u8,
+    },
+    ImmediateT2 {
+        rt: u8,
+        imm8: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/STR--register->
+    RegisterT1 {
+        rm: u8,
+        rn: u8,
+        rt: u8,
+    },
+}
+
+pub fn parse_str(i: &[u8]) -> IResult<&'_ [u8], Str> {
+    // Immediate
+    let parse_immediate_t1 = parse_bits!(
+        (0b01100, 5u8),
+        (5u8, 3u8, 3u8),
+        (imm5, rn, rt),
+        Str::ImmediateT1 { imm5, rn, rt }
+    );
+    let parse_immediate_t2 = parse_bits!(
+        (0b10010, 5u8),
+        (3u8, 8u8),
+        (rt, imm8),
+        Str::ImmediateT2 { rt, imm8 }
+    );
+    // Register
+    let parse_register_t1 = parse_bits!(
+        (0b0101000, 7u8),
+        (3u8, 3u8, 3u8),
+        (rm, rn, rt),
+        Str::RegisterT1 { rm, rn, rt }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((
+        parse_immediate_t1,
+        parse_immediate_t2,
+        parse_register_t1,
+    )))(i)
+}
+
+impl Execute<Armv6M> for Str {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (rt, address) = match *self {
+            Str::ImmediateT1 { imm5, rn, rt } => (rt, on.reg(rn).wrapping_add(u32::from(imm5) << 2)),
+            Str::ImmediateT2 { rt, imm8 } => (rt, on.reg(13).wrapping_add(u32::from(imm8) << 2)),
+            Str::RegisterT1 { rm, rn, rt } => (rt, on.reg(rn).wrapping_add(on.reg(rm))),
+        };
+
+        on.write_memory(address, 4, on.reg(rt))?;
+        Ok(())
+    }
+}
+
+impl Disassemble for Str {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Str::ImmediateT1 { imm5, rn, rt } => format!(
+                "str\t{}, [{}, #{}]",
+                register(rt),
+                register(rn),
+                u32::from(imm5) << 2
+            ),
+            Str::ImmediateT2 { rt, imm8 } => {
+                format!("str\t{}, [sp, #{}]", register(rt), u32::from(imm8) << 2)
+            }
+            Str::RegisterT1 { rm, rn, rt } => {
+                format!("str\t{}, [{}, {}]", register(rt), register(rn), register(rm))
+            }
+        }
+    }
+}
+
+impl Encode for Str {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Str::ImmediateT1 { imm5, rn, rt } => encode_bits!((0b01100, 5), (imm5, 5), (rn, 3), (rt, 3)),
+            Str::ImmediateT2 { rt, imm8 } => encode_bits!((0b10010, 5), (rt, 3), (imm8, 8)),
+            Str::RegisterT1 { rm, rn, rt } => encode_bits!((0b0101000, 7), (rm, 3), (rn, 3), (rt, 3)),
+        }
+    }
+}
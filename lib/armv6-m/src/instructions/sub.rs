@@ -0,0 +1,116 @@
+use nom::error::Error as NomError;
+use nom::{bits::bits, branch::alt, IResult};
+
+use crate::abi::Execute;
+use crate::alu::add_with_carry;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sub {
+    /// Immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/SUB--immediate->
+    ImmediateT1 {
+        imm3: u8,
+        rn: u8,
+        rd: u8,
+    },
+    ImmediateT2 {
+        rdn: u8,
+        imm8: u8,
+    },
+    /// Register: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/SUB--register->
+    RegisterT1 {
+        rm: u8,
+        rn: u8,
+        rd: u8,
+    },
+    /// SP minus immediate: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/SUB--SP-minus-immediate->
+    SpMinusImmediateT1 {
+        imm7: u8,
+    },
+}
+
+pub fn parse_sub(i: &[u8]) -> IResult<&'_ [u8], Sub> {
+    // Immediate
+    let parse_immediate_t1 = parse_bits!(
+        (0b0001111, 7u8),
+        (3u8, 3u8, 3u8),
+        (imm3, rn, rd),
+        Sub::ImmediateT1 { imm3, rn, rd }
+    );
+    let parse_immediate_t2 = parse_bits!(
+        (0b00111, 5u8),
+        (3u8, 8u8),
+        (rdn, imm8),
+        Sub::ImmediateT2 { rdn, imm8 }
+    );
+    // Register
+    let parse_register_t1 = parse_bits!(
+        (0b0001101, 7u8),
+        (3u8, 3u8, 3u8),
+        (rm, rn, rd),
+        Sub::RegisterT1 { rm, rn, rd }
+    );
+    // SP Minus Immediate
+    let parse_sp_minus_immediate_t1 = parse_bits!(
+        (0b101100001, 9u16),
+        7u8,
+        imm7,
+        Sub::SpMinusImmediateT1 { imm7 }
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((
+        parse_immediate_t1,
+        parse_immediate_t2,
+        parse_register_t1,
+        parse_sp_minus_immediate_t1,
+    )))(i)
+}
+
+impl Execute<Armv6M> for Sub {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let (rd, left, right) = match *self {
+            Sub::ImmediateT1 { imm3, rn, rd } => (rd, on.reg(rn), u32::from(imm3)),
+            Sub::ImmediateT2 { rdn, imm8 } => (rdn, on.reg(rdn), u32::from(imm8)),
+            Sub::RegisterT1 { rm, rn, rd } => (rd, on.reg(rn), on.reg(rm)),
+            Sub::SpMinusImmediateT1 { imm7 } => {
+                let result = on.reg(13).wrapping_sub(u32::from(imm7) << 2);
+                on.set_reg(13, result);
+                return Ok(());
+            }
+        };
+
+        let (result, carry, overflow) = add_with_carry(left, !right, true);
+        on.set_reg(rd, result);
+        on.set_nzcv(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl Disassemble for Sub {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Sub::ImmediateT1 { imm3, rn, rd } => {
+                format!("subs\t{}, {}, #{imm3}", register(rd), register(rn))
+            }
+            Sub::ImmediateT2 { rdn, imm8 } => format!("subs\t{}, #{imm8}", register(rdn)),
+            Sub::RegisterT1 { rm, rn, rd } => {
+                format!("subs\t{}, {}, {}", register(rd), register(rn), register(rm))
+            }
+            Sub::SpMinusImmediateT1 { imm7 } => format!("sub\tsp, #{}", u32::from(imm7) << 2),
+        }
+    }
+}
+
+impl Encode for Sub {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Sub::ImmediateT1 { imm3, rn, rd } => encode_bits!((0b0001111, 7), (imm3, 3), (rn, 3), (rd, 3)),
+            Sub::ImmediateT2 { rdn, imm8 } => encode_bits!((0b00111, 5), (rdn, 3), (imm8, 8)),
+            Sub::RegisterT1 { rm, rn, rd } => encode_bits!((0b0001101, 7), (rm, 3), (rn, 3), (rd, 3)),
+            Sub::SpMinusImmediateT1 { imm7 } => encode_bits!((0b101100001, 9), (imm7, 7)),
+        }
+    }
+}
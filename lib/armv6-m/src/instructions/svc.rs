@@ -0,0 +1,44 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/SVC>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Svc {
+    pub imm8: u8,
+}
+
+pub fn parse_svc(i: &[u8]) -> IResult<&'_ [u8], Svc> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b11011111, 8u8),
+        8u8,
+        imm8,
+        Svc { imm8 }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Svc {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        on.call_supervisor()
+    }
+}
+
+impl Disassemble for Svc {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("svc\t{}", self.imm8)
+    }
+}
+
+impl Encode for Svc {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b11011111, 8),
+            (self.imm8, 8)
+        )
+    }
+}
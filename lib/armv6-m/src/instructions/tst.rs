@@ -0,0 +1,48 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/TST--register->
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tst {
+    pub rm: u8,
+    pub rn: u8,
+}
+
+pub fn parse_tst(i: &[u8]) -> IResult<&'_ [u8], Tst> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b0100001000, 10u8),
+        (3u8, 3u8),
+        (rm, rn),
+        Tst { rm, rn }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Tst {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        let result = on.reg(self.rn) & on.reg(self.rm);
+        on.set_nz(result);
+        Ok(())
+    }
+}
+
+impl Disassemble for Tst {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("tst\t{}, {}", register(self.rn), register(self.rm))
+    }
+}
+
+impl Encode for Tst {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b0100001000, 10),
+            (self.rm, 3),
+            (self.rn, 3)
+        )
+    }
+}
@@ -0,0 +1,65 @@
+use nom::bits::bits;
+use nom::bits::streaming::{tag, take};
+use nom::branch::alt;
+use nom::combinator::map;
+use nom::error::Error as NomError;
+use nom::sequence::tuple;
+use nom::IResult;
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::structure::Exception;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/UDF>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Udf {
+    T1 {
+        imm8: u8,
+    },
+    T2 {
+        imm4: u8,
+        imm12: u16,
+    },
+}
+
+pub fn parse_udf(i: &[u8]) -> IResult<&'_ [u8], Udf> {
+    let parse_t1 = parse_bits!((0b11011110, 8u8), 8u8, imm8, Udf::T1 { imm8 });
+    let parse_t2 = map(
+        tuple((
+            tag(0b111101111111, 12u8),
+            take(4u8),
+            tag(0b1010, 4u8),
+            take(12u8),
+        )),
+        |(_, imm4, _, imm12)| Udf::T2 { imm4, imm12 },
+    );
+
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(alt((parse_t1, parse_t2)))(i)
+}
+
+impl Execute<Armv6M> for Udf {
+    fn execute(&self, _on: &mut Armv6M) -> Result<(), Error> {
+        Err(Error::Fault(Exception::HardFault))
+    }
+}
+
+impl Disassemble for Udf {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        match *self {
+            Udf::T1 { imm8 } => format!("udf\t#{imm8}"),
+            Udf::T2 { imm4, imm12 } => format!("udf.w\t#{}", u16::from(imm4) << 12 | imm12),
+        }
+    }
+}
+
+impl Encode for Udf {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        match *self {
+            Udf::T1 { imm8 } => encode_bits!((0b11011110, 8), (imm8, 8)),
+            Udf::T2 { imm4, imm12 } => encode_bits!((0b111101111111, 12), (imm4, 4), (0b1010, 4), (imm12, 12)),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use nom::error::Error as NomError;
+use nom::{bits, IResult};
+
+use crate::abi::Execute;
+use crate::disassembler::{Context, Disassemble, register};
+use crate::encoder::Encode;
+use crate::error::Error;
+use crate::{encode_bits, parse_bits, Armv6M};
+
+// Source: <https://developer.arm.com/documentation/ddi0419/c/Application-Level-Architecture/Thumb-Instruction-Details/Alphabetical-list-of-ARMv6-M-Thumb-instructions/UXTB>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uxtb {
+    pub rm: u8,
+    pub rd: u8,
+}
+
+pub fn parse_uxtb(i: &[u8]) -> IResult<&'_ [u8], Uxtb> {
+    bits::<_, _, NomError<(&[u8], usize)>, _, _>(parse_bits!(
+        (0b1011001011, 10u8),
+        (3u8, 3u8),
+        (rm, rd),
+        Uxtb { rm, rd }
+    ))(i)
+}
+
+impl Execute<Armv6M> for Uxtb {
+    fn execute(&self, on: &mut Armv6M) -> Result<(), Error> {
+        on.set_reg(self.rd, on.reg(self.rm) & 0xFF);
+        Ok(())
+    }
+}
+
+impl Disassemble for Uxtb {
+    fn disassemble(&self, _: &Context<'_>) -> String {
+        format!("uxtb\t{}, {}", register(self.rd), register(self.rm))
+    }
+}
+
+impl Encode for Uxtb {
+    fn encode(&self) -> Result<Vec<u16>, Error> {
+        encode_bits!(
+            (0b1011001011, 10),
+            (self.rm, 3),
+            (self.rd, 3)
+        )
+    }
+}
@@ -1,20 +1,67 @@
-use abi::Runtime;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use abi::{Execute, MemoryMutation, Runtime, RuntimeExtras, StopReason};
+use backtrace::Backtrace;
+use block_cache::{BlockCache, CacheStats};
+use breakpoint::Breakpoint;
+use debug_info::DebugInfo;
+use disassembler::Line;
+use history::{CheckpointConfig, History};
+use memory::{Access, AccessKind, Memory};
+use mutation::{MemoryChange, Mutation, RegisterChange};
+use nvic::{Nvic, SCS_END, SCS_START, THREAD_PRIORITY};
+use peripheral::{Peripheral, Power, ResetCause};
+use snapshot::Snapshot;
+use structure::{
+    Apsr, Epsr, Exception, Ipsr, RegisterId, EXC_RETURN_HANDLER, EXC_RETURN_THREAD_MAIN,
+    EXC_RETURN_THREAD_PROCESS,
+};
+use symbols::Symbols;
+use systick::SysTick;
+use timing::{MemoryTiming, WaitStates, EXCEPTION_ENTRY_CYCLES, NRF51_FREQUENCY};
+use trace::{TraceEntry, Tracer};
+use variables::Value;
+use watchpoint::Watchpoint;
 
 pub mod abi;
+pub mod assembler;
+pub mod backtrace;
+pub mod block_cache;
+pub mod breakpoint;
+pub mod debug_info;
+pub mod decoder;
+pub mod disassembler;
+pub mod encoder;
 pub mod error;
+pub mod expression;
+pub mod gdb;
+pub mod history;
 pub mod instructions;
+pub mod loader;
+pub mod memory;
+pub mod mutation;
+pub mod nrf51;
+pub mod nvic;
+pub mod peripheral;
+pub mod snapshot;
 pub mod structure;
+pub mod symbols;
+pub mod systick;
+pub mod timing;
+pub mod trace;
+pub mod variables;
+pub mod watchpoint;
 
+mod alu;
 mod macros;
 
-struct Armv6M {
-    memory: [u8; structure::MEMORY_MAX_ADDRESSABLE_ADDRESS],
+pub struct Armv6M {
+    memory: Memory,
 
     // NOTE: Generic ARM Registers
     registers: [u32; 13],
 
-    // Point to SP_main or to SP_process
-    sp: usize,
     // Link Register
     lr: u32,
     // Program counter
@@ -22,16 +69,59 @@ struct Armv6M {
 
     // NOTE: Specific Armv6-M SP Registers
     // SP_main or MSP
-    sp_main: usize,
+    sp_main: u32,
     // SP_process or PSP
-    sp_process: usize,
+    sp_process: u32,
     control: u32,
+
+    // NOTE: Special-purpose registers
+    primask: u32,
+    apsr: Apsr,
+    ipsr: Ipsr,
+    epsr: Epsr,
+
+    // NOTE: Execution state
+    // Target of the branch taken by the current instruction
+    branch: Option<u32>,
+    // Set by the current instruction to give control back
+    stop: Option<StopReason>,
+    // Event Register, set by SEV and consumed by WFE
+    event: bool,
+    // Memory writes of the current instruction
+    journal: Vec<MemoryChange>,
+    // Cycles of the current instruction, charged at the end of the step
+    charged: u64,
+    // Cycles elapsed since the reset
+    cycles: u64,
+    // Data accesses of the last step, on the memory and the peripherals
+    accesses: Vec<Access>,
+    history: History,
+    // Decoded blocks of the executed code
+    blocks: BlockCache,
+    // Records the steps when set
+    tracer: Option<Tracer>,
+    watchpoints: Vec<Watchpoint>,
+    breakpoints: BTreeMap<u32, Breakpoint>,
+    // Whether a lockup resets the system rather than stopping it
+    reset_on_lockup: bool,
+    // PC whose breakpoint was checked by the last step, it is not checked again before executing
+    checked_breakpoint: Option<u32>,
+    // Symbols of the loaded ELF file
+    symbols: Symbols,
+    // Line table and unwinding information of the loaded ELF file
+    debug_info: DebugInfo,
+
+    // NOTE: Devices
+    nvic: Nvic,
+    sys_tick: SysTick,
+    memory_timing: MemoryTiming,
+    peripherals: Vec<Box<dyn Peripheral>>,
 }
 
 macro_rules! get_register_generator {
     ($(($i:ident, $idx:expr)),*) => {
         $(
-            fn $i(&self) -> u32 {
+            pub fn $i(&self) -> u32 {
                 self.registers[$idx]
             }
         )*
@@ -41,7 +131,7 @@ macro_rules! get_register_generator {
 macro_rules! set_register_generator {
     ($(($i:ident, $idx:expr)),*) => {
         $(
-            fn $i(&mut self, value: u32) {
+            pub fn $i(&mut self, value: u32) {
                 self.registers[$idx] = value;
             }
         )*
@@ -79,46 +169,1171 @@ impl Armv6M {
         (set_r11, 11),
         (set_r12, 12)
     );
+
+    /// Current stack pointer, SP_process in Thread mode when CONTROL.SPSEL is set, SP_main otherwise
+    pub fn get_sp(&self) -> u32 {
+        if self.uses_process_stack() {
+            self.sp_process
+        } else {
+            self.sp_main
+        }
+    }
+
+    pub fn set_sp(&mut self, value: u32) {
+        if self.uses_process_stack() {
+            self.sp_process = value & !0b11;
+        } else {
+            self.sp_main = value & !0b11;
+        }
+    }
+
+    pub fn get_lr(&self) -> u32 {
+        self.lr
+    }
+
+    pub fn set_lr(&mut self, value: u32) {
+        self.lr = value;
+    }
+
+    pub fn get_pc(&self) -> u32 {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, value: u32) {
+        self.pc = value & !1;
+    }
+
+    pub fn get_apsr(&self) -> Apsr {
+        self.apsr
+    }
+
+    /// APSR, IPSR and EPSR combined
+    pub fn get_xpsr(&self) -> u32 {
+        self.apsr.bits() | self.ipsr.bits() | self.epsr.bits()
+    }
+
+    pub fn set_xpsr(&mut self, value: u32) {
+        self.apsr = Apsr::from_bits(value);
+        self.ipsr = Ipsr::from_bits(value);
+        self.epsr = Epsr::from_bits(value);
+    }
+
+    pub fn read_register(&self, register: RegisterId) -> u32 {
+        match register {
+            RegisterId::R0 => self.registers[0],
+            RegisterId::R1 => self.registers[1],
+            RegisterId::R2 => self.registers[2],
+            RegisterId::R3 => self.registers[3],
+            RegisterId::R4 => self.registers[4],
+            RegisterId::R5 => self.registers[5],
+            RegisterId::R6 => self.registers[6],
+            RegisterId::R7 => self.registers[7],
+            RegisterId::R8 => self.registers[8],
+            RegisterId::R9 => self.registers[9],
+            RegisterId::R10 => self.registers[10],
+            RegisterId::R11 => self.registers[11],
+            RegisterId::R12 => self.registers[12],
+            RegisterId::SpMain => self.sp_main,
+            RegisterId::SpProcess => self.sp_process,
+            RegisterId::Lr => self.lr,
+            RegisterId::Pc => self.pc,
+            RegisterId::Apsr => self.apsr.bits(),
+            RegisterId::Ipsr => self.ipsr.bits(),
+            RegisterId::Epsr => self.epsr.bits(),
+            RegisterId::Primask => self.primask,
+            RegisterId::Control => self.control,
+        }
+    }
+
+    pub fn write_register(&mut self, register: RegisterId, value: u32) {
+        match register {
+            RegisterId::R0 => self.registers[0] = value,
+            RegisterId::R1 => self.registers[1] = value,
+            RegisterId::R2 => self.registers[2] = value,
+            RegisterId::R3 => self.registers[3] = value,
+            RegisterId::R4 => self.registers[4] = value,
+            RegisterId::R5 => self.registers[5] = value,
+            RegisterId::R6 => self.registers[6] = value,
+            RegisterId::R7 => self.registers[7] = value,
+            RegisterId::R8 => self.registers[8] = value,
+            RegisterId::R9 => self.registers[9] = value,
+            RegisterId::R10 => self.registers[10] = value,
+            RegisterId::R11 => self.registers[11] = value,
+            RegisterId::R12 => self.registers[12] = value,
+            RegisterId::SpMain => self.sp_main = value & !0b11,
+            RegisterId::SpProcess => self.sp_process = value & !0b11,
+            RegisterId::Lr => self.lr = value,
+            RegisterId::Pc => self.pc = value & !1,
+            RegisterId::Apsr => self.apsr = Apsr::from_bits(value),
+            RegisterId::Ipsr => self.ipsr = Ipsr::from_bits(value),
+            RegisterId::Epsr => self.epsr = Epsr::from_bits(value),
+            RegisterId::Primask => self.primask = value & 1,
+            RegisterId::Control => self.control = value & 0b11,
+        }
+    }
+
+    /// Handler mode is entered on exceptions, Thread mode is used otherwise
+    pub fn is_handler_mode(&self) -> bool {
+        self.ipsr.exception_number() != 0
+    }
+
+    fn uses_process_stack(&self) -> bool {
+        !self.is_handler_mode() && self.control & 0b10 != 0
+    }
+
+    pub fn is_privileged(&self) -> bool {
+        self.is_handler_mode() || self.control & 1 == 0
+    }
+
+    /// Banked stack pointer currently used as SP
+    pub fn sp_register(&self) -> RegisterId {
+        if self.uses_process_stack() {
+            RegisterId::SpProcess
+        } else {
+            RegisterId::SpMain
+        }
+    }
+
+    /// Data accesses done by the last step, in order
+    pub fn last_accesses(&self) -> &[Access] {
+        &self.accesses
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn symbols(&self) -> &Symbols {
+        &self.symbols
+    }
+
+    pub fn debug_info(&self) -> &DebugInfo {
+        &self.debug_info
+    }
+
+    /// Call stack, from the current frame through the exception frames to the outermost caller.
+    /// On a HardFault, stop at the handler with a breakpoint to get the frames of the faulting code.
+    pub fn backtrace(&self) -> Backtrace {
+        backtrace::unwind(self)
+    }
+
+    /// Parameters and locals of the function at the pc, the ones without a location there are left out
+    pub fn locals(&self) -> Vec<Value> {
+        variables::locals(self)
+    }
+
+    /// Local of the function at the pc named `name`, or else the global of that name
+    pub fn variable(&self, name: &str) -> Option<Value> {
+        variables::lookup(self, name)
+    }
+
+    /// Decode the instruction at `address`, labelled with the symbols of the loaded program
+    pub fn disassemble(&self, address: u32) -> Line {
+        disassembler::disassemble_at(&self.memory, address, Some(&self.symbols))
+    }
+
+    pub fn set_checkpoint_config(&mut self, config: CheckpointConfig) -> &mut Self {
+        self.history.set_config(config, &self.memory);
+        self
+    }
+
+    pub fn block_cache_stats(&self) -> CacheStats {
+        self.blocks.stats()
+    }
+
+    /// The block cache is enabled by default, disabling it decodes every instruction when executed
+    pub fn set_block_cache(&mut self, enabled: bool) -> &mut Self {
+        self.blocks.set_enabled(enabled);
+        self
+    }
+
+    /// Cycles of the core clock elapsed since the reset, shared by SysTick and the peripherals
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Time elapsed since the reset at the 16 MHz of the nRF51822
+    pub fn elapsed(&self) -> Duration {
+        timing::duration(self.cycles, NRF51_FREQUENCY)
+    }
+
+    /// Add wait states to the accesses of a memory region, replacing those of the same region
+    pub fn set_wait_states(&mut self, wait_states: WaitStates) -> &mut Self {
+        self.memory_timing.set(wait_states);
+        self
+    }
+
+    pub fn memory_timing(&self) -> &MemoryTiming {
+        &self.memory_timing
+    }
+
+    pub fn sys_tick(&self) -> &SysTick {
+        &self.sys_tick
+    }
+
+    /// Step until at least `cycles` cycles elapsed or something stops the execution
+    pub fn run_for(&mut self, cycles: u64) -> Result<Option<StopReason>, error::Error> {
+        let end = self.cycles.saturating_add(cycles);
+        while self.cycles < end {
+            if let Some(reason) = self.step()? {
+                return Ok(Some(reason));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Record every step in `tracer` from now on
+    pub fn set_tracer(&mut self, tracer: Tracer) -> &mut Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    pub fn tracer(&self) -> Option<&Tracer> {
+        self.tracer.as_ref()
+    }
+
+    /// Stop tracing, the returned tracer still has to be flushed or dumped
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    /// Stop after the instructions accessing the watched memory or peripheral registers
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> &mut Self {
+        self.watchpoints.push(watchpoint);
+        self
+    }
+
+    /// Returns whether the watchpoint was set
+    pub fn remove_watchpoint(&mut self, watchpoint: &Watchpoint) -> bool {
+        let index = self
+            .watchpoints
+            .iter()
+            .position(|other| other == watchpoint);
+        index.map(|index| self.watchpoints.remove(index)).is_some()
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// Stop before the instruction at the address of the breakpoint, replacing the one already there
+    pub fn add_breakpoint(&mut self, mut breakpoint: Breakpoint) -> &mut Self {
+        breakpoint.address &= !1;
+        self.breakpoints.insert(breakpoint.address, breakpoint);
+        self
+    }
+
+    pub fn remove_breakpoint(&mut self, address: u32) -> Option<Breakpoint> {
+        self.breakpoints.remove(&(address & !1))
+    }
+
+    pub fn breakpoint_mut(&mut self, address: u32) -> Option<&mut Breakpoint> {
+        self.breakpoints.get_mut(&(address & !1))
+    }
+
+    /// Execute the instruction at the PC on the next step, even if a breakpoint is there
+    pub(crate) fn resume_over_breakpoint(&mut self) {
+        self.checked_breakpoint = Some(self.pc);
+    }
+
+    /// Breakpoints by address
+    pub fn breakpoints(&self) -> impl Iterator<Item = &Breakpoint> + '_ {
+        self.breakpoints.values()
+    }
+
+    pub fn nvic(&self) -> &Nvic {
+        &self.nvic
+    }
+
+    pub fn nvic_mut(&mut self) -> &mut Nvic {
+        &mut self.nvic
+    }
+
+    /// Map a peripheral on the bus, it must not overlap the System Control Space or another peripheral
+    pub fn add_peripheral(&mut self, peripheral: Box<dyn Peripheral>) -> &mut Self {
+        self.peripherals.push(peripheral);
+        self
+    }
+
+    pub fn peripherals(&self) -> impl Iterator<Item = &dyn Peripheral> + '_ {
+        self.peripherals.iter().map(AsRef::as_ref)
+    }
+
+    /// Serialize the registers, memory, interrupts and peripherals states
+    pub fn snapshot(&self) -> Result<Vec<u8>, error::Error> {
+        let peripherals = self
+            .peripherals
+            .iter()
+            .map(|peripheral| Ok((peripheral.name().to_owned(), peripheral.save_state()?)))
+            .collect::<Result<_, error::Error>>()?;
+
+        Snapshot {
+            registers: self.register_bank().to_vec(),
+            event: self.event,
+            cycles: self.cycles,
+            nvic: self.nvic.clone(),
+            sys_tick: self.sys_tick.clone(),
+            memory_timing: self.memory_timing.clone(),
+            pages: self
+                .memory
+                .pages()
+                .map(|(address, page)| (address, page.to_vec()))
+                .collect(),
+            peripherals,
+        }
+        .encode()
+    }
+
+    /// Resume from a snapshot, the attached peripherals must be the ones it was taken with.
+    /// The history is cleared, the restored memory becomes the first checkpoint.
+    ///
+    /// Nothing is changed when the snapshot cannot be restored.
+    pub fn restore_snapshot(&mut self, bytes: &[u8]) -> Result<&mut Self, error::Error> {
+        let snapshot = Snapshot::decode(bytes)?;
+        let registers: [u32; RegisterId::ALL.len()] = snapshot
+            .registers
+            .try_into()
+            .map_err(|_registers| error::Error::Snapshot("wrong number of registers"))?;
+        if let Some((name, _)) = snapshot.peripherals.iter().find(|(name, _)| {
+            !self
+                .peripherals
+                .iter()
+                .any(|peripheral| peripheral.name() == name)
+        }) {
+            return Err(error::Error::SnapshotPeripheral(name.clone()));
+        }
+        let states = self
+            .peripherals
+            .iter()
+            .map(|peripheral| {
+                snapshot
+                    .peripherals
+                    .iter()
+                    .find(|(name, _)| name == peripheral.name())
+                    .map(|(_, state)| state.as_slice())
+                    .ok_or_else(|| error::Error::SnapshotPeripheral(peripheral.name().to_owned()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut memory = Memory::new();
+        for (address, page) in &snapshot.pages {
+            memory.write_bytes(*address, page);
+        }
+
+        // NOTE: The peripherals decode their own states, the ones already loaded are put back
+        // when a later one fails so that the machine is left untouched
+        let previous = self
+            .peripherals
+            .iter()
+            .map(|peripheral| peripheral.save_state())
+            .collect::<Result<Vec<_>, _>>()?;
+        for (loaded, state) in states.iter().enumerate() {
+            if let Err(error) = self.peripherals[loaded].load_state(state) {
+                for (peripheral, state) in self.peripherals.iter_mut().zip(&previous).take(loaded) {
+                    peripheral.load_state(state)?;
+                }
+                return Err(error);
+            }
+        }
+
+        self.memory = memory;
+        self.blocks.clear();
+        for (register, value) in RegisterId::ALL.iter().zip(registers) {
+            self.write_register(*register, value);
+        }
+        self.event = snapshot.event;
+        self.cycles = snapshot.cycles;
+        self.nvic = snapshot.nvic;
+        self.sys_tick = snapshot.sys_tick;
+        self.memory_timing = snapshot.memory_timing;
+
+        self.branch = None;
+        self.stop = None;
+        self.checked_breakpoint = None;
+        self.journal.clear();
+        self.history.reset(&self.memory);
+        Ok(self)
+    }
+
+    /// Take the reset exception, SP_main and PC are loaded from the vector table.
+    /// The history is cleared, the current memory becomes the first checkpoint.
+    pub fn reset(&mut self) -> &mut Self {
+        self.reset_core();
+        self.journal.clear();
+        self.accesses.clear();
+        self.history.reset(&self.memory);
+        // NOTE: The programs are loaded before a reset
+        self.blocks.clear();
+        self.charged = 0;
+        self.cycles = 0;
+        self
+    }
+
+    /// Reset the system as the chip does: the core takes the reset exception and the peripherals
+    /// are given the cause. The memory and the cycles are kept, the registers changed by the reset
+    /// are recorded in the history like a step.
+    pub fn system_reset(&mut self, cause: ResetCause) -> &mut Self {
+        let address = self.pc;
+        let registers = self.register_bank();
+        self.reset_core();
+        for peripheral in &mut self.peripherals {
+            peripheral.reset(cause);
+        }
+
+        let mutation = Mutation {
+            address,
+            cycles: 0,
+            registers: self.register_changes(&registers),
+            memory: Vec::new(),
+            debugger: false,
+        };
+        self.history.step(mutation, &self.memory);
+        self
+    }
+
+    /// Reset the system on a lockup as the chip does without a debugger, instead of stopping with
+    /// `Error::Lockup`
+    pub fn set_reset_on_lockup(&mut self, enabled: bool) -> &mut Self {
+        self.reset_on_lockup = enabled;
+        self
+    }
+
+    fn reset_core(&mut self) {
+        let reset_vector = self.memory.read_u32(4 * Exception::Reset.number());
+
+        self.registers = [0; 13];
+        self.lr = u32::MAX;
+        self.sp_main = self.memory.read_u32(0) & !0b11;
+        self.sp_process = 0;
+        self.control = 0;
+        self.primask = 0;
+        self.apsr = Apsr::default();
+        self.ipsr = Ipsr::default();
+        self.epsr = Epsr::default();
+        self.epsr.set_t(reset_vector & 1 == 1);
+        self.pc = reset_vector & !1;
+
+        self.branch = None;
+        self.stop = None;
+        self.checked_breakpoint = None;
+        self.event = false;
+        self.nvic = Nvic::new();
+        self.sys_tick = SysTick::new();
+    }
+
+    fn register_bank(&self) -> [u32; RegisterId::ALL.len()] {
+        RegisterId::ALL.map(|register| self.read_register(register))
+    }
+
+    /// Registers which differ from the bank `registers`
+    fn register_changes(&self, registers: &[u32; RegisterId::ALL.len()]) -> Vec<RegisterChange> {
+        RegisterId::ALL
+            .iter()
+            .zip(registers)
+            .filter_map(|(register, old)| {
+                let new = self.read_register(*register);
+                (*old != new).then_some(RegisterChange {
+                    register: *register,
+                    old: *old,
+                    new,
+                })
+            })
+            .collect()
+    }
+
+    /// Priority of the current exception, boosted to 0 by PRIMASK
+    fn execution_priority(&self) -> i16 {
+        let current = Exception::from_number(self.ipsr.exception_number())
+            .map_or(THREAD_PRIORITY, |exception| self.nvic.priority(exception));
+
+        if self.primask & 1 == 1 {
+            current.min(0)
+        } else {
+            current
+        }
+    }
+
+    /// Pending exception that preempts the current execution
+    fn preempting_exception(&self) -> Option<Exception> {
+        self.nvic
+            .highest_pending()
+            .filter(|exception| self.nvic.priority(*exception) < self.execution_priority())
+    }
+
+    /// Whether WFI and WFE should wake up, PRIMASK does not prevent it
+    pub(crate) fn wake_up_pending(&self) -> bool {
+        let current = Exception::from_number(self.ipsr.exception_number())
+            .map_or(THREAD_PRIORITY, |exception| self.nvic.priority(exception));
+
+        self.nvic
+            .highest_pending()
+            .is_some_and(|exception| self.nvic.priority(exception) < current)
+    }
+
+    /// Reset asked by AIRCR or a peripheral, `Off` while a peripheral keeps the system off
+    fn poll_power(&mut self) -> Power {
+        let mut cause = self
+            .nvic
+            .take_reset_request()
+            .then_some(ResetCause::SoftReset);
+        let mut off = false;
+        for peripheral in &mut self.peripherals {
+            match peripheral.power() {
+                Power::On => {}
+                Power::Off => off = true,
+                Power::Reset(reset) => cause = cause.or(Some(reset)),
+            }
+        }
+
+        match cause {
+            Some(cause) => Power::Reset(cause),
+            None if off => Power::Off,
+            None => Power::On,
+        }
+    }
+
+    /// Level sensitive interrupts become pending while the peripheral asserts them,
+    /// unless their handler is running
+    fn sample_interrupts(&mut self) {
+        for peripheral in &self.peripherals {
+            if let Some(irq) = peripheral.interrupt() {
+                let exception = Exception::Interrupt(irq);
+                if exception.number() != self.ipsr.exception_number() {
+                    self.nvic.set_pending(exception, true);
+                }
+            }
+        }
+    }
+
+    fn execute_instruction(&mut self, address: u32) -> Result<u32, error::Error> {
+        if !self.epsr.t() {
+            return Err(error::Error::Fault(Exception::HardFault));
+        }
+
+        // NOTE: Undefined instructions escalate to HardFault on Armv6-M
+        let Some((instruction, size)) = self.blocks.fetch(&self.memory, address) else {
+            return Err(error::Error::Fault(Exception::HardFault));
+        };
+        instruction.execute(self)?;
+        self.charged += timing::cycles(&instruction, self.branch.is_some())
+            + self.memory_timing.wait_states(address);
+
+        Ok(size)
+    }
+
+    /// Undo the side effects of an instruction that did not complete
+    fn abort_instruction(&mut self, registers: &[u32; RegisterId::ALL.len()]) {
+        for change in self.journal.drain(..).rev() {
+            self.memory.write(change.address, change.size, change.old);
+            self.blocks.invalidate(change.address, change.size.into());
+        }
+        for (register, value) in RegisterId::ALL.iter().zip(registers) {
+            self.write_register(*register, *value);
+        }
+        self.accesses.clear();
+        self.branch = None;
+        self.stop = None;
+        self.charged = 0;
+    }
+
+    /// Let `cycles` cycles elapse for SysTick and the peripherals
+    fn advance(&mut self, cycles: u64) {
+        self.cycles += cycles;
+        if self.sys_tick.tick(cycles) {
+            self.nvic.set_pending(Exception::SysTick, true);
+        }
+        for peripheral in &mut self.peripherals {
+            peripheral.tick(cycles);
+        }
+    }
+
+    /// Skip the time until the next event of SysTick or a peripheral, while WFI or WFE sleeps.
+    /// The instruction is executed again until an exception wakes the core up.
+    /// Returns false when nothing can happen anymore.
+    pub(crate) fn sleep(&mut self) -> bool {
+        let next = self
+            .peripherals
+            .iter()
+            .filter_map(|peripheral| peripheral.next_event())
+            .chain(self.sys_tick.next_event())
+            .min();
+        let Some(cycles) = next else {
+            return false;
+        };
+
+        self.advance(cycles);
+        self.sample_interrupts();
+        if !self.wake_up_pending() {
+            self.branch = Some(self.pc);
+        }
+        true
+    }
+
+    /* === Helpers for the instructions === */
+
+    /// Read R0-R15 as an operand, the PC reads as the address of the instruction plus 4
+    pub(crate) fn reg(&self, n: u8) -> u32 {
+        match n {
+            0..=12 => self.registers[usize::from(n)],
+            13 => self.get_sp(),
+            14 => self.lr,
+            _ => self.pc.wrapping_add(4),
+        }
+    }
+
+    /// Write R0-R15 as a result, writing the PC is a simple branch (`ALUWritePC`)
+    pub(crate) fn set_reg(&mut self, n: u8, value: u32) {
+        match n {
+            0..=12 => self.registers[usize::from(n)] = value,
+            13 => self.set_sp(value),
+            14 => self.lr = value,
+            _ => self.branch_write_pc(value),
+        }
+    }
+
+    pub(crate) fn carry(&self) -> bool {
+        self.apsr.c()
+    }
+
+    pub(crate) fn set_nz(&mut self, result: u32) {
+        self.apsr.set_n(result >> 31 == 1);
+        self.apsr.set_z(result == 0);
+    }
+
+    pub(crate) fn set_nzc(&mut self, result: u32, carry: bool) {
+        self.set_nz(result);
+        self.apsr.set_c(carry);
+    }
+
+    pub(crate) fn set_nzcv(&mut self, result: u32, carry: bool, overflow: bool) {
+        self.set_nzc(result, carry);
+        self.apsr.set_v(overflow);
+    }
+
+    /// `BranchWritePC()`
+    pub(crate) fn branch_write_pc(&mut self, address: u32) {
+        self.branch = Some(address & !1);
+    }
+
+    /// `BXWritePC()`, an EXC_RETURN value in Handler mode returns from the exception
+    pub(crate) fn bx_write_pc(&mut self, address: u32) -> Result<(), error::Error> {
+        if self.is_handler_mode() && address >> 28 == 0xF {
+            return self.exception_return(address);
+        }
+
+        self.epsr.set_t(address & 1 == 1);
+        self.branch = Some(address & !1);
+        Ok(())
+    }
+
+    pub(crate) fn stop(&mut self, reason: StopReason) {
+        self.stop = Some(reason);
+    }
+
+    pub(crate) fn send_event(&mut self) {
+        self.event = true;
+    }
+
+    /// Consume the Event Register, returns whether it was set
+    pub(crate) fn take_event(&mut self) -> bool {
+        std::mem::take(&mut self.event)
+    }
+
+    /// Accesses must be aligned on their size, a HardFault is raised otherwise
+    pub(crate) fn read_memory(&mut self, address: u32, size: u8) -> Result<u32, error::Error> {
+        if !address.is_multiple_of(u32::from(size)) {
+            return Err(error::Error::Fault(Exception::HardFault));
+        }
+
+        let value = if (SCS_START..=SCS_END).contains(&address) {
+            // NOTE: System Control Space registers are only word accessible
+            if size != 4 {
+                0
+            } else if SysTick::contains(address) {
+                self.sys_tick.read(address)
+            } else {
+                self.nvic.read(address, self.ipsr.exception_number())
+            }
+        } else if let Some(peripheral) = self
+            .peripherals
+            .iter_mut()
+            .find(|peripheral| peripheral.contains(address))
+        {
+            let offset = address - peripheral.base();
+            peripheral.read(offset, size)
+        } else {
+            self.memory.read(address, size)
+        };
+
+        let access = Access {
+            kind: AccessKind::Read,
+            address,
+            size,
+            value,
+        };
+        self.accesses.push(access);
+        self.check_watchpoints(access);
+        Ok(value)
+    }
+
+    /// Accesses must be aligned on their size, a HardFault is raised otherwise
+    pub(crate) fn write_memory(
+        &mut self,
+        address: u32,
+        size: u8,
+        value: u32,
+    ) -> Result<(), error::Error> {
+        if !address.is_multiple_of(u32::from(size)) {
+            return Err(error::Error::Fault(Exception::HardFault));
+        }
+
+        let value = value & (u64::from(u32::MAX) >> (32 - 8 * u32::from(size))) as u32;
+        let access = Access {
+            kind: AccessKind::Write,
+            address,
+            size,
+            value,
+        };
+        self.accesses.push(access);
+        // NOTE: Checked before the write, to get the old value
+        self.check_watchpoints(access);
+
+        if (SCS_START..=SCS_END).contains(&address) {
+            if size == 4 && SysTick::contains(address) {
+                self.sys_tick.write(address, value);
+            } else if size == 4 {
+                self.nvic.write(address, value);
+            }
+        } else if let Some(peripheral) = self
+            .peripherals
+            .iter_mut()
+            .find(|peripheral| peripheral.contains(address))
+        {
+            let offset = address - peripheral.base();
+            peripheral.write(offset, size, value);
+        } else {
+            let old = self.memory.read(address, size);
+            self.memory.write(address, size, value);
+            self.blocks.invalidate(address, size.into());
+            self.journal.push(MemoryChange {
+                address,
+                size,
+                old,
+                new: value,
+            });
+        }
+        Ok(())
+    }
+
+    /// Value at `address` without side effects on the peripherals
+    fn peek(&self, address: u32, size: u8) -> Option<u32> {
+        if (SCS_START..=SCS_END).contains(&address) {
+            Some(if size != 4 {
+                0
+            } else if SysTick::contains(address) {
+                self.sys_tick.peek(address)
+            } else {
+                self.nvic.read(address, self.ipsr.exception_number())
+            })
+        } else if let Some(peripheral) = self
+            .peripherals
+            .iter()
+            .find(|peripheral| peripheral.contains(address))
+        {
+            peripheral.peek(address - peripheral.base(), size)
+        } else {
+            Some(self.memory.read(address, size))
+        }
+    }
+
+    /// Stop after the current instruction when the access matches a watchpoint
+    fn check_watchpoints(&mut self, access: Access) {
+        if self.stop.is_some() {
+            return;
+        }
+        let Some(watchpoint) = self
+            .watchpoints
+            .iter()
+            .find(|watchpoint| watchpoint.matches(&access))
+        else {
+            return;
+        };
+
+        let old = match access.kind {
+            AccessKind::Read => Some(access.value),
+            AccessKind::Write => self.peek(access.address, access.size),
+        };
+        self.stop = Some(StopReason::Watchpoint {
+            kind: watchpoint.kind,
+            pc: self.pc,
+            address: access.address,
+            old,
+            new: access.value,
+        });
+    }
+
+    /// Stop before the next instruction when it has a breakpoint whose condition holds
+    fn check_breakpoint(&mut self) {
+        if self.stop.is_some() || self.breakpoints.is_empty() {
+            return;
+        }
+        // NOTE: Taken out of the map while the condition reads the core
+        let Some(mut breakpoint) = self.breakpoints.remove(&self.pc) else {
+            return;
+        };
+        if breakpoint.reached(self) {
+            self.stop = Some(StopReason::Breakpoint(self.pc));
+        }
+        self.breakpoints.insert(self.pc, breakpoint);
+    }
+
+    /// `CallSupervisor()`, the SVCall handler returns after the SVC instruction
+    pub(crate) fn call_supervisor(&mut self) -> Result<(), error::Error> {
+        self.exception_entry(Exception::SvCall, self.pc.wrapping_add(2))
+    }
+
+    /// `PushStack()` followed by `ExceptionTaken()`
+    /// <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Level-Programmers--Model/Armv6-M-exception-model/Exception-entry-behavior>
+    fn exception_entry(
+        &mut self,
+        exception: Exception,
+        return_address: u32,
+    ) -> Result<(), error::Error> {
+        if exception == Exception::HardFault
+            && matches!(
+                Exception::from_number(self.ipsr.exception_number()),
+                Some(Exception::HardFault | Exception::Nmi)
+            )
+        {
+            return Err(error::Error::Lockup(return_address));
+        }
+
+        // NOTE: The frame is always 8 bytes aligned, bit 9 of the stacked xPSR records the realignment
+        let sp = self.get_sp();
+        let realigned = sp & 0b100;
+        let frame = sp.wrapping_sub(0x20) & !0b100;
+        let xpsr = (self.get_xpsr() & !(1 << 9)) | (realigned << 7);
+        let stacked = [
+            self.registers[0],
+            self.registers[1],
+            self.registers[2],
+            self.registers[3],
+            self.registers[12],
+            self.lr,
+            return_address,
+            xpsr,
+        ];
+
+        self.charged += EXCEPTION_ENTRY_CYCLES;
+        self.set_sp(frame);
+        for (i, value) in stacked.into_iter().enumerate() {
+            self.write_memory(frame.wrapping_add(4 * i as u32), 4, value)?;
+        }
+
+        self.lr = if self.is_handler_mode() {
+            EXC_RETURN_HANDLER
+        } else if self.uses_process_stack() {
+            EXC_RETURN_THREAD_PROCESS
+        } else {
+            EXC_RETURN_THREAD_MAIN
+        };
+
+        let vector = self.memory.read_u32(4 * exception.number());
+        self.nvic.set_pending(exception, false);
+        self.ipsr = Ipsr::from_bits(exception.number());
+        self.control &= !0b10;
+        self.epsr.set_t(vector & 1 == 1);
+        self.branch = Some(vector & !1);
+        self.event = true;
+        Ok(())
+    }
+
+    /// `ExceptionReturn()`
+    /// <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Level-Programmers--Model/Armv6-M-exception-model/Exception-return-behavior>
+    fn exception_return(&mut self, exc_return: u32) -> Result<(), error::Error> {
+        let process = match exc_return {
+            EXC_RETURN_HANDLER | EXC_RETURN_THREAD_MAIN => false,
+            EXC_RETURN_THREAD_PROCESS => true,
+            _ => return Err(error::Error::Fault(Exception::HardFault)),
+        };
+
+        let frame = if process {
+            self.sp_process
+        } else {
+            self.sp_main
+        };
+        let mut stacked = [0; 8];
+        for (i, value) in stacked.iter_mut().enumerate() {
+            *value = self.read_memory(frame.wrapping_add(4 * i as u32), 4)?;
+        }
+        let [r0, r1, r2, r3, r12, lr, pc, xpsr] = stacked;
+
+        self.registers[..4].copy_from_slice(&[r0, r1, r2, r3]);
+        self.registers[12] = r12;
+        self.lr = lr;
+
+        let sp = frame.wrapping_add(0x20) | ((xpsr >> 7) & 0b100);
+        if process {
+            self.sp_process = sp;
+        } else {
+            self.sp_main = sp;
+        }
+
+        self.control = (self.control & !0b10) | if process { 0b10 } else { 0 };
+        self.set_xpsr(xpsr);
+        if exc_return == EXC_RETURN_HANDLER {
+            // NOTE: Returning to Handler mode with IPSR 0 is UNPREDICTABLE, it would continue in Thread mode
+            if self.ipsr.exception_number() == 0 {
+                return Err(error::Error::Fault(Exception::HardFault));
+            }
+        } else {
+            self.ipsr = Ipsr::default();
+        }
+        self.branch = Some(pc & !1);
+        self.event = true;
+        Ok(())
+    }
 }
 
 impl Runtime for Armv6M {
     type Error = error::Error;
 
-    type Mutation = instructions::add::Add;
+    type Mutation = Mutation;
+
+    type Memory = Memory;
 
     fn init() -> Self {
-        todo!()
+        let memory = Memory::new();
+        let history = History::new(CheckpointConfig::default(), &memory);
+
+        Self {
+            memory,
+            registers: [0; 13],
+            lr: 0,
+            pc: 0,
+            sp_main: 0,
+            sp_process: 0,
+            control: 0,
+            primask: 0,
+            apsr: Apsr::default(),
+            ipsr: Ipsr::default(),
+            epsr: Epsr::default(),
+            branch: None,
+            stop: None,
+            event: false,
+            journal: Vec::new(),
+            charged: 0,
+            cycles: 0,
+            accesses: Vec::new(),
+            history,
+            blocks: BlockCache::new(),
+            tracer: None,
+            watchpoints: Vec::new(),
+            breakpoints: BTreeMap::new(),
+            reset_on_lockup: false,
+            checked_breakpoint: None,
+            symbols: Symbols::new(),
+            debug_info: DebugInfo::new(),
+            nvic: Nvic::new(),
+            sys_tick: SysTick::new(),
+            memory_timing: MemoryTiming::new(),
+            peripherals: Vec::new(),
+        }
     }
 
+    /// ELF files are detected by their magic, `.hex` files are read as Intel HEX
+    /// and anything else as a raw flash image
     fn load_file(&mut self, path: &str) -> Result<&mut Self, Self::Error> {
-        todo!()
+        let bytes = std::fs::read(path)?;
+
+        if loader::is_elf(&bytes) {
+            loader::load_elf(&mut self.memory, &bytes)?;
+            self.symbols = Symbols::from_elf(&bytes)?;
+            self.debug_info = DebugInfo::from_elf(&bytes)?;
+            Ok(self.reset())
+        } else if path.to_ascii_lowercase().ends_with(".hex") {
+            let hex = String::from_utf8_lossy(&bytes);
+            self.symbols = Symbols::new();
+            self.debug_info = DebugInfo::new();
+            self.load_hex(&hex)
+        } else {
+            self.symbols = Symbols::new();
+            self.debug_info = DebugInfo::new();
+            self.load_bytes(&bytes)
+        }
     }
 
     fn load_hex(&mut self, hex: &str) -> Result<&mut Self, Self::Error> {
-        todo!()
+        loader::load_hex(&mut self.memory, hex)?;
+        Ok(self.reset())
     }
 
+    /// The bytes are written at the start of the flash, where the vector table lives
     fn load_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self, Self::Error> {
-        todo!()
+        self.memory.write_bytes(0, bytes);
+        Ok(self.reset())
+    }
+
+    fn save_snapshot(&self, path: &str) -> Result<(), Self::Error> {
+        std::fs::write(path, self.snapshot()?)?;
+        Ok(())
+    }
+
+    fn load_snapshot(&mut self, path: &str) -> Result<&mut Self, Self::Error> {
+        let bytes = std::fs::read(path)?;
+        self.restore_snapshot(&bytes)
+    }
+
+    fn get_memory(&self) -> &Self::Memory {
+        &self.memory
     }
 
-    fn get_memory(&self) -> std::rc::Rc<[u8]> {
-        todo!()
+    fn get_mutations_history(&self) -> &[Self::Mutation] {
+        self.history.mutations()
     }
 
-    fn get_mutations_history(&self) -> std::rc::Rc<[Self::Mutation]> {
-        todo!()
+    fn get_memory_at_mutation(&self, idx: usize) -> Result<Self::Memory, Self::Error> {
+        self.history
+            .memory_at(idx, &self.memory)
+            .ok_or(error::Error::MutationOutOfRange {
+                index: idx,
+                len: self.history.len(),
+            })
     }
 
-    fn get_memory_at_mutation(&self, idx: usize) -> Vec<u8> {
-        todo!()
+    fn step(&mut self) -> Result<Option<StopReason>, Self::Error> {
+        match self.poll_power() {
+            Power::On => {}
+            Power::Off => return Ok(Some(StopReason::SystemOff)),
+            // NOTE: The reset takes the step, the core starts from the reset vector on the next one
+            Power::Reset(cause) => {
+                self.system_reset(cause);
+                return Ok(None);
+            }
+        }
+        // NOTE: Checked before executing when the last step did not, at the start of a run
+        // or after it stopped on a watchpoint
+        if self.checked_breakpoint.take() != Some(self.pc) {
+            self.check_breakpoint();
+            if let Some(reason) = self.stop.take() {
+                self.checked_breakpoint = Some(self.pc);
+                return Ok(Some(reason));
+            }
+        }
+        let address = self.pc;
+        let registers = self.register_bank();
+        let cycles = self.cycles;
+        self.charged = 0;
+        self.journal.clear();
+        self.accesses.clear();
+        self.branch = None;
+        self.stop = None;
+        // NOTE: Disassembled before the instruction may overwrite itself
+        let line = self
+            .tracer
+            .as_ref()
+            .filter(|tracer| tracer.traces(address))
+            .map(|_| self.disassemble(address));
+
+        // NOTE: A pending exception is taken instead of the next instruction
+        let mut taken = self.preempting_exception();
+        let result = match taken {
+            Some(exception) => self.exception_entry(exception, address).map(|()| 0),
+            None => self.execute_instruction(address),
+        };
+
+        let mut faulted = false;
+        match result {
+            Ok(size) => self.pc = self.branch.unwrap_or(address.wrapping_add(size)),
+            Err(error::Error::Fault(exception)) => {
+                self.abort_instruction(&registers);
+                if let Err(error) = self.exception_entry(exception, address) {
+                    self.abort_instruction(&registers);
+                    if let Some(tracer) = &mut self.tracer {
+                        tracer.dump()?;
+                    }
+                    if self.reset_on_lockup && matches!(error, error::Error::Lockup(_)) {
+                        self.system_reset(ResetCause::Lockup);
+                        return Ok(None);
+                    }
+                    return Err(error);
+                }
+                self.pc = self.branch.unwrap_or(address);
+                taken = Some(exception);
+                faulted = true;
+            }
+            Err(error) => {
+                self.abort_instruction(&registers);
+                return Err(error);
+            }
+        }
+
+        let wait_states = self
+            .accesses
+            .iter()
+            .map(|access| self.memory_timing.wait_states(access.address))
+            .sum::<u64>();
+        self.advance(self.charged + wait_states);
+
+        let mutation = Mutation {
+            address,
+            cycles: self.cycles - cycles,
+            registers: self.register_changes(&registers),
+            memory: std::mem::take(&mut self.journal),
+            debugger: false,
+        };
+        if let (Some(line), Some(tracer)) = (line, &mut self.tracer) {
+            let (halfwords, text) = if taken.is_some() && !faulted {
+                (Vec::new(), String::new())
+            } else {
+                (line.halfwords, line.text)
+            };
+            tracer.record(TraceEntry {
+                address,
+                halfwords,
+                text,
+                exception: taken,
+                registers: mutation.registers.clone(),
+                accesses: self.accesses.clone(),
+                cycles: mutation.cycles,
+                cycle: self.cycles,
+            })?;
+            if faulted {
+                tracer.dump()?;
+            }
+        }
+        self.history.step(mutation, &self.memory);
+        self.sample_interrupts();
+        if self.stop.is_none() {
+            self.check_breakpoint();
+            self.checked_breakpoint = Some(self.pc);
+        }
+
+        Ok(self.stop.take())
     }
 
-    fn step(&mut self) -> Self {
-        todo!()
+    fn run(&mut self) -> Result<StopReason, Self::Error> {
+        loop {
+            if let Some(reason) = self.step()? {
+                return Ok(reason);
+            }
+        }
+    }
+}
+
+impl RuntimeExtras for Armv6M {
+    fn apply_mutation(
+        &mut self,
+        mutation: <Self as Runtime>::Mutation,
+    ) -> Result<&mut Self, <Self as Runtime>::Error> {
+        mutation.apply(self);
+        self.history.push(mutation);
+        Ok(self)
     }
 
-    fn run(&mut self) -> Self {
-        todo!()
+    fn rollback_last_mutation(&mut self) -> Result<&mut Self, <Self as Runtime>::Error> {
+        let mutation = self.history.pop().ok_or(error::Error::EmptyHistory)?;
+        mutation.rollback(self);
+        Ok(self)
     }
 }
@@ -0,0 +1,104 @@
+//! Program images parsers, they write the image content in memory.
+
+use elf::abi::{PT_LOAD, SHF_ALLOC, SHT_PROGBITS};
+use elf::endian::LittleEndian;
+use elf::ElfBytes;
+
+use crate::error::Error;
+use crate::memory::Memory;
+
+pub const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+pub fn is_elf(bytes: &[u8]) -> bool {
+    bytes.starts_with(ELF_MAGIC)
+}
+
+/// Load the `PT_LOAD` segments at their physical address, so initialized data lands in flash
+/// like when flashing the board. Objects without segments get their allocated sections loaded.
+pub fn load_elf(memory: &mut Memory, bytes: &[u8]) -> Result<(), Error> {
+    let file = ElfBytes::<LittleEndian>::minimal_parse(bytes)?;
+
+    let mut loaded = false;
+    for segment in file.segments().into_iter().flatten() {
+        if segment.p_type == PT_LOAD && segment.p_filesz > 0 {
+            let data = file.segment_data(&segment)?;
+            memory.write_bytes(segment.p_paddr as u32, &data[..segment.p_filesz as usize]);
+            loaded = true;
+        }
+    }
+
+    if !loaded {
+        for section in file.section_headers().into_iter().flatten() {
+            if section.sh_type == SHT_PROGBITS && section.sh_flags & u64::from(SHF_ALLOC) != 0 {
+                let (data, _) = file.section_data(&section)?;
+                memory.write_bytes(section.sh_addr as u32, data);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Intel HEX, as produced by the micro:bit toolchains.
+/// Records after the End Of File record, like the MakeCode sources, are ignored.
+pub fn load_hex(memory: &mut Memory, hex: &str) -> Result<(), Error> {
+    let mut base = 0u32;
+
+    for (line, record) in hex.lines().map(str::trim).enumerate() {
+        let line = line + 1;
+        if record.is_empty() {
+            continue;
+        }
+
+        let bytes = record
+            .strip_prefix(':')
+            .filter(|digits| digits.len() % 2 == 0 && digits.len() >= 10)
+            .and_then(|digits| {
+                (0..digits.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+                    .collect::<Option<Vec<u8>>>()
+            })
+            .ok_or(Error::Hex {
+                line,
+                reason: "malformed record",
+            })?;
+
+        let length = usize::from(bytes[0]);
+        if bytes.len() != length + 5 {
+            return Err(Error::Hex {
+                line,
+                reason: "record length mismatch",
+            });
+        }
+        if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+            return Err(Error::Hex {
+                line,
+                reason: "bad checksum",
+            });
+        }
+
+        let offset = u32::from(u16::from_be_bytes([bytes[1], bytes[2]]));
+        let data = &bytes[4..4 + length];
+        match (bytes[3], data) {
+            // Data
+            (0x00, _) => memory.write_bytes(base.wrapping_add(offset), data),
+            // End Of File
+            (0x01, _) => break,
+            // Extended Segment Address
+            (0x02, [high, low]) => base = u32::from(u16::from_be_bytes([*high, *low])) << 4,
+            // Extended Linear Address
+            (0x04, [high, low]) => base = u32::from(u16::from_be_bytes([*high, *low])) << 16,
+            // Start Segment Address and Start Linear Address, the vector table is used instead
+            (0x03 | 0x05, _) => {}
+            _ => {
+                return Err(Error::Hex {
+                    line,
+                    reason: "unsupported record",
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
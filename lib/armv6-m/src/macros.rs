@@ -46,4 +46,27 @@ macro_rules! parse_bits {
             |(_, ($($fields),+))| $res
         )
     };
-}
\ No newline at end of file
+}
+
+/// Inverse of `parse_bits!`, concatenate `(value, width)` fields into halfwords with `encoder::halfwords`
+#[macro_export]
+macro_rules! encode_bits {
+    ($(($value:expr, $width:expr)),+ $(,)?) => {
+        $crate::encoder::halfwords(&[$(($value as u32, $width)),+])
+    };
+}
+
+/// Assemble Thumb code with `assembler::assemble`, one string per line,
+/// at address 0 or at the address given before `=>`. Panic when the source is invalid.
+#[macro_export]
+macro_rules! thumb {
+    ($origin:expr => $($line:expr),* $(,)?) => {
+        match $crate::assembler::assemble(concat!($($line, "\n"),*), $origin) {
+            Ok(program) => program,
+            Err(error) => panic!("{}", error),
+        }
+    };
+    ($($line:expr),* $(,)?) => {
+        $crate::thumb!(0 => $($line),*)
+    };
+}
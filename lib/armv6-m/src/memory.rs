@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+/* === Accesses === */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+/// A data access done by the core, on the memory or on a peripheral
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Access {
+    pub kind: AccessKind,
+    pub address: u32,
+    /// 1, 2 or 4 bytes
+    pub size: u8,
+    /// Value read or written
+    pub value: u32,
+}
+
+/* === Pages === */
+/// Pages are 4KiB, as the nRF51 flash and RAM are made of 1KiB pages / 4KiB blocks
+pub const PAGE_SHIFT: u32 = 12;
+pub const PAGE_SIZE: usize = 1 << PAGE_SHIFT;
+
+pub type Page = [u8; PAGE_SIZE];
+
+const PAGE_MASK: u32 = PAGE_SIZE as u32 - 1;
+
+/// Sparse memory covering the whole 32 bits address space.
+///
+/// Pages are only allocated once written to, unallocated pages read as zero.
+/// They are reference counted and copied on write, so cloning a `Memory` is cheap
+/// and clones only pay for the pages they modify afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    pages: BTreeMap<u32, Rc<Page>>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read_u8(&self, address: u32) -> u8 {
+        self.pages
+            .get(&(address >> PAGE_SHIFT))
+            .map_or(0, |page| page[(address & PAGE_MASK) as usize])
+    }
+
+    pub fn write_u8(&mut self, address: u32, value: u8) {
+        let page = self
+            .pages
+            .entry(address >> PAGE_SHIFT)
+            .or_insert_with(|| Rc::new([0; PAGE_SIZE]));
+
+        Rc::make_mut(page)[(address & PAGE_MASK) as usize] = value;
+    }
+
+    /// Read `size` bytes (1, 2 or 4) in little endian
+    pub fn read(&self, address: u32, size: u8) -> u32 {
+        (0..u32::from(size)).fold(0, |value, i| {
+            value | u32::from(self.read_u8(address.wrapping_add(i))) << (8 * i)
+        })
+    }
+
+    /// Write the `size` (1, 2 or 4) low bytes of `value` in little endian
+    pub fn write(&mut self, address: u32, size: u8, value: u32) {
+        for i in 0..u32::from(size) {
+            self.write_u8(address.wrapping_add(i), (value >> (8 * i)) as u8);
+        }
+    }
+
+    pub fn read_u16(&self, address: u32) -> u16 {
+        self.read(address, 2) as u16
+    }
+
+    pub fn read_u32(&self, address: u32) -> u32 {
+        self.read(address, 4)
+    }
+
+    pub fn write_u16(&mut self, address: u32, value: u16) {
+        self.write(address, 2, u32::from(value));
+    }
+
+    pub fn write_u32(&mut self, address: u32, value: u32) {
+        self.write(address, 4, value);
+    }
+
+    pub fn read_bytes(&self, address: u32, buffer: &mut [u8]) {
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte = self.read_u8(address.wrapping_add(i as u32));
+        }
+    }
+
+    pub fn write_bytes(&mut self, address: u32, bytes: &[u8]) {
+        for (i, byte) in bytes.iter().enumerate() {
+            self.write_u8(address.wrapping_add(i as u32), *byte);
+        }
+    }
+
+    /// Allocated pages with the address of their first byte
+    pub fn pages(&self) -> impl Iterator<Item = (u32, &Rc<Page>)> + '_ {
+        self.pages
+            .iter()
+            .map(|(number, page)| (number << PAGE_SHIFT, page))
+    }
+
+    pub fn allocated_pages(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+impl PartialEq for Memory {
+    /// Unallocated pages are equal to zeroed pages
+    fn eq(&self, other: &Self) -> bool {
+        let zero = [0; PAGE_SIZE];
+        let same = |a: &Self, b: &Self| {
+            a.pages
+                .iter()
+                .all(|(number, page)| match b.pages.get(number) {
+                    Some(other) => Rc::ptr_eq(page, other) || page[..] == other[..],
+                    None => page[..] == zero[..],
+                })
+        };
+
+        same(self, other) && same(other, self)
+    }
+}
+
+impl Eq for Memory {}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::abi::MemoryMutation;
+use crate::memory::Memory;
+use crate::structure::RegisterId;
+use crate::Armv6M;
+
+/// A register modified by an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterChange {
+    pub register: RegisterId,
+    pub old: u32,
+    pub new: u32,
+}
+
+/// A memory write done by an instruction, `size` is 1, 2 or 4 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryChange {
+    pub address: u32,
+    pub size: u8,
+    pub old: u32,
+    pub new: u32,
+}
+
+/// Every side effect of a single step of the core, enough to replay it or to undo it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mutation {
+    /// Address of the executed instruction
+    pub address: u32,
+    /// Cycles elapsed during the step, sleeping included
+    pub cycles: u64,
+    pub registers: Vec<RegisterChange>,
+    /// Writes in the order they happened
+    pub memory: Vec<MemoryChange>,
+    /// Written by a debugger rather than executed by the core, reverse execution does not count it as a step
+    pub debugger: bool,
+}
+
+impl Mutation {
+    pub fn is_empty(&self) -> bool {
+        self.registers.is_empty() && self.memory.is_empty() && self.cycles == 0
+    }
+
+    pub fn apply_memory(&self, memory: &mut Memory) {
+        for change in &self.memory {
+            memory.write(change.address, change.size, change.new);
+        }
+    }
+
+    pub fn rollback_memory(&self, memory: &mut Memory) {
+        for change in self.memory.iter().rev() {
+            memory.write(change.address, change.size, change.old);
+        }
+    }
+
+    /// Drop the decoded blocks overwritten by the memory writes
+    fn invalidate_blocks(&self, on: &mut Armv6M) {
+        for change in &self.memory {
+            on.blocks.invalidate(change.address, change.size.into());
+        }
+    }
+}
+
+impl MemoryMutation<Armv6M> for Mutation {
+    fn apply(&self, on: &mut Armv6M) {
+        self.apply_memory(&mut on.memory);
+        self.invalidate_blocks(on);
+        on.cycles += self.cycles;
+        for change in &self.registers {
+            on.write_register(change.register, change.new);
+        }
+    }
+
+    fn rollback(&self, on: &mut Armv6M) {
+        self.rollback_memory(&mut on.memory);
+        self.invalidate_blocks(on);
+        on.cycles = on.cycles.saturating_sub(self.cycles);
+        for change in &self.registers {
+            on.write_register(change.register, change.old);
+        }
+    }
+}
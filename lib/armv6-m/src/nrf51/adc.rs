@@ -0,0 +1,398 @@
+//! ADC of the nRF51, converting the voltage of an analog input or of the supply to 8, 9 or 10
+//! bits against the band gap, an external or the supply reference.
+//!
+//! The host sets the voltage of the analog inputs AIN0 to AIN7, of the supply and of the
+//! external references, or drives an input with a waveform of the emulated time. The voltage is
+//! sampled at the end of the conversion.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{read_bytes, write_bytes, write_inten, INTEN, INTENCLR, INTENSET};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+use crate::timing::NRF51_FREQUENCY;
+
+pub const ADC_BASE: u32 = 0x4000_7000;
+pub const ADC_IRQ: u8 = 7;
+pub const INPUTS: usize = 8;
+/// GPIO pins of AIN0 to AIN7
+pub const AIN_PINS: [u8; INPUTS] = [26, 27, 1, 2, 3, 4, 5, 6];
+
+pub const TASKS_START: u32 = 0x000;
+pub const TASKS_STOP: u32 = 0x004;
+pub const EVENTS_END: u32 = 0x100;
+pub const BUSY: u32 = 0x400;
+pub const ENABLE: u32 = 0x500;
+pub const CONFIG: u32 = 0x504;
+pub const RESULT: u32 = 0x508;
+
+/// Band gap reference
+pub const VBG: f64 = 1.2;
+pub const DEFAULT_SUPPLY: f64 = 3.3;
+/// Cycles of the core clock of a conversion at 8, 9 and 10 bits, 20, 36 and 68 µs
+pub const CONVERSION: [u64; 3] = [320, 576, 1088];
+
+const INTEN_END: u32 = 1 << 0;
+const RES_MASK: u32 = 0x3;
+const INPSEL_SHIFT: u32 = 2;
+const REFSEL_SHIFT: u32 = 5;
+const PSEL_SHIFT: u32 = 8;
+const EXTREFSEL_SHIFT: u32 = 16;
+/// RES, INPSEL, REFSEL, PSEL and EXTREFSEL
+const CONFIG_MASK: u32 = 0x3_FF7F;
+/// Supply with one-third prescaling, 8 bits and the band gap reference
+const CONFIG_RESET: u32 = 0x18;
+
+/// Input selected by CONFIG.INPSEL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// Analog input selected by PSEL, with its prescaling
+    Pin {
+        prescaling: Prescaling,
+    },
+    Supply {
+        prescaling: Prescaling,
+    },
+    Reserved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prescaling {
+    None,
+    TwoThirds,
+    OneThird,
+}
+
+impl Prescaling {
+    fn factor(self) -> f64 {
+        match self {
+            Prescaling::None => 1.0,
+            Prescaling::TwoThirds => 2.0 / 3.0,
+            Prescaling::OneThird => 1.0 / 3.0,
+        }
+    }
+}
+
+/// Reference selected by CONFIG.REFSEL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reference {
+    BandGap,
+    /// AREF0 or AREF1 selected by EXTREFSEL, `None` without any
+    External(Option<u8>),
+    SupplyOneHalf,
+    SupplyOneThird,
+}
+
+enum Source {
+    Voltage(f64),
+    /// Voltage from the time in seconds since the ADC was attached
+    Waveform(Box<dyn Fn(f64) -> f64>),
+}
+
+/// Voltages set by the host
+struct Host {
+    inputs: [Source; INPUTS],
+    supply: f64,
+    references: [f64; 2],
+    /// Cycles of the core clock elapsed since the ADC was attached
+    cycle: u64,
+}
+
+impl Host {
+    fn input(&self, ain: usize) -> f64 {
+        match &self.inputs[ain] {
+            Source::Voltage(volts) => *volts,
+            Source::Waveform(waveform) => waveform(self.cycle as f64 / NRF51_FREQUENCY as f64),
+        }
+    }
+}
+
+/// Handle on the analog inputs of an `Adc`, for the host.
+///
+/// The methods panic on an input other than AIN0 to AIN7 or a reference other than AREF0 and
+/// AREF1.
+#[derive(Clone)]
+pub struct Analog(Rc<RefCell<Host>>);
+
+/// Index of AIN0 to AIN7, panics on the other inputs
+fn input(ain: u8) -> usize {
+    let index = usize::from(ain);
+    assert!(
+        index < INPUTS,
+        "AIN{ain} does not exist, the inputs are AIN0 to AIN7"
+    );
+    index
+}
+
+/// Index of AREF0 or AREF1, panics on the other references
+fn reference(aref: u8) -> usize {
+    let index = usize::from(aref);
+    assert!(
+        index < 2,
+        "AREF{aref} does not exist, the references are AREF0 and AREF1"
+    );
+    index
+}
+
+impl Analog {
+    /// Voltage of AIN0 to AIN7, 0 V until set
+    pub fn set_voltage(&self, ain: u8, volts: f64) {
+        self.0.borrow_mut().inputs[input(ain)] = Source::Voltage(volts);
+    }
+
+    /// Drive an analog input with the voltage given by `waveform` for the time in seconds since
+    /// the ADC was attached
+    pub fn set_waveform(&self, ain: u8, waveform: impl Fn(f64) -> f64 + 'static) {
+        self.0.borrow_mut().inputs[input(ain)] = Source::Waveform(Box::new(waveform));
+    }
+
+    /// Voltage of an analog input now
+    pub fn voltage(&self, ain: u8) -> f64 {
+        self.0.borrow().input(input(ain))
+    }
+
+    /// Supply voltage VDD, 3.3 V by default
+    pub fn set_supply(&self, volts: f64) {
+        self.0.borrow_mut().supply = volts;
+    }
+
+    /// Voltage of the external reference AREF0 or AREF1, 0 V until set
+    pub fn set_reference(&self, aref: u8, volts: f64) {
+        self.0.borrow_mut().references[reference(aref)] = volts;
+    }
+}
+
+/// Analog input of a GPIO pin
+pub fn ain(pin: u8) -> Option<u8> {
+    AIN_PINS
+        .iter()
+        .position(|ain_pin| *ain_pin == pin)
+        .map(|ain| ain as u8)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    /// Cycles until the end of the conversion
+    converting: Option<u64>,
+    result: u32,
+    end: bool,
+    inten: u32,
+    enable: u32,
+    config: u32,
+    cycle: u64,
+}
+
+impl State {
+    fn new(cycle: u64) -> Self {
+        Self {
+            converting: None,
+            result: 0,
+            end: false,
+            inten: 0,
+            enable: 0,
+            config: CONFIG_RESET,
+            cycle,
+        }
+    }
+}
+
+pub struct Adc {
+    analog: Analog,
+    state: State,
+}
+
+impl Adc {
+    pub fn new() -> Self {
+        Self {
+            analog: Analog(Rc::new(RefCell::new(Host {
+                inputs: std::array::from_fn(|_| Source::Voltage(0.0)),
+                supply: DEFAULT_SUPPLY,
+                references: [0.0; 2],
+                cycle: 0,
+            }))),
+            state: State::new(0),
+        }
+    }
+
+    pub fn analog(&self) -> Analog {
+        self.analog.clone()
+    }
+
+    /// Resolution in bits
+    pub fn bits(&self) -> u32 {
+        match self.state.config & RES_MASK {
+            0 => 8,
+            1 => 9,
+            _ => 10,
+        }
+    }
+
+    pub fn input(&self) -> Input {
+        match self.state.config >> INPSEL_SHIFT & 0x7 {
+            0 => Input::Pin {
+                prescaling: Prescaling::None,
+            },
+            1 => Input::Pin {
+                prescaling: Prescaling::TwoThirds,
+            },
+            2 => Input::Pin {
+                prescaling: Prescaling::OneThird,
+            },
+            5 => Input::Supply {
+                prescaling: Prescaling::TwoThirds,
+            },
+            6 => Input::Supply {
+                prescaling: Prescaling::OneThird,
+            },
+            _ => Input::Reserved,
+        }
+    }
+
+    /// Analog input selected by PSEL, the lowest one when several are
+    pub fn pin(&self) -> Option<u8> {
+        let psel = self.state.config >> PSEL_SHIFT & 0xFF;
+        (psel != 0).then(|| psel.trailing_zeros() as u8)
+    }
+
+    pub fn reference(&self) -> Reference {
+        match self.state.config >> REFSEL_SHIFT & 0x3 {
+            0 => Reference::BandGap,
+            1 => Reference::External(match self.state.config >> EXTREFSEL_SHIFT & 0x3 {
+                1 => Some(0),
+                2 => Some(1),
+                _ => None,
+            }),
+            2 => Reference::SupplyOneHalf,
+            _ => Reference::SupplyOneThird,
+        }
+    }
+
+    /// `[V(input) * prescaling / V(reference)] * (2^bits - 1)`, saturated
+    fn convert(&self) -> u32 {
+        let host = self.analog.0.borrow();
+        let input = match self.input() {
+            Input::Pin { prescaling } => {
+                self.pin().map_or(0.0, |ain| host.input(usize::from(ain))) * prescaling.factor()
+            }
+            Input::Supply { prescaling } => host.supply * prescaling.factor(),
+            Input::Reserved => 0.0,
+        };
+        let reference = match self.reference() {
+            Reference::BandGap => VBG,
+            Reference::External(aref) => {
+                aref.map_or(0.0, |aref| host.references[usize::from(aref)])
+            }
+            Reference::SupplyOneHalf => host.supply / 2.0,
+            Reference::SupplyOneThird => host.supply / 3.0,
+        };
+
+        let max = (1u32 << self.bits()) - 1;
+        if reference <= 0.0 {
+            return if input > 0.0 { max } else { 0 };
+        }
+        let result = (input / reference * f64::from(max)).round();
+        result.clamp(0.0, f64::from(max)) as u32
+    }
+}
+
+impl Default for Adc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Peripheral for Adc {
+    fn name(&self) -> &str {
+        "ADC"
+    }
+
+    fn base(&self) -> u32 {
+        ADC_BASE
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let state = &self.state;
+        let word = match offset & !3 {
+            EVENTS_END => u32::from(state.end),
+            INTEN | INTENSET | INTENCLR => state.inten,
+            BUSY => u32::from(state.converting.is_some()),
+            ENABLE => state.enable,
+            CONFIG => state.config,
+            RESULT => state.result,
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let triggered = bits & 1 != 0;
+        match offset & !3 {
+            TASKS_START if triggered && self.state.enable == 1 => {
+                let resolution = (self.state.config & RES_MASK).min(2) as usize;
+                self.state.converting = Some(CONVERSION[resolution]);
+            }
+            TASKS_STOP if triggered => self.state.converting = None,
+            EVENTS_END => self.state.end = bits != 0,
+            ENABLE => {
+                self.state.enable = (self.state.enable & !mask | bits) & 0x3;
+                if self.state.enable != 1 {
+                    self.state.converting = None;
+                }
+            }
+            CONFIG => self.state.config = (self.state.config & !mask | bits) & CONFIG_MASK,
+            register => {
+                write_inten(&mut self.state.inten, register, bits, mask);
+                self.state.inten &= INTEN_END;
+            }
+        }
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        (self.state.end && self.state.inten & INTEN_END != 0).then_some(ADC_IRQ)
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.state.cycle += cycles;
+        self.analog.0.borrow_mut().cycle = self.state.cycle;
+        match self.state.converting {
+            Some(left) if left > cycles => self.state.converting = Some(left - cycles),
+            Some(_) => {
+                self.state.converting = None;
+                self.state.result = self.convert();
+                self.state.end = true;
+            }
+            None => {}
+        }
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        self.state.converting
+    }
+
+    /// The time of the waveforms goes on
+    fn reset(&mut self, _cause: ResetCause) {
+        self.state = State::new(self.state.cycle);
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        self.state = bincode::deserialize(state)?;
+        self.analog.0.borrow_mut().cycle = self.state.cycle;
+        Ok(())
+    }
+}
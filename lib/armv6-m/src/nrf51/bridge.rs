@@ -0,0 +1,262 @@
+//! Bridge between the UART and a terminal of the host, a pseudo-terminal or a TCP socket, for
+//! `screen`, `minicom`, `mpremote` and the like to talk to the emulated micro:bit as to a board
+//! over USB serial.
+//!
+//! The `Serial` handle is not `Send`, the bridge is pumped from the thread running the core.
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::{fs::File, path::Path, path::PathBuf};
+
+use super::uart::Serial;
+use crate::abi::StopReason;
+use crate::error::Error;
+use crate::timing::NRF51_FREQUENCY;
+use crate::Armv6M;
+
+/// Cycles run between two exchanges, a millisecond of the core clock
+const SLICE: u64 = NRF51_FREQUENCY / 1000;
+/// Wait while the core sleeps and the host sends nothing
+const IDLE: std::time::Duration = std::time::Duration::from_millis(5);
+/// Output kept while the host does not read it, the oldest bytes are dropped past this count
+pub const MAX_UNSENT: usize = 0x1_0000;
+
+enum Link {
+    Tcp {
+        listener: TcpListener,
+        client: Option<TcpStream>,
+    },
+    #[cfg(unix)]
+    Pty {
+        master: File,
+        /// Kept open so that reading the master does not fail while no terminal is attached
+        _slave: File,
+        path: PathBuf,
+    },
+}
+
+/// Exchanges the bytes of a `Serial` with the host.
+///
+/// The bridge reads the output with `Serial::take_output`, it sees nothing once a callback is set
+/// with `Serial::on_transmit` or a channel created with `Serial::channel`.
+pub struct Bridge {
+    serial: Serial,
+    link: Link,
+    unsent: Vec<u8>,
+}
+
+impl Bridge {
+    /// Listen on `address` for one client at a time, the output waits for a client
+    pub fn tcp(serial: Serial, address: impl ToSocketAddrs) -> Result<Self, Error> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            serial,
+            link: Link::Tcp {
+                listener,
+                client: None,
+            },
+            unsent: Vec::new(),
+        })
+    }
+
+    /// Open a pseudo-terminal in raw mode, its path is given by `path`
+    #[cfg(unix)]
+    pub fn pty(serial: Serial) -> Result<Self, Error> {
+        let (master, slave, path) = open_pty()?;
+        Ok(Self {
+            serial,
+            link: Link::Pty {
+                master,
+                _slave: slave,
+                path,
+            },
+            unsent: Vec::new(),
+        })
+    }
+
+    /// Address of the TCP socket
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.link {
+            Link::Tcp { listener, .. } => listener.local_addr().ok(),
+            #[cfg(unix)]
+            Link::Pty { .. } => None,
+        }
+    }
+
+    /// Path of the pseudo-terminal, like `/dev/pts/3`
+    #[cfg(unix)]
+    pub fn path(&self) -> Option<&Path> {
+        match &self.link {
+            Link::Pty { path, .. } => Some(path),
+            Link::Tcp { .. } => None,
+        }
+    }
+
+    /// Whether a TCP client is connected, always true for a pseudo-terminal
+    pub fn is_connected(&self) -> bool {
+        match &self.link {
+            Link::Tcp { client, .. } => client.is_some(),
+            #[cfg(unix)]
+            Link::Pty { .. } => true,
+        }
+    }
+
+    /// Exchange the pending bytes without blocking, returns whether the host sent some
+    pub fn pump(&mut self) -> Result<bool, Error> {
+        let stream: &mut dyn ReadWrite = match &mut self.link {
+            Link::Tcp { listener, client } => {
+                if client.is_none() {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            stream.set_nonblocking(true)?;
+                            stream.set_nodelay(true)?;
+                            *client = Some(stream);
+                        }
+                        Err(error) if error.kind() == ErrorKind::WouldBlock => {}
+                        Err(error) => return Err(error.into()),
+                    }
+                }
+                let Some(stream) = client else {
+                    // NOTE: The output waits for a client without growing past `MAX_UNSENT`
+                    queue_output(&self.serial, &mut self.unsent);
+                    return Ok(false);
+                };
+                stream
+            }
+            #[cfg(unix)]
+            Link::Pty { master, .. } => master,
+        };
+
+        match exchange(stream, &self.serial, &mut self.unsent) {
+            Ok(received) => Ok(received),
+            Err(error) if is_disconnection(&error) => {
+                if let Link::Tcp { client, .. } = &mut self.link {
+                    *client = None;
+                }
+                Ok(false)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Run the core until a breakpoint or a watchpoint stops it, exchanging the bytes with the
+    /// host every millisecond of emulated time and waiting for it while the core sleeps
+    pub fn run(&mut self, machine: &mut Armv6M) -> Result<StopReason, Error> {
+        loop {
+            match machine.run_for(SLICE)? {
+                None => {
+                    self.pump()?;
+                }
+                Some(StopReason::Sleeping) => {
+                    if !self.pump()? {
+                        std::thread::sleep(IDLE);
+                    }
+                }
+                Some(reason) => {
+                    self.pump()?;
+                    return Ok(reason);
+                }
+            }
+        }
+    }
+}
+
+trait ReadWrite: Read + Write {}
+
+impl<T: Read + Write> ReadWrite for T {}
+
+/// Feed the received bytes to the UART and send its output, returns whether bytes were received.
+/// A closed connection is an `UnexpectedEof` error.
+fn exchange(stream: &mut dyn ReadWrite, serial: &Serial, unsent: &mut Vec<u8>) -> io::Result<bool> {
+    let mut received = false;
+    let mut buffer = [0; 256];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(count) => {
+                serial.feed(&buffer[..count]);
+                received = true;
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    queue_output(serial, unsent);
+    while !unsent.is_empty() {
+        match stream.write(unsent) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(count) => {
+                unsent.drain(..count);
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(received)
+}
+
+/// Move the output of the UART after the unsent bytes, dropping the oldest ones past `MAX_UNSENT`
+fn queue_output(serial: &Serial, unsent: &mut Vec<u8>) {
+    unsent.extend(serial.take_output());
+    if unsent.len() > MAX_UNSENT {
+        unsent.drain(..unsent.len() - MAX_UNSENT);
+    }
+}
+
+fn is_disconnection(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::UnexpectedEof
+            | ErrorKind::WriteZero
+            | ErrorKind::BrokenPipe
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+    )
+}
+
+/// Open the master of a new pseudo-terminal in non-blocking mode, and its slave in raw mode
+#[cfg(unix)]
+fn open_pty() -> io::Result<(File, File, PathBuf)> {
+    use std::ffi::CStr;
+    use std::os::fd::FromRawFd;
+    use std::os::unix::ffi::OsStrExt;
+
+    fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(result)
+        }
+    }
+
+    // SAFETY: The descriptors are owned by the returned files, or by `master` on errors, and
+    // the path returned by `ptsname` is copied before any other call
+    unsafe {
+        let fd = check(libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY))?;
+        let master = File::from_raw_fd(fd);
+        check(libc::grantpt(fd))?;
+        check(libc::unlockpt(fd))?;
+        let flags = check(libc::fcntl(fd, libc::F_GETFL))?;
+        check(libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+
+        // NOTE: `ptsname` is not thread-safe but is the only variant available on every unix
+        let name = libc::ptsname(fd);
+        if name.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let path = PathBuf::from(std::ffi::OsStr::from_bytes(CStr::from_ptr(name).to_bytes()));
+
+        let slave = File::options().read(true).write(true).open(&path)?;
+        let slave_fd = std::os::fd::AsRawFd::as_raw_fd(&slave);
+        let mut termios = std::mem::zeroed::<libc::termios>();
+        check(libc::tcgetattr(slave_fd, &mut termios))?;
+        libc::cfmakeraw(&mut termios);
+        check(libc::tcsetattr(slave_fd, libc::TCSANOW, &termios))?;
+        Ok((master, slave, path))
+    }
+}
@@ -0,0 +1,330 @@
+//! GPIO port of the nRF51, the 32 pins P0.0 to P0.31.
+//!
+//! The level of a pin comes from, in order, a GPIOTE task channel, the chip when the pin is an
+//! output, the host when it drives the pin, and the pull resistor.
+//! A floating pin without pull reads low.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{read_bytes, write_bytes};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+
+pub const GPIO_BASE: u32 = 0x5000_0000;
+pub const PINS: u8 = 32;
+
+pub const OUT: u32 = 0x504;
+pub const OUTSET: u32 = 0x508;
+pub const OUTCLR: u32 = 0x50C;
+pub const IN: u32 = 0x510;
+pub const DIR: u32 = 0x514;
+pub const DIRSET: u32 = 0x518;
+pub const DIRCLR: u32 = 0x51C;
+/// PIN_CNF[n] is at `PIN_CNF + 4 * n`
+pub const PIN_CNF: u32 = 0x700;
+
+const CNF_DIR_OUTPUT: u32 = 1 << 0;
+const CNF_INPUT_DISCONNECT: u32 = 1 << 1;
+const CNF_PULL_SHIFT: u32 = 2;
+const CNF_DRIVE_SHIFT: u32 = 8;
+const CNF_SENSE_SHIFT: u32 = 16;
+/// DIR, INPUT, PULL, DRIVE and SENSE
+const CNF_MASK: u32 = 0x0003_070F;
+/// Input with the buffer disconnected
+const CNF_RESET: u32 = CNF_INPUT_DISCONNECT;
+
+/// Changes kept for the host, the oldest ones are dropped past this count
+pub const MAX_CHANGES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pull {
+    Disabled,
+    Down,
+    Up,
+}
+
+/// Level detected by the SENSE mechanism
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sense {
+    Disabled,
+    High,
+    Low,
+}
+
+/// Level of a pin changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinChange {
+    pub pin: u8,
+    pub level: bool,
+    /// Cycles of the core clock elapsed since the GPIO was attached
+    pub cycle: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    out: u32,
+    pin_cnf: [u32; PINS as usize],
+    /// Levels driven by the host, `None` for the pins it leaves floating
+    external: [Option<bool>; PINS as usize],
+    /// Levels forced by another peripheral, over the GPIO configuration
+    overrides: [Option<bool>; PINS as usize],
+    /// Levels of the pins after the last change
+    levels: u32,
+    cycle: u64,
+    #[serde(skip)]
+    changes: VecDeque<PinChange>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            out: 0,
+            pin_cnf: [CNF_RESET; PINS as usize],
+            external: [None; PINS as usize],
+            overrides: [None; PINS as usize],
+            levels: 0,
+            cycle: 0,
+            changes: VecDeque::new(),
+        }
+    }
+
+    fn is_output(&self, pin: usize) -> bool {
+        self.pin_cnf[pin] & CNF_DIR_OUTPUT != 0
+    }
+
+    fn pull(&self, pin: usize) -> Pull {
+        match self.pin_cnf[pin] >> CNF_PULL_SHIFT & 0x3 {
+            1 => Pull::Down,
+            3 => Pull::Up,
+            _ => Pull::Disabled,
+        }
+    }
+
+    fn sense(&self, pin: usize) -> Sense {
+        match self.pin_cnf[pin] >> CNF_SENSE_SHIFT & 0x3 {
+            2 => Sense::High,
+            3 => Sense::Low,
+            _ => Sense::Disabled,
+        }
+    }
+
+    fn output(&self, pin: usize) -> Option<bool> {
+        if let Some(level) = self.overrides[pin] {
+            Some(level)
+        } else {
+            self.is_output(pin).then(|| self.out >> pin & 1 == 1)
+        }
+    }
+
+    fn level(&self, pin: usize) -> bool {
+        self.output(pin)
+            .or(self.external[pin])
+            .unwrap_or(self.pull(pin) == Pull::Up)
+    }
+
+    fn dir(&self) -> u32 {
+        (0..PINS as usize).fold(0, |dir, pin| dir | u32::from(self.is_output(pin)) << pin)
+    }
+
+    /// IN register, the pins with their input buffer disconnected read 0
+    fn input(&self) -> u32 {
+        (0..PINS as usize)
+            .filter(|pin| self.pin_cnf[*pin] & CNF_INPUT_DISCONNECT == 0)
+            .fold(0, |input, pin| input | u32::from(self.level(pin)) << pin)
+    }
+
+    fn set_dir(&mut self, dir: u32, mask: u32) {
+        for pin in (0..PINS as usize).filter(|pin| mask >> pin & 1 == 1) {
+            if dir >> pin & 1 == 1 {
+                self.pin_cnf[pin] |= CNF_DIR_OUTPUT;
+            } else {
+                self.pin_cnf[pin] &= !CNF_DIR_OUTPUT;
+            }
+        }
+    }
+
+    /// Record the pins whose level changed
+    fn update(&mut self) {
+        let levels =
+            (0..PINS as usize).fold(0, |levels, pin| levels | u32::from(self.level(pin)) << pin);
+        let changed = levels ^ self.levels;
+        self.levels = levels;
+        for pin in (0..PINS).filter(|pin| changed >> pin & 1 == 1) {
+            if self.changes.len() == MAX_CHANGES {
+                self.changes.pop_front();
+            }
+            self.changes.push_back(PinChange {
+                pin,
+                level: levels >> pin & 1 == 1,
+                cycle: self.cycle,
+            });
+        }
+    }
+}
+
+/// Handle on the pins of a `Gpio`, for the host and the other peripherals
+#[derive(Debug, Clone)]
+pub struct Pins(Rc<RefCell<State>>);
+
+impl Pins {
+    /// Drive a pin from outside of the chip, `None` leaves it floating
+    pub fn drive(&self, pin: u8, level: Option<bool>) {
+        let mut state = self.0.borrow_mut();
+        state.external[usize::from(pin % PINS)] = level;
+        state.update();
+    }
+
+    /// Level of the pin, whoever drives it
+    pub fn level(&self, pin: u8) -> bool {
+        self.0.borrow().level(usize::from(pin % PINS))
+    }
+
+    /// Level the chip drives on the pin, `None` when it is an input
+    pub fn output(&self, pin: u8) -> Option<bool> {
+        self.0.borrow().output(usize::from(pin % PINS))
+    }
+
+    /// Levels of all the pins, P0.0 in bit 0
+    pub fn levels(&self) -> u32 {
+        self.0.borrow().levels
+    }
+
+    pub fn pull(&self, pin: u8) -> Pull {
+        self.0.borrow().pull(usize::from(pin % PINS))
+    }
+
+    pub fn sense(&self, pin: u8) -> Sense {
+        self.0.borrow().sense(usize::from(pin % PINS))
+    }
+
+    /// DRIVE field of PIN_CNF, 0 for the standard drive S0S1
+    pub fn drive_strength(&self, pin: u8) -> u8 {
+        (self.0.borrow().pin_cnf[usize::from(pin % PINS)] >> CNF_DRIVE_SHIFT & 0x7) as u8
+    }
+
+    /// DETECT signal, set while a pin with SENSE enabled is at the sensed level
+    pub fn detect(&self) -> bool {
+        let state = self.0.borrow();
+        (0..PINS as usize).any(|pin| match state.sense(pin) {
+            Sense::Disabled => false,
+            Sense::High => state.level(pin),
+            Sense::Low => !state.level(pin),
+        })
+    }
+
+    /// Level changes since the last call, oldest first
+    pub fn take_changes(&self) -> Vec<PinChange> {
+        self.0.borrow_mut().changes.drain(..).collect()
+    }
+
+    /// Force the level of a pin over the GPIO configuration, `None` gives it back
+    pub(crate) fn set_override(&self, pin: u8, level: Option<bool>) {
+        let mut state = self.0.borrow_mut();
+        state.overrides[usize::from(pin % PINS)] = level;
+        state.update();
+    }
+}
+
+pub struct Gpio {
+    pins: Pins,
+}
+
+impl Gpio {
+    pub fn new() -> Self {
+        Self {
+            pins: Pins(Rc::new(RefCell::new(State::new()))),
+        }
+    }
+
+    pub fn pins(&self) -> Pins {
+        self.pins.clone()
+    }
+}
+
+impl Default for Gpio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Peripheral for Gpio {
+    fn name(&self) -> &str {
+        "GPIO"
+    }
+
+    fn base(&self) -> u32 {
+        GPIO_BASE
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let state = self.pins.0.borrow();
+        let word = match offset & !3 {
+            OUT | OUTSET | OUTCLR => state.out,
+            IN => state.input(),
+            DIR | DIRSET | DIRCLR => state.dir(),
+            register @ PIN_CNF..0x780 => state.pin_cnf[((register - PIN_CNF) / 4) as usize],
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let mut state = self.pins.0.borrow_mut();
+        match offset & !3 {
+            OUT => state.out = state.out & !mask | bits,
+            OUTSET => state.out |= bits,
+            OUTCLR => state.out &= !bits,
+            DIR => state.set_dir(bits, mask),
+            DIRSET => state.set_dir(bits, bits),
+            DIRCLR => state.set_dir(0, bits),
+            register @ PIN_CNF..0x780 => {
+                let cnf = &mut state.pin_cnf[((register - PIN_CNF) / 4) as usize];
+                *cnf = (*cnf & !mask | bits) & CNF_MASK;
+            }
+            _ => return,
+        }
+        state.update();
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.pins.0.borrow_mut().cycle += cycles;
+    }
+
+    /// The configuration is kept through System OFF for the pins to wake the system up, the other
+    /// resets restore it. The levels driven by the host stay.
+    fn reset(&mut self, cause: ResetCause) {
+        if cause == ResetCause::WakeUp {
+            return;
+        }
+        let mut state = self.pins.0.borrow_mut();
+        state.out = 0;
+        state.pin_cnf = [CNF_RESET; PINS as usize];
+        state.overrides = [None; PINS as usize];
+        state.update();
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&*self.pins.0.borrow())?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        let mut loaded: State = bincode::deserialize(state)?;
+        let mut current = self.pins.0.borrow_mut();
+        loaded.changes = std::mem::take(&mut current.changes);
+        *current = loaded;
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+//! GPIO tasks and events of the nRF51: four channels, each detecting the edges of a pin in event
+//! mode or driving it with the OUT task in task mode, and the PORT event raised when the DETECT
+//! signal of the GPIO SENSE mechanism rises.
+//!
+//! The pins are sampled after every step, a change made by the host while the core sleeps
+//! wakes it up on the next sample.
+
+use serde::{Deserialize, Serialize};
+
+use super::gpio::Pins;
+use super::{read_bytes, write_bytes, write_inten, INTENCLR, INTENSET};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+
+pub const GPIOTE_BASE: u32 = 0x4000_6000;
+pub const GPIOTE_IRQ: u8 = 6;
+pub const CHANNELS: usize = 4;
+
+/// TASKS_OUT[n] is at `TASKS_OUT + 4 * n`
+pub const TASKS_OUT: u32 = 0x000;
+/// EVENTS_IN[n] is at `EVENTS_IN + 4 * n`
+pub const EVENTS_IN: u32 = 0x100;
+pub const EVENTS_PORT: u32 = 0x17C;
+/// CONFIG[n] is at `CONFIG + 4 * n`
+pub const CONFIG: u32 = 0x510;
+
+/// INTEN bit of the PORT event, the IN events use bits 0 to 3
+const INTEN_PORT: u32 = 1 << 31;
+const INTEN_MASK: u32 = INTEN_PORT | 0xF;
+
+const MODE_EVENT: u32 = 1;
+const MODE_TASK: u32 = 3;
+const PSEL_SHIFT: u32 = 8;
+const POLARITY_SHIFT: u32 = 16;
+const OUTINIT: u32 = 1 << 20;
+/// MODE, PSEL, POLARITY and OUTINIT
+const CONFIG_MASK: u32 = 0x0013_1F03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    None,
+    LoToHi,
+    HiToLo,
+    Toggle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Disabled,
+    /// Detect the edges of the pin
+    Event,
+    /// Drive the pin with the OUT task
+    Task,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    config: [u32; CHANNELS],
+    events_in: [bool; CHANNELS],
+    event_port: bool,
+    inten: u32,
+    /// Levels of the task channel pins
+    outputs: [bool; CHANNELS],
+    /// Pin levels and DETECT signal at the last sample
+    levels: u32,
+    detect: bool,
+}
+
+pub struct Gpiote {
+    pins: Pins,
+    state: State,
+}
+
+impl Gpiote {
+    /// Channels on the pins of a GPIO port
+    pub fn new(pins: Pins) -> Self {
+        let state = State {
+            levels: pins.levels(),
+            detect: pins.detect(),
+            ..State::default()
+        };
+        Self { pins, state }
+    }
+
+    pub fn mode(&self, channel: usize) -> Mode {
+        match self.state.config[channel % CHANNELS] & 0x3 {
+            MODE_EVENT => Mode::Event,
+            MODE_TASK => Mode::Task,
+            _ => Mode::Disabled,
+        }
+    }
+
+    pub fn pin(&self, channel: usize) -> u8 {
+        (self.state.config[channel % CHANNELS] >> PSEL_SHIFT & 0x1F) as u8
+    }
+
+    pub fn polarity(&self, channel: usize) -> Polarity {
+        match self.state.config[channel % CHANNELS] >> POLARITY_SHIFT & 0x3 {
+            1 => Polarity::LoToHi,
+            2 => Polarity::HiToLo,
+            3 => Polarity::Toggle,
+            _ => Polarity::None,
+        }
+    }
+
+    /// Force the pins of the task channels, release the other ones
+    fn drive_pins(&self, previous: &[(Mode, u8); CHANNELS]) {
+        for (channel, (mode, pin)) in previous.iter().enumerate() {
+            if *mode == Mode::Task
+                && (self.mode(channel) != Mode::Task || self.pin(channel) != *pin)
+            {
+                self.pins.set_override(*pin, None);
+            }
+        }
+        for channel in (0..CHANNELS).filter(|channel| self.mode(*channel) == Mode::Task) {
+            self.pins
+                .set_override(self.pin(channel), Some(self.state.outputs[channel]));
+        }
+    }
+
+    fn task_out(&mut self, channel: usize) {
+        if self.mode(channel) != Mode::Task {
+            return;
+        }
+        let output = self.state.outputs[channel];
+        let output = match self.polarity(channel) {
+            Polarity::None => output,
+            Polarity::LoToHi => true,
+            Polarity::HiToLo => false,
+            Polarity::Toggle => !output,
+        };
+        self.state.outputs[channel] = output;
+        self.pins.set_override(self.pin(channel), Some(output));
+    }
+
+    /// Raise the events of the edges since the last sample
+    fn sample(&mut self) {
+        let levels = self.pins.levels();
+        let changed = levels ^ self.state.levels;
+        for channel in 0..CHANNELS {
+            let pin = self.pin(channel);
+            if self.mode(channel) != Mode::Event || changed >> pin & 1 == 0 {
+                continue;
+            }
+            let rising = levels >> pin & 1 == 1;
+            self.state.events_in[channel] |= match self.polarity(channel) {
+                Polarity::None => false,
+                Polarity::LoToHi => rising,
+                Polarity::HiToLo => !rising,
+                Polarity::Toggle => true,
+            };
+        }
+        self.state.levels = levels;
+
+        let detect = self.pins.detect();
+        self.state.event_port |= detect && !self.state.detect;
+        self.state.detect = detect;
+    }
+
+    fn events(&self) -> u32 {
+        let events_in = (0..CHANNELS).fold(0, |events, channel| {
+            events | u32::from(self.state.events_in[channel]) << channel
+        });
+        events_in | if self.state.event_port { INTEN_PORT } else { 0 }
+    }
+}
+
+impl Peripheral for Gpiote {
+    fn name(&self) -> &str {
+        "GPIOTE"
+    }
+
+    fn base(&self) -> u32 {
+        GPIOTE_BASE
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let word = match offset & !3 {
+            register @ EVENTS_IN..0x110 => {
+                u32::from(self.state.events_in[((register - EVENTS_IN) / 4) as usize])
+            }
+            EVENTS_PORT => u32::from(self.state.event_port),
+            INTENSET | INTENCLR => self.state.inten,
+            register @ CONFIG..0x520 => self.state.config[((register - CONFIG) / 4) as usize],
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        match offset & !3 {
+            register @ TASKS_OUT..0x010 if bits & 1 != 0 => {
+                self.task_out(((register - TASKS_OUT) / 4) as usize);
+            }
+            register @ EVENTS_IN..0x110 => {
+                self.state.events_in[((register - EVENTS_IN) / 4) as usize] = bits != 0;
+            }
+            EVENTS_PORT => self.state.event_port = bits != 0,
+            register @ CONFIG..0x520 => {
+                let channel = ((register - CONFIG) / 4) as usize;
+                let previous: [(Mode, u8); CHANNELS] =
+                    std::array::from_fn(|channel| (self.mode(channel), self.pin(channel)));
+                let config = &mut self.state.config[channel];
+                *config = (*config & !mask | bits) & CONFIG_MASK;
+                self.state.outputs[channel] = *config & OUTINIT != 0;
+                self.drive_pins(&previous);
+            }
+            register @ (INTENSET | INTENCLR) => {
+                write_inten(&mut self.state.inten, register, bits, mask);
+                self.state.inten &= INTEN_MASK;
+            }
+            _ => {}
+        }
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        (self.events() & self.state.inten != 0).then_some(GPIOTE_IRQ)
+    }
+
+    fn tick(&mut self, _cycles: u64) {
+        self.sample();
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        let changed =
+            self.pins.levels() != self.state.levels || self.pins.detect() != self.state.detect;
+        changed.then_some(0)
+    }
+
+    /// The pins of the task channels are released
+    fn reset(&mut self, _cause: ResetCause) {
+        let previous = std::array::from_fn(|channel| (self.mode(channel), self.pin(channel)));
+        self.state = State {
+            levels: self.pins.levels(),
+            detect: self.pins.detect(),
+            ..State::default()
+        };
+        self.drive_pins(&previous);
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        let previous = std::array::from_fn(|channel| (self.mode(channel), self.pin(channel)));
+        self.state = bincode::deserialize(state)?;
+        self.drive_pins(&previous);
+        Ok(())
+    }
+}
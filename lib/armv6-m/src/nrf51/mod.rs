@@ -0,0 +1,141 @@
+//! Peripherals of the nRF51822 of the micro:bit.
+//! <https://infocenter.nordicsemi.com/pdf/nRF51_RM_v3.0.pdf>
+//!
+//! Each peripheral gives out a handle sharing its state, to drive and observe it from the host
+//! once it is attached to the core, and to wire it to the other peripherals.
+
+pub mod adc;
+pub mod bridge;
+pub mod gpio;
+pub mod gpiote;
+pub mod power;
+pub mod rng;
+pub mod rtc;
+pub mod temp;
+pub mod timer;
+pub mod uart;
+
+/// Registers shared by the peripherals with tasks and events. Writing 1 to a task triggers it,
+/// an event reads 1 once it happened until 0 is written to it.
+pub const TASKS: u32 = 0x000;
+pub const EVENTS: u32 = 0x100;
+pub const SHORTS: u32 = 0x200;
+pub const INTEN: u32 = 0x300;
+pub const INTENSET: u32 = 0x304;
+pub const INTENCLR: u32 = 0x308;
+
+/// Bytes read by an access of `size` bytes at `offset` of a word register holding `word`
+pub(crate) fn read_bytes(word: u32, offset: u32, size: u8) -> u32 {
+    let shift = 8 * (offset & 3);
+    let mask = if size >= 4 {
+        u32::MAX
+    } else {
+        (1 << (8 * u32::from(size))) - 1
+    };
+    word >> shift & mask
+}
+
+/// Bits written by an access of `size` bytes at `offset` of a word register, with their mask.
+/// Narrow accesses only change the bytes they reach.
+pub(crate) fn write_bytes(offset: u32, size: u8, value: u32) -> (u32, u32) {
+    let shift = 8 * (offset & 3);
+    let mask = if size >= 4 {
+        u32::MAX
+    } else {
+        ((1 << (8 * u32::from(size))) - 1) << shift
+    };
+    (value << shift & mask, mask)
+}
+
+/// Bit of an event in the mask of the events and in INTEN, from the offset of its register
+pub(crate) fn event_bit(register: u32) -> Option<u32> {
+    (EVENTS..EVENTS + 0x80)
+        .contains(&register)
+        .then(|| 1 << ((register - EVENTS) / 4))
+}
+
+/// How a write changes an enable register such as INTEN, from the register it goes through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Enable {
+    /// The bytes reached by the access are written, as INTEN
+    Write,
+    /// The bits written as 1 are set, as INTENSET
+    Set,
+    /// The bits written as 1 are cleared, as INTENCLR
+    Clear,
+}
+
+/// Apply a write of `bits` to the bytes of `mask` of an enable register
+pub(crate) fn write_enable(enable: &mut u32, mode: Enable, bits: u32, mask: u32) {
+    match mode {
+        Enable::Write => *enable = *enable & !mask | bits,
+        Enable::Set => *enable |= bits,
+        Enable::Clear => *enable &= !bits,
+    }
+}
+
+/// Apply a write to INTEN, INTENSET or INTENCLR, returns false for the other registers
+pub(crate) fn write_inten(inten: &mut u32, register: u32, bits: u32, mask: u32) -> bool {
+    let mode = match register {
+        INTEN => Enable::Write,
+        INTENSET => Enable::Set,
+        INTENCLR => Enable::Clear,
+        _ => return false,
+    };
+    write_enable(inten, mode, bits, mask);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_the_register_accesses() {
+        // NOTE: Register written, offset and size of the access, value written over 0x1234_5678
+        // and the register after the write, `None` when write_inten leaves the register alone
+        let writes = [
+            (INTEN, 0, 4, 0xCAFE_F00D, Some(0xCAFE_F00D)),
+            (INTEN, 1, 1, 0xAB, Some(0x1234_AB78)),
+            (INTEN, 2, 2, 0xCDEF, Some(0xCDEF_5678)),
+            (INTEN, 3, 1, 0x1FF, Some(0xFF34_5678)),
+            (INTENSET, 0, 4, 0x87, Some(0x1234_56FF)),
+            (INTENSET, 1, 1, 0x80, Some(0x1234_D678)),
+            (INTENCLR, 0, 4, 0x70, Some(0x1234_5608)),
+            (INTENCLR, 3, 1, 0x12, Some(0x0034_5678)),
+            (SHORTS, 0, 4, 0, None),
+            (EVENTS, 0, 4, 1, None),
+        ];
+        for (register, offset, size, value, expected) in writes {
+            let mut inten = 0x1234_5678;
+            let (bits, mask) = write_bytes(offset, size, value);
+            let written = write_inten(&mut inten, register, bits, mask);
+            let context = format!("{size} bytes of {value:#x} at {register:#x} + {offset}");
+            assert_eq!(written.then_some(inten), expected, "{context}");
+            assert!(written || inten == 0x1234_5678, "{context}");
+        }
+
+        let reads = [
+            (0, 4, 0x1234_5678),
+            (0, 2, 0x5678),
+            (1, 1, 0x56),
+            (1, 2, 0x3456),
+            (2, 2, 0x1234),
+            (3, 1, 0x12),
+        ];
+        for (offset, size, expected) in reads {
+            assert_eq!(read_bytes(0x1234_5678, offset, size), expected);
+        }
+
+        let events = [
+            (TASKS, None),
+            (EVENTS, Some(1)),
+            (EVENTS + 4, Some(1 << 1)),
+            (EVENTS + 0x7C, Some(1 << 31)),
+            (EVENTS + 0x80, None),
+        ];
+        for (register, expected) in events {
+            assert_eq!(event_bit(register), expected, "{register:#x}");
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! POWER and CLOCK of the nRF51, sharing their registers space and their interrupt.
+//!
+//! CLOCK starts the 16 MHz crystal oscillator and the 32.768 kHz clock after their startup time,
+//! and runs the calibration of the RC oscillator and its timer. The core always runs at 16 MHz,
+//! whichever source is selected.
+//!
+//! POWER records the cause of the resets in RESETREAS and keeps GPREGRET across them, except a
+//! power-on reset. SYSTEMOFF stops the core until a pin with SENSE enabled raises the DETECT
+//! signal of the GPIO, which resets the system. The supply voltage never drops, POFWARN is never
+//! raised.
+
+use serde::{Deserialize, Serialize};
+
+use super::gpio::Pins;
+use super::{event_bit, read_bytes, write_bytes, write_inten, INTENCLR, INTENSET};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, Power, ResetCause};
+
+pub const POWER_CLOCK_BASE: u32 = 0x4000_0000;
+pub const POWER_CLOCK_IRQ: u8 = 0;
+
+pub const TASKS_HFCLKSTART: u32 = 0x000;
+pub const TASKS_HFCLKSTOP: u32 = 0x004;
+pub const TASKS_LFCLKSTART: u32 = 0x008;
+pub const TASKS_LFCLKSTOP: u32 = 0x00C;
+pub const TASKS_CAL: u32 = 0x010;
+pub const TASKS_CTSTART: u32 = 0x014;
+pub const TASKS_CTSTOP: u32 = 0x018;
+pub const TASKS_CONSTLAT: u32 = 0x078;
+pub const TASKS_LOWPWR: u32 = 0x07C;
+pub const EVENTS_HFCLKSTARTED: u32 = 0x100;
+pub const EVENTS_LFCLKSTARTED: u32 = 0x104;
+pub const EVENTS_POFWARN: u32 = 0x108;
+pub const EVENTS_DONE: u32 = 0x10C;
+pub const EVENTS_CTTO: u32 = 0x110;
+pub const RESETREAS: u32 = 0x400;
+pub const HFCLKRUN: u32 = 0x408;
+pub const HFCLKSTAT: u32 = 0x40C;
+pub const LFCLKRUN: u32 = 0x414;
+pub const LFCLKSTAT: u32 = 0x418;
+pub const LFCLKSRCCOPY: u32 = 0x41C;
+pub const RAMSTATUS: u32 = 0x428;
+pub const SYSTEMOFF: u32 = 0x500;
+pub const POFCON: u32 = 0x510;
+pub const GPREGRET: u32 = 0x51C;
+pub const LFCLKSRC: u32 = 0x518;
+pub const RAMON: u32 = 0x524;
+pub const RESET: u32 = 0x544;
+pub const CTIV: u32 = 0x538;
+pub const XTALFREQ: u32 = 0x550;
+pub const RAMONB: u32 = 0x554;
+pub const DCDCEN: u32 = 0x578;
+
+/// RESETREAS bits
+pub const RESETREAS_RESETPIN: u32 = 1 << 0;
+pub const RESETREAS_DOG: u32 = 1 << 1;
+pub const RESETREAS_SREQ: u32 = 1 << 2;
+pub const RESETREAS_LOCKUP: u32 = 1 << 3;
+pub const RESETREAS_OFF: u32 = 1 << 16;
+const RESETREAS_MASK: u32 = 0x7_000F;
+
+/// Startup times in cycles of the core clock
+pub const HFXO_STARTUP: u64 = 12_800;
+pub const LFRC_STARTUP: u64 = 9_600;
+pub const LFXO_STARTUP: u64 = 4_800_000;
+pub const LFSYNT_STARTUP: u64 = 1_600;
+/// Calibration of the RC oscillator
+pub const CALIBRATION: u64 = 256_000;
+/// Unit of CTIV, 250 ms
+const CTIV_UNIT: u64 = 4_000_000;
+
+/// Running bit of HFCLKSTAT and LFCLKSTAT
+const STAT_RUNNING: u32 = 1 << 16;
+const LFCLKSRC_XTAL: u32 = 1;
+const LFCLKSRC_SYNTH: u32 = 2;
+const INTEN_MASK: u32 = 0x1F;
+
+/// Source of the 32.768 kHz clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LfclkSource {
+    Rc,
+    Xtal,
+    Synth,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    /// Events by bit of INTEN
+    events: u32,
+    inten: u32,
+    /// Cycles until the clocks start, the calibration ends and the calibration timer expires
+    hfclk_starting: Option<u64>,
+    lfclk_starting: Option<u64>,
+    calibrating: Option<u64>,
+    calibration_timer: Option<u64>,
+    hfclk_run: bool,
+    hfxo_running: bool,
+    lfclk_run: bool,
+    lfclk_running: bool,
+    lfclksrc: u32,
+    lfclksrc_copy: u32,
+    ctiv: u32,
+    xtalfreq: u32,
+    resetreas: u32,
+    off: bool,
+    pofcon: u32,
+    gpregret: u32,
+    ramon: u32,
+    ramonb: u32,
+    reset: u32,
+    dcdcen: u32,
+    constant_latency: bool,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            events: 0,
+            inten: 0,
+            hfclk_starting: None,
+            lfclk_starting: None,
+            calibrating: None,
+            calibration_timer: None,
+            hfclk_run: false,
+            hfxo_running: false,
+            lfclk_run: false,
+            lfclk_running: false,
+            lfclksrc: 0,
+            lfclksrc_copy: 0,
+            ctiv: 0,
+            xtalfreq: 0xFF,
+            resetreas: 0,
+            off: false,
+            pofcon: 0,
+            gpregret: 0,
+            ramon: 0x3,
+            ramonb: 0x3,
+            reset: 0,
+            dcdcen: 0,
+            constant_latency: false,
+        }
+    }
+
+    fn raise(&mut self, event: u32) {
+        self.events |= event_bit(event).unwrap_or(0);
+    }
+
+    fn start_lfclk(&mut self) {
+        self.lfclk_run = true;
+        self.lfclk_running = false;
+        self.lfclksrc_copy = self.lfclksrc & 0x3;
+        self.lfclk_starting = Some(match self.lfclksrc_copy {
+            LFCLKSRC_XTAL => LFXO_STARTUP,
+            LFCLKSRC_SYNTH => LFSYNT_STARTUP,
+            _ => LFRC_STARTUP,
+        });
+    }
+}
+
+pub struct PowerClock {
+    pins: Pins,
+    state: State,
+}
+
+impl PowerClock {
+    /// POWER and CLOCK woken up from System OFF by the pins of a GPIO port
+    pub fn new(pins: Pins) -> Self {
+        Self {
+            pins,
+            state: State::new(),
+        }
+    }
+
+    pub fn is_hfxo_running(&self) -> bool {
+        self.state.hfxo_running
+    }
+
+    /// Source of the running 32.768 kHz clock, `None` while it is stopped
+    pub fn lfclk(&self) -> Option<LfclkSource> {
+        self.state
+            .lfclk_running
+            .then_some(match self.state.lfclksrc_copy {
+                LFCLKSRC_XTAL => LfclkSource::Xtal,
+                LFCLKSRC_SYNTH => LfclkSource::Synth,
+                _ => LfclkSource::Rc,
+            })
+    }
+
+    pub fn is_off(&self) -> bool {
+        self.state.off
+    }
+
+    pub fn reset_reason(&self) -> u32 {
+        self.state.resetreas
+    }
+
+    pub fn gpregret(&self) -> u8 {
+        self.state.gpregret as u8
+    }
+
+    /// RAM blocks 0 to 3 powered on, as in RAMSTATUS
+    fn ram_status(&self) -> u32 {
+        self.state.ramon & 0x3 | (self.state.ramonb & 0x3) << 2
+    }
+}
+
+/// Count `cycles` down, returns whether the countdown just reached 0
+fn count_down(countdown: &mut Option<u64>, cycles: u64) -> bool {
+    match countdown {
+        Some(left) if *left > cycles => {
+            *left -= cycles;
+            false
+        }
+        Some(_) => {
+            *countdown = None;
+            true
+        }
+        None => false,
+    }
+}
+
+impl Peripheral for PowerClock {
+    fn name(&self) -> &str {
+        "POWER_CLOCK"
+    }
+
+    fn base(&self) -> u32 {
+        POWER_CLOCK_BASE
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let state = &self.state;
+        let word = match offset & !3 {
+            register @ EVENTS_HFCLKSTARTED..=EVENTS_CTTO => {
+                u32::from(state.events & event_bit(register).unwrap_or(0) != 0)
+            }
+            INTENSET | INTENCLR => state.inten,
+            RESETREAS => state.resetreas,
+            HFCLKRUN => u32::from(state.hfclk_run),
+            HFCLKSTAT if state.hfxo_running => 1 | STAT_RUNNING,
+            LFCLKRUN => u32::from(state.lfclk_run),
+            LFCLKSTAT if state.lfclk_running => state.lfclksrc_copy | STAT_RUNNING,
+            LFCLKSRCCOPY => state.lfclksrc_copy,
+            RAMSTATUS => self.ram_status(),
+            POFCON => state.pofcon,
+            GPREGRET => state.gpregret,
+            LFCLKSRC => state.lfclksrc,
+            RAMON => state.ramon,
+            RESET => state.reset,
+            CTIV => state.ctiv,
+            XTALFREQ => state.xtalfreq,
+            RAMONB => state.ramonb,
+            DCDCEN => state.dcdcen,
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let triggered = bits & 1 != 0;
+        let state = &mut self.state;
+        match offset & !3 {
+            TASKS_HFCLKSTART if triggered => {
+                state.hfclk_run = true;
+                if !state.hfxo_running {
+                    state.hfclk_starting = Some(HFXO_STARTUP);
+                }
+            }
+            TASKS_HFCLKSTOP if triggered => {
+                state.hfclk_starting = None;
+                state.hfxo_running = false;
+            }
+            TASKS_LFCLKSTART if triggered => state.start_lfclk(),
+            TASKS_LFCLKSTOP if triggered => {
+                state.lfclk_starting = None;
+                state.lfclk_running = false;
+            }
+            TASKS_CAL if triggered => state.calibrating = Some(CALIBRATION),
+            TASKS_CTSTART if triggered => {
+                state.calibration_timer = Some(u64::from(state.ctiv) * CTIV_UNIT);
+            }
+            TASKS_CTSTOP if triggered => state.calibration_timer = None,
+            TASKS_CONSTLAT if triggered => state.constant_latency = true,
+            TASKS_LOWPWR if triggered => state.constant_latency = false,
+            register @ EVENTS_HFCLKSTARTED..=EVENTS_CTTO => {
+                let event = event_bit(register).unwrap_or(0);
+                if bits == 0 {
+                    state.events &= !event;
+                } else {
+                    state.events |= event;
+                }
+            }
+            register @ (INTENSET | INTENCLR) => {
+                write_inten(&mut state.inten, register, bits, mask);
+                state.inten &= INTEN_MASK;
+            }
+            RESETREAS => state.resetreas &= !bits,
+            SYSTEMOFF if triggered => state.off = true,
+            POFCON => state.pofcon = (state.pofcon & !mask | bits) & 0x7,
+            GPREGRET => state.gpregret = (state.gpregret & !mask | bits) & 0xFF,
+            LFCLKSRC => state.lfclksrc = (state.lfclksrc & !mask | bits) & 0x3,
+            RAMON => state.ramon = (state.ramon & !mask | bits) & 0x3_0003,
+            RESET => state.reset = (state.reset & !mask | bits) & 1,
+            CTIV => state.ctiv = (state.ctiv & !mask | bits) & 0x7F,
+            XTALFREQ => state.xtalfreq = (state.xtalfreq & !mask | bits) & 0xFF,
+            RAMONB => state.ramonb = (state.ramonb & !mask | bits) & 0x3_0003,
+            DCDCEN => state.dcdcen = (state.dcdcen & !mask | bits) & 1,
+            _ => {}
+        }
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        (self.state.events & self.state.inten != 0).then_some(POWER_CLOCK_IRQ)
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        let state = &mut self.state;
+        if count_down(&mut state.hfclk_starting, cycles) {
+            state.hfxo_running = true;
+            state.raise(EVENTS_HFCLKSTARTED);
+        }
+        if count_down(&mut state.lfclk_starting, cycles) {
+            state.lfclk_running = true;
+            state.raise(EVENTS_LFCLKSTARTED);
+        }
+        if count_down(&mut state.calibrating, cycles) {
+            state.raise(EVENTS_DONE);
+        }
+        if count_down(&mut state.calibration_timer, cycles) {
+            state.raise(EVENTS_CTTO);
+        }
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        let state = &self.state;
+        [
+            state.hfclk_starting,
+            state.lfclk_starting,
+            state.calibrating,
+            state.calibration_timer,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    fn power(&mut self) -> Power {
+        if !self.state.off {
+            Power::On
+        } else if self.pins.detect() {
+            Power::Reset(ResetCause::WakeUp)
+        } else {
+            Power::Off
+        }
+    }
+
+    fn reset(&mut self, cause: ResetCause) {
+        let previous = std::mem::replace(&mut self.state, State::new());
+        let reason = match cause {
+            ResetCause::PowerOn => return,
+            ResetCause::Pin => RESETREAS_RESETPIN,
+            ResetCause::Watchdog => RESETREAS_DOG,
+            ResetCause::SoftReset => RESETREAS_SREQ,
+            ResetCause::Lockup => RESETREAS_LOCKUP,
+            ResetCause::WakeUp => RESETREAS_OFF,
+        };
+        // NOTE: RESETREAS accumulates the causes until the firmware clears them
+        self.state.resetreas = (previous.resetreas | reason) & RESETREAS_MASK;
+        self.state.gpregret = previous.gpregret;
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        self.state = bincode::deserialize(state)?;
+        Ok(())
+    }
+}
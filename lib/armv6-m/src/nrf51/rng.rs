@@ -0,0 +1,198 @@
+//! RNG of the nRF51, producing a random byte in VALUE after each generation delay while started.
+//!
+//! The bytes come from a SplitMix64 generator seeded by the host, runs are reproducible unless
+//! the seed is taken from the entropy of the host. The generator is part of the snapshots.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use super::{read_bytes, write_bytes, write_inten, INTEN, INTENCLR, INTENSET, SHORTS};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+
+pub const RNG_BASE: u32 = 0x4000_D000;
+pub const RNG_IRQ: u8 = 13;
+
+pub const TASKS_START: u32 = 0x000;
+pub const TASKS_STOP: u32 = 0x004;
+pub const EVENTS_VALRDY: u32 = 0x100;
+pub const CONFIG: u32 = 0x504;
+pub const VALUE: u32 = 0x508;
+
+/// Cycles of the core clock to generate a byte, 167 µs
+pub const GENERATION: u64 = 2_672;
+/// Cycles to generate a byte with the digital error correction, 677 µs
+pub const CORRECTED_GENERATION: u64 = 10_832;
+
+const SHORTS_VALRDY_STOP: u32 = 1 << 0;
+const INTEN_VALRDY: u32 = 1 << 0;
+const CONFIG_DERCEN: u32 = 1 << 0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    /// Cycles until the next byte, while started
+    generating: Option<u64>,
+    value: u8,
+    valrdy: bool,
+    shorts: u32,
+    inten: u32,
+    config: u32,
+    /// SplitMix64 state
+    generator: u64,
+}
+
+impl State {
+    fn new(generator: u64) -> Self {
+        Self {
+            generating: None,
+            value: 0,
+            valrdy: false,
+            shorts: 0,
+            inten: 0,
+            config: 0,
+            generator,
+        }
+    }
+}
+
+pub struct Rng {
+    seed: u64,
+    state: State,
+}
+
+impl Rng {
+    /// Reproducible bytes, the same seed gives the same bytes
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            state: State::new(seed),
+        }
+    }
+
+    /// Bytes seeded from the entropy of the host, `seed` gives the seed to reproduce them
+    pub fn from_entropy() -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        if let Ok(time) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+            hasher.write_u128(time.as_nanos());
+        }
+        Self::new(hasher.finish())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Whether the digital error correction removes the bias of the bytes
+    pub fn is_corrected(&self) -> bool {
+        self.state.config & CONFIG_DERCEN != 0
+    }
+
+    fn generation(&self) -> u64 {
+        if self.is_corrected() {
+            CORRECTED_GENERATION
+        } else {
+            GENERATION
+        }
+    }
+
+    /// SplitMix64 <https://prng.di.unimi.it/splitmix64.c>
+    fn next_byte(&mut self) -> u8 {
+        self.state.generator = self.state.generator.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state.generator;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) as u8
+    }
+}
+
+impl Peripheral for Rng {
+    fn name(&self) -> &str {
+        "RNG"
+    }
+
+    fn base(&self) -> u32 {
+        RNG_BASE
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let state = &self.state;
+        let word = match offset & !3 {
+            EVENTS_VALRDY => u32::from(state.valrdy),
+            SHORTS => state.shorts,
+            INTEN | INTENSET | INTENCLR => state.inten,
+            CONFIG => state.config,
+            VALUE => u32::from(state.value),
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let triggered = bits & 1 != 0;
+        match offset & !3 {
+            TASKS_START if triggered && self.state.generating.is_none() => {
+                self.state.generating = Some(self.generation());
+            }
+            TASKS_STOP if triggered => self.state.generating = None,
+            EVENTS_VALRDY => self.state.valrdy = bits != 0,
+            SHORTS => {
+                self.state.shorts = (self.state.shorts & !mask | bits) & SHORTS_VALRDY_STOP;
+            }
+            CONFIG => self.state.config = (self.state.config & !mask | bits) & CONFIG_DERCEN,
+            register => {
+                write_inten(&mut self.state.inten, register, bits, mask);
+                self.state.inten &= INTEN_VALRDY;
+            }
+        }
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        (self.state.valrdy && self.state.inten & INTEN_VALRDY != 0).then_some(RNG_IRQ)
+    }
+
+    fn tick(&mut self, mut cycles: u64) {
+        while let Some(left) = self.state.generating {
+            if cycles < left {
+                self.state.generating = Some(left - cycles);
+                return;
+            }
+            cycles -= left;
+            self.state.value = self.next_byte();
+            self.state.valrdy = true;
+            self.state.generating = if self.state.shorts & SHORTS_VALRDY_STOP != 0 {
+                None
+            } else {
+                Some(self.generation())
+            };
+        }
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        self.state.generating
+    }
+
+    /// The generator goes on, a reset does not give the same bytes again
+    fn reset(&mut self, _cause: ResetCause) {
+        self.state = State::new(self.state.generator);
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        self.state = bincode::deserialize(state)?;
+        Ok(())
+    }
+}
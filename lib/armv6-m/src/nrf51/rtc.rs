@@ -0,0 +1,312 @@
+//! RTC0 and RTC1 of the nRF51, 24 bits counters of the 32.768 kHz low frequency clock divided by
+//! PRESCALER + 1, with the TICK, OVRFLW and COMPARE events.
+//!
+//! The low frequency clock is derived from the cycles of the core at 16 MHz, without drift.
+//! An event is only generated while it is enabled in EVTEN or INTEN.
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    event_bit, read_bytes, write_bytes, write_enable, write_inten, Enable, INTENCLR, INTENSET,
+};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+use crate::timing::NRF51_FREQUENCY;
+
+pub const RTC0_BASE: u32 = 0x4000_B000;
+pub const RTC1_BASE: u32 = 0x4001_1000;
+pub const RTC0_IRQ: u8 = 11;
+pub const RTC1_IRQ: u8 = 17;
+pub const LFCLK_FREQUENCY: u64 = 32_768;
+
+pub const TASKS_START: u32 = 0x000;
+pub const TASKS_STOP: u32 = 0x004;
+pub const TASKS_CLEAR: u32 = 0x008;
+pub const TASKS_TRIGOVRFLW: u32 = 0x00C;
+pub const EVENTS_TICK: u32 = 0x100;
+pub const EVENTS_OVRFLW: u32 = 0x104;
+/// EVENTS_COMPARE[n] is at `EVENTS_COMPARE + 4 * n`
+pub const EVENTS_COMPARE: u32 = 0x140;
+pub const EVTEN: u32 = 0x340;
+pub const EVTENSET: u32 = 0x344;
+pub const EVTENCLR: u32 = 0x348;
+pub const COUNTER: u32 = 0x504;
+pub const PRESCALER: u32 = 0x508;
+/// CC[n] is at `CC + 4 * n`
+pub const CC: u32 = 0x540;
+
+const COUNTER_MASK: u32 = 0xFF_FFFF;
+const PERIOD: u64 = 1 << 24;
+const PRESCALER_MASK: u32 = 0xFFF;
+/// Counter set by TRIGOVRFLW, 16 ticks before the overflow
+const TRIGOVRFLW_COUNTER: u32 = 0xFF_FFF0;
+const TICK: u32 = 1 << 0;
+const OVRFLW: u32 = 1 << 1;
+/// COMPARE[n] is bit 16 + n of INTEN and EVTEN
+const COMPARE_SHIFT: u32 = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    running: bool,
+    counter: u32,
+    /// Progress of the low frequency clock, in 1 / (16 MHz * 32.768 kHz) seconds
+    phase: u64,
+    /// Ticks of the low frequency clock not making a full tick of the prescaled clock yet
+    remainder: u32,
+    prescaler: u32,
+    cc: Vec<u32>,
+    /// Events by bit of INTEN
+    events: u32,
+    evten: u32,
+    inten: u32,
+}
+
+impl State {
+    fn new(channels: usize) -> Self {
+        Self {
+            running: false,
+            counter: 0,
+            phase: 0,
+            remainder: 0,
+            prescaler: 0,
+            cc: vec![0; channels],
+            events: 0,
+            evten: 0,
+            inten: 0,
+        }
+    }
+}
+
+pub struct Rtc {
+    index: u8,
+    name: String,
+    state: State,
+}
+
+impl Rtc {
+    /// RTC0, with 3 CC registers, or RTC1, with 4 of them, for the indexes above 0
+    pub fn new(index: u8) -> Self {
+        let index = index.min(1);
+        Self {
+            index,
+            name: format!("RTC{index}"),
+            state: State::new(if index == 0 { 3 } else { 4 }),
+        }
+    }
+
+    pub fn counter(&self) -> u32 {
+        self.state.counter
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.running
+    }
+
+    /// Frequency of the counter in Hz
+    pub fn frequency(&self) -> f64 {
+        LFCLK_FREQUENCY as f64 / f64::from(self.state.prescaler + 1)
+    }
+
+    /// Bits of the events of this RTC in INTEN and EVTEN
+    fn valid(&self) -> u32 {
+        let channels = (1 << self.state.cc.len()) - 1;
+        TICK | OVRFLW | channels << COMPARE_SHIFT
+    }
+
+    /// Bit of the event at `register`, `None` for the other registers
+    fn event(&self, register: u32) -> Option<u32> {
+        event_bit(register).filter(|bit| bit & self.valid() != 0)
+    }
+
+    /// Events enabled in EVTEN or INTEN
+    fn enabled(&self) -> u32 {
+        (self.state.evten | self.state.inten) & self.valid()
+    }
+
+    /// Increments of the counter until the next enabled event
+    fn increments_to_event(&self) -> Option<u64> {
+        let enabled = self.enabled();
+        if enabled & TICK != 0 {
+            return Some(1);
+        }
+        let counter = u64::from(self.state.counter);
+        let overflow = (enabled & OVRFLW != 0).then_some(PERIOD - counter);
+        let compares = self
+            .state
+            .cc
+            .iter()
+            .enumerate()
+            .filter_map(|(channel, cc)| {
+                let bit = event_bit(EVENTS_COMPARE + 4 * channel as u32).unwrap_or(0);
+                let distance = u64::from(*cc).wrapping_sub(counter) & (PERIOD - 1);
+                (enabled & bit != 0).then_some(if distance == 0 { PERIOD } else { distance })
+            });
+        overflow.into_iter().chain(compares).min()
+    }
+
+    /// Advance the counter by `increments`, raising the enabled events it goes through
+    fn count(&mut self, mut increments: u64) {
+        let enabled = self.enabled();
+        if increments > 0 {
+            self.state.events |= enabled & TICK;
+        }
+        while increments > 0 {
+            let counter = u64::from(self.state.counter);
+            let distance = self
+                .state
+                .cc
+                .iter()
+                .map(
+                    |cc| match u64::from(*cc).wrapping_sub(counter) & (PERIOD - 1) {
+                        0 => PERIOD,
+                        distance => distance,
+                    },
+                )
+                .chain([PERIOD - counter])
+                .min()
+                .unwrap_or(PERIOD);
+            if increments < distance {
+                self.state.counter = (counter + increments) as u32 & COUNTER_MASK;
+                return;
+            }
+            increments -= distance;
+            self.state.counter = (counter + distance) as u32 & COUNTER_MASK;
+            if self.state.counter == 0 {
+                self.state.events |= enabled & OVRFLW;
+            }
+            for channel in 0..self.state.cc.len() {
+                if self.state.cc[channel] == self.state.counter {
+                    let bit = event_bit(EVENTS_COMPARE + 4 * channel as u32).unwrap_or(0);
+                    self.state.events |= enabled & bit;
+                }
+            }
+        }
+    }
+}
+
+impl Peripheral for Rtc {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn base(&self) -> u32 {
+        if self.index == 0 {
+            RTC0_BASE
+        } else {
+            RTC1_BASE
+        }
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let state = &self.state;
+        let channels = state.cc.len() as u32;
+        let word = match offset & !3 {
+            register if self.event(register).is_some() => {
+                u32::from(state.events & self.event(register).unwrap_or(0) != 0)
+            }
+            INTENSET | INTENCLR => state.inten,
+            EVTEN | EVTENSET | EVTENCLR => state.evten,
+            COUNTER => state.counter,
+            PRESCALER => state.prescaler,
+            register if (CC..CC + 4 * channels).contains(&register) => {
+                state.cc[((register - CC) / 4) as usize]
+            }
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let triggered = bits & 1 != 0;
+        let channels = self.state.cc.len() as u32;
+        let valid = self.valid();
+        match offset & !3 {
+            TASKS_START if triggered => self.state.running = true,
+            TASKS_STOP if triggered => self.state.running = false,
+            TASKS_CLEAR if triggered => {
+                self.state.counter = 0;
+                self.state.remainder = 0;
+            }
+            TASKS_TRIGOVRFLW if triggered => self.state.counter = TRIGOVRFLW_COUNTER,
+            register if self.event(register).is_some() => {
+                let event = self.event(register).unwrap_or(0);
+                if bits == 0 {
+                    self.state.events &= !event;
+                } else {
+                    self.state.events |= event;
+                }
+            }
+            register @ (INTENSET | INTENCLR) => {
+                write_inten(&mut self.state.inten, register, bits, mask);
+                self.state.inten &= valid;
+            }
+            register @ (EVTEN | EVTENSET | EVTENCLR) => {
+                let mode = match register {
+                    EVTEN => Enable::Write,
+                    EVTENSET => Enable::Set,
+                    _ => Enable::Clear,
+                };
+                write_enable(&mut self.state.evten, mode, bits, mask);
+                self.state.evten &= valid;
+            }
+            // NOTE: PRESCALER can only be written while the RTC is stopped
+            PRESCALER if !self.state.running => {
+                self.state.prescaler = (self.state.prescaler & !mask | bits) & PRESCALER_MASK;
+            }
+            register if (CC..CC + 4 * channels).contains(&register) => {
+                let cc = &mut self.state.cc[((register - CC) / 4) as usize];
+                *cc = (*cc & !mask | bits) & COUNTER_MASK;
+            }
+            _ => {}
+        }
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        let irq = if self.index == 0 { RTC0_IRQ } else { RTC1_IRQ };
+        (self.state.events & self.state.inten != 0).then_some(irq)
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        if !self.state.running {
+            return;
+        }
+        let phase = self.state.phase + cycles * LFCLK_FREQUENCY;
+        self.state.phase = phase % NRF51_FREQUENCY;
+        let ticks = phase / NRF51_FREQUENCY + u64::from(self.state.remainder);
+        let divider = u64::from(self.state.prescaler) + 1;
+        self.state.remainder = (ticks % divider) as u32;
+        self.count(ticks / divider);
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        if !self.state.running {
+            return None;
+        }
+        let increments = self.increments_to_event()?;
+        let ticks =
+            increments * (u64::from(self.state.prescaler) + 1) - u64::from(self.state.remainder);
+        Some((ticks * NRF51_FREQUENCY - self.state.phase).div_ceil(LFCLK_FREQUENCY))
+    }
+
+    fn reset(&mut self, _cause: ResetCause) {
+        self.state = State::new(self.state.cc.len());
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        self.state = bincode::deserialize(state)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+//! TEMP of the nRF51, measuring the die temperature in 0.25 °C units after a conversion delay.
+//!
+//! The host sets the temperature, or scripts it over the emulated time with points between which
+//! it changes linearly. It is 25 °C until the host sets it.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{read_bytes, write_bytes, write_inten, INTEN, INTENCLR, INTENSET};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+
+pub const TEMP_BASE: u32 = 0x4000_C000;
+pub const TEMP_IRQ: u8 = 12;
+
+pub const TASKS_START: u32 = 0x000;
+pub const TASKS_STOP: u32 = 0x004;
+pub const EVENTS_DATARDY: u32 = 0x100;
+pub const TEMP: u32 = 0x508;
+
+/// Cycles of the core clock of a measurement, 36 µs
+pub const CONVERSION: u64 = 576;
+pub const DEFAULT_TEMPERATURE: f64 = 25.0;
+
+const INTEN_DATARDY: u32 = 1 << 0;
+
+/// Temperature set by the host
+struct Host {
+    /// Cycles and temperatures in °C, sorted by cycle
+    points: Vec<(u64, f64)>,
+    /// Cycles of the core clock elapsed since the TEMP was attached
+    cycle: u64,
+}
+
+impl Host {
+    fn celsius(&self) -> f64 {
+        let after = self
+            .points
+            .partition_point(|(cycle, _)| *cycle <= self.cycle);
+        match (
+            after.checked_sub(1).map(|before| self.points[before]),
+            self.points.get(after).copied(),
+        ) {
+            (None, None) => DEFAULT_TEMPERATURE,
+            (Some((_, celsius)), None) | (None, Some((_, celsius))) => celsius,
+            (Some((start, from)), Some((end, to))) => {
+                let progress = (self.cycle - start) as f64 / (end - start) as f64;
+                from + (to - from) * progress
+            }
+        }
+    }
+}
+
+/// Handle on the die temperature of a `Temp`, for the host
+#[derive(Clone)]
+pub struct Die(Rc<RefCell<Host>>);
+
+impl Die {
+    /// Keep the temperature at `celsius`, replacing the script
+    pub fn set(&self, celsius: f64) {
+        self.0.borrow_mut().points = vec![(0, celsius)];
+    }
+
+    /// Change the temperature linearly between the points, given as cycles of the core clock
+    /// since the TEMP was attached and temperatures in °C. It stays at the first and the last
+    /// temperatures before and after the points.
+    pub fn script(&self, points: impl IntoIterator<Item = (u64, f64)>) {
+        let mut points: Vec<_> = points.into_iter().collect();
+        points.sort_by_key(|(cycle, _)| *cycle);
+        self.0.borrow_mut().points = points;
+    }
+
+    /// Temperature in °C at the current cycle
+    pub fn celsius(&self) -> f64 {
+        self.0.borrow().celsius()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    /// Cycles until the end of the measurement
+    measuring: Option<u64>,
+    temp: i32,
+    datardy: bool,
+    inten: u32,
+    cycle: u64,
+}
+
+impl State {
+    fn new(cycle: u64) -> Self {
+        Self {
+            measuring: None,
+            temp: 0,
+            datardy: false,
+            inten: 0,
+            cycle,
+        }
+    }
+}
+
+pub struct Temp {
+    die: Die,
+    state: State,
+}
+
+impl Temp {
+    pub fn new() -> Self {
+        Self {
+            die: Die(Rc::new(RefCell::new(Host {
+                points: Vec::new(),
+                cycle: 0,
+            }))),
+            state: State::new(0),
+        }
+    }
+
+    pub fn die(&self) -> Die {
+        self.die.clone()
+    }
+}
+
+impl Default for Temp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Peripheral for Temp {
+    fn name(&self) -> &str {
+        "TEMP"
+    }
+
+    fn base(&self) -> u32 {
+        TEMP_BASE
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let word = match offset & !3 {
+            EVENTS_DATARDY => u32::from(self.state.datardy),
+            INTEN | INTENSET | INTENCLR => self.state.inten,
+            TEMP => self.state.temp as u32,
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let triggered = bits & 1 != 0;
+        match offset & !3 {
+            TASKS_START if triggered => self.state.measuring = Some(CONVERSION),
+            TASKS_STOP if triggered => self.state.measuring = None,
+            EVENTS_DATARDY => self.state.datardy = bits != 0,
+            register => {
+                write_inten(&mut self.state.inten, register, bits, mask);
+                self.state.inten &= INTEN_DATARDY;
+            }
+        }
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        (self.state.datardy && self.state.inten & INTEN_DATARDY != 0).then_some(TEMP_IRQ)
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.state.cycle += cycles;
+        self.die.0.borrow_mut().cycle = self.state.cycle;
+        match self.state.measuring {
+            Some(left) if left > cycles => self.state.measuring = Some(left - cycles),
+            Some(_) => {
+                self.state.measuring = None;
+                // NOTE: The measurement is a single shot in 0.25 °C units
+                self.state.temp = (self.die.celsius() * 4.0).round() as i32;
+                self.state.datardy = true;
+            }
+            None => {}
+        }
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        self.state.measuring
+    }
+
+    /// The time of the die script goes on
+    fn reset(&mut self, _cause: ResetCause) {
+        self.state = State::new(self.state.cycle);
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        self.state = bincode::deserialize(state)?;
+        self.die.0.borrow_mut().cycle = self.state.cycle;
+        Ok(())
+    }
+}
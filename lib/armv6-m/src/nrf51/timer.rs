@@ -0,0 +1,307 @@
+//! TIMER0, TIMER1 and TIMER2 of the nRF51, counting the 16 MHz clock divided by 2^PRESCALER in
+//! timer mode, or the COUNT tasks in counter mode, up to their four capture/compare registers.
+//!
+//! The clock of the timers is the clock of the core, they advance with the cycles of the steps.
+//! TIMER1 and TIMER2 are 16 bits wide, the 24 and 32 bits BITMODE count on 16 bits for them.
+
+use serde::{Deserialize, Serialize};
+
+use super::{event_bit, read_bytes, write_bytes, write_inten, INTENCLR, INTENSET, SHORTS};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+
+pub const TIMER0_BASE: u32 = 0x4000_8000;
+pub const TIMER1_BASE: u32 = 0x4000_9000;
+pub const TIMER2_BASE: u32 = 0x4000_A000;
+pub const TIMER0_IRQ: u8 = 8;
+pub const TIMER1_IRQ: u8 = 9;
+pub const TIMER2_IRQ: u8 = 10;
+pub const CHANNELS: usize = 4;
+
+pub const TASKS_START: u32 = 0x000;
+pub const TASKS_STOP: u32 = 0x004;
+pub const TASKS_COUNT: u32 = 0x008;
+pub const TASKS_CLEAR: u32 = 0x00C;
+pub const TASKS_SHUTDOWN: u32 = 0x010;
+/// TASKS_CAPTURE[n] is at `TASKS_CAPTURE + 4 * n`
+pub const TASKS_CAPTURE: u32 = 0x040;
+/// EVENTS_COMPARE[n] is at `EVENTS_COMPARE + 4 * n`
+pub const EVENTS_COMPARE: u32 = 0x140;
+pub const MODE: u32 = 0x504;
+pub const BITMODE: u32 = 0x508;
+pub const PRESCALER: u32 = 0x510;
+/// CC[n] is at `CC + 4 * n`
+pub const CC: u32 = 0x540;
+
+/// COMPARE[n]_CLEAR is bit n of SHORTS, COMPARE[n]_STOP is bit 8 + n
+const SHORTS_STOP_SHIFT: u32 = 8;
+const SHORTS_MASK: u32 = 0xF0F;
+/// COMPARE[n] is bit 16 + n of INTEN, as its event
+const INTEN_MASK: u32 = 0xF << 16;
+const MAX_PRESCALER: u32 = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Count the prescaled clock
+    Timer,
+    /// Count the COUNT tasks
+    Counter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    running: bool,
+    counter: u32,
+    /// Cycles of the core clock not making a full tick of the prescaled clock yet
+    remainder: u64,
+    mode: u32,
+    bitmode: u32,
+    prescaler: u32,
+    cc: [u32; CHANNELS],
+    /// COMPARE events by bit of INTEN
+    events: u32,
+    shorts: u32,
+    inten: u32,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            running: false,
+            counter: 0,
+            remainder: 0,
+            mode: 0,
+            bitmode: 0,
+            prescaler: 4,
+            cc: [0; CHANNELS],
+            events: 0,
+            shorts: 0,
+            inten: 0,
+        }
+    }
+}
+
+pub struct Timer {
+    index: u8,
+    name: String,
+    state: State,
+}
+
+impl Timer {
+    /// TIMER0, TIMER1 or TIMER2, the indexes above 2 give TIMER2
+    pub fn new(index: u8) -> Self {
+        let index = index.min(2);
+        Self {
+            index,
+            name: format!("TIMER{index}"),
+            state: State::new(),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        if self.state.mode & 1 == 0 {
+            Mode::Timer
+        } else {
+            Mode::Counter
+        }
+    }
+
+    /// Width of the counter in bits
+    pub fn bits(&self) -> u32 {
+        let bits = match self.state.bitmode & 0x3 {
+            0 => 16,
+            1 => 8,
+            2 => 24,
+            _ => 32,
+        };
+        if self.index == 0 {
+            bits
+        } else {
+            bits.min(16)
+        }
+    }
+
+    /// Internal counter, only visible to the firmware through the CAPTURE tasks
+    pub fn counter(&self) -> u32 {
+        self.state.counter
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.running
+    }
+
+    /// Cycles of the core clock per tick of the counter in timer mode
+    pub fn tick_cycles(&self) -> u64 {
+        1 << self.state.prescaler
+    }
+
+    fn wrap(&self, counter: u64) -> u32 {
+        (counter & ((1 << self.bits()) - 1)) as u32
+    }
+
+    /// Ticks until the counter reaches the closest CC register, a full period for the one it is
+    /// already at. A CC register wider than the counter is never reached, `None` when all are.
+    fn ticks_to_compare(&self) -> Option<u64> {
+        let period = 1u64 << self.bits();
+        self.state
+            .cc
+            .iter()
+            .filter(|cc| u64::from(**cc) < period)
+            .map(|cc| {
+                let distance =
+                    u64::from(*cc).wrapping_sub(u64::from(self.state.counter)) & (period - 1);
+                if distance == 0 {
+                    period
+                } else {
+                    distance
+                }
+            })
+            .min()
+    }
+
+    /// Advance the counter by `ticks`, raising the COMPARE events it reaches and applying their
+    /// shortcuts
+    fn count(&mut self, mut ticks: u64) {
+        while ticks > 0 && self.state.running {
+            let distance = self.ticks_to_compare().unwrap_or(u64::MAX);
+            if ticks < distance {
+                self.state.counter = self.wrap(u64::from(self.state.counter) + ticks);
+                return;
+            }
+            ticks -= distance;
+            self.state.counter = self.wrap(u64::from(self.state.counter) + distance);
+            self.compare();
+        }
+    }
+
+    fn compare(&mut self) {
+        let counter = self.state.counter;
+        let mut clear = false;
+        for channel in 0..CHANNELS {
+            if self.state.cc[channel] != counter {
+                continue;
+            }
+            self.state.events |= event_bit(EVENTS_COMPARE + 4 * channel as u32).unwrap_or(0);
+            clear |= self.state.shorts >> channel & 1 == 1;
+            if self.state.shorts >> (SHORTS_STOP_SHIFT + channel as u32) & 1 == 1 {
+                self.state.running = false;
+            }
+        }
+        if clear {
+            self.state.counter = 0;
+        }
+    }
+}
+
+impl Peripheral for Timer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn base(&self) -> u32 {
+        TIMER0_BASE + 0x1000 * u32::from(self.index)
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        self.peek(offset, size).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let state = &self.state;
+        let word = match offset & !3 {
+            register @ EVENTS_COMPARE..0x150 => {
+                u32::from(state.events & event_bit(register).unwrap_or(0) != 0)
+            }
+            SHORTS => state.shorts,
+            INTENSET | INTENCLR => state.inten,
+            MODE => state.mode,
+            BITMODE => state.bitmode,
+            PRESCALER => state.prescaler,
+            register @ CC..0x550 => state.cc[((register - CC) / 4) as usize],
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let triggered = bits & 1 != 0;
+        match offset & !3 {
+            TASKS_START if triggered => self.state.running = true,
+            TASKS_STOP | TASKS_SHUTDOWN if triggered => self.state.running = false,
+            TASKS_COUNT if triggered && self.mode() == Mode::Counter => self.count(1),
+            TASKS_CLEAR if triggered => {
+                self.state.counter = 0;
+                self.state.remainder = 0;
+            }
+            register @ TASKS_CAPTURE..0x050 if triggered => {
+                self.state.cc[((register - TASKS_CAPTURE) / 4) as usize] = self.state.counter;
+            }
+            register @ EVENTS_COMPARE..0x150 => {
+                let event = event_bit(register).unwrap_or(0);
+                if bits == 0 {
+                    self.state.events &= !event;
+                } else {
+                    self.state.events |= event;
+                }
+            }
+            SHORTS => self.state.shorts = (self.state.shorts & !mask | bits) & SHORTS_MASK,
+            MODE => self.state.mode = (self.state.mode & !mask | bits) & 1,
+            BITMODE => {
+                self.state.bitmode = (self.state.bitmode & !mask | bits) & 0x3;
+                self.state.counter = self.wrap(u64::from(self.state.counter));
+            }
+            PRESCALER => {
+                let prescaler = (self.state.prescaler & !mask | bits) & 0xF;
+                self.state.prescaler = prescaler.min(MAX_PRESCALER);
+            }
+            register @ CC..0x550 => {
+                let cc = &mut self.state.cc[((register - CC) / 4) as usize];
+                *cc = *cc & !mask | bits;
+            }
+            register @ (INTENSET | INTENCLR) => {
+                write_inten(&mut self.state.inten, register, bits, mask);
+                self.state.inten &= INTEN_MASK;
+            }
+            _ => {}
+        }
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        (self.state.events & self.state.inten != 0).then_some(TIMER0_IRQ + self.index)
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        if !self.state.running || self.mode() != Mode::Timer {
+            return;
+        }
+        let elapsed = self.state.remainder + cycles;
+        self.state.remainder = elapsed & (self.tick_cycles() - 1);
+        self.count(elapsed >> self.state.prescaler);
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        if !self.state.running || self.mode() != Mode::Timer {
+            return None;
+        }
+        self.ticks_to_compare()
+            .map(|ticks| ticks * self.tick_cycles() - self.state.remainder)
+    }
+
+    fn reset(&mut self, _cause: ResetCause) {
+        self.state = State::new();
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        self.state = bincode::deserialize(state)?;
+        Ok(())
+    }
+}
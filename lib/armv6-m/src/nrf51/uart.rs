@@ -0,0 +1,492 @@
+//! UART of the nRF51, the serial port of the micro:bit over USB.
+//!
+//! A byte takes its frame, the start bit, 8 data bits, the parity bit when enabled and the stop
+//! bit, at the rate of BAUDRATE to go through. The receiver has a 6 bytes FIFO: with the hardware
+//! flow control the host waits while RTS is deasserted, without it the bytes arriving on a full
+//! FIFO are lost with an OVERRUN error.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use serde::{Deserialize, Serialize};
+
+use super::{event_bit, read_bytes, write_bytes, write_inten, INTENCLR, INTENSET, SHORTS};
+use crate::error::Error;
+use crate::peripheral::{Peripheral, ResetCause};
+
+pub const UART_BASE: u32 = 0x4000_2000;
+pub const UART_IRQ: u8 = 2;
+
+pub const TASKS_STARTRX: u32 = 0x000;
+pub const TASKS_STOPRX: u32 = 0x004;
+pub const TASKS_STARTTX: u32 = 0x008;
+pub const TASKS_STOPTX: u32 = 0x00C;
+pub const TASKS_SUSPEND: u32 = 0x01C;
+pub const EVENTS_CTS: u32 = 0x100;
+pub const EVENTS_NCTS: u32 = 0x104;
+pub const EVENTS_RXDRDY: u32 = 0x108;
+pub const EVENTS_TXDRDY: u32 = 0x11C;
+pub const EVENTS_ERROR: u32 = 0x124;
+pub const EVENTS_RXTO: u32 = 0x144;
+pub const ERRORSRC: u32 = 0x480;
+pub const ENABLE: u32 = 0x500;
+pub const PSELRTS: u32 = 0x508;
+pub const PSELTXD: u32 = 0x50C;
+pub const PSELCTS: u32 = 0x510;
+pub const PSELRXD: u32 = 0x514;
+pub const RXD: u32 = 0x518;
+pub const TXD: u32 = 0x51C;
+pub const BAUDRATE: u32 = 0x524;
+pub const CONFIG: u32 = 0x56C;
+
+/// BAUDRATE values of the common rates
+pub const BAUD_9600: u32 = 0x0027_5000;
+pub const BAUD_115200: u32 = 0x01D7_E000;
+
+pub const ENABLE_ENABLED: u32 = 4;
+pub const ERRORSRC_OVERRUN: u32 = 1 << 0;
+const CONFIG_HWFC: u32 = 1 << 0;
+const CONFIG_PARITY: u32 = 0x7 << 1;
+const SHORTS_CTS_STARTRX: u32 = 1 << 3;
+const SHORTS_NCTS_STOPRX: u32 = 1 << 4;
+/// Events with an interrupt: CTS, NCTS, RXDRDY, TXDRDY, ERROR and RXTO
+const INTEN_MASK: u32 = 0x0002_0287;
+
+pub const RX_FIFO: usize = 6;
+/// RTS is deasserted once the FIFO holds this many bytes
+const RTS_THRESHOLD: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct State {
+    enable: u32,
+    psel: [u32; 4],
+    baudrate: u32,
+    config: u32,
+    shorts: u32,
+    inten: u32,
+    /// Events by bit of INTEN
+    events: u32,
+    errorsrc: u32,
+    rx_started: bool,
+    tx_started: bool,
+    rx_fifo: VecDeque<u8>,
+    /// Byte on the wire and the cycles until its stop bit
+    rx_shift: Option<(u8, u64)>,
+    tx_shift: Option<(u8, u64)>,
+    /// TXD written while a byte is sent, or waiting for CTS
+    tx_pending: Option<u8>,
+    /// CTS level at the last sample
+    cts: bool,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            enable: 0,
+            psel: [u32::MAX; 4],
+            baudrate: 0x0400_0000,
+            config: 0,
+            shorts: 0,
+            inten: 0,
+            events: 0,
+            errorsrc: 0,
+            rx_started: false,
+            tx_started: false,
+            rx_fifo: VecDeque::new(),
+            rx_shift: None,
+            tx_shift: None,
+            tx_pending: None,
+            cts: true,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enable == ENABLE_ENABLED
+    }
+
+    fn flow_control(&self) -> bool {
+        self.config & CONFIG_HWFC != 0
+    }
+
+    /// Cycles of the core clock to send or receive a byte
+    fn frame_cycles(&self) -> u64 {
+        let bits = if self.config & CONFIG_PARITY == CONFIG_PARITY {
+            11
+        } else {
+            10
+        };
+        // NOTE: BAUDRATE is the bit rate scaled by 2^32 / 16 MHz
+        (bits << 32) / u64::from(self.baudrate.max(1))
+    }
+
+    fn rts(&self) -> bool {
+        !self.flow_control() || self.rx_started && self.rx_fifo.len() < RTS_THRESHOLD
+    }
+
+    fn raise(&mut self, event: u32) {
+        self.events |= event_bit(event).unwrap_or(0);
+    }
+}
+
+/// Host side of the wires
+#[derive(Default)]
+struct Host {
+    rx: VecDeque<u8>,
+    tx: VecDeque<u8>,
+    cts: bool,
+    rts: bool,
+    listener: Option<Box<dyn FnMut(u8)>>,
+    channel: Option<Sender<u8>>,
+}
+
+/// Handle on the wires of a `Uart`, for the host.
+///
+/// Transmitted bytes go to the callback when one is set, else to the channel when one was
+/// created, else they are kept until `take_output`.
+#[derive(Clone)]
+pub struct Serial(Rc<RefCell<Host>>);
+
+impl Serial {
+    /// Queue bytes for the receiver, they arrive at the baud rate once it is started
+    pub fn feed(&self, bytes: &[u8]) {
+        self.0.borrow_mut().rx.extend(bytes);
+    }
+
+    /// Bytes queued for the receiver which did not arrive yet
+    pub fn pending_input(&self) -> usize {
+        self.0.borrow().rx.len()
+    }
+
+    /// Bytes transmitted since the last call
+    pub fn take_output(&self) -> Vec<u8> {
+        self.0.borrow_mut().tx.drain(..).collect()
+    }
+
+    /// Call `listener` with each transmitted byte, they are no longer given by `take_output`
+    /// so a `Bridge` on this handle stops sending them
+    pub fn on_transmit(&self, listener: impl FnMut(u8) + 'static) {
+        self.0.borrow_mut().listener = Some(Box::new(listener));
+    }
+
+    /// Receive the transmitted bytes on a channel, unless a callback is set. They are no longer
+    /// given by `take_output` so a `Bridge` on this handle stops sending them
+    pub fn channel(&self) -> Receiver<u8> {
+        let (sender, receiver) = mpsc::channel();
+        self.0.borrow_mut().channel = Some(sender);
+        receiver
+    }
+
+    /// Level of CTS driven by the host, asserted by default
+    pub fn set_cts(&self, asserted: bool) {
+        self.0.borrow_mut().cts = asserted;
+    }
+
+    /// Whether the receiver accepts bytes, always with the hardware flow control disabled
+    pub fn rts(&self) -> bool {
+        self.0.borrow().rts
+    }
+
+    fn transmit(&self, byte: u8) {
+        let listener = self.0.borrow_mut().listener.take();
+        if let Some(mut listener) = listener {
+            // NOTE: The listener may use the handle, it is not borrowed while it runs
+            listener(byte);
+            self.0.borrow_mut().listener.get_or_insert(listener);
+            return;
+        }
+
+        let mut host = self.0.borrow_mut();
+        let sent = host
+            .channel
+            .as_ref()
+            .is_some_and(|channel| channel.send(byte).is_ok());
+        if !sent {
+            host.channel = None;
+            host.tx.push_back(byte);
+        }
+    }
+}
+
+pub struct Uart {
+    serial: Serial,
+    state: State,
+}
+
+impl Uart {
+    pub fn new() -> Self {
+        let serial = Serial(Rc::new(RefCell::new(Host {
+            cts: true,
+            rts: true,
+            ..Host::default()
+        })));
+        Self {
+            serial,
+            state: State::new(),
+        }
+    }
+
+    pub fn serial(&self) -> Serial {
+        self.serial.clone()
+    }
+
+    fn start_tx(&mut self, byte: u8) {
+        let cleared = !self.state.flow_control() || self.state.cts;
+        if self.state.tx_started && self.state.tx_shift.is_none() && cleared {
+            self.state.tx_shift = Some((byte, self.state.frame_cycles()));
+        } else {
+            self.state.tx_pending = Some(byte);
+        }
+    }
+
+    /// Take the next byte of the host when the receiver is idle and accepts it
+    fn start_rx(&mut self) {
+        let state = &mut self.state;
+        if state.rx_shift.is_none() && state.rx_started && state.rts() {
+            if let Some(byte) = self.serial.0.borrow_mut().rx.pop_front() {
+                state.rx_shift = Some((byte, state.frame_cycles()));
+            }
+        }
+    }
+
+    fn receive(&mut self, byte: u8) {
+        if self.state.rx_fifo.len() == RX_FIFO {
+            self.state.errorsrc |= ERRORSRC_OVERRUN;
+            self.state.raise(EVENTS_ERROR);
+        } else {
+            self.state.rx_fifo.push_back(byte);
+            // NOTE: RXDRDY is raised when the byte reaches RXD, the head of the FIFO
+            if self.state.rx_fifo.len() == 1 {
+                self.state.raise(EVENTS_RXDRDY);
+            }
+        }
+    }
+
+    fn stop_rx(&mut self) {
+        if self.state.rx_started {
+            self.state.rx_started = false;
+            self.state.rx_shift = None;
+            self.state.raise(EVENTS_RXTO);
+        }
+    }
+
+    /// Raise the CTS and NCTS events when the host changed CTS
+    fn sample_cts(&mut self) {
+        let cts = self.serial.0.borrow().cts;
+        if cts == self.state.cts || !self.state.is_enabled() {
+            return;
+        }
+        self.state.cts = cts;
+        if cts {
+            self.state.raise(EVENTS_CTS);
+            if self.state.shorts & SHORTS_CTS_STARTRX != 0 {
+                self.state.rx_started = true;
+            }
+            if let Some(byte) = self.state.tx_pending.take() {
+                self.start_tx(byte);
+            }
+        } else {
+            self.state.raise(EVENTS_NCTS);
+            if self.state.shorts & SHORTS_NCTS_STOPRX != 0 {
+                self.stop_rx();
+            }
+        }
+    }
+
+    fn advance_tx(&mut self, mut cycles: u64) {
+        while let Some((byte, left)) = self.state.tx_shift {
+            if cycles < left {
+                self.state.tx_shift = Some((byte, left - cycles));
+                return;
+            }
+            cycles -= left;
+            self.state.tx_shift = None;
+            self.serial.transmit(byte);
+            self.state.raise(EVENTS_TXDRDY);
+            if let Some(next) = self.state.tx_pending.take() {
+                self.start_tx(next);
+            }
+        }
+    }
+
+    fn advance_rx(&mut self, mut cycles: u64) {
+        self.start_rx();
+        while let Some((byte, left)) = self.state.rx_shift {
+            if cycles < left {
+                self.state.rx_shift = Some((byte, left - cycles));
+                return;
+            }
+            cycles -= left;
+            self.state.rx_shift = None;
+            self.receive(byte);
+            self.start_rx();
+        }
+    }
+
+    /// Show RTS to the host
+    fn sync(&self) {
+        self.serial.0.borrow_mut().rts = self.state.rts();
+    }
+}
+
+impl Default for Uart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Peripheral for Uart {
+    fn name(&self) -> &str {
+        "UART"
+    }
+
+    fn base(&self) -> u32 {
+        UART_BASE
+    }
+
+    fn size(&self) -> u32 {
+        0x1000
+    }
+
+    fn read(&mut self, offset: u32, size: u8) -> u32 {
+        let value = self.peek(offset, size).unwrap_or(0);
+        if offset & !3 == RXD && self.state.rx_fifo.pop_front().is_some() {
+            if !self.state.rx_fifo.is_empty() {
+                self.state.raise(EVENTS_RXDRDY);
+            }
+            self.start_rx();
+            self.sync();
+        }
+        value
+    }
+
+    fn peek(&self, offset: u32, size: u8) -> Option<u32> {
+        let state = &self.state;
+        let register = offset & !3;
+        let word = match register {
+            _ if event_bit(register).is_some() => {
+                u32::from(state.events & event_bit(register).unwrap_or(0) != 0)
+            }
+            SHORTS => state.shorts,
+            INTENSET | INTENCLR => state.inten,
+            ERRORSRC => state.errorsrc,
+            ENABLE => state.enable,
+            PSELRTS..=PSELRXD => state.psel[((register - PSELRTS) / 4) as usize],
+            RXD => state.rx_fifo.front().copied().map_or(0, u32::from),
+            BAUDRATE => state.baudrate,
+            CONFIG => state.config,
+            _ => 0,
+        };
+        Some(read_bytes(word, offset, size))
+    }
+
+    fn write(&mut self, offset: u32, size: u8, value: u32) {
+        let (bits, mask) = write_bytes(offset, size, value);
+        let register = offset & !3;
+        let triggered = bits & 1 != 0 && self.state.is_enabled();
+        match register {
+            TASKS_STARTRX if triggered => {
+                self.state.rx_started = true;
+                self.start_rx();
+            }
+            TASKS_STOPRX if triggered => self.stop_rx(),
+            TASKS_STARTTX if triggered => {
+                self.state.tx_started = true;
+                if let Some(byte) = self.state.tx_pending.take() {
+                    self.start_tx(byte);
+                }
+            }
+            TASKS_STOPTX if triggered => self.state.tx_started = false,
+            TASKS_SUSPEND if triggered => {
+                self.state.tx_started = false;
+                self.stop_rx();
+            }
+            _ if event_bit(register).is_some() => {
+                let event = event_bit(register).unwrap_or(0);
+                if bits == 0 {
+                    self.state.events &= !event;
+                } else {
+                    self.state.events |= event;
+                }
+            }
+            SHORTS => {
+                self.state.shorts =
+                    (self.state.shorts & !mask | bits) & (SHORTS_CTS_STARTRX | SHORTS_NCTS_STOPRX);
+            }
+            ERRORSRC => self.state.errorsrc &= !bits,
+            ENABLE => {
+                self.state.enable = (self.state.enable & !mask | bits) & 0x7;
+                if !self.state.is_enabled() {
+                    self.state.tx_started = false;
+                    self.state.rx_started = false;
+                    self.state.rx_shift = None;
+                    self.state.tx_shift = None;
+                }
+            }
+            PSELRTS..=PSELRXD => {
+                let psel = &mut self.state.psel[((register - PSELRTS) / 4) as usize];
+                *psel = *psel & !mask | bits;
+            }
+            TXD if self.state.is_enabled() => {
+                if self.state.tx_shift.is_some() {
+                    self.state.tx_pending = Some(bits as u8);
+                } else {
+                    self.start_tx(bits as u8);
+                }
+            }
+            BAUDRATE => self.state.baudrate = self.state.baudrate & !mask | bits,
+            CONFIG => self.state.config = (self.state.config & !mask | bits) & 0xF,
+            register @ (INTENSET | INTENCLR) => {
+                write_inten(&mut self.state.inten, register, bits, mask);
+                self.state.inten &= INTEN_MASK;
+            }
+            _ => {}
+        }
+        self.sync();
+    }
+
+    fn interrupt(&self) -> Option<u8> {
+        (self.state.events & self.state.inten != 0).then_some(UART_IRQ)
+    }
+
+    fn tick(&mut self, cycles: u64) {
+        self.sample_cts();
+        self.advance_tx(cycles);
+        self.advance_rx(cycles);
+        self.sync();
+    }
+
+    fn next_event(&self) -> Option<u64> {
+        let host = self.serial.0.borrow();
+        let cts_changed = self.state.is_enabled() && host.cts != self.state.cts;
+        let receiving = self.state.rx_shift.map(|(_, left)| left).or_else(|| {
+            (self.state.rx_started && self.state.rts() && !host.rx.is_empty())
+                .then(|| self.state.frame_cycles())
+        });
+        [
+            cts_changed.then_some(0),
+            self.state.tx_shift.map(|(_, left)| left),
+            receiving,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// The bytes on the wires are lost, the ones queued by the host are kept
+    fn reset(&mut self, _cause: ResetCause) {
+        self.state = State::new();
+        self.sync();
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serialize(&self.state)?)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error> {
+        self.state = bincode::deserialize(state)?;
+        self.sync();
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+//! Nested Vectored Interrupt Controller and the exception related registers of the System Control Block.
+//! <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Address-Map/Nested-Vectored-Interrupt-Controller--NVIC>
+
+use serde::{Deserialize, Serialize};
+
+use crate::structure::Exception;
+
+/// The System Control Space, NVIC and SCB registers live there
+pub const SCS_START: u32 = 0xE000_E000;
+pub const SCS_END: u32 = 0xE000_EFFF;
+
+const ISER: u32 = 0xE000_E100;
+const ICER: u32 = 0xE000_E180;
+const ISPR: u32 = 0xE000_E200;
+const ICPR: u32 = 0xE000_E280;
+const IPR_START: u32 = 0xE000_E400;
+const IPR_END: u32 = 0xE000_E41C;
+const CPUID: u32 = 0xE000_ED00;
+const ICSR: u32 = 0xE000_ED04;
+const AIRCR: u32 = 0xE000_ED0C;
+const SHPR2: u32 = 0xE000_ED1C;
+const SHPR3: u32 = 0xE000_ED20;
+
+/// Cortex-M0 r0p0, as found in the nRF51822
+const CPUID_VALUE: u32 = 0x410C_C200;
+/// VECTKEYSTAT, little endian
+const AIRCR_VALUE: u32 = 0xFA05_0000;
+/// VECTKEY, required by the writes of AIRCR
+const AIRCR_VECTKEY: u32 = 0x05FA;
+const AIRCR_SYSRESETREQ: u32 = 1 << 2;
+
+/// Only the 2 most significant bits of a priority are implemented on Armv6-M
+const PRIORITY_MASK: u8 = 0b1100_0000;
+
+/// Priority of Thread mode, lower than any configurable priority
+pub const THREAD_PRIORITY: i16 = 256;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Nvic {
+    /// One bit per external interrupt (ISER / ICER)
+    enabled: u32,
+    /// One bit per external interrupt (ISPR / ICPR)
+    pending: u32,
+    /// External interrupts priorities (IPR0-IPR7)
+    priorities: [u8; 32],
+    nmi_pending: bool,
+    pend_sv_pending: bool,
+    sys_tick_pending: bool,
+    /// SHPR2 and SHPR3 fields
+    sv_call_priority: u8,
+    pend_sv_priority: u8,
+    sys_tick_priority: u8,
+    /// AIRCR.SYSRESETREQ was written, it is handled before the next step
+    #[serde(skip)]
+    reset_requested: bool,
+}
+
+impl Nvic {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self, irq: u8) -> bool {
+        self.enabled >> irq & 1 == 1
+    }
+
+    pub fn set_enabled(&mut self, irq: u8, enabled: bool) {
+        self.enabled = (self.enabled & !(1 << irq)) | (u32::from(enabled) << irq);
+    }
+
+    pub fn is_pending(&self, exception: Exception) -> bool {
+        match exception {
+            Exception::Nmi => self.nmi_pending,
+            Exception::PendSv => self.pend_sv_pending,
+            Exception::SysTick => self.sys_tick_pending,
+            Exception::Interrupt(irq) => self.pending >> irq & 1 == 1,
+            // NOTE: Synchronous exceptions are taken right away, they are never pending
+            Exception::Reset | Exception::HardFault | Exception::SvCall => false,
+        }
+    }
+
+    pub fn set_pending(&mut self, exception: Exception, pending: bool) {
+        match exception {
+            Exception::Nmi => self.nmi_pending = pending,
+            Exception::PendSv => self.pend_sv_pending = pending,
+            Exception::SysTick => self.sys_tick_pending = pending,
+            Exception::Interrupt(irq) => {
+                self.pending = (self.pending & !(1 << irq)) | (u32::from(pending) << irq);
+            }
+            Exception::Reset | Exception::HardFault | Exception::SvCall => {}
+        }
+    }
+
+    /// Whether AIRCR.SYSRESETREQ was written since the last call
+    pub fn take_reset_request(&mut self) -> bool {
+        std::mem::take(&mut self.reset_requested)
+    }
+
+    /// Fixed priorities are negative, lower values have a higher priority
+    pub fn priority(&self, exception: Exception) -> i16 {
+        match exception {
+            Exception::Reset => -3,
+            Exception::Nmi => -2,
+            Exception::HardFault => -1,
+            Exception::SvCall => i16::from(self.sv_call_priority),
+            Exception::PendSv => i16::from(self.pend_sv_priority),
+            Exception::SysTick => i16::from(self.sys_tick_priority),
+            Exception::Interrupt(irq) => i16::from(self.priorities[usize::from(irq)]),
+        }
+    }
+
+    /// Pending exception with the highest priority, the lowest exception number wins ties
+    pub fn highest_pending(&self) -> Option<Exception> {
+        let externals = (0..32)
+            .filter(|irq| self.is_enabled(*irq))
+            .map(Exception::Interrupt);
+
+        [Exception::Nmi, Exception::SysTick, Exception::PendSv]
+            .into_iter()
+            .chain(externals)
+            .filter(|exception| self.is_pending(*exception))
+            .min_by_key(|exception| (self.priority(*exception), exception.number()))
+    }
+
+    /// Word read of a System Control Space register, `active` is the current exception number
+    pub fn read(&self, address: u32, active: u32) -> u32 {
+        match address {
+            ISER | ICER => self.enabled,
+            ISPR | ICPR => self.pending,
+            IPR_START..=IPR_END => {
+                let first = (address - IPR_START) as usize;
+                u32::from_le_bytes(
+                    self.priorities[first..first + 4]
+                        .try_into()
+                        .unwrap_or_default(),
+                )
+            }
+            CPUID => CPUID_VALUE,
+            ICSR => {
+                let pending = self
+                    .highest_pending()
+                    .map_or(0, |exception| exception.number());
+                u32::from(self.nmi_pending) << 31
+                    | u32::from(self.pend_sv_pending) << 28
+                    | u32::from(self.sys_tick_pending) << 26
+                    | u32::from(self.pending & self.enabled != 0) << 22
+                    | pending << 12
+                    | active
+            }
+            AIRCR => AIRCR_VALUE,
+            SHPR2 => u32::from(self.sv_call_priority) << 24,
+            SHPR3 => {
+                u32::from(self.sys_tick_priority) << 24 | u32::from(self.pend_sv_priority) << 16
+            }
+            _ => 0,
+        }
+    }
+
+    /// Word write of a System Control Space register
+    pub fn write(&mut self, address: u32, value: u32) {
+        match address {
+            ISER => self.enabled |= value,
+            ICER => self.enabled &= !value,
+            ISPR => self.pending |= value,
+            ICPR => self.pending &= !value,
+            IPR_START..=IPR_END => {
+                let first = (address - IPR_START) as usize;
+                for (priority, byte) in self.priorities[first..first + 4]
+                    .iter_mut()
+                    .zip(value.to_le_bytes())
+                {
+                    *priority = byte & PRIORITY_MASK;
+                }
+            }
+            ICSR => {
+                if value >> 31 & 1 == 1 {
+                    self.nmi_pending = true;
+                }
+                if value >> 28 & 1 == 1 {
+                    self.pend_sv_pending = true;
+                } else if value >> 27 & 1 == 1 {
+                    self.pend_sv_pending = false;
+                }
+                if value >> 26 & 1 == 1 {
+                    self.sys_tick_pending = true;
+                } else if value >> 25 & 1 == 1 {
+                    self.sys_tick_pending = false;
+                }
+            }
+            SHPR2 => self.sv_call_priority = (value >> 24) as u8 & PRIORITY_MASK,
+            SHPR3 => {
+                self.sys_tick_priority = (value >> 24) as u8 & PRIORITY_MASK;
+                self.pend_sv_priority = (value >> 16) as u8 & PRIORITY_MASK;
+            }
+            AIRCR if value >> 16 == AIRCR_VECTKEY && value & AIRCR_SYSRESETREQ != 0 => {
+                self.reset_requested = true;
+            }
+            // NOTE: AIRCR.VECTCLRACTIVE and the other registers are not implemented
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::Error;
+
+/// Cause of a reset of the system, given to the peripherals by `Armv6M::system_reset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetCause {
+    PowerOn,
+    /// Reset pin
+    Pin,
+    Watchdog,
+    /// AIRCR.SYSRESETREQ
+    SoftReset,
+    Lockup,
+    /// Wake up from System OFF
+    WakeUp,
+}
+
+/// Power state of the system wanted by a peripheral
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Power {
+    On,
+    /// The core stops until a peripheral asks for a reset
+    Off,
+    Reset(ResetCause),
+}
+
+/// A memory mapped device attached to the core.
+///
+/// Accesses falling in `base..base + size` are forwarded to the peripheral instead of the memory,
+/// they are not recorded in the mutations history.
+pub trait Peripheral {
+    /// Unique name, used to find the peripheral state in snapshots
+    fn name(&self) -> &str;
+
+    /// Address of the first register
+    fn base(&self) -> u32;
+    /// Size of the mapped region in bytes
+    fn size(&self) -> u32;
+
+    /// Read `size` bytes (1, 2 or 4) at `offset` from the base
+    fn read(&mut self, offset: u32, size: u8) -> u32;
+    /// Read without side effects, for the debugger. `None` when reading has side effects
+    /// that cannot be avoided.
+    fn peek(&self, _offset: u32, _size: u8) -> Option<u32> {
+        None
+    }
+
+    /// Write the `size` (1, 2 or 4) low bytes of `value` at `offset` from the base
+    fn write(&mut self, offset: u32, size: u8, value: u32);
+
+    /// External interrupt asserted by the peripheral, it stays pending in the NVIC while asserted
+    fn interrupt(&self) -> Option<u8> {
+        None
+    }
+
+    /// Let `cycles` cycles of the core clock elapse, called after every step
+    fn tick(&mut self, _cycles: u64) {}
+
+    /// Cycles until the peripheral may assert its interrupt, the time is skipped up to there
+    /// while the core sleeps
+    fn next_event(&self) -> Option<u64> {
+        None
+    }
+
+    /// Power state wanted by the peripheral, polled before every step.
+    /// A reset is asked for once.
+    fn power(&mut self) -> Power {
+        Power::On
+    }
+
+    /// The system was reset, the peripherals are not reset by `Armv6M::reset`
+    fn reset(&mut self, _cause: ResetCause) {}
+
+    /// Serialize the internal state, to be given back to `load_state`
+    fn save_state(&self) -> Result<Vec<u8>, Error>;
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Error>;
+
+    fn contains(&self, address: u32) -> bool {
+        address.wrapping_sub(self.base()) < self.size()
+    }
+}
@@ -0,0 +1,56 @@
+//! Versioned binary format of a whole machine state.
+//!
+//! A snapshot is the magic, the format version in little endian, then the bincode encoded state.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::nvic::Nvic;
+use crate::systick::SysTick;
+use crate::timing::MemoryTiming;
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"A6MS";
+/// Bumped on every change of `Snapshot`
+pub const SNAPSHOT_VERSION: u32 = 3;
+
+/// Everything needed to resume the execution, the mutations history is not kept
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Values of `RegisterId::ALL`, in the same order
+    pub registers: Vec<u32>,
+    /// Event Register
+    pub event: bool,
+    /// Cycles elapsed since the reset
+    pub cycles: u64,
+    pub nvic: Nvic,
+    pub sys_tick: SysTick,
+    pub memory_timing: MemoryTiming,
+    /// Allocated memory pages with the address of their first byte
+    pub pages: Vec<(u32, Vec<u8>)>,
+    /// Peripheral states by name, as returned by `Peripheral::save_state`
+    pub peripherals: Vec<(String, Vec<u8>)>,
+}
+
+impl Snapshot {
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = SNAPSHOT_MAGIC.to_vec();
+        bytes.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self)?;
+        Ok(bytes)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let payload = bytes
+            .strip_prefix(SNAPSHOT_MAGIC)
+            .ok_or(Error::Snapshot("not a snapshot"))?;
+        let (version, state) = payload
+            .split_first_chunk::<4>()
+            .ok_or(Error::Snapshot("truncated header"))?;
+
+        if u32::from_le_bytes(*version) != SNAPSHOT_VERSION {
+            return Err(Error::Snapshot("unsupported version"));
+        }
+
+        Ok(bincode::deserialize(state)?)
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 /* === Memory === */
 /// Memory is addressed with 32 bits addresses
 pub const MEMORY_MAX_ADDRESSABLE_ADDRESS: usize = u32::MAX as usize;
@@ -6,16 +8,85 @@ pub const MEMORY_MAX_ADDRESSABLE_ADDRESS: usize = u32::MAX as usize;
 
 /// Application Program Status Register
 /// <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Level-Programmers--Model/Registers/The-special-purpose-program-status-registers--xPSR>
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Apsr(u32);
 
 /// Interrupt Program Status Register
 /// <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Level-Programmers--Model/Registers/The-special-purpose-program-status-registers--xPSR>
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Ipsr(u32);
 
 /// Execution Program Status Register
 /// <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Level-Programmers--Model/Registers/The-special-purpose-program-status-registers--xPSR>
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Epsr(u32);
 
+macro_rules! psr_flag_generator {
+    ($(($get:ident, $set:ident, $bit:expr)),*) => {
+        $(
+            pub fn $get(&self) -> bool {
+                self.0 >> $bit & 1 == 1
+            }
+
+            pub fn $set(&mut self, value: bool) {
+                self.0 = (self.0 & !(1 << $bit)) | (u32::from(value) << $bit);
+            }
+        )*
+    };
+}
+
+impl Apsr {
+    /// Only the N, Z, C and V flags are implemented on Armv6-M
+    pub const MASK: u32 = 0xF000_0000;
+
+    pub fn from_bits(bits: u32) -> Self {
+        Self(bits & Self::MASK)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    psr_flag_generator!(
+        (n, set_n, 31),
+        (z, set_z, 30),
+        (c, set_c, 29),
+        (v, set_v, 28)
+    );
+}
+
+impl Ipsr {
+    /// Exception number of the current exception, 0 in Thread mode
+    pub const MASK: u32 = 0x3F;
+
+    pub fn from_bits(bits: u32) -> Self {
+        Self(bits & Self::MASK)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn exception_number(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Epsr {
+    /// Only the Thumb bit is implemented on Armv6-M
+    pub const MASK: u32 = 1 << 24;
+
+    pub fn from_bits(bits: u32) -> Self {
+        Self(bits & Self::MASK)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    psr_flag_generator!((t, set_t, 24));
+}
+
 /// List of the registers:
 /// - General purpose registers R0-R12.
 /// - Two Stack Pointer registers, `SP_main` and `SP_process`. These are banked versions of SP, also described as R13.
@@ -65,6 +136,112 @@ pub enum Register {
     EPSR(Epsr),
 }
 
+/// Identifies every register of the core, used to record and restore register changes.
+///
+/// APSR, IPSR and EPSR are kept apart, they can still be read together as `xPSR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RegisterId {
+    R0,
+    R1,
+    R2,
+    R3,
+    R4,
+    R5,
+    R6,
+    R7,
+    R8,
+    R9,
+    R10,
+    R11,
+    R12,
+    SpMain,
+    SpProcess,
+    Lr,
+    Pc,
+    Apsr,
+    Ipsr,
+    Epsr,
+    Primask,
+    Control,
+}
+
+impl RegisterId {
+    pub const ALL: [RegisterId; 22] = [
+        RegisterId::R0,
+        RegisterId::R1,
+        RegisterId::R2,
+        RegisterId::R3,
+        RegisterId::R4,
+        RegisterId::R5,
+        RegisterId::R6,
+        RegisterId::R7,
+        RegisterId::R8,
+        RegisterId::R9,
+        RegisterId::R10,
+        RegisterId::R11,
+        RegisterId::R12,
+        RegisterId::SpMain,
+        RegisterId::SpProcess,
+        RegisterId::Lr,
+        RegisterId::Pc,
+        RegisterId::Apsr,
+        RegisterId::Ipsr,
+        RegisterId::Epsr,
+        RegisterId::Primask,
+        RegisterId::Control,
+    ];
+}
+
+/* === Exceptions === */
+
+/// Exceptions supported by the Armv6-M exception model
+/// <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Level-Programmers--Model/Armv6-M-exception-model/Exception-number-definition>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Exception {
+    Reset,
+    Nmi,
+    HardFault,
+    SvCall,
+    PendSv,
+    SysTick,
+    /// External interrupt, from 0 to 31
+    Interrupt(u8),
+}
+
+impl Exception {
+    /// Exception number, used as IPSR value and as index in the vector table
+    pub fn number(&self) -> u32 {
+        match self {
+            Exception::Reset => 1,
+            Exception::Nmi => 2,
+            Exception::HardFault => 3,
+            Exception::SvCall => 11,
+            Exception::PendSv => 14,
+            Exception::SysTick => 15,
+            Exception::Interrupt(n) => 16 + u32::from(*n),
+        }
+    }
+
+    pub fn from_number(number: u32) -> Option<Self> {
+        match number {
+            1 => Some(Exception::Reset),
+            2 => Some(Exception::Nmi),
+            3 => Some(Exception::HardFault),
+            11 => Some(Exception::SvCall),
+            14 => Some(Exception::PendSv),
+            15 => Some(Exception::SysTick),
+            16..=47 => Some(Exception::Interrupt((number - 16) as u8)),
+            _ => None,
+        }
+    }
+}
+
+/// Magic values loaded in LR on exception entry, branching to them returns from the exception
+/// <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Level-Programmers--Model/Armv6-M-exception-model/Exception-return-behavior>
+pub const EXC_RETURN_HANDLER: u32 = 0xFFFF_FFF1;
+pub const EXC_RETURN_THREAD_MAIN: u32 = 0xFFFF_FFF9;
+pub const EXC_RETURN_THREAD_PROCESS: u32 = 0xFFFF_FFFD;
+
 /* === Instruction set === */
 pub enum Instruction {
     Bits32(Instruction32),
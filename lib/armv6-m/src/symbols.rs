@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+
+use elf::abi::{STB_GLOBAL, STT_FUNC, STT_NOTYPE, STT_OBJECT};
+use elf::endian::LittleEndian;
+use elf::ElfBytes;
+
+use crate::error::Error;
+
+/// Named addresses of a program, from its ELF symbol table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbols {
+    names: BTreeMap<u32, String>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Functions, objects and labels of the `.symtab`, Thumb functions have their bit 0 cleared.
+    /// When symbols share an address, functions win over objects and labels, then globals over locals.
+    pub fn from_elf(bytes: &[u8]) -> Result<Self, Error> {
+        let file = ElfBytes::<LittleEndian>::minimal_parse(bytes)?;
+        let mut ranked: BTreeMap<u32, ((u8, bool), String)> = BTreeMap::new();
+
+        if let Some((table, strings)) = file.symbol_table()? {
+            for symbol in table {
+                let kind = symbol.st_symtype();
+                let rank = match kind {
+                    STT_FUNC => 2,
+                    STT_OBJECT => 1,
+                    STT_NOTYPE => 0,
+                    _ => continue,
+                };
+                let name = strings.get(symbol.st_name as usize)?;
+                // NOTE: `$t`, `$d` and `$a` are mapping symbols, they mark code and data
+                if symbol.is_undefined() || name.is_empty() || name.starts_with('$') {
+                    continue;
+                }
+
+                let mut address = symbol.st_value as u32;
+                if kind == STT_FUNC {
+                    address &= !1;
+                }
+                let rank = (rank, symbol.st_bind() == STB_GLOBAL);
+                if ranked.get(&address).is_none_or(|(other, _)| rank > *other) {
+                    ranked.insert(address, (rank, name.to_owned()));
+                }
+            }
+        }
+
+        Ok(Self {
+            names: ranked
+                .into_iter()
+                .map(|(address, (_, name))| (address, name))
+                .collect(),
+        })
+    }
+
+    pub fn insert(&mut self, address: u32, name: impl Into<String>) {
+        self.names.insert(address, name.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> + '_ {
+        self.names
+            .iter()
+            .map(|(address, name)| (*address, name.as_str()))
+    }
+
+    /// Symbol starting exactly at `address`
+    pub fn get(&self, address: u32) -> Option<&str> {
+        self.names.get(&address).map(String::as_str)
+    }
+
+    pub fn address_of(&self, name: &str) -> Option<u32> {
+        self.iter()
+            .find(|(_, symbol)| *symbol == name)
+            .map(|(address, _)| address)
+    }
+
+    /// Closest symbol at or before `address`, with the offset from it
+    pub fn lookup(&self, address: u32) -> Option<(&str, u32)> {
+        self.names
+            .range(..=address)
+            .next_back()
+            .map(|(start, name)| (name.as_str(), address - start))
+    }
+
+    /// `name` or `name+0xoffset`, like in `objdump` outputs
+    pub fn label(&self, address: u32) -> Option<String> {
+        self.lookup(address).map(|(name, offset)| match offset {
+            0 => name.to_owned(),
+            _ => format!("{name}+{offset:#x}"),
+        })
+    }
+}
@@ -0,0 +1,141 @@
+//! SysTick timer of the System Control Space, clocked by the core clock.
+//! <https://developer.arm.com/documentation/ddi0419/c/System-Level-Architecture/System-Address-Map/The-system-timer--SysTick>
+
+use serde::{Deserialize, Serialize};
+
+pub const SYST_CSR: u32 = 0xE000_E010;
+pub const SYST_RVR: u32 = 0xE000_E014;
+pub const SYST_CVR: u32 = 0xE000_E018;
+pub const SYST_CALIB: u32 = 0xE000_E01C;
+
+const ENABLE: u32 = 1 << 0;
+const TICKINT: u32 = 1 << 1;
+const CLKSOURCE: u32 = 1 << 2;
+const COUNTFLAG: u32 = 1 << 16;
+
+/// The counters are 24 bits wide
+const COUNTER_MASK: u32 = 0x00FF_FFFF;
+/// NOREF and SKEW, there is no reference clock nor calibration value
+const CALIB_VALUE: u32 = 0xC000_0000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SysTick {
+    /// ENABLE, TICKINT, CLKSOURCE and COUNTFLAG of SYST_CSR
+    control: u32,
+    reload: u32,
+    current: u32,
+}
+
+impl SysTick {
+    pub fn new() -> Self {
+        Self {
+            // NOTE: Without a reference clock CLKSOURCE reads as the processor clock
+            control: CLKSOURCE,
+            reload: 0,
+            current: 0,
+        }
+    }
+
+    pub fn contains(address: u32) -> bool {
+        (SYST_CSR..=SYST_CALIB).contains(&address)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.control & ENABLE != 0
+    }
+
+    fn raises_interrupt(&self) -> bool {
+        self.is_enabled() && self.control & TICKINT != 0
+    }
+
+    /// Word read, reading SYST_CSR clears COUNTFLAG
+    pub fn read(&mut self, address: u32) -> u32 {
+        let value = self.peek(address);
+        if address == SYST_CSR {
+            self.control &= !COUNTFLAG;
+        }
+        value
+    }
+
+    /// Word read without side effects
+    pub fn peek(&self, address: u32) -> u32 {
+        match address {
+            SYST_CSR => self.control,
+            SYST_RVR => self.reload,
+            SYST_CVR => self.current,
+            SYST_CALIB => CALIB_VALUE,
+            _ => 0,
+        }
+    }
+
+    /// Word write, any write to SYST_CVR clears it and COUNTFLAG
+    pub fn write(&mut self, address: u32, value: u32) {
+        match address {
+            SYST_CSR => {
+                self.control =
+                    (self.control & COUNTFLAG) | (value & (ENABLE | TICKINT)) | CLKSOURCE;
+            }
+            SYST_RVR => self.reload = value & COUNTER_MASK,
+            SYST_CVR => {
+                self.current = 0;
+                self.control &= !COUNTFLAG;
+            }
+            _ => {}
+        }
+    }
+
+    /// Count down `cycles` times, the counter is reloaded on the cycle after it reaches 0.
+    /// Returns whether the SysTick exception must become pending.
+    pub fn tick(&mut self, cycles: u64) -> bool {
+        if !self.is_enabled() || cycles == 0 {
+            return false;
+        }
+
+        let mut remaining = cycles;
+        let mut wrapped = false;
+        if self.current > 0 {
+            let current = u64::from(self.current);
+            if remaining < current {
+                self.current -= remaining as u32;
+                return false;
+            }
+            remaining -= current;
+            self.current = 0;
+            wrapped = true;
+        }
+
+        // NOTE: The counter stays at 0 once it is reloaded with 0
+        if self.reload > 0 && remaining > 0 {
+            let period = u64::from(self.reload) + 1;
+            wrapped |= remaining >= period;
+            remaining %= period;
+            if remaining > 0 {
+                self.current = self.reload - (remaining - 1) as u32;
+            }
+        }
+
+        if wrapped {
+            self.control |= COUNTFLAG;
+        }
+        wrapped && self.control & TICKINT != 0
+    }
+
+    /// Cycles until the counter raises the SysTick exception
+    pub fn next_event(&self) -> Option<u64> {
+        if !self.raises_interrupt() {
+            None
+        } else if self.current > 0 {
+            Some(u64::from(self.current))
+        } else if self.reload > 0 {
+            Some(u64::from(self.reload) + 1)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for SysTick {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,101 @@
+//! Cortex-M0 timing model, in cycles of the core clock.
+//! <https://developer.arm.com/documentation/ddi0432/c/programmers-model/instruction-set-summary>
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::instructions::add::Add;
+use crate::instructions::b::B;
+use crate::instructions::hint::Hint;
+use crate::instructions::mov::Mov;
+use crate::instructions::Instruction;
+
+/// Core clock of the nRF51822
+pub const NRF51_FREQUENCY: u64 = 16_000_000;
+
+/// Stacking of the exception frame and fetch of the vector, with zero wait state memory
+pub const EXCEPTION_ENTRY_CYCLES: u64 = 16;
+
+/// Cycles taken by an instruction with zero wait state memory, `taken` tells whether it branched.
+/// The nRF51 has the single cycle multiplier.
+pub fn cycles(instruction: &Instruction, taken: bool) -> u64 {
+    let registers = |list: u8| u64::from(list.count_ones());
+    match instruction {
+        Instruction::B(B::T1 { .. }) if !taken => 1,
+        Instruction::B(_) | Instruction::Blx(_) | Instruction::Bx(_) => 3,
+        Instruction::Add(Add::RegisterT2 { rdn, dn, .. }) if dn << 3 | rdn == 15 => 3,
+        Instruction::Add(Add::SpPlusRegisterT1 { dm, rdm }) if dm << 3 | rdm == 15 => 3,
+        Instruction::Mov(Mov::RegisterT1 { d, rd, .. }) if d << 3 | rd == 15 => 3,
+        Instruction::Ldr(_)
+        | Instruction::Ldrb(_)
+        | Instruction::Ldrh(_)
+        | Instruction::Ldrsb(_)
+        | Instruction::Ldrsh(_)
+        | Instruction::Str(_)
+        | Instruction::Strb(_)
+        | Instruction::Strh(_)
+        | Instruction::Hint(Hint::Wfe | Hint::Wfi) => 2,
+        Instruction::Ldm(ldm) => 1 + registers(ldm.register_list),
+        Instruction::Stm(stm) => 1 + registers(stm.register_list),
+        Instruction::Push(push) => 1 + registers(push.register_list) + u64::from(push.m),
+        Instruction::Pop(pop) if pop.p == 1 => 4 + registers(pop.register_list),
+        Instruction::Pop(pop) => 1 + registers(pop.register_list),
+        Instruction::Bl(_)
+        | Instruction::Mrs(_)
+        | Instruction::Msr(_)
+        | Instruction::Dmb(_)
+        | Instruction::Dsb(_)
+        | Instruction::Isb(_) => 4,
+        // NOTE: BKPT is not executed, SVC and UDF cost their exception entry
+        Instruction::Bkpt(_) | Instruction::Svc(_) | Instruction::Udf(_) => 0,
+        _ => 1,
+    }
+}
+
+/// Time taken by `cycles` at `frequency` Hz
+pub fn duration(cycles: u64, frequency: u64) -> Duration {
+    let seconds = cycles / frequency;
+    let nanos = (cycles % frequency) * 1_000_000_000 / frequency;
+    Duration::new(seconds, nanos as u32)
+}
+
+/// Extra cycles of every access to `start..=end`, instruction fetches included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitStates {
+    pub start: u32,
+    pub end: u32,
+    pub cycles: u64,
+}
+
+/// Wait states of the memory regions, the nRF51 flash and RAM have none at 16 MHz
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryTiming {
+    /// The last region set wins where regions overlap
+    regions: Vec<WaitStates>,
+}
+
+impl MemoryTiming {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, wait_states: WaitStates) {
+        self.regions
+            .retain(|region| (region.start, region.end) != (wait_states.start, wait_states.end));
+        self.regions.push(wait_states);
+    }
+
+    pub fn regions(&self) -> &[WaitStates] {
+        &self.regions
+    }
+
+    /// Wait states of an access at `address`
+    pub fn wait_states(&self, address: u32) -> u64 {
+        self.regions
+            .iter()
+            .rev()
+            .find(|region| (region.start..=region.end).contains(&address))
+            .map_or(0, |region| region.cycles)
+    }
+}
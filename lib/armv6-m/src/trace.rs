@@ -0,0 +1,338 @@
+//! Opt-in recording of the executed instructions, written to a text, JSON lines or binary sink.
+//!
+//! The binary format is the magic, the format version in little endian,
+//! then the bincode encoded entries one after the other.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::memory::{Access, AccessKind};
+use crate::mutation::RegisterChange;
+use crate::structure::Exception;
+
+pub const TRACE_MAGIC: &[u8; 4] = b"A6MT";
+/// Bumped on every change of `TraceEntry`
+pub const TRACE_VERSION: u32 = 1;
+
+/// What a single step did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// Address of the instruction, or of the preempted one when an exception was taken instead
+    pub address: u32,
+    /// Halfwords of the instruction, empty when an exception was taken instead
+    pub halfwords: Vec<u16>,
+    /// Disassembly of the instruction
+    pub text: String,
+    /// Exception taken by the step, instead of the instruction or because it faulted
+    pub exception: Option<Exception>,
+    pub registers: Vec<RegisterChange>,
+    /// Data accesses, on the memory and the peripherals
+    pub accesses: Vec<Access>,
+    /// Cycles taken by the step
+    pub cycles: u64,
+    /// Cycle counter after the step
+    pub cycle: u64,
+}
+
+/// Where the trace entries are written
+pub trait TraceSink {
+    fn record(&mut self, entry: &TraceEntry) -> Result<(), Error>;
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Entries kept in memory
+impl TraceSink for Vec<TraceEntry> {
+    fn record(&mut self, entry: &TraceEntry) -> Result<(), Error> {
+        self.push(entry.clone());
+        Ok(())
+    }
+}
+
+/// One line per instruction, for humans
+pub struct TextSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TextSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> TraceSink for TextSink<W> {
+    fn record(&mut self, entry: &TraceEntry) -> Result<(), Error> {
+        writeln!(self.writer, "{}", format_text(entry))?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// `cycle address: halfwords instruction | register changes | accesses`
+pub fn format_text(entry: &TraceEntry) -> String {
+    let raw = entry
+        .halfwords
+        .iter()
+        .map(|halfword| format!("{halfword:04x} "))
+        .collect::<String>();
+    let text = match entry.exception {
+        Some(exception) if entry.halfwords.is_empty() => format!("<{exception:?} exception>"),
+        Some(exception) => format!("{} <{exception:?} exception>", entry.text),
+        None => entry.text.clone(),
+    };
+
+    let mut line = format!(
+        "{:>10} {:08x}: {raw:<10} {:<28}",
+        entry.cycle,
+        entry.address,
+        text.replace('\t', " ")
+    );
+    for change in &entry.registers {
+        let _ = write!(
+            line,
+            " | {:?} {:#010x} -> {:#010x}",
+            change.register, change.old, change.new
+        );
+    }
+    for access in &entry.accesses {
+        let kind = match access.kind {
+            AccessKind::Read => "read",
+            AccessKind::Write => "write",
+        };
+        let _ = write!(
+            line,
+            " | {kind}{} [{:#010x}] {:#x}",
+            8 * access.size,
+            access.address,
+            access.value
+        );
+    }
+    line.trim_end().to_owned()
+}
+
+/// One JSON object per line, for scripts
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> TraceSink for JsonLinesSink<W> {
+    fn record(&mut self, entry: &TraceEntry) -> Result<(), Error> {
+        writeln!(self.writer, "{}", format_json(entry))?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The entry as a single line JSON object, numbers are kept as numbers
+pub fn format_json(entry: &TraceEntry) -> String {
+    let list = |items: Vec<String>| format!("[{}]", items.join(","));
+    let halfwords = list(entry.halfwords.iter().map(u16::to_string).collect());
+    let exception = entry.exception.map_or("null".to_owned(), |exception| {
+        json_string(&format!("{exception:?}"))
+    });
+    let registers = list(
+        entry
+            .registers
+            .iter()
+            .map(|change| {
+                format!(
+                    r#"{{"register":{},"old":{},"new":{}}}"#,
+                    json_string(&format!("{:?}", change.register)),
+                    change.old,
+                    change.new
+                )
+            })
+            .collect(),
+    );
+    let accesses = list(
+        entry
+            .accesses
+            .iter()
+            .map(|access| {
+                let kind = match access.kind {
+                    AccessKind::Read => "read",
+                    AccessKind::Write => "write",
+                };
+                format!(
+                    r#"{{"kind":"{kind}","address":{},"size":{},"value":{}}}"#,
+                    access.address, access.size, access.value
+                )
+            })
+            .collect(),
+    );
+
+    format!(
+        r#"{{"address":{},"halfwords":{halfwords},"text":{},"exception":{exception},"registers":{registers},"accesses":{accesses},"cycles":{},"cycle":{}}}"#,
+        entry.address,
+        json_string(&entry.text),
+        entry.cycles,
+        entry.cycle
+    )
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", u32::from(c));
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Bincode encoded entries, for large runs
+pub struct BinarySink<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl<W: Write> BinarySink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header_written: false,
+        }
+    }
+}
+
+impl<W: Write> TraceSink for BinarySink<W> {
+    fn record(&mut self, entry: &TraceEntry) -> Result<(), Error> {
+        if !self.header_written {
+            self.writer.write_all(TRACE_MAGIC)?;
+            self.writer.write_all(&TRACE_VERSION.to_le_bytes())?;
+            self.header_written = true;
+        }
+        bincode::serialize_into(&mut self.writer, entry)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Decode the entries written by a `BinarySink`
+pub fn read_binary(bytes: &[u8]) -> Result<Vec<TraceEntry>, Error> {
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let payload = bytes
+        .strip_prefix(TRACE_MAGIC)
+        .ok_or(Error::Trace("not a trace"))?;
+    let (version, mut entries) = payload
+        .split_first_chunk::<4>()
+        .ok_or(Error::Trace("truncated header"))?;
+    if u32::from_le_bytes(*version) != TRACE_VERSION {
+        return Err(Error::Trace("unsupported version"));
+    }
+
+    let mut decoded = Vec::new();
+    while !entries.is_empty() {
+        decoded.push(bincode::deserialize_from(&mut entries)?);
+    }
+    Ok(decoded)
+}
+
+/// Records the steps of the core into a sink, see `Armv6M::set_tracer`.
+///
+/// In ring buffer mode only the last entries are kept, they are written to the sink
+/// when a fault happens or when `dump` is called.
+pub struct Tracer {
+    sink: Box<dyn TraceSink>,
+    /// Addresses of the traced instructions, everything is traced when empty
+    filters: Vec<RangeInclusive<u32>>,
+    /// Capacity and content of the ring buffer
+    ring: Option<(usize, VecDeque<TraceEntry>)>,
+}
+
+impl Tracer {
+    pub fn new(sink: impl TraceSink + 'static) -> Self {
+        Self {
+            sink: Box::new(sink),
+            filters: Vec::new(),
+            ring: None,
+        }
+    }
+
+    /// Only trace the instructions in `start..=end`, can be called for several ranges
+    pub fn with_filter(mut self, start: u32, end: u32) -> Self {
+        self.filters.push(start..=end);
+        self
+    }
+
+    /// Keep the last `capacity` entries instead of writing them as they come
+    pub fn with_ring_buffer(mut self, capacity: usize) -> Self {
+        self.ring = Some((capacity, VecDeque::with_capacity(capacity)));
+        self
+    }
+
+    /// Whether the instruction at `address` passes the filters
+    pub fn traces(&self, address: u32) -> bool {
+        self.filters.is_empty() || self.filters.iter().any(|range| range.contains(&address))
+    }
+
+    pub fn record(&mut self, entry: TraceEntry) -> Result<(), Error> {
+        match &mut self.ring {
+            Some((capacity, entries)) => {
+                if entries.len() == *capacity {
+                    entries.pop_front();
+                }
+                if *capacity > 0 {
+                    entries.push_back(entry);
+                }
+                Ok(())
+            }
+            None => self.sink.record(&entry),
+        }
+    }
+
+    /// Entries of the ring buffer, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> + '_ {
+        self.ring.iter().flat_map(|(_, entries)| entries)
+    }
+
+    /// Write the ring buffer to the sink and empty it
+    pub fn dump(&mut self) -> Result<(), Error> {
+        if let Some((_, entries)) = &mut self.ring {
+            for entry in entries.drain(..) {
+                self.sink.record(&entry)?;
+            }
+        }
+        self.sink.flush()
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.sink.flush()
+    }
+}
@@ -0,0 +1,760 @@
+//! Variables of the DWARF `.debug_info`: the globals and the parameters and locals of each
+//! function, with their types, to show them as source level values like `counter = 42`.
+//!
+//! The location expressions are evaluated against the current frame, so they can be relative
+//! to SP, to the frame pointer R7, to the frame base of the function or to its CFA.
+//! Values split in several pieces and the bits of bit fields are not supported.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use gimli::{
+    AttributeValue, DebuggingInformationEntry, DwAt, EndianSlice, EntriesTreeNode,
+    EvaluationResult, UnitOffset,
+};
+
+use crate::backtrace;
+use crate::error::Error;
+use crate::Armv6M;
+
+/// Elements of an array shown before the rest is elided
+pub const MAX_ELEMENTS: usize = 64;
+/// Bytes read for a value, the larger ones are truncated
+pub const MAX_SIZE: u32 = 0x1_0000;
+
+type Slice<'a> = EndianSlice<'a, gimli::LittleEndian>;
+
+/// How the bytes of a base type are interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Signed,
+    Unsigned,
+    SignedChar,
+    UnsignedChar,
+    Boolean,
+    Float,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Base {
+        name: String,
+        size: u32,
+        encoding: Encoding,
+    },
+    /// Pointers and references, with the name of the type they point to
+    Pointer {
+        name: String,
+    },
+    /// Structures, classes and unions
+    Structure {
+        name: String,
+        size: u32,
+        members: Vec<Member>,
+    },
+    Array {
+        element: Arc<Type>,
+        count: u32,
+    },
+    Enumeration {
+        name: String,
+        size: u32,
+        values: Vec<(String, i64)>,
+    },
+    /// Typedefs and qualified types
+    Alias {
+        name: String,
+        target: Arc<Type>,
+    },
+    /// `void`, functions and the other types without a value to show
+    Opaque {
+        name: String,
+        size: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    /// Offset in bytes from the start of the structure
+    pub offset: u32,
+    pub ty: Arc<Type>,
+}
+
+impl Type {
+    pub fn name(&self) -> String {
+        match self {
+            Type::Base { name, .. }
+            | Type::Pointer { name }
+            | Type::Structure { name, .. }
+            | Type::Enumeration { name, .. }
+            | Type::Alias { name, .. }
+            | Type::Opaque { name, .. } => name.clone(),
+            Type::Array { element, count } => format!("{}[{count}]", element.name()),
+        }
+    }
+
+    /// Size in bytes
+    pub fn size(&self) -> u32 {
+        match self {
+            Type::Base { size, .. }
+            | Type::Structure { size, .. }
+            | Type::Enumeration { size, .. }
+            | Type::Opaque { size, .. } => *size,
+            Type::Pointer { .. } => 4,
+            Type::Array { element, count } => element.size().saturating_mul(*count),
+            Type::Alias { target, .. } => target.size(),
+        }
+    }
+
+    /// Write the value stored in `bytes`, the missing bytes read as zeros
+    fn format(&self, bytes: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = bytes
+            .iter()
+            .take(8)
+            .rev()
+            .fold(0u64, |value, byte| value << 8 | u64::from(*byte));
+        match self {
+            Type::Base { size, encoding, .. } => match encoding {
+                Encoding::Signed => write!(f, "{}", sign_extend(raw, *size)),
+                Encoding::Unsigned => write!(f, "{raw}"),
+                Encoding::SignedChar | Encoding::UnsignedChar => {
+                    if *encoding == Encoding::SignedChar {
+                        write!(f, "{}", sign_extend(raw, *size))?;
+                    } else {
+                        write!(f, "{raw}")?;
+                    }
+                    if (0x20..0x7F).contains(&raw) {
+                        write!(f, " '{}'", char::from(raw as u8))?;
+                    }
+                    Ok(())
+                }
+                Encoding::Boolean => write!(f, "{}", raw != 0),
+                Encoding::Float if *size == 4 => write!(f, "{}", f32::from_bits(raw as u32)),
+                Encoding::Float if *size == 8 => write!(f, "{}", f64::from_bits(raw)),
+                Encoding::Float => write!(f, "{raw:#x}"),
+            },
+            Type::Pointer { .. } => write!(f, "{raw:#010x}"),
+            Type::Structure { members, .. } => {
+                if members.is_empty() {
+                    return f.write_str("{}");
+                }
+                f.write_str("{")?;
+                for (n, member) in members.iter().enumerate() {
+                    let separator = if n == 0 { " " } else { ", " };
+                    write!(f, "{separator}{} = ", member.name)?;
+                    member
+                        .ty
+                        .format(field(bytes, member.offset, member.ty.size()), f)?;
+                }
+                f.write_str(" }")
+            }
+            Type::Array { element, count } => {
+                if *count == 0 {
+                    return f.write_str("{}");
+                }
+                f.write_str("{")?;
+                let size = element.size();
+                for n in 0..(*count).min(MAX_ELEMENTS as u32) {
+                    let separator = if n == 0 { " " } else { ", " };
+                    f.write_str(separator)?;
+                    element.format(field(bytes, n.saturating_mul(size), size), f)?;
+                }
+                if *count as usize > MAX_ELEMENTS {
+                    f.write_str(", ...")?;
+                }
+                f.write_str(" }")
+            }
+            Type::Enumeration { size, values, .. } => {
+                let value = sign_extend(raw, *size);
+                match values.iter().find(|(_, enumerator)| *enumerator == value) {
+                    Some((name, _)) => f.write_str(name),
+                    None => write!(f, "{value}"),
+                }
+            }
+            Type::Alias { target, .. } => target.format(bytes, f),
+            Type::Opaque { name, .. } => write!(f, "<{name}>"),
+        }
+    }
+}
+
+/// Bytes of a member or an element, empty past the end of the value
+fn field(bytes: &[u8], offset: u32, size: u32) -> &[u8] {
+    let start = (offset as usize).min(bytes.len());
+    let end = (offset.saturating_add(size) as usize).min(bytes.len());
+    &bytes[start..end]
+}
+
+fn sign_extend(raw: u64, size: u32) -> i64 {
+    let shift = 64 - 8 * size.clamp(1, 8);
+    ((raw << shift) as i64) >> shift
+}
+
+/// A global, or a parameter or local of a function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    pub name: String,
+    pub ty: Arc<Type>,
+    pub parameter: bool,
+    /// Addresses of the lexical block declaring it, empty for the whole function
+    pub scope: Vec<Range<u32>>,
+    /// Location expressions with the addresses they are valid at
+    locations: Vec<(Range<u32>, Vec<u8>)>,
+    encoding: gimli::Encoding,
+}
+
+impl Variable {
+    fn in_scope(&self, pc: u32) -> bool {
+        self.scope.is_empty() || self.scope.iter().any(|range| range.contains(&pc))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    pub ranges: Vec<Range<u32>>,
+    /// Parameters and locals in declaration order
+    pub variables: Vec<Variable>,
+    /// Expression of `DW_OP_fbreg` locations, empty when the function has no frame base
+    frame_base: Vec<u8>,
+    encoding: gimli::Encoding,
+}
+
+impl Function {
+    pub fn contains(&self, address: u32) -> bool {
+        self.ranges.iter().any(|range| range.contains(&address))
+    }
+}
+
+/// Value of a variable, read from the memory or the registers of the core
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Value {
+    pub name: String,
+    pub ty: Arc<Type>,
+    /// Where it is stored, `None` for a register or a computed value
+    pub address: Option<u32>,
+    /// Little-endian bytes
+    pub bytes: Vec<u8>,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = ", self.name)?;
+        self.ty.format(&self.bytes, f)
+    }
+}
+
+/// Globals and functions of every compilation unit
+pub(crate) fn parse(
+    dwarf: &gimli::Dwarf<Slice<'_>>,
+) -> Result<(Vec<Variable>, Vec<Function>), Error> {
+    let mut globals = Vec::new();
+    let mut functions = Vec::new();
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut parser = UnitParser {
+            dwarf,
+            unit: &unit,
+            types: HashMap::new(),
+        };
+        let mut tree = unit.entries_tree(None)?;
+        parser.walk(tree.root()?, "", &mut globals, &mut functions)?;
+    }
+    functions.sort_by_key(|function: &Function| function.ranges.first().map(|range| range.start));
+    Ok((globals, functions))
+}
+
+struct UnitParser<'a, 'u> {
+    dwarf: &'a gimli::Dwarf<Slice<'a>>,
+    unit: &'u gimli::Unit<Slice<'a>>,
+    /// Types already resolved, by offset of their entry
+    types: HashMap<UnitOffset, Arc<Type>>,
+}
+
+impl<'a> UnitParser<'a, '_> {
+    /// Globals and functions under `node`, the names in namespaces are prefixed with them
+    fn walk(
+        &mut self,
+        node: EntriesTreeNode<'_, '_, '_, Slice<'a>>,
+        prefix: &str,
+        globals: &mut Vec<Variable>,
+        functions: &mut Vec<Function>,
+    ) -> Result<(), Error> {
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            match child.entry().tag() {
+                gimli::DW_TAG_namespace => {
+                    let name = self
+                        .name(child.entry())?
+                        .unwrap_or_else(|| "(anonymous namespace)".to_owned());
+                    self.walk(child, &format!("{prefix}{name}::"), globals, functions)?;
+                }
+                gimli::DW_TAG_variable => {
+                    if let Some(mut variable) = self.variable(child.entry(), &[])? {
+                        variable.name.insert_str(0, prefix);
+                        globals.push(variable);
+                    }
+                }
+                gimli::DW_TAG_subprogram => {
+                    if let Some(function) = self.function(child, prefix)? {
+                        functions.push(function);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Attribute of the entry, or of the declaration or abstract instance it completes
+    fn attribute(
+        &self,
+        entry: &DebuggingInformationEntry<'_, '_, Slice<'a>>,
+        name: DwAt,
+    ) -> Result<Option<AttributeValue<Slice<'a>>>, Error> {
+        if let Some(value) = entry.attr_value(name)? {
+            return Ok(Some(value));
+        }
+        for origin in [gimli::DW_AT_specification, gimli::DW_AT_abstract_origin] {
+            if let Some(AttributeValue::UnitRef(offset)) = entry.attr_value(origin)? {
+                // NOTE: An origin is never itself completed by another one
+                return Ok(self.unit.entry(offset)?.attr_value(name)?);
+            }
+        }
+        Ok(None)
+    }
+
+    fn name(
+        &self,
+        entry: &DebuggingInformationEntry<'_, '_, Slice<'a>>,
+    ) -> Result<Option<String>, Error> {
+        match self.attribute(entry, gimli::DW_AT_name)? {
+            Some(value) => Ok(Some(
+                self.dwarf
+                    .attr_string(self.unit, value)?
+                    .to_string_lossy()
+                    .into_owned(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Type of the entry, `void` when it has none
+    fn type_of(
+        &mut self,
+        entry: &DebuggingInformationEntry<'_, '_, Slice<'a>>,
+    ) -> Result<Arc<Type>, Error> {
+        match self.attribute(entry, gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(offset)) => self.resolve(offset),
+            _ => Ok(Arc::new(Type::Opaque {
+                name: "void".to_owned(),
+                size: 0,
+            })),
+        }
+    }
+
+    fn variable(
+        &mut self,
+        entry: &DebuggingInformationEntry<'_, '_, Slice<'a>>,
+        scope: &[Range<u32>],
+    ) -> Result<Option<Variable>, Error> {
+        // NOTE: Declarations and optimized out variables have no location
+        let Some(location) = entry.attr_value(gimli::DW_AT_location)? else {
+            return Ok(None);
+        };
+        let Some(name) = self.name(entry)? else {
+            return Ok(None);
+        };
+
+        let locations = match location {
+            AttributeValue::Exprloc(expression) => vec![(0..u32::MAX, expression.0.to_vec())],
+            location => {
+                let Some(mut list) = self.dwarf.attr_locations(self.unit, location)? else {
+                    return Ok(None);
+                };
+                let mut locations = Vec::new();
+                while let Some(entry) = list.next()? {
+                    locations.push((
+                        entry.range.begin as u32..entry.range.end as u32,
+                        entry.data.0.to_vec(),
+                    ));
+                }
+                locations
+            }
+        };
+
+        Ok(Some(Variable {
+            name,
+            ty: self.type_of(entry)?,
+            parameter: entry.tag() == gimli::DW_TAG_formal_parameter,
+            scope: scope.to_vec(),
+            locations,
+            encoding: self.unit.encoding(),
+        }))
+    }
+
+    fn ranges(
+        &self,
+        entry: &DebuggingInformationEntry<'_, '_, Slice<'a>>,
+    ) -> Result<Vec<Range<u32>>, Error> {
+        let mut ranges = Vec::new();
+        let mut iter = self.dwarf.die_ranges(self.unit, entry)?;
+        while let Some(range) = iter.next()? {
+            ranges.push(range.begin as u32..range.end as u32);
+        }
+        Ok(ranges)
+    }
+
+    /// Function with code, the declarations and the inlined only ones are left out
+    fn function(
+        &mut self,
+        node: EntriesTreeNode<'_, '_, '_, Slice<'a>>,
+        prefix: &str,
+    ) -> Result<Option<Function>, Error> {
+        let entry = node.entry();
+        let ranges = self.ranges(entry)?;
+        if ranges.is_empty() {
+            return Ok(None);
+        }
+        let name = self.name(entry)?.unwrap_or_else(|| "??".to_owned());
+        let frame_base = match entry.attr_value(gimli::DW_AT_frame_base)? {
+            Some(AttributeValue::Exprloc(expression)) => expression.0.to_vec(),
+            _ => Vec::new(),
+        };
+
+        let mut variables = Vec::new();
+        self.locals(node, &[], &mut variables)?;
+        Ok(Some(Function {
+            name: format!("{prefix}{name}"),
+            ranges,
+            variables,
+            frame_base,
+            encoding: self.unit.encoding(),
+        }))
+    }
+
+    /// Parameters and locals of a function or of a lexical block covering `scope`
+    fn locals(
+        &mut self,
+        node: EntriesTreeNode<'_, '_, '_, Slice<'a>>,
+        scope: &[Range<u32>],
+        variables: &mut Vec<Variable>,
+    ) -> Result<(), Error> {
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            match child.entry().tag() {
+                gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable => {
+                    if let Some(variable) = self.variable(child.entry(), scope)? {
+                        variables.push(variable);
+                    }
+                }
+                gimli::DW_TAG_lexical_block => {
+                    let ranges = self.ranges(child.entry())?;
+                    let scope = if ranges.is_empty() {
+                        scope.to_vec()
+                    } else {
+                        ranges
+                    };
+                    self.locals(child, &scope, variables)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the type at `offset`, without resolving what pointers point to
+    fn type_name(&self, offset: UnitOffset) -> Result<String, Error> {
+        let entry = self.unit.entry(offset)?;
+        let target = |parser: &Self| match entry.attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(target)) => parser.type_name(target),
+            _ => Ok("void".to_owned()),
+        };
+        Ok(match entry.tag() {
+            gimli::DW_TAG_pointer_type => format!("{} *", target(self)?),
+            gimli::DW_TAG_reference_type | gimli::DW_TAG_rvalue_reference_type => {
+                format!("{} &", target(self)?)
+            }
+            gimli::DW_TAG_const_type => format!("const {}", target(self)?),
+            gimli::DW_TAG_volatile_type => format!("volatile {}", target(self)?),
+            gimli::DW_TAG_array_type => format!("{}[]", target(self)?),
+            gimli::DW_TAG_subroutine_type => "function".to_owned(),
+            _ => self
+                .name(&entry)?
+                .unwrap_or_else(|| "<anonymous>".to_owned()),
+        })
+    }
+
+    fn resolve(&mut self, offset: UnitOffset) -> Result<Arc<Type>, Error> {
+        if let Some(ty) = self.types.get(&offset) {
+            return Ok(ty.clone());
+        }
+
+        let unit = self.unit;
+        let entry = unit.entry(offset)?;
+        let name = self.type_name(offset)?;
+        let size = entry
+            .attr_value(gimli::DW_AT_byte_size)?
+            .and_then(|size| size.udata_value())
+            .map_or(0, |size| size as u32);
+
+        let ty = match entry.tag() {
+            gimli::DW_TAG_base_type => {
+                let encoding = match entry.attr_value(gimli::DW_AT_encoding)? {
+                    Some(AttributeValue::Encoding(gimli::DW_ATE_signed)) => Encoding::Signed,
+                    Some(AttributeValue::Encoding(gimli::DW_ATE_signed_char)) => {
+                        Encoding::SignedChar
+                    }
+                    Some(AttributeValue::Encoding(
+                        gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF,
+                    )) => Encoding::UnsignedChar,
+                    Some(AttributeValue::Encoding(gimli::DW_ATE_boolean)) => Encoding::Boolean,
+                    Some(AttributeValue::Encoding(gimli::DW_ATE_float)) => Encoding::Float,
+                    _ => Encoding::Unsigned,
+                };
+                Type::Base {
+                    name,
+                    size,
+                    encoding,
+                }
+            }
+            gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type => Type::Pointer { name },
+            gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type | gimli::DW_TAG_union_type => {
+                let mut members = Vec::new();
+                let mut tree = unit.entries_tree(Some(offset))?;
+                let mut children = tree.root()?.children();
+                while let Some(child) = children.next()? {
+                    let member = child.entry();
+                    // NOTE: Static members are declarations, stored elsewhere
+                    if !matches!(
+                        member.tag(),
+                        gimli::DW_TAG_member | gimli::DW_TAG_inheritance
+                    ) || member.attr_value(gimli::DW_AT_declaration)?.is_some()
+                    {
+                        continue;
+                    }
+                    let ty = self.type_of(member)?;
+                    let offset = member
+                        .attr_value(gimli::DW_AT_data_member_location)?
+                        .and_then(|offset| offset.udata_value())
+                        .map_or(0, |offset| offset as u32);
+                    let name = match self.name(member)? {
+                        Some(name) => name,
+                        None => ty.name(),
+                    };
+                    members.push(Member { name, offset, ty });
+                }
+                Type::Structure {
+                    name,
+                    size,
+                    members,
+                }
+            }
+            gimli::DW_TAG_array_type => {
+                let mut counts = Vec::new();
+                let mut tree = unit.entries_tree(Some(offset))?;
+                let mut children = tree.root()?.children();
+                while let Some(child) = children.next()? {
+                    let subrange = child.entry();
+                    if subrange.tag() != gimli::DW_TAG_subrange_type {
+                        continue;
+                    }
+                    let count = match subrange.attr_value(gimli::DW_AT_count)? {
+                        Some(count) => count.udata_value(),
+                        None => subrange
+                            .attr_value(gimli::DW_AT_upper_bound)?
+                            .and_then(|bound| bound.udata_value())
+                            .map(|bound| bound + 1),
+                    };
+                    counts.push(count.map_or(0, |count| count as u32));
+                }
+                // NOTE: The first subrange is the outermost dimension
+                let mut ty = self.type_of(&entry)?;
+                for count in counts.into_iter().rev() {
+                    ty = Arc::new(Type::Array { element: ty, count });
+                }
+                self.types.insert(offset, ty.clone());
+                return Ok(ty);
+            }
+            gimli::DW_TAG_enumeration_type => {
+                let mut values = Vec::new();
+                let mut tree = unit.entries_tree(Some(offset))?;
+                let mut children = tree.root()?.children();
+                while let Some(child) = children.next()? {
+                    let enumerator = child.entry();
+                    let value = match enumerator.attr_value(gimli::DW_AT_const_value)? {
+                        Some(AttributeValue::Sdata(value)) => Some(value),
+                        Some(value) => value.udata_value().map(|value| value as i64),
+                        None => None,
+                    };
+                    if let (Some(name), Some(value)) = (self.name(enumerator)?, value) {
+                        values.push((name, value));
+                    }
+                }
+                Type::Enumeration { name, size, values }
+            }
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type => Type::Alias {
+                name,
+                target: self.type_of(&entry)?,
+            },
+            _ => Type::Opaque { name, size },
+        };
+
+        let ty = Arc::new(ty);
+        self.types.insert(offset, ty.clone());
+        Ok(ty)
+    }
+}
+
+/// Where a location expression put a variable
+enum Place {
+    Address(u32),
+    Register(u16),
+    Value(u32),
+}
+
+/// Register of the current frame, by DWARF number
+fn register(core: &Armv6M, register: u16) -> Option<u32> {
+    match register {
+        0..=12 => Some(core.registers[usize::from(register)]),
+        13 => Some(core.get_sp()),
+        14 => Some(core.lr),
+        15 => Some(core.pc),
+        _ => None,
+    }
+}
+
+/// Canonical Frame Address of the current frame, the SP before the call
+fn call_frame_cfa(core: &Armv6M) -> Option<u32> {
+    let (rule, _) = backtrace::frame_rule(core, core.pc)?;
+    Some(register(core, u16::from(rule.cfa_register))?.wrapping_add_signed(rule.cfa_offset))
+}
+
+fn evaluate(
+    core: &Armv6M,
+    expression: &[u8],
+    encoding: gimli::Encoding,
+    frame_base: Option<u32>,
+) -> Option<Place> {
+    let mut evaluation =
+        gimli::Expression(EndianSlice::new(expression, gimli::LittleEndian)).evaluation(encoding);
+    let mut result = evaluation.evaluate().ok()?;
+    loop {
+        result = match result {
+            EvaluationResult::Complete => break,
+            EvaluationResult::RequiresMemory { address, size, .. } => {
+                let value = core.peek(address as u32, size)?;
+                evaluation.resume_with_memory(gimli::Value::Generic(value.into()))
+            }
+            EvaluationResult::RequiresRegister { register: id, .. } => {
+                let value = register(core, id.0)?;
+                evaluation.resume_with_register(gimli::Value::Generic(value.into()))
+            }
+            EvaluationResult::RequiresFrameBase => {
+                evaluation.resume_with_frame_base(frame_base?.into())
+            }
+            EvaluationResult::RequiresCallFrameCfa => {
+                evaluation.resume_with_call_frame_cfa(call_frame_cfa(core)?.into())
+            }
+            EvaluationResult::RequiresRelocatedAddress(address) => {
+                evaluation.resume_with_relocated_address(address)
+            }
+            _ => return None,
+        }
+        .ok()?;
+    }
+
+    match evaluation.result().first()?.location {
+        gimli::Location::Address { address } => Some(Place::Address(address as u32)),
+        gimli::Location::Register { register } => Some(Place::Register(register.0)),
+        gimli::Location::Value { value } => {
+            Some(Place::Value(value.to_u64(u64::from(u32::MAX)).ok()? as u32))
+        }
+        _ => None,
+    }
+}
+
+/// Address `DW_OP_fbreg` is relative to, a register location stands for its value
+fn frame_base(core: &Armv6M, function: &Function) -> Option<u32> {
+    match evaluate(core, &function.frame_base, function.encoding, None)? {
+        Place::Address(address) => Some(address),
+        Place::Register(id) => register(core, id),
+        Place::Value(value) => Some(value),
+    }
+}
+
+/// Read a variable at the current pc, `None` when its location is not known there
+fn read(core: &Armv6M, variable: &Variable, frame_base: Option<u32>) -> Option<Value> {
+    let (_, expression) = variable
+        .locations
+        .iter()
+        .find(|(range, _)| range.contains(&core.pc))?;
+    let size = variable.ty.size().min(MAX_SIZE);
+
+    let (address, word) = match evaluate(core, expression, variable.encoding, frame_base)? {
+        Place::Address(address) => (Some(address), None),
+        Place::Register(id) => (None, Some(register(core, id)?)),
+        Place::Value(value) => (None, Some(value)),
+    };
+    let bytes = match (address, word) {
+        (Some(address), _) => (0..size)
+            .map(|n| core.peek(address.wrapping_add(n), 1).map(|byte| byte as u8))
+            .collect::<Option<Vec<_>>>()?,
+        (None, word) => word?
+            .to_le_bytes()
+            .into_iter()
+            .take(size as usize)
+            .collect(),
+    };
+
+    Some(Value {
+        name: variable.name.clone(),
+        ty: variable.ty.clone(),
+        address,
+        bytes,
+    })
+}
+
+/// Parameters and locals of the function at the pc, in scope and with a known location
+pub(crate) fn locals(core: &Armv6M) -> Vec<Value> {
+    let Some(function) = core.debug_info.function(core.pc) else {
+        return Vec::new();
+    };
+    let base = frame_base(core, function);
+    function
+        .variables
+        .iter()
+        .filter(|variable| variable.in_scope(core.pc))
+        .filter_map(|variable| read(core, variable, base))
+        .collect()
+}
+
+/// Local named `name` of the function at the pc, or else the global. The innermost local wins.
+pub(crate) fn lookup(core: &Armv6M, name: &str) -> Option<Value> {
+    if let Some(function) = core.debug_info.function(core.pc) {
+        let local = function
+            .variables
+            .iter()
+            .rev()
+            .find(|variable| variable.name == name && variable.in_scope(core.pc));
+        if let Some(local) = local {
+            return read(core, local, frame_base(core, function));
+        }
+    }
+
+    let global = core
+        .debug_info
+        .globals()
+        .iter()
+        .find(|global| global.name == name)?;
+    read(core, global, None)
+}
@@ -0,0 +1,54 @@
+//! Data watchpoints, checked by the core on every access of the memory bus.
+
+use crate::memory::{Access, AccessKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Write,
+    Read,
+    Access,
+}
+
+/// Data watchpoint on `length` bytes starting at `address`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub kind: WatchKind,
+    pub address: u32,
+    pub length: u32,
+    /// Only trigger when the value read or written is this one
+    pub value: Option<u32>,
+}
+
+impl Watchpoint {
+    pub fn new(kind: WatchKind, address: u32, length: u32) -> Self {
+        Self {
+            kind,
+            address,
+            length,
+            value: None,
+        }
+    }
+
+    pub fn with_value(mut self, value: u32) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    pub fn matches(&self, access: &Access) -> bool {
+        let kind = match self.kind {
+            WatchKind::Write => access.kind == AccessKind::Write,
+            WatchKind::Read => access.kind == AccessKind::Read,
+            WatchKind::Access => true,
+        };
+
+        kind && self.overlaps(access.address, access.size)
+            && self.value.is_none_or(|value| value == access.value)
+    }
+
+    pub fn overlaps(&self, address: u32, size: u8) -> bool {
+        let start = u64::from(self.address);
+        let other = u64::from(address);
+
+        other < start + u64::from(self.length) && start < other + u64::from(size)
+    }
+}
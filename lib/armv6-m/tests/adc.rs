@@ -0,0 +1,213 @@
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::adc::{
+    ain, Adc, Analog, Input, Prescaling, Reference, ADC_IRQ, BUSY, CONFIG, CONVERSION, ENABLE,
+    EVENTS_END, RESULT, TASKS_START, TASKS_STOP,
+};
+use armv6_m::nrf51::INTENSET;
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Samples AIN4 in r6 at 10 bits with one-third prescaling against one third of the supply, as
+/// `analogRead()` of the micro:bit on pin 0
+fn program() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r0, =0x40007000",
+        "    ldr r4, =0x40007100",
+        "    ldr r5, =0x40007500",
+        "    movs r1, #1",
+        "    str r1, [r5]",
+        "    ldr r2, =0x106A",
+        "    str r2, [r5, #4]",
+        "    str r1, [r0]",
+        "wait:",
+        "    ldr r2, [r4]",
+        "    cmp r2, #0",
+        "    beq wait",
+        "    movs r2, #0",
+        "    str r2, [r4]",
+        "    ldr r6, [r5, #8]",
+        "done:",
+        "    bkpt #0",
+    )
+}
+
+fn sample(set: impl FnOnce(&Analog)) -> u32 {
+    let program = program();
+    let adc = Adc::new();
+    set(&adc.analog());
+    let mut machine = common::load(&program, vec![Box::new(adc)]);
+    common::run_to(&mut machine, &program, "done");
+    assert!(machine.cycles() > CONVERSION[2]);
+    machine.get_r6()
+}
+
+#[test]
+fn samples_the_voltage_set_by_the_host() {
+    let pin = ain(3).unwrap();
+    assert_eq!(pin, 4);
+    assert_eq!(sample(|_| {}), 0);
+    assert_eq!(sample(|analog| analog.set_voltage(pin, 1.0)), 310);
+    assert_eq!(sample(|analog| analog.set_voltage(pin, 3.3)), 1023);
+    assert_eq!(sample(|analog| analog.set_voltage(pin, 5.0)), 1023);
+    // NOTE: The reference follows the supply
+    assert_eq!(
+        sample(|analog| {
+            analog.set_supply(2.0);
+            analog.set_voltage(pin, 0.5);
+        }),
+        256
+    );
+    assert_eq!(sample(|analog| analog.set_voltage(0, 1.0)), 0);
+}
+
+#[test]
+fn converts_with_the_configured_resolution_and_reference() {
+    let mut adc = Adc::new();
+    let analog = adc.analog();
+    analog.set_voltage(2, 0.3);
+    adc.write(ENABLE, 4, 1);
+    adc.write(INTENSET, 4, 1);
+
+    // NOTE: 8 bits, AIN2 without prescaling against the band gap
+    adc.write(CONFIG, 4, 0x0400);
+    assert_eq!(adc.bits(), 8);
+    adc.write(TASKS_START, 4, 1);
+    assert_eq!(adc.read(BUSY, 4), 1);
+    assert_eq!(adc.next_event(), Some(CONVERSION[0]));
+    adc.tick(CONVERSION[0] - 1);
+    assert_eq!(adc.read(EVENTS_END, 4), 0);
+    adc.tick(1);
+    assert_eq!(adc.read(BUSY, 4), 0);
+    assert_eq!(adc.read(EVENTS_END, 4), 1);
+    assert_eq!(adc.interrupt(), Some(ADC_IRQ));
+    assert_eq!(adc.read(RESULT, 4), 64);
+
+    // NOTE: 9 bits, the supply with two-thirds prescaling against AREF1
+    adc.write(EVENTS_END, 4, 0);
+    analog.set_reference(1, 2.5);
+    adc.write(CONFIG, 4, 0x2_0035);
+    adc.write(TASKS_START, 4, 1);
+    adc.tick(CONVERSION[1]);
+    assert_eq!(adc.read(RESULT, 4), 450);
+
+    // NOTE: A stopped conversion gives no result, a disabled ADC does not start
+    adc.write(EVENTS_END, 4, 0);
+    adc.write(TASKS_START, 4, 1);
+    adc.write(TASKS_STOP, 4, 1);
+    adc.write(ENABLE, 4, 0);
+    adc.write(TASKS_START, 4, 1);
+    adc.tick(CONVERSION[2]);
+    assert_eq!(adc.read(EVENTS_END, 4), 0);
+    assert_eq!(adc.read(RESULT, 4), 450);
+}
+
+#[test]
+fn samples_a_waveform_at_the_end_of_the_conversion() {
+    let mut adc = Adc::new();
+    let analog = adc.analog();
+    // NOTE: A ramp of 1 V per ms
+    analog.set_waveform(0, |seconds| seconds * 1000.0);
+    adc.write(ENABLE, 4, 1);
+    adc.write(CONFIG, 4, 0x0102);
+
+    adc.tick(14_400 - CONVERSION[2]);
+    adc.write(TASKS_START, 4, 1);
+    adc.tick(CONVERSION[2]);
+    assert!((analog.voltage(0) - 0.9).abs() < 1e-9);
+    assert_eq!(adc.read(RESULT, 4), 767);
+
+    analog.set_voltage(0, 0.3);
+    adc.write(TASKS_START, 4, 1);
+    adc.tick(CONVERSION[2]);
+    assert_eq!(adc.read(RESULT, 4), 256);
+}
+
+#[test]
+fn decodes_the_configuration() {
+    let pin = |prescaling| Input::Pin { prescaling };
+    let supply = |prescaling| Input::Supply { prescaling };
+    let configurations = [
+        (
+            0x18,
+            8,
+            supply(Prescaling::OneThird),
+            Reference::BandGap,
+            None,
+        ),
+        (
+            0x106A,
+            10,
+            pin(Prescaling::OneThird),
+            Reference::SupplyOneThird,
+            Some(4),
+        ),
+        (
+            0x2_0035,
+            9,
+            supply(Prescaling::TwoThirds),
+            Reference::External(Some(1)),
+            None,
+        ),
+        (
+            0x0C4C,
+            8,
+            Input::Reserved,
+            Reference::SupplyOneHalf,
+            Some(2),
+        ),
+        (
+            0x0020,
+            8,
+            pin(Prescaling::None),
+            Reference::External(None),
+            None,
+        ),
+    ];
+    let mut adc = Adc::new();
+    for (config, bits, input, reference, ain) in configurations {
+        adc.write(CONFIG, 4, config);
+        assert_eq!(adc.bits(), bits, "{config:#x}");
+        assert_eq!(adc.input(), input, "{config:#x}");
+        assert_eq!(adc.reference(), reference, "{config:#x}");
+        assert_eq!(adc.pin(), ain, "{config:#x}");
+    }
+
+    // NOTE: Bit 7 and the bits above EXTREFSEL are reserved
+    adc.write(CONFIG, 4, u32::MAX);
+    assert_eq!(adc.read(CONFIG, 4), 0x3_FF7F);
+}
+
+#[test]
+fn resets_the_registers_and_keeps_the_inputs() {
+    let mut adc = Adc::new();
+    let analog = adc.analog();
+    let config = adc.read(CONFIG, 4);
+    analog.set_voltage(2, 1.5);
+    adc.write(ENABLE, 4, 1);
+    adc.write(CONFIG, 4, 0x0402);
+    adc.write(TASKS_START, 4, 1);
+    adc.tick(CONVERSION[2]);
+    assert_ne!(adc.read(RESULT, 4), 0);
+
+    adc.reset(ResetCause::SoftReset);
+    assert_eq!(adc.read(ENABLE, 4), 0);
+    assert_eq!(adc.read(CONFIG, 4), config);
+    assert_eq!(adc.read(RESULT, 4), 0);
+    assert_eq!(adc.read(EVENTS_END, 4), 0);
+    assert!((analog.voltage(2) - 1.5).abs() < f64::EPSILON);
+}
+
+#[test]
+#[should_panic(expected = "AIN8 does not exist")]
+fn rejects_inputs_past_ain7() {
+    Adc::new().analog().set_voltage(8, 1.0);
+}
+
+#[test]
+#[should_panic(expected = "AREF2 does not exist")]
+fn rejects_references_past_aref1() {
+    Adc::new().analog().set_reference(2, 1.0);
+}
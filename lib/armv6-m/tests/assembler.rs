@@ -0,0 +1,181 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::assembler::assemble;
+use armv6_m::disassembler::{Context, Disassemble};
+use armv6_m::error::Error;
+use armv6_m::instructions::parse_instruction;
+use armv6_m::{thumb, Armv6M};
+
+/// Encodings produced by GNU as for the same source
+#[test]
+fn encodes_like_gnu_as() {
+    let program = thumb!(
+        "start:",
+        "    adds r1, r2, #3",
+        "    add r8, r1",
+        "    add r2, sp, r2",
+        "    add sp, #8",
+        "    asrs r1, r2, #32",
+        "    beq start",
+        "    bl start",
+        "    cmp r8, r2",
+        "    ldm r0, {r0, r2}",
+        "    ldr r1, =0x20000000",
+        "    mrs r0, primask",
+        "    msr apsr_nzcvq, r0",
+        "    muls r1, r2, r1",
+        "    pop {r4, pc}",
+        "    push {r4, lr}",
+        "    sub sp, #8",
+        "    udf.w #0x1234",
+        "    dmb sy",
+    );
+
+    assert_eq!(
+        program.halfwords(),
+        [
+            0x1cd1, 0x4488, 0x446a, 0xb002, 0x1011, 0xd0f9, 0xf7ff, 0xfff8, 0x4590, 0xc805, 0x4906,
+            0xf3ef, 0x8010, 0xf380, 0x8800, 0x4351, 0xbd10, 0xb510, 0xb082, 0xf7f1, 0xa234, 0xf3bf,
+            0x8f5f, 0x0000, 0x0000, 0x2000,
+        ]
+    );
+}
+
+/// Each line decodes to an instruction disassembled as the same line
+#[test]
+fn round_trips_through_the_disassembler() {
+    let source = [
+        "adcs r1, r2",
+        "adds r1, r2, #7",
+        "adds r3, #200",
+        "adds r1, r2, r3",
+        "add r8, r1",
+        "add r1, sp, #16",
+        "add sp, #8",
+        "add r2, sp, r2",
+        "add sp, r3",
+        "ands r1, r2",
+        "asrs r1, r2, #3",
+        "bics r1, r2",
+        "bkpt 0x0012",
+        "blx r3",
+        "bx lr",
+        "cmn r1, r2",
+        "cmp r1, #5",
+        "cmp r1, r2",
+        "cpsid i",
+        "cpsie i",
+        "dsb sy",
+        "eors r1, r2",
+        "isb sy",
+        "ldmia r0!, {r1, r2}",
+        "ldr r1, [r2, #4]",
+        "ldr r1, [sp, #8]",
+        "ldr r1, [r2, r3]",
+        "ldrb r1, [r2, #5]",
+        "ldrh r1, [r2, #6]",
+        "ldrsb r1, [r2, r3]",
+        "ldrsh r1, [r2, r3]",
+        "lsls r1, r2, #3",
+        "lsrs r1, r2",
+        "movs r1, #7",
+        "mov r8, r1",
+        "movs r1, r2",
+        "mrs r0, PRIMASK",
+        "msr CONTROL, r1",
+        "muls r1, r2",
+        "mvns r1, r2",
+        "negs r1, r2",
+        "nop",
+        "orrs r1, r2",
+        "pop {r4, r5, pc}",
+        "push {r0, lr}",
+        "rev r1, r2",
+        "rev16 r1, r2",
+        "revsh r1, r2",
+        "rors r1, r2",
+        "sbcs r1, r2",
+        "sev",
+        "stmia r0!, {r1, r2}",
+        "str r1, [r2, r3]",
+        "strb r1, [r2, #5]",
+        "strh r1, [r2, r3]",
+        "subs r1, r2, #3",
+        "subs r1, #200",
+        "subs r1, r2, r3",
+        "sub sp, #8",
+        "svc 10",
+        "sxtb r1, r2",
+        "sxth r1, r2",
+        "tst r1, r2",
+        "udf #3",
+        "udf.w #4660",
+        "uxtb r1, r2",
+        "uxth r1, r2",
+        "wfe",
+        "wfi",
+        "yield",
+    ];
+
+    for line in source {
+        let program = assemble(line, 0).unwrap();
+        let bytes = program
+            .halfwords()
+            .iter()
+            .flat_map(|halfword| halfword.to_be_bytes())
+            .collect::<Vec<_>>();
+        let (rest, instruction) = parse_instruction(&bytes).unwrap();
+        assert!(rest.is_empty(), "{line}");
+        let text = instruction.disassemble(&Context::default());
+        assert_eq!(text.replace('\t', " "), line);
+    }
+}
+
+#[test]
+fn runs_an_assembled_program() {
+    let program = thumb!(
+        "vectors:",
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    movs r0, #0",
+        "    movs r1, #10",
+        "loop:",
+        "    adds r0, r0, r1",
+        "    subs r1, #1",
+        "    bne loop",
+        "    ldr r2, =result",
+        "    str r0, [r2]",
+        "done:",
+        "    bkpt #0",
+        "    .ltorg",
+        "result:",
+        "    .word 0",
+    );
+
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    let stop = machine.run().unwrap();
+
+    let result = program.label("result").unwrap();
+    assert_eq!(stop, StopReason::Breakpoint(program.label("done").unwrap()));
+    assert_eq!(machine.get_r0(), 55);
+    assert_eq!(machine.get_memory().read_u32(result), 55);
+}
+
+#[test]
+fn reports_the_line_of_errors() {
+    let errors = [
+        ("nop\n    b nowhere", 2),
+        ("movs r0, #256", 1),
+        ("ldr r1, [r2, #3]", 1),
+        ("\n\nmov r0, r1, r2", 3),
+        ("loop:\nloop:", 2),
+    ];
+
+    for (source, expected) in errors {
+        match assemble(source, 0) {
+            Err(Error::Assembly { line, .. }) => assert_eq!(line, expected, "{source}"),
+            other => panic!("{source} assembled to {other:?}"),
+        }
+    }
+}
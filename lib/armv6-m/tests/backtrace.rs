@@ -0,0 +1,118 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::backtrace::Unwind;
+use armv6_m::debug_info::{FrameRule, Location};
+use armv6_m::structure::Exception;
+use armv6_m::Armv6M;
+
+/// Built from `fixtures/fault.s`, see the commands at its top
+const FAULT_ELF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/fault.elf");
+
+/// Stopped on the BKPT of the HardFault handler
+fn faulted() -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_file(FAULT_ELF).unwrap();
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(0x3C));
+    machine
+}
+
+#[test]
+fn unwinds_through_the_exception_frame() {
+    let machine = faulted();
+    let backtrace = machine.backtrace();
+
+    let frames = backtrace
+        .frames
+        .iter()
+        .map(|frame| (frame.pc, frame.unwind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        frames,
+        [
+            (0x3C, Some(Unwind::PrologueScan)),
+            (0x34, Some(Unwind::PrologueScan)),
+            (0x2A, Some(Unwind::Exidx)),
+            (0x1E, Some(Unwind::DebugFrame)),
+            (0x14, None),
+        ]
+    );
+
+    let stacked = backtrace.frames[1].exception.unwrap();
+    assert_eq!(stacked.exception, Some(Exception::HardFault));
+    assert_eq!((stacked.r0, stacked.lr, stacked.pc), (1, 0x2B, 0x34));
+    assert_eq!(stacked.address, backtrace.frames[0].sp);
+    assert_eq!(backtrace.frames[1].sp, stacked.address + 0x20);
+    // NOTE: leaf pushed 3 registers and reserved 4 bytes, middle pushed 2 and reserved 8
+    assert_eq!(backtrace.frames[2].sp, backtrace.frames[1].sp + 16);
+    assert_eq!(backtrace.frames[3].sp, backtrace.frames[2].sp + 16);
+    assert_eq!(backtrace.frames[4].sp, 0x2000_1000);
+}
+
+#[test]
+fn symbolizes_the_frames() {
+    let machine = faulted();
+
+    assert_eq!(
+        machine.backtrace().to_string(),
+        "#0  0x0000003c in hard_fault at fault.s:69\n\
+         #1  0x00000034 in leaf+0x6 at fault.s:57\n    \
+         <HardFault exception> r0 0x00000001 r1 0x00000000 r2 0x00000000 r3 0x00000000 \
+         r12 0x00000000 lr 0x0000002b pc 0x00000034 xpsr 0x01000000\n\
+         #2  0x0000002a in middle+0xa at fault.s:46\n\
+         #3  0x0000001e in main+0x8 at fault.s:33\n\
+         #4  0x00000014 in reset+0x4 at fault.s:21\n"
+    );
+}
+
+#[test]
+fn reads_the_unwinding_information() {
+    let machine = faulted();
+    let debug_info = machine.debug_info();
+
+    let mut rule = debug_info.debug_frame_rule(0x1A).unwrap();
+    rule.saved.sort_unstable();
+    assert_eq!(
+        rule,
+        FrameRule {
+            cfa_register: 13,
+            cfa_offset: 8,
+            saved: vec![(7, -8), (14, -4)],
+        }
+    );
+    assert_eq!(debug_info.debug_frame_rule(0x16).unwrap().cfa_offset, 0);
+    assert_eq!(debug_info.debug_frame_rule(0x20), None);
+
+    assert_eq!(
+        debug_info.exidx_rule(0x2A),
+        Some((
+            0x20,
+            Some(&FrameRule {
+                cfa_register: 13,
+                cfa_offset: 16,
+                saved: vec![(4, -8), (14, -4)],
+            })
+        ))
+    );
+    assert_eq!(debug_info.exidx_rule(0x40), Some((0x40, None)));
+
+    assert_eq!(
+        debug_info.location(0x34),
+        Some(&Location {
+            file: "fault.s".to_owned(),
+            line: 57,
+        })
+    );
+}
+
+#[test]
+fn stops_at_the_current_frame_without_debug_information() {
+    let mut code = vec![0; 0x40];
+    faulted().get_memory().read_bytes(0, &mut code);
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&code).unwrap();
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(0x3C));
+
+    let backtrace = machine.backtrace();
+    assert_eq!(backtrace.frames.len(), 1);
+    assert_eq!(backtrace.frames[0].function, None);
+    assert_eq!(backtrace.to_string(), "#0  0x0000003c\n");
+}
@@ -0,0 +1,72 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::{thumb, Armv6M};
+
+fn run(program: &Program, block_cache: bool) -> (Armv6M, StopReason) {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine.set_block_cache(block_cache);
+    let stop = machine.run().unwrap();
+    (machine, stop)
+}
+
+#[test]
+fn executes_the_instructions_written_over_a_cached_block() {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    movs r5, #0",
+        "patched:",
+        "    movs r0, #1",
+        "    cmp r5, #0",
+        "    bne done",
+        "    movs r5, #1",
+        "    ldr r1, =patched",
+        "    ldr r2, =0x2002",
+        "    strh r2, [r1]",
+        "    b patched",
+        "done:",
+        "    bkpt #0",
+    );
+
+    let (machine, stop) = run(&program, true);
+
+    assert_eq!(stop, StopReason::Breakpoint(program.label("done").unwrap()));
+    assert_eq!(machine.get_r0(), 2);
+    assert!(machine.block_cache_stats().invalidations > 0);
+}
+
+#[test]
+fn runs_loops_from_the_cache() {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    movs r0, #0",
+        "    movs r1, #100",
+        "loop:",
+        "    adds r0, r0, r1",
+        "    subs r1, #1",
+        "    bne loop",
+        "    bkpt #0",
+    );
+
+    let (cached, stop) = run(&program, true);
+    let (uncached, _) = run(&program, false);
+
+    assert_eq!(
+        stop,
+        StopReason::Breakpoint(program.label("loop").unwrap() + 6)
+    );
+    assert_eq!(cached.get_r0(), 5050);
+    assert_eq!(
+        cached.get_mutations_history(),
+        uncached.get_mutations_history()
+    );
+
+    let stats = cached.block_cache_stats();
+    assert_eq!(stats.misses, 3);
+    assert_eq!(stats.hits, 98);
+    assert_eq!(uncached.block_cache_stats().hits, 0);
+}
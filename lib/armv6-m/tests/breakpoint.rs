@@ -0,0 +1,142 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::breakpoint::Breakpoint;
+use armv6_m::expression::Expression;
+use armv6_m::{thumb, Armv6M};
+
+fn program() -> Program {
+    thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    ldr r0, =0x20000000",
+        "    movs r1, #0",
+        "loop:",
+        "    adds r1, #1",
+        "    str r1, [r0]",
+        "body:",
+        "    cmp r1, #10",
+        "    bne loop",
+        "done:",
+        "    bkpt #0",
+    )
+}
+
+fn load(program: &Program) -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine
+}
+
+fn condition(source: &str) -> Expression {
+    Expression::parse(source).unwrap()
+}
+
+#[test]
+fn stops_before_the_instruction() {
+    let program = program();
+    let mut machine = load(&program);
+    let body = program.label("body").unwrap();
+    machine.add_breakpoint(Breakpoint::new(body));
+
+    for count in 1..=10 {
+        assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(body));
+        assert_eq!(machine.get_pc(), body);
+        assert_eq!(machine.get_r1(), count);
+    }
+    assert_eq!(
+        machine.run().unwrap(),
+        StopReason::Breakpoint(program.label("done").unwrap())
+    );
+    assert_eq!(machine.breakpoints().next().unwrap().hits, 10);
+}
+
+#[test]
+fn checks_registers_and_memory() {
+    let program = program();
+    let mut machine = load(&program);
+    let body = program.label("body").unwrap();
+    machine.add_breakpoint(
+        Breakpoint::new(body).with_condition(condition("r0 == 0x20000000 && [r0] > 3 && !z")),
+    );
+
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(body));
+    assert_eq!(machine.get_r1(), 4);
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(body));
+    assert_eq!(machine.get_r1(), 5);
+}
+
+#[test]
+fn ignores_the_first_hits() {
+    let program = program();
+    let mut machine = load(&program);
+    let body = program.label("body").unwrap();
+    machine.add_breakpoint(
+        Breakpoint::new(body)
+            .with_condition(condition("r1 % 2 == 0"))
+            .with_ignore_count(2),
+    );
+
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(body));
+    assert_eq!(machine.get_r1(), 6);
+    let breakpoint = machine.breakpoints().next().unwrap();
+    assert_eq!((breakpoint.hits, breakpoint.ignore_count), (6, 0));
+
+    machine.breakpoint_mut(body).unwrap().ignore_count = 1;
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(body));
+    assert_eq!(machine.get_r1(), 10);
+}
+
+#[test]
+fn counts_hits_in_the_condition() {
+    let program = program();
+    let mut machine = load(&program);
+    let loop_start = program.label("loop").unwrap();
+    machine.add_breakpoint(Breakpoint::new(loop_start).with_condition(condition("hits == 3")));
+
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(loop_start));
+    assert_eq!(machine.get_r1(), 2);
+
+    assert!(machine.remove_breakpoint(loop_start).is_some());
+    assert_eq!(
+        machine.run().unwrap(),
+        StopReason::Breakpoint(program.label("done").unwrap())
+    );
+}
+
+#[test]
+fn stops_at_the_starting_instruction() {
+    let program = program();
+    let mut machine = load(&program);
+    let main = program.label("main").unwrap();
+    machine.add_breakpoint(Breakpoint::new(main));
+
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(main));
+    assert!(machine.get_mutations_history().is_empty());
+    assert_eq!(
+        machine.run().unwrap(),
+        StopReason::Breakpoint(program.label("done").unwrap())
+    );
+    assert_eq!(machine.breakpoints().next().unwrap().hits, 1);
+
+    // NOTE: A reset starts over, the breakpoint stops it again
+    machine.reset();
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(main));
+    assert_eq!(machine.breakpoints().next().unwrap().hits, 2);
+}
+
+#[test]
+fn ignores_the_thumb_bit_of_the_address() {
+    let program = program();
+    let mut machine = load(&program);
+    let body = program.label("body").unwrap();
+    let mut breakpoint = Breakpoint::new(body);
+    breakpoint.address |= 1;
+    machine.add_breakpoint(breakpoint);
+
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(body));
+    assert_eq!(machine.breakpoints().next().unwrap().address, body);
+    assert!(machine.breakpoint_mut(body | 1).is_some());
+    assert!(machine.remove_breakpoint(body).is_some());
+    assert_eq!(machine.breakpoints().count(), 0);
+}
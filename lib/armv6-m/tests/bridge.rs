@@ -0,0 +1,135 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use armv6_m::abi::StopReason;
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::bridge::Bridge;
+use armv6_m::nrf51::uart::Uart;
+
+#[macro_use]
+mod common;
+
+/// Prints "> " at 115200 bauds, then echoes the received bytes up to a newline
+fn program() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r5, =0x40002100",
+        "    ldr r6, =0x40002500",
+        "    ldr r1, =0x01D7E000",
+        "    str r1, [r6, #36]",
+        "    movs r1, #4",
+        "    str r1, [r6, #0]",
+        "    ldr r4, =0x40002000",
+        "    movs r1, #1",
+        "    str r1, [r4, #8]",
+        "    str r1, [r4, #0]",
+        "    movs r1, #62",
+        "    bl putc",
+        "    movs r1, #32",
+        "    bl putc",
+        "echo:",
+        "    ldr r1, [r5, #8]",
+        "    cmp r1, #0",
+        "    beq echo",
+        "    movs r1, #0",
+        "    str r1, [r5, #8]",
+        "    ldr r1, [r6, #24]",
+        "    bl putc",
+        "    cmp r1, #10",
+        "    bne echo",
+        "done:",
+        "    bkpt #0",
+        "putc:",
+        "    str r1, [r6, #28]",
+        "wait:",
+        "    ldr r2, [r5, #28]",
+        "    cmp r2, #0",
+        "    beq wait",
+        "    movs r2, #0",
+        "    str r2, [r5, #28]",
+        "    bx lr",
+    )
+}
+
+#[test]
+fn talks_to_a_tcp_client() {
+    let program = program();
+    let uart = Uart::new();
+    let serial = uart.serial();
+    let mut machine = common::load(&program, vec![Box::new(uart)]);
+    let mut bridge = Bridge::tcp(serial, "127.0.0.1:0").unwrap();
+    let address = bridge.local_addr().unwrap();
+    assert!(!bridge.is_connected());
+
+    let client = std::thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut prompt = [0; 2];
+        stream.read_exact(&mut prompt).unwrap();
+        stream.write_all(b"ping\n").unwrap();
+        let mut echo = [0; 5];
+        stream.read_exact(&mut echo).unwrap();
+        (prompt, echo)
+    });
+
+    let done = program.label("done").unwrap();
+    assert_eq!(
+        bridge.run(&mut machine).unwrap(),
+        StopReason::Breakpoint(done)
+    );
+    let (prompt, echo) = client.join().unwrap();
+    assert_eq!(&prompt, b"> ");
+    assert_eq!(&echo, b"ping\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn talks_through_a_pseudo_terminal() {
+    let program = program();
+    let uart = Uart::new();
+    let serial = uart.serial();
+    let mut machine = common::load(&program, vec![Box::new(uart)]);
+    let mut bridge = Bridge::pty(serial).unwrap();
+    let path = bridge.path().unwrap().to_owned();
+    assert!(path.starts_with("/dev"));
+
+    let mut terminal = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .unwrap();
+    terminal.write_all(b"pong\n").unwrap();
+
+    let done = program.label("done").unwrap();
+    assert_eq!(
+        bridge.run(&mut machine).unwrap(),
+        StopReason::Breakpoint(done)
+    );
+    let mut output = [0; 7];
+    terminal.read_exact(&mut output).unwrap();
+    assert_eq!(&output, b"> pong\n");
+}
+
+#[test]
+fn keeps_the_output_until_a_client_connects() {
+    let program = program();
+    let uart = Uart::new();
+    let serial = uart.serial();
+    let mut machine = common::load(&program, vec![Box::new(uart)]);
+    let mut bridge = Bridge::tcp(serial.clone(), "127.0.0.1:0").unwrap();
+    let address = bridge.local_addr().unwrap();
+
+    // NOTE: The prompt takes 174 us at 115200 bauds, a millisecond is 16000 cycles
+    for _ in 0..4 {
+        assert_eq!(machine.run_for(16_000).unwrap(), None);
+        assert!(!bridge.pump().unwrap());
+    }
+    assert!(serial.take_output().is_empty());
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    while !bridge.is_connected() {
+        bridge.pump().unwrap();
+    }
+    let mut prompt = [0; 2];
+    stream.read_exact(&mut prompt).unwrap();
+    assert_eq!(&prompt, b"> ");
+}
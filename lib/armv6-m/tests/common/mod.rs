@@ -0,0 +1,40 @@
+//! Fixture shared by the tests of the nRF51 peripherals, running a firmware against them
+// NOTE: Every test crate builds its own copy, each one only uses a part of it
+#![allow(dead_code)]
+
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::peripheral::Peripheral;
+use armv6_m::Armv6M;
+
+/// Program with the vector table of a firmware, the stack at the top of the first 4 KiB of RAM
+/// and the reset handler at `main`, followed by `lines`
+macro_rules! firmware {
+    ($($line:expr),* $(,)?) => {
+        armv6_m::thumb!("    .word 0x20001000", "    .word main + 1", $($line),*)
+    };
+}
+
+/// Machine running `program` with `peripherals` mapped
+pub fn load(program: &Program, peripherals: Vec<Box<dyn Peripheral>>) -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    for peripheral in peripherals {
+        machine.add_peripheral(peripheral);
+    }
+    machine
+}
+
+/// Run `machine` until it stops at the breakpoint of `label` in `program`
+pub fn run_to(machine: &mut Armv6M, program: &Program, label: &str) {
+    let address = program.label(label).unwrap();
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(address));
+}
+
+/// Peripheral of `machine` called `name`
+pub fn peripheral<'a>(machine: &'a Armv6M, name: &str) -> &'a dyn Peripheral {
+    machine
+        .peripherals()
+        .find(|peripheral| peripheral.name() == name)
+        .unwrap()
+}
@@ -0,0 +1,32 @@
+use armv6_m::decoder::decode;
+use armv6_m::instructions::{is_32bits, parse_instruction, Instruction};
+
+/// Decoded by the nom parsers, which are the reference
+fn reference(first: u16, second: u16) -> Option<(Instruction, u32)> {
+    let mut bytes = [0; 4];
+    bytes[..2].copy_from_slice(&first.to_be_bytes());
+    bytes[2..].copy_from_slice(&second.to_be_bytes());
+    parse_instruction(&bytes)
+        .ok()
+        .map(|(rest, instruction)| (instruction, 4 - rest.len() as u32))
+}
+
+#[test]
+fn table_matches_the_reference_parsers() {
+    for first in 0..=u16::MAX {
+        let seconds: &[u16] = if is_32bits(first) {
+            &[
+                0x0000, 0x8010, 0x8800, 0x8F4F, 0x8F5F, 0x8F6F, 0xA123, 0xD000, 0xF7FF, 0xFFFF,
+            ]
+        } else {
+            &[0x0000]
+        };
+        for second in seconds {
+            assert_eq!(
+                decode(first, *second),
+                reference(first, *second),
+                "{first:04x} {second:04x}"
+            );
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use armv6_m::abi::Runtime;
+use armv6_m::disassembler::{disassemble, disassemble_at};
+use armv6_m::Armv6M;
+
+const LISTING_ELF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/listing.elf");
+
+fn loaded() -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_file(LISTING_ELF).unwrap();
+    machine
+}
+
+/// Listing of `start..end`, one line per instruction
+fn listing(machine: &Armv6M, start: u32, end: u32) -> String {
+    disassemble(machine.get_memory(), start, end, Some(machine.symbols()))
+        .iter()
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Output of `arm-none-eabi-objdump -d` for the code of the fixture, literal pools left out
+#[test]
+fn lists_like_objdump() {
+    let machine = loaded();
+
+    assert_eq!(
+        listing(&machine, 0x08, 0x1A),
+        concat!(
+            "\n00000008 <reset>:\n",
+            "       8:\t4804      \tldr\tr0, [pc, #16]\t@ (1c <reset+0x14>)\n",
+            "       a:\t4905      \tldr\tr1, [pc, #20]\t@ (20 <reset+0x18>)\n",
+            "       c:\tc91c      \tldmia\tr1!, {r2, r3, r4}\n",
+            "       e:\tc014      \tstmia\tr0!, {r2, r4}\n",
+            "      10:\tf000 f808 \tbl\t24 <copy>\n",
+            "      14:\t42a3      \tcmp\tr3, r4\n",
+            "      16:\td8f7      \tbhi.n\t8 <reset>\n",
+            "      18:\te010      \tb.n\t3c <done>\n",
+        )
+    );
+    assert_eq!(
+        listing(&machine, 0x24, 0x38),
+        concat!(
+            "\n00000024 <copy>:\n",
+            "      24:\tb5f0      \tpush\t{r4, r5, r6, r7, lr}\n",
+            "      26:\t4647      \tmov\tr7, r8\n",
+            "      28:\tb480      \tpush\t{r7}\n",
+            "      2a:\t4d03      \tldr\tr5, [pc, #12]\t@ (38 <copy+0x14>)\n",
+            "      2c:\tcd25      \tldmia\tr5, {r0, r2, r5}\n",
+            "      2e:\t3a01      \tsubs\tr2, #1\n",
+            "      30:\td1fd      \tbne.n\t2e <copy+0xa>\n",
+            "      32:\tbc80      \tpop\t{r7}\n",
+            "      34:\t46b8      \tmov\tr8, r7\n",
+            "      36:\tbdf0      \tpop\t{r4, r5, r6, r7, pc}\n",
+        )
+    );
+    assert_eq!(
+        listing(&machine, 0x3C, 0x40),
+        concat!(
+            "\n0000003c <done>:\n",
+            "      3c:\tbe12      \tbkpt\t0x0012\n",
+            "      3e:\te7fd      \tb.n\t3c <done>\n",
+        )
+    );
+}
+
+/// The comments point at the words the loads read
+#[test]
+fn comments_literal_pool_loads() {
+    let machine = loaded();
+    let memory = machine.get_memory();
+    let table = machine.symbols().address_of("table").unwrap();
+
+    for (address, value) in [(0x08, 0x4000_0000), (0x0A, table), (0x2A, 0x2000_0000)] {
+        let line = disassemble_at(memory, address, Some(machine.symbols()));
+        let (_, comment) = line.text.split_once("@ (").unwrap();
+        let literal = u32::from_str_radix(comment.split(' ').next().unwrap(), 16).unwrap();
+        assert_eq!(memory.read_u32(literal), value, "{line}");
+    }
+}
+
+/// Without symbols the targets are bare addresses
+#[test]
+fn lists_addresses_without_symbols() {
+    let machine = loaded();
+    let text = |address| disassemble_at(machine.get_memory(), address, None).text;
+
+    assert_eq!(text(0x08), "ldr\tr0, [pc, #16]\t@ (1c)");
+    assert_eq!(text(0x10), "bl\t24");
+    assert_eq!(text(0x16), "bhi.n\t8");
+    assert_eq!(text(0x30), "bne.n\t2e");
+}
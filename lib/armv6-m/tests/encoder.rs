@@ -0,0 +1,76 @@
+use armv6_m::encoder::Encode;
+use armv6_m::instructions::{is_32bits, parse_instruction, Instruction};
+
+fn decode(halfwords: &[u16]) -> Option<Instruction> {
+    let bytes = halfwords
+        .iter()
+        .flat_map(|halfword| halfword.to_be_bytes())
+        .collect::<Vec<_>>();
+    match parse_instruction(&bytes) {
+        Ok(([], instruction)) => Some(instruction),
+        _ => None,
+    }
+}
+
+/// Encodings decoded as an instruction that has another encoding
+fn is_alias(halfwords: &[u16]) -> bool {
+    match halfwords {
+        // NOTE: Unallocated hints execute as NOP
+        [halfword] => halfword & 0xFF0F == 0xBF00 && halfword >> 4 & 0xF > 0b0100,
+        _ => false,
+    }
+}
+
+/// Decode then encode, the encoder refuses the fields of other instructions
+/// so decoder overlaps are caught even when the halfwords would match
+fn round_trip(halfwords: &[u16]) -> bool {
+    let Some(instruction) = decode(halfwords) else {
+        return false;
+    };
+    let encoded = instruction
+        .encode()
+        .unwrap_or_else(|error| panic!("{halfwords:04x?} decoded as {instruction:?}: {error}"));
+
+    if is_alias(halfwords) {
+        assert_eq!(decode(&encoded), Some(instruction), "{halfwords:04x?}");
+    } else {
+        assert_eq!(encoded, halfwords, "{instruction:?}");
+    }
+    true
+}
+
+#[test]
+fn every_16_bits_encoding_round_trips() {
+    let decoded = (0..=u16::MAX)
+        .filter(|halfword| !is_32bits(*halfword))
+        .filter(|halfword| round_trip(&[*halfword]))
+        .count();
+
+    // NOTE: Undefined are BX and BLX with their low bits set (224), CBZ and CBNZ (1024),
+    // 0xB600 to 0xB8FF except CPSIE i and CPSID i (766), REV with op 0b10 (64) and IT (240)
+    let undefined = 224 + 1024 + 766 + 64 + 240;
+    assert_eq!(decoded, 0x10000 - 0x1800 - undefined);
+}
+
+#[test]
+fn every_32_bits_encoding_round_trips() {
+    // NOTE: Every second halfword of the first halfwords used by BL, MSR, MRS, the barriers and UDF
+    let first_halfwords = [0xF000, 0xF400, 0xF7FF, 0xF3EF, 0xF3BF]
+        .into_iter()
+        .chain(0xF380..=0xF38F)
+        .chain(0xF7F0..=0xF7FF);
+    for first in first_halfwords {
+        for second in 0..=u16::MAX {
+            round_trip(&[first, second]);
+        }
+    }
+
+    // NOTE: Some second halfwords of every first halfword
+    for first in (0..=u16::MAX).filter(|halfword| is_32bits(*halfword)) {
+        for second in (0..16).flat_map(|top| {
+            [0x000, 0x0F4F, 0x0F5F, 0x0F6F, 0x0810, 0x0FFF].map(|low| top << 12 | low)
+        }) {
+            round_trip(&[first, second]);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use armv6_m::error::Error;
+use armv6_m::expression::{Environment, Expression, Variable};
+
+/// Registers hold their number times 0x10, memory holds the low byte of the address everywhere
+struct Fake {
+    hits: u32,
+}
+
+impl Environment for Fake {
+    fn variable(&self, variable: Variable) -> u32 {
+        match variable {
+            Variable::Register(n) => u32::from(n) * 0x10,
+            Variable::Z | Variable::C => 1,
+            Variable::Hits => self.hits,
+            _ => 0,
+        }
+    }
+
+    fn read(&self, address: u32, size: u8) -> u32 {
+        let byte = address & 0xFF;
+        match size {
+            1 => byte,
+            2 => byte << 8 | byte,
+            _ => u32::from_le_bytes([byte as u8; 4]),
+        }
+    }
+}
+
+fn evaluate(source: &str) -> u32 {
+    Expression::parse(source)
+        .unwrap()
+        .evaluate(&Fake { hits: 7 })
+}
+
+#[test]
+fn follows_the_c_precedence() {
+    assert_eq!(evaluate("1 + 2 * 3"), 7);
+    assert_eq!(evaluate("(1 + 2) * 3"), 9);
+    assert_eq!(evaluate("1 << 4 + 1"), 32);
+    assert_eq!(evaluate("6 & 3 == 3"), 0);
+    assert_eq!(evaluate("1 | 2 ^ 3 & 6"), 1);
+    assert_eq!(evaluate("2 < 3 == 1 && 0 || 1"), 1);
+    assert_eq!(evaluate("-1 / 2"), 0x7FFF_FFFF);
+    assert_eq!(evaluate("~0 - !0 + !5"), 0xFFFF_FFFE);
+}
+
+#[test]
+fn wraps_and_compares_unsigned() {
+    assert_eq!(evaluate("0xFFFFFFFF + 2"), 1);
+    assert_eq!(evaluate("-1 > 1"), 1);
+    assert_eq!(evaluate("5 / 0"), 0);
+    assert_eq!(evaluate("5 % 0"), 0);
+    assert_eq!(evaluate("1 << 32"), 0);
+    assert_eq!(evaluate("0b1010 >= 10 && 0x10 != 16"), 0);
+}
+
+#[test]
+fn reads_variables_and_memory() {
+    assert_eq!(
+        evaluate("r0 == 0 && R3 == 0x30 && sp == 0xd0 && PC == 0xf0"),
+        1
+    );
+    assert_eq!(evaluate("z && c && !n && !v"), 1);
+    assert_eq!(evaluate("hits % 7"), 0);
+    assert_eq!(evaluate("[sp + 4]"), 0xD4D4_D4D4);
+    assert_eq!(evaluate("u16[r1]"), 0x1010);
+    assert_eq!(evaluate("u8[ r2 + 1 ] > 3"), 1);
+    assert_eq!(evaluate("[[r1]]"), 0x1010_1010);
+}
+
+#[test]
+fn reports_the_column_of_errors() {
+    let column = |source: &str| match Expression::parse(source) {
+        Err(Error::Expression { column, .. }) => column,
+        other => panic!("{source} parsed as {other:?}"),
+    };
+
+    assert_eq!(column("r0 == "), 7);
+    assert_eq!(column("r0 == r16"), 7);
+    assert_eq!(column("(1 + 2"), 7);
+    assert_eq!(column("[sp + 4"), 8);
+    assert_eq!(column("0x1g"), 1);
+    assert_eq!(column("1 2"), 3);
+    assert_eq!(column("foo"), 1);
+}
+
+#[test]
+fn keeps_its_source() {
+    let expression = Expression::parse("  r0 == 0x20001000 && [sp+4] > 3 ").unwrap();
+    assert_eq!(expression.source(), "r0 == 0x20001000 && [sp+4] > 3");
+    assert_eq!(expression.to_string(), expression.source());
+}
@@ -0,0 +1,70 @@
+@ Firmware faulting three calls deep, each caller described by a different unwind source:
+@ `main` by `.debug_frame`, `middle` by `.ARM.exidx` and `leaf` by nothing.
+@
+@ llvm-mc -triple=thumbv6m-none-eabi -filetype=obj -g -dwarf-version=4 fault.s -o fault.o
+@ rust-lld -flavor gnu -T fault.ld -e 0x11 -z max-page-size=4 fault.o -o fault.elf
+
+    .syntax unified
+    .thumb
+    .cfi_sections .debug_frame
+
+    .section .text
+vectors:
+    .word 0x20001000
+    .word reset
+    .word nmi
+    .word hard_fault
+
+    .thumb_func
+    .type reset, %function
+reset:
+    bl main
+    b reset
+
+    .thumb_func
+    .type main, %function
+main:
+    .cfi_startproc
+    push {r7, lr}
+    .cfi_def_cfa_offset 8
+    .cfi_offset lr, -4
+    .cfi_offset r7, -8
+    movs r0, #1
+    bl middle
+    pop {r7, pc}
+    .cfi_endproc
+
+    .thumb_func
+    .type middle, %function
+middle:
+    .fnstart
+    .save {r4, lr}
+    push {r4, lr}
+    .pad #8
+    sub sp, #8
+    movs r4, #2
+    bl leaf
+    add sp, #8
+    pop {r4, pc}
+    .fnend
+
+    .thumb_func
+    .type leaf, %function
+leaf:
+    push {r5, r6, lr}
+    sub sp, #4
+    movs r5, #3
+    udf #0
+    add sp, #4
+    pop {r5, r6, pc}
+
+    .thumb_func
+    .type nmi, %function
+nmi:
+    b nmi
+
+    .thumb_func
+    .type hard_fault, %function
+hard_fault:
+    bkpt #0
+    b hard_fault
@@ -0,0 +1,53 @@
+@ Code exercising the listing of the disassembler: register lists, branches to symbols
+@ and loads from a literal pool, `objdump -d` of it is the golden output of tests/disassembler.rs.
+@
+@ llvm-mc -triple=thumbv6m-none-eabi -filetype=obj listing.s -o listing.o
+@ rust-lld -flavor gnu -T fault.ld -e 0x9 -z max-page-size=4 listing.o -o listing.elf
+
+    .syntax unified
+    .thumb
+
+    .section .text
+vectors:
+    .word 0x20001000
+    .word reset
+
+    .thumb_func
+    .type reset, %function
+reset:
+    ldr r0, =0x40000000
+    ldr r1, =table
+    ldmia r1!, {r2, r3, r4}
+    stmia r0!, {r2, r4}
+    bl copy
+    cmp r3, r4
+    bhi reset
+    b done
+    .ltorg
+
+    .thumb_func
+    .type copy, %function
+copy:
+    push {r4, r5, r6, r7, lr}
+    mov r7, r8
+    push {r7}
+    ldr r5, =0x20000000
+    ldm r5, {r0, r2, r5}
+1:
+    subs r2, #1
+    bne 1b
+    pop {r7}
+    mov r8, r7
+    pop {r4, r5, r6, r7, pc}
+    .ltorg
+
+    .thumb_func
+    .type done, %function
+done:
+    bkpt #0x12
+    b done
+
+    .align 2
+    .type table, %object
+table:
+    .word 1, 2, 3
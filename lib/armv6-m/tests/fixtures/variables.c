@@ -0,0 +1,19 @@
+int counter = 42;
+static unsigned char flags[4] = {1, 2, 3, 4};
+struct point { short x; short y; } origin = {3, -4};
+int *last = &counter;
+
+int accumulate(int step) {
+    int total = counter + step + flags[1];
+    counter = total;
+    __asm volatile("bkpt #0");
+    return total;
+}
+
+void reset(void) {
+    int result = accumulate(5);
+    for (;;) {
+    }
+}
+
+__attribute__((section(".vectors"))) void *const vectors[2] = {(void *)0x20001000, reset};
@@ -0,0 +1,102 @@
+; Hand written equivalent of `clang -O0 -g --target=thumbv6m-none-eabi -S -emit-llvm variables.c`
+;
+; llc -O0 -filetype=obj variables.ll -o variables.o
+; rust-lld -flavor gnu -T variables.ld -e 0x1 -z max-page-size=4 variables.o -o variables.elf
+
+target datalayout = "e-m:e-p:32:32-Fi8-i64:64-v128:64:128-a:0:32-n32-S64"
+target triple = "thumbv6m-none-unknown-eabi"
+
+%struct.point = type { i16, i16 }
+
+@counter = dso_local global i32 42, align 4, !dbg !0
+@flags = internal global [4 x i8] c"\01\02\03\04", align 1, !dbg !5
+@origin = dso_local global %struct.point { i16 3, i16 -4 }, align 2, !dbg !7
+@last = dso_local global i32* @counter, align 4, !dbg !9
+@vectors = dso_local constant [2 x i8*] [i8* inttoptr (i32 536875008 to i8*), i8* bitcast (void ()* @reset to i8*)], section ".vectors", align 4
+@llvm.used = appending global [1 x i8*] [i8* bitcast ([2 x i8*]* @vectors to i8*)], section "llvm.metadata"
+
+define dso_local i32 @accumulate(i32 %step) #0 !dbg !30 {
+entry:
+  %step.addr = alloca i32, align 4
+  %total = alloca i32, align 4
+  store i32 %step, i32* %step.addr, align 4
+  call void @llvm.dbg.declare(metadata i32* %step.addr, metadata !34, metadata !DIExpression()), !dbg !35
+  call void @llvm.dbg.declare(metadata i32* %total, metadata !36, metadata !DIExpression()), !dbg !37
+  %0 = load i32, i32* @counter, align 4, !dbg !38
+  %1 = load i32, i32* %step.addr, align 4, !dbg !38
+  %add = add nsw i32 %0, %1, !dbg !38
+  %2 = load i8, i8* getelementptr inbounds ([4 x i8], [4 x i8]* @flags, i32 0, i32 1), align 1, !dbg !38
+  %conv = zext i8 %2 to i32, !dbg !38
+  %add1 = add nsw i32 %add, %conv, !dbg !38
+  store i32 %add1, i32* %total, align 4, !dbg !37
+  %3 = load i32, i32* %total, align 4, !dbg !39
+  store i32 %3, i32* @counter, align 4, !dbg !39
+  call void asm sideeffect "bkpt #0", ""(), !dbg !40
+  %4 = load i32, i32* %total, align 4, !dbg !41
+  ret i32 %4, !dbg !41
+}
+
+define dso_local void @reset() #0 !dbg !50 {
+entry:
+  %result = alloca i32, align 4
+  call void @llvm.dbg.declare(metadata i32* %result, metadata !55, metadata !DIExpression()), !dbg !56
+  %call = call i32 @accumulate(i32 5), !dbg !51
+  store i32 %call, i32* %result, align 4, !dbg !56
+  br label %for.cond, !dbg !52
+
+for.cond:
+  br label %for.cond, !dbg !52
+}
+
+declare void @llvm.dbg.declare(metadata, metadata, metadata) #1
+
+attributes #0 = { noinline nounwind optnone "frame-pointer"="all" "target-cpu"="cortex-m0" }
+attributes #1 = { nofree nosync nounwind readnone speculatable willreturn }
+
+!llvm.dbg.cu = !{!2}
+!llvm.module.flags = !{!20, !21, !22}
+
+!0 = !DIGlobalVariableExpression(var: !1, expr: !DIExpression())
+!1 = distinct !DIGlobalVariable(name: "counter", scope: !2, file: !3, line: 1, type: !11, isLocal: false, isDefinition: true)
+!2 = distinct !DICompileUnit(language: DW_LANG_C99, file: !3, producer: "clang", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug, globals: !4, splitDebugInlining: false, nameTableKind: None)
+!3 = !DIFile(filename: "variables.c", directory: "/fixtures")
+!4 = !{!0, !5, !7, !9}
+!5 = !DIGlobalVariableExpression(var: !6, expr: !DIExpression())
+!6 = distinct !DIGlobalVariable(name: "flags", scope: !2, file: !3, line: 2, type: !12, isLocal: true, isDefinition: true)
+!7 = !DIGlobalVariableExpression(var: !8, expr: !DIExpression())
+!8 = distinct !DIGlobalVariable(name: "origin", scope: !2, file: !3, line: 3, type: !16, isLocal: false, isDefinition: true)
+!9 = !DIGlobalVariableExpression(var: !10, expr: !DIExpression())
+!10 = distinct !DIGlobalVariable(name: "last", scope: !2, file: !3, line: 4, type: !19, isLocal: false, isDefinition: true)
+!11 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!12 = !DICompositeType(tag: DW_TAG_array_type, baseType: !13, size: 32, elements: !14)
+!13 = !DIBasicType(name: "unsigned char", size: 8, encoding: DW_ATE_unsigned_char)
+!14 = !{!15}
+!15 = !DISubrange(count: 4)
+!16 = distinct !DICompositeType(tag: DW_TAG_structure_type, name: "point", file: !3, line: 3, size: 32, elements: !17)
+!17 = !{!23, !24}
+!18 = !DIBasicType(name: "short", size: 16, encoding: DW_ATE_signed)
+!19 = !DIDerivedType(tag: DW_TAG_pointer_type, baseType: !11, size: 32)
+!20 = !{i32 7, !"Dwarf Version", i32 4}
+!21 = !{i32 2, !"Debug Info Version", i32 3}
+!22 = !{i32 1, !"wchar_size", i32 4}
+!23 = !DIDerivedType(tag: DW_TAG_member, name: "x", scope: !16, file: !3, line: 3, baseType: !18, size: 16)
+!24 = !DIDerivedType(tag: DW_TAG_member, name: "y", scope: !16, file: !3, line: 3, baseType: !18, size: 16, offset: 16)
+!30 = distinct !DISubprogram(name: "accumulate", scope: !3, file: !3, line: 6, type: !31, scopeLine: 6, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !2, retainedNodes: !33)
+!31 = !DISubroutineType(types: !32)
+!32 = !{!11, !11}
+!33 = !{}
+!34 = !DILocalVariable(name: "step", arg: 1, scope: !30, file: !3, line: 6, type: !11)
+!35 = !DILocation(line: 6, column: 20, scope: !30)
+!36 = !DILocalVariable(name: "total", scope: !30, file: !3, line: 7, type: !11)
+!37 = !DILocation(line: 7, column: 9, scope: !30)
+!38 = !DILocation(line: 7, column: 17, scope: !30)
+!39 = !DILocation(line: 8, column: 13, scope: !30)
+!40 = !DILocation(line: 9, column: 5, scope: !30)
+!41 = !DILocation(line: 10, column: 5, scope: !30)
+!50 = distinct !DISubprogram(name: "reset", scope: !3, file: !3, line: 13, type: !53, scopeLine: 13, flags: DIFlagPrototyped, spFlags: DISPFlagDefinition, unit: !2, retainedNodes: !33)
+!51 = !DILocation(line: 14, column: 18, scope: !50)
+!52 = !DILocation(line: 15, column: 5, scope: !50)
+!53 = !DISubroutineType(types: !54)
+!54 = !{null}
+!55 = !DILocalVariable(name: "result", scope: !50, file: !3, line: 14, type: !11)
+!56 = !DILocation(line: 14, column: 9, scope: !50)
@@ -0,0 +1,292 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use armv6_m::abi::Runtime;
+use armv6_m::assembler::Program;
+use armv6_m::gdb::GdbServer;
+use armv6_m::nrf51::gpio::Gpio;
+use armv6_m::structure::RegisterId;
+use armv6_m::{thumb, Armv6M};
+
+/// Stores 1 to 5 in RAM, then spins
+fn program() -> Program {
+    thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    movs r0, #1",
+        "    ldr r1, =0x20000000",
+        "    str r0, [r1]",
+        "count:",
+        "    adds r0, r0, #1",
+        "    str r0, [r1]",
+        "    cmp r0, #5",
+        "    bne count",
+        "done:",
+        "    nop",
+        "spin:",
+        "    b spin",
+    )
+}
+
+fn load(program: &Program) -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine
+}
+
+/// GDB side of the connection
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn connect(address: SocketAddr) -> Self {
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_nodelay(true).unwrap();
+        Self { stream }
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn send(&mut self, packet: &[u8]) {
+        let checksum = packet.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        let frame = [b"$", packet, format!("#{checksum:02x}").as_bytes()].concat();
+        self.stream.write_all(&frame).unwrap();
+        assert_eq!(self.read_byte(), b'+');
+    }
+
+    fn receive(&mut self) -> String {
+        while self.read_byte() != b'$' {}
+        let mut data = Vec::new();
+        loop {
+            match self.read_byte() {
+                b'#' => break,
+                byte => data.push(byte),
+            }
+        }
+        let mut checksum = [0; 2];
+        self.stream.read_exact(&mut checksum).unwrap();
+        self.stream.write_all(b"+").unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    fn request(&mut self, packet: &str) -> String {
+        self.send(packet.as_bytes());
+        self.receive()
+    }
+}
+
+/// Serve `machine` to `client`, which runs in its own thread and ends the session
+fn session(machine: &mut Armv6M, client: impl FnOnce(&mut Client) + Send + 'static) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let client = thread::spawn(move || {
+        let mut gdb = Client::connect(address);
+        client(&mut gdb);
+        gdb.send(b"k");
+    });
+
+    let (stream, _) = listener.accept().unwrap();
+    let served = GdbServer::new(machine).serve(stream);
+    client.join().unwrap();
+    served.unwrap();
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn word(value: u32) -> String {
+    hex(&value.to_le_bytes())
+}
+
+#[test]
+fn describes_the_target() {
+    let mut machine = load(&program());
+    session(&mut machine, |gdb| {
+        let supported = gdb.request("qSupported:multiprocess+;swbreak+");
+        assert!(supported.contains("PacketSize=4000"), "{supported}");
+        assert!(supported.contains("qXfer:features:read+"), "{supported}");
+        assert!(supported.contains("ReverseStep+"), "{supported}");
+        assert_eq!(gdb.request("?"), "S05");
+
+        let xml = gdb.request("qXfer:features:read:target.xml:0,ffff");
+        assert!(xml.starts_with("l<?xml"), "{xml}");
+        assert!(xml.contains("org.gnu.gdb.arm.m-profile"), "{xml}");
+        let page = gdb.request("qXfer:features:read:target.xml:0,10");
+        assert_eq!(page, format!("m{}", &xml[1..17]));
+
+        assert_eq!(gdb.request("vMustReplyEmpty"), "");
+    });
+}
+
+#[test]
+fn reads_and_writes_registers() {
+    let program = program();
+    let main = program.label("main").unwrap();
+    let mut machine = load(&program);
+    session(&mut machine, move |gdb| {
+        let registers = gdb.request("g");
+        assert_eq!(registers.len(), 21 * 8);
+        assert_eq!(&registers[13 * 8..14 * 8], word(0x2000_1000));
+        assert_eq!(&registers[15 * 8..16 * 8], word(main));
+        assert_eq!(gdb.request("pf"), word(main));
+
+        assert_eq!(gdb.request(&format!("P0={}", word(0x1234_5678))), "OK");
+        assert_eq!(gdb.request("p0"), word(0x1234_5678));
+        assert_eq!(gdb.request("p15"), "E01");
+
+        let written = [&registers[..8], &word(0xCAFE)[..], &registers[16..]].concat();
+        assert_eq!(gdb.request(&format!("G{written}")), "OK");
+        assert_eq!(&gdb.request("g")[8..16], word(0xCAFE));
+    });
+
+    assert_eq!(machine.read_register(RegisterId::R1), 0xCAFE);
+    // NOTE: `G` writes every register again, `P0` is overwritten by the value read before it
+    assert_eq!(machine.read_register(RegisterId::R0), 0);
+    assert_eq!(machine.get_mutations_history().len(), 2);
+}
+
+#[test]
+fn reads_and_writes_memory() {
+    let mut machine = load(&program());
+    machine.add_peripheral(Box::new(Gpio::new()));
+
+    session(&mut machine, |gdb| {
+        assert_eq!(gdb.request("M20000100,4:01020304"), "OK");
+        assert_eq!(gdb.request("m20000100,4"), "01020304");
+        // NOTE: 0x7D is escaped as `}` followed by 0x5D
+        gdb.send(b"X20000105,3:\x00}\x5d\x41");
+        assert_eq!(gdb.receive(), "OK");
+        assert_eq!(gdb.request("m20000101,7"), "02030400007d41");
+        assert_eq!(gdb.request("m20000100,0"), "");
+        assert_eq!(gdb.request("M20000100,4:01"), "E01");
+
+        // NOTE: PIN_CNF[3] comes from the GPIO, its input buffer is disconnected at reset
+        assert_eq!(gdb.request("m5000070c,4"), word(2));
+        assert_eq!(gdb.request("m5000070c,2"), "0200");
+    });
+
+    let memory = machine.get_memory();
+    assert_eq!(memory.read_u8(0x2000_0106), 0x7D);
+    assert_eq!(machine.get_mutations_history().len(), 2);
+}
+
+#[test]
+fn stops_at_breakpoints_and_watchpoints() {
+    let program = program();
+    let main = program.label("main").unwrap();
+    let count = program.label("count").unwrap();
+    let done = program.label("done").unwrap();
+    let mut machine = load(&program);
+    session(&mut machine, move |gdb| {
+        assert_eq!(gdb.request("s"), "S05");
+        assert_eq!(gdb.request("pf"), word(main + 2));
+
+        assert_eq!(gdb.request("Z2,20000000,4"), "OK");
+        assert_eq!(gdb.request("c"), "T05watch:20000000;");
+        assert_eq!(gdb.request("pf"), word(count));
+        assert_eq!(gdb.request("m20000000,4"), word(1));
+        assert_eq!(gdb.request("z2,20000000,4"), "OK");
+
+        assert_eq!(gdb.request(&format!("Z0,{done:x},2")), "OK");
+        assert_eq!(gdb.request("c"), "T05swbreak:;");
+        assert_eq!(gdb.request("pf"), word(done));
+        assert_eq!(gdb.request("m20000000,4"), word(5));
+        assert_eq!(gdb.request(&format!("z0,{done:x},2")), "OK");
+    });
+
+    assert_eq!(machine.get_pc(), done);
+    assert!(machine.watchpoints().is_empty());
+}
+
+#[test]
+fn interrupts_a_running_target() {
+    let program = program();
+    let spin = program.label("spin").unwrap();
+    let mut machine = load(&program);
+    session(&mut machine, move |gdb| {
+        gdb.send(format!("c{spin:x}").as_bytes());
+        thread::sleep(Duration::from_millis(50));
+        gdb.stream.write_all(&[0x03]).unwrap();
+        assert_eq!(gdb.receive(), "S02");
+        assert_eq!(gdb.request("?"), "S02");
+        assert_eq!(gdb.request("pf"), word(spin));
+    });
+}
+
+#[test]
+fn reverse_steps_over_debugger_writes() {
+    let program = program();
+    let main = program.label("main").unwrap();
+    let count = program.label("count").unwrap();
+    let mut machine = load(&program);
+    machine.add_peripheral(Box::new(Gpio::new()));
+    session(&mut machine, move |gdb| {
+        for _ in 0..3 {
+            assert_eq!(gdb.request("s"), "S05");
+        }
+        assert_eq!(gdb.request("pf"), word(count));
+        assert_eq!(gdb.request(&format!("P0={}", word(0x99))), "OK");
+        assert_eq!(gdb.request("M20000004,4:01020304"), "OK");
+
+        let warning = gdb.request("bs");
+        assert_eq!(
+            warning,
+            format!(
+                "O{}",
+                hex(b"warning: peripherals states are not rolled back\n")
+            )
+        );
+        assert_eq!(gdb.receive(), "S05");
+        assert_eq!(gdb.request("pf"), word(main + 4));
+        assert_eq!(gdb.request("p0"), word(1));
+        assert_eq!(gdb.request("m20000000,8"), "0000000000000000");
+
+        assert_eq!(gdb.request("bs"), "S05");
+        assert_eq!(gdb.request("bs"), "S05");
+        assert_eq!(gdb.request("pf"), word(main));
+        assert_eq!(gdb.request("bs"), "T05replaylog:begin;");
+    });
+
+    assert!(machine.get_mutations_history().is_empty());
+}
+
+#[test]
+fn reverse_continues_to_breakpoints_and_watchpoints() {
+    let program = program();
+    let count = program.label("count").unwrap();
+    let done = program.label("done").unwrap();
+    let mut machine = load(&program);
+    session(&mut machine, move |gdb| {
+        let command = format!("break {count:#x} if r0 == 3");
+        assert_eq!(
+            gdb.request(&format!("qRcmd,{}", hex(command.as_bytes()))),
+            "OK"
+        );
+        assert_eq!(gdb.request(&format!("Z0,{done:x},2")), "OK");
+        assert_eq!(gdb.request("c"), "S05");
+        assert_eq!(gdb.request("p0"), word(3));
+        assert_eq!(gdb.request("c"), "T05swbreak:;");
+
+        assert_eq!(gdb.request("bc"), "S05");
+        assert_eq!(gdb.request("pf"), word(count));
+        assert_eq!(gdb.request("p0"), word(3));
+
+        assert_eq!(gdb.request("Z2,20000000,4"), "OK");
+        assert_eq!(gdb.request("bc"), "T05watch:20000000;");
+        assert_eq!(gdb.request("pf"), word(count + 2));
+        assert_eq!(gdb.request("m20000000,4"), word(2));
+    });
+
+    // NOTE: Only executing forward counts the hits
+    let breakpoint = machine.breakpoints().next().unwrap();
+    assert_eq!(breakpoint.hits, 4);
+}
@@ -0,0 +1,144 @@
+use armv6_m::abi::Runtime;
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::gpio::{Gpio, PinChange, Pull, Sense, DIR, DIRCLR, DIRSET, OUT, PIN_CNF};
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Lights the LED on P0.13, reads the button on P0.17 configured with a pull-up and SENSE low
+fn program() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r1, =0x50000700",
+        "    movs r2, #1",
+        "    str r2, [r1, #52]",
+        "    ldr r2, =0x3000C",
+        "    str r2, [r1, #68]",
+        "    ldr r4, =0x50000500",
+        "    ldr r5, =0x2000",
+        "    str r5, [r4, #8]",
+        "    ldr r6, [r4, #16]",
+        "lit:",
+        "    bkpt #0",
+        "    str r5, [r4, #12]",
+        "    ldr r6, [r4, #16]",
+        "off:",
+        "    bkpt #1",
+    )
+}
+
+#[test]
+fn drives_the_outputs() {
+    let program = program();
+    let gpio = Gpio::new();
+    let pins = gpio.pins();
+    let mut machine = common::load(&program, vec![Box::new(gpio)]);
+
+    common::run_to(&mut machine, &program, "lit");
+    assert_eq!(pins.output(13), Some(true));
+    assert_eq!(pins.output(17), None);
+    assert!(pins.level(13));
+    assert_eq!(machine.get_r6(), 1 << 13 | 1 << 17);
+
+    let changes = pins.take_changes();
+    assert_eq!(
+        changes
+            .iter()
+            .map(|change| (change.pin, change.level))
+            .collect::<Vec<_>>(),
+        [(17, true), (13, true)]
+    );
+    assert!(changes[0].cycle > 0 && changes[0].cycle < changes[1].cycle);
+
+    machine.set_pc(machine.get_pc() + 2);
+    common::run_to(&mut machine, &program, "off");
+    assert_eq!(pins.output(13), Some(false));
+    assert_eq!(machine.get_r6(), 1 << 17);
+    assert!(matches!(
+        pins.take_changes()[..],
+        [PinChange {
+            pin: 13,
+            level: false,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn reads_the_inputs_driven_by_the_host() {
+    let program = program();
+    let gpio = Gpio::new();
+    let pins = gpio.pins();
+    let mut machine = common::load(&program, vec![Box::new(gpio)]);
+    pins.drive(17, Some(false));
+
+    common::run_to(&mut machine, &program, "lit");
+    assert_eq!(machine.get_r6(), 1 << 13);
+    assert_eq!(pins.pull(17), Pull::Up);
+    assert_eq!(pins.sense(17), Sense::Low);
+    assert!(pins.detect());
+
+    pins.drive(17, None);
+    assert!(pins.level(17));
+    assert!(!pins.detect());
+}
+
+#[test]
+fn mirrors_the_direction_in_the_pin_configurations() {
+    let mut gpio = Gpio::new();
+    let pins = gpio.pins();
+
+    gpio.write(DIRSET, 4, 0b101);
+    assert_eq!(gpio.read(DIR, 4), 0b101);
+    assert_eq!(gpio.read(PIN_CNF, 4), 0b11);
+    gpio.write(DIRCLR, 4, 0b001);
+    assert_eq!(gpio.read(PIN_CNF, 4), 0b10);
+    gpio.write(DIR, 1, 0xF0);
+    assert_eq!(gpio.read(DIR, 4), 0xF0);
+
+    // NOTE: A byte access only changes the byte it reaches
+    gpio.write(OUT, 4, 0x1234_5678);
+    gpio.write(OUT + 1, 1, 0xAB);
+    assert_eq!(gpio.read(OUT, 4), 0x1234_AB78);
+    assert_eq!(gpio.read(OUT + 2, 2), 0x1234);
+
+    gpio.write(PIN_CNF + 4 * 3, 4, 0xFFFF_FFFF);
+    assert_eq!(gpio.read(PIN_CNF + 4 * 3, 4), 0x0003_070F);
+    assert_eq!(pins.drive_strength(3), 7);
+}
+
+#[test]
+fn restores_the_pins_from_a_snapshot() {
+    let program = program();
+    let gpio = Gpio::new();
+    let pins = gpio.pins();
+    let mut machine = common::load(&program, vec![Box::new(gpio)]);
+    machine.run().unwrap();
+    let snapshot = machine.snapshot().unwrap();
+
+    machine.set_pc(machine.get_pc() + 2);
+    machine.run().unwrap();
+    assert_eq!(pins.output(13), Some(false));
+    machine.restore_snapshot(&snapshot).unwrap();
+    assert_eq!(pins.output(13), Some(true));
+}
+
+#[test]
+fn keeps_the_configuration_through_system_off_only() {
+    let mut gpio = Gpio::new();
+    let pins = gpio.pins();
+    pins.drive(3, Some(true));
+    gpio.write(PIN_CNF + 4 * 5, 4, 1);
+    gpio.write(OUT, 4, 1 << 5);
+    assert_eq!(pins.output(5), Some(true));
+
+    gpio.reset(ResetCause::WakeUp);
+    assert_eq!(pins.output(5), Some(true));
+
+    gpio.reset(ResetCause::SoftReset);
+    assert_eq!(gpio.read(PIN_CNF + 4 * 5, 4), 2);
+    assert_eq!(gpio.read(OUT, 4), 0);
+    assert_eq!(pins.output(5), None);
+    assert!(pins.level(3));
+}
@@ -0,0 +1,156 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::gpio::{Gpio, PIN_CNF};
+use armv6_m::nrf51::gpiote::{Gpiote, Mode, Polarity, CONFIG, EVENTS_PORT, GPIOTE_IRQ};
+use armv6_m::nrf51::{INTEN, INTENSET};
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Toggles the LED on P0.13 with the GPIOTE task channel 1 each time the button on P0.17 is pressed,
+/// counting the presses in r7
+fn program() -> Program {
+    firmware!(
+        "    .space 80",
+        "    .word gpiote + 1",
+        "main:",
+        "    ldr r0, =0x50000700",
+        "    movs r1, #0xC",
+        "    str r1, [r0, #68]",
+        "    ldr r0, =0x40006510",
+        "    ldr r1, =0x21101",
+        "    str r1, [r0]",
+        "    ldr r1, =0x30D03",
+        "    str r1, [r0, #4]",
+        "    ldr r0, =0x40006304",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "    ldr r0, =0xE000E100",
+        "    movs r1, #64",
+        "    str r1, [r0]",
+        "    movs r7, #0",
+        "    cpsie i",
+        "sleep:",
+        "    wfi",
+        "    b sleep",
+        "gpiote:",
+        "    ldr r0, =0x40006100",
+        "    movs r1, #0",
+        "    str r1, [r0]",
+        "    ldr r0, =0x40006004",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "    adds r7, #1",
+        "    bx lr",
+    )
+}
+
+#[test]
+fn button_presses_reach_the_interrupt_handler() {
+    let program = program();
+    let gpio = Gpio::new();
+    let pins = gpio.pins();
+    let gpiote = Gpiote::new(gpio.pins());
+    let mut machine = common::load(&program, vec![Box::new(gpiote), Box::new(gpio)]);
+
+    assert_eq!(machine.run().unwrap(), StopReason::Sleeping);
+    assert_eq!(pins.output(13), Some(false));
+    assert_eq!(machine.get_r7(), 0);
+
+    pins.drive(17, Some(false));
+    assert_eq!(machine.run().unwrap(), StopReason::Sleeping);
+    assert_eq!(machine.get_r7(), 1);
+    assert_eq!(pins.output(13), Some(true));
+
+    // NOTE: Releasing the button is a rising edge, the channel only detects the falling ones
+    pins.drive(17, None);
+    assert_eq!(machine.run().unwrap(), StopReason::Sleeping);
+    assert_eq!(machine.get_r7(), 1);
+
+    pins.drive(17, Some(false));
+    assert_eq!(machine.run().unwrap(), StopReason::Sleeping);
+    assert_eq!(machine.get_r7(), 2);
+    assert_eq!(pins.output(13), Some(false));
+}
+
+#[test]
+fn decodes_the_channel_configurations() {
+    let program = program();
+    let gpio = Gpio::new();
+    let gpiote = Gpiote::new(gpio.pins());
+    let mut machine = common::load(&program, vec![Box::new(gpiote), Box::new(gpio)]);
+    machine.run().unwrap();
+
+    let gpiote = common::peripheral(&machine, "GPIOTE");
+    assert_eq!(gpiote.peek(CONFIG, 4), Some(0x21101));
+    assert_eq!(gpiote.peek(CONFIG + 4, 4), Some(0x30D03));
+}
+
+#[test]
+fn raises_the_port_event_on_sense() {
+    let mut gpio = Gpio::new();
+    let pins = gpio.pins();
+    let mut gpiote = Gpiote::new(gpio.pins());
+    gpiote.write(INTENSET, 4, 1 << 31);
+    // NOTE: The GPIOTE has no INTEN register, only INTENSET and INTENCLR
+    gpiote.write(INTEN, 4, 0);
+    assert_eq!(gpiote.read(INTEN, 4), 0);
+    assert_eq!(gpiote.read(INTENSET, 4), 1 << 31);
+
+    // NOTE: Pull-up and SENSE low
+    gpio.write(PIN_CNF + 4 * 26, 4, 0x3_000C);
+    assert_eq!(gpiote.next_event(), Some(0));
+    gpiote.tick(0);
+    assert_eq!(gpiote.next_event(), None);
+    pins.drive(26, Some(false));
+    assert_eq!(gpiote.next_event(), Some(0));
+    gpiote.tick(0);
+
+    assert_eq!(gpiote.read(EVENTS_PORT, 4), 1);
+    assert_eq!(gpiote.interrupt(), Some(GPIOTE_IRQ));
+    gpiote.write(EVENTS_PORT, 4, 0);
+    assert_eq!(gpiote.interrupt(), None);
+
+    // NOTE: The event is raised again only when DETECT rises again
+    pins.drive(26, Some(true));
+    gpiote.tick(0);
+    pins.drive(26, Some(false));
+    gpiote.tick(0);
+    assert_eq!(gpiote.read(EVENTS_PORT, 4), 1);
+}
+
+#[test]
+fn task_channels_take_over_their_pin() {
+    let gpio = Gpio::new();
+    let pins = gpio.pins();
+    let mut gpiote = Gpiote::new(gpio.pins());
+
+    gpiote.write(CONFIG + 8, 4, 3 | 4 << 8 | 1 << 16 | 1 << 20);
+    assert_eq!(gpiote.mode(2), Mode::Task);
+    assert_eq!(gpiote.polarity(2), Polarity::LoToHi);
+    assert_eq!(pins.output(4), Some(true));
+
+    gpiote.write(CONFIG + 8, 4, 3 | 4 << 8 | 2 << 16 | 1 << 20);
+    gpiote.write(8, 4, 1);
+    assert_eq!(pins.output(4), Some(false));
+
+    gpiote.write(CONFIG + 8, 4, 0);
+    assert_eq!(gpiote.mode(2), Mode::Disabled);
+    assert_eq!(pins.output(4), None);
+}
+
+#[test]
+fn releases_the_task_pins_on_reset() {
+    let gpio = Gpio::new();
+    let pins = gpio.pins();
+    let mut gpiote = Gpiote::new(gpio.pins());
+    gpiote.write(CONFIG + 4, 4, 3 | 13 << 8 | 3 << 16 | 1 << 20);
+    gpiote.write(INTENSET, 4, 1 << 31);
+    assert_eq!(pins.output(13), Some(true));
+
+    gpiote.reset(ResetCause::Pin);
+    assert_eq!(gpiote.mode(1), Mode::Disabled);
+    assert_eq!(gpiote.read(INTENSET, 4), 0);
+    assert_eq!(pins.output(13), None);
+}
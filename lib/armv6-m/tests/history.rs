@@ -0,0 +1,204 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::error::Error;
+use armv6_m::history::{CheckpointConfig, History};
+use armv6_m::memory::{Memory, PAGE_SIZE};
+use armv6_m::mutation::{MemoryChange, Mutation};
+use armv6_m::Armv6M;
+
+/// Write `value` at `address` in `memory`, recorded as a mutation
+fn write(memory: &mut Memory, address: u32, value: u32) -> Mutation {
+    let old = memory.read_u32(address);
+    memory.write_u32(address, value);
+    Mutation {
+        address: 0,
+        registers: Vec::new(),
+        memory: vec![MemoryChange {
+            address,
+            size: 4,
+            old,
+            new: value,
+        }],
+        cycles: 1,
+        debugger: false,
+    }
+}
+
+/// Push `count` writes made by `address_of` and return the memory after each of them
+fn record(
+    history: &mut History,
+    memory: &mut Memory,
+    count: u32,
+    address_of: impl Fn(u32) -> u32,
+) -> Vec<Memory> {
+    let mut expected = vec![memory.clone()];
+    for i in 0..count {
+        let mutation = write(memory, address_of(i), i + 1);
+        history.step(mutation, memory);
+        expected.push(memory.clone());
+    }
+    expected
+}
+
+#[test]
+fn rebuilds_memory_at_between_and_after_checkpoints() {
+    let config = CheckpointConfig {
+        interval: 4,
+        budget: usize::MAX,
+    };
+    let mut memory = Memory::new();
+    let mut history = History::new(config, &memory);
+    let expected = record(&mut history, &mut memory, 10, |i| 0x2000_0000 + 4 * (i % 3));
+
+    assert_eq!(history.checkpoints().collect::<Vec<_>>(), [0, 4, 8]);
+    for (idx, expected) in expected.iter().enumerate() {
+        assert_eq!(
+            history.memory_at(idx, &memory).as_ref(),
+            Some(expected),
+            "{idx}"
+        );
+    }
+    assert_eq!(history.memory_at(11, &memory), None);
+}
+
+#[test]
+fn pops_the_checkpoints_after_the_last_mutation() {
+    let config = CheckpointConfig {
+        interval: 2,
+        budget: usize::MAX,
+    };
+    let mut memory = Memory::new();
+    let mut history = History::new(config, &memory);
+    let expected = record(&mut history, &mut memory, 6, |i| 4 * i);
+
+    for _ in 0..3 {
+        history.pop().unwrap().rollback_memory(&mut memory);
+    }
+    assert_eq!(history.checkpoints().collect::<Vec<_>>(), [0, 2]);
+    assert_eq!(memory, expected[3]);
+    for (idx, expected) in expected[..=3].iter().enumerate() {
+        assert_eq!(
+            history.memory_at(idx, &memory).as_ref(),
+            Some(expected),
+            "{idx}"
+        );
+    }
+    assert_eq!(history.memory_at(4, &memory), None);
+}
+
+#[test]
+fn thins_out_old_checkpoints_over_budget() {
+    let config = CheckpointConfig {
+        interval: 1,
+        budget: 3 * PAGE_SIZE,
+    };
+    let mut memory = Memory::new();
+    memory.write_u32(0x2000_0000, u32::MAX);
+    let mut history = History::new(config, &memory);
+    // NOTE: Every write copies the same page, each checkpoint keeps its own copy alive
+    let expected = record(&mut history, &mut memory, 20, |i| 0x2000_0000 + 4 * i);
+
+    let checkpoints = history.checkpoints().collect::<Vec<_>>();
+    assert!(history.retained_bytes(&memory) <= config.budget);
+    assert_eq!(checkpoints.len(), 4);
+    assert_eq!(checkpoints.first(), Some(&0));
+    assert_eq!(checkpoints.last(), Some(&20));
+    for (idx, expected) in expected.iter().enumerate() {
+        assert_eq!(
+            history.memory_at(idx, &memory).as_ref(),
+            Some(expected),
+            "{idx}"
+        );
+    }
+
+    // NOTE: A smaller budget applies right away
+    history.set_config(
+        CheckpointConfig {
+            budget: PAGE_SIZE,
+            ..config
+        },
+        &memory,
+    );
+    assert_eq!(history.checkpoints().count(), 2);
+    assert_eq!(history.memory_at(7, &memory).as_ref(), Some(&expected[7]));
+}
+
+#[test]
+fn returns_the_machine_memory_at_each_mutation() {
+    // NOTE: Stores an incrementing counter in RAM until it reaches 8
+    let code: [u16; 8] = [
+        0x4802, // ldr r0, [pc, #8]
+        0x2100, // movs r1, #0
+        0x3101, // loop: adds r1, #1
+        0x6001, // str r1, [r0]
+        0x2908, // cmp r1, #8
+        0xD1FB, // bne loop
+        0xBE00, // bkpt #0
+        0x0000, // .align 2
+    ];
+    let mut bytes = [0x2000_1000u32, 0x41, 0, 0]
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect::<Vec<_>>();
+    bytes.resize(0x40, 0);
+    bytes.extend(code.iter().flat_map(|halfword| halfword.to_le_bytes()));
+    bytes.extend(0x2000_0000u32.to_le_bytes());
+
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&bytes).unwrap();
+    machine.set_checkpoint_config(CheckpointConfig {
+        interval: 5,
+        budget: usize::MAX,
+    });
+
+    let mut expected = vec![machine.get_memory().clone()];
+    while machine.step().unwrap() != Some(StopReason::Breakpoint(0x4C)) {
+        expected.push(machine.get_memory().clone());
+    }
+
+    let len = machine.get_mutations_history().len();
+    assert_eq!(len, expected.len() - 1);
+    for (idx, expected) in expected.iter().enumerate() {
+        assert_eq!(
+            &machine.get_memory_at_mutation(idx).unwrap(),
+            expected,
+            "{idx}"
+        );
+    }
+    assert!(matches!(
+        machine.get_memory_at_mutation(len + 1),
+        Err(Error::MutationOutOfRange { index, len: actual }) if index == len + 1 && actual == len
+    ));
+}
+
+#[test]
+fn counts_executed_instructions_between_checkpoints() {
+    let config = CheckpointConfig {
+        interval: 3,
+        budget: usize::MAX,
+    };
+    let mut memory = Memory::new();
+    let mut history = History::new(config, &memory);
+
+    // NOTE: Instructions without side effects are not recorded but still count
+    history.step(Mutation::default(), &memory);
+    history.step(Mutation::default(), &memory);
+    let mutation = write(&mut memory, 0x2000_0000, 1);
+    history.step(mutation, &memory);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.checkpoints().collect::<Vec<_>>(), [0, 1]);
+
+    // NOTE: Mutations made by the debugger are not instructions
+    let mutation = write(&mut memory, 0x2000_0004, 2);
+    history.push(mutation);
+    assert_eq!(history.checkpoints().collect::<Vec<_>>(), [0, 1]);
+
+    for _ in 0..6 {
+        history.step(Mutation::default(), &memory);
+    }
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.checkpoints().collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(
+        history.memory_at(1, &memory).unwrap().read_u32(0x2000_0004),
+        0
+    );
+}
@@ -0,0 +1,307 @@
+use armv6_m::abi::{Runtime, RuntimeExtras, StopReason};
+use armv6_m::error::Error;
+use armv6_m::structure::{RegisterId, EXC_RETURN_THREAD_MAIN};
+use armv6_m::Armv6M;
+
+const STACK: u32 = 0x2000_1000;
+const CODE: u32 = 0x40;
+const HARD_FAULT: u32 = 0x200;
+const SV_CALL: u32 = 0x300;
+
+/// Vector table, `code` at `CODE` and default handlers.
+/// HardFault stops on `BKPT #1`, SVCall increments R4 and returns.
+fn image(code: &[u16]) -> Vec<u8> {
+    let mut bytes = vec![0; 0x400];
+    let mut vector = |number: usize, address: u32| {
+        bytes[4 * number..4 * number + 4].copy_from_slice(&address.to_le_bytes());
+    };
+    vector(0, STACK);
+    vector(1, CODE | 1);
+    vector(3, HARD_FAULT | 1);
+    vector(11, SV_CALL | 1);
+
+    let mut place = |address: u32, halfwords: &[u16]| {
+        for (i, halfword) in halfwords.iter().enumerate() {
+            let at = address as usize + 2 * i;
+            bytes[at..at + 2].copy_from_slice(&halfword.to_le_bytes());
+        }
+    };
+    place(CODE, code);
+    // bkpt #1
+    place(HARD_FAULT, &[0xBE01]);
+    // adds r4, #1 ; bx lr
+    place(SV_CALL, &[0x3401, 0x4770]);
+    bytes
+}
+
+fn load(bytes: &[u8]) -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(bytes).unwrap();
+    machine
+}
+
+/// Run `code` until it reaches a breakpoint
+fn run(code: &[u16]) -> (Armv6M, StopReason) {
+    let mut machine = load(&image(code));
+    let reason = machine.run().unwrap();
+    (machine, reason)
+}
+
+/// Address of the `index`th halfword of the code
+fn at(index: u32) -> u32 {
+    CODE + 2 * index
+}
+
+#[test]
+fn resets_from_the_vector_table() {
+    let machine = load(&image(&[0xBE00]));
+
+    assert_eq!(machine.get_pc(), CODE);
+    assert_eq!(machine.get_sp(), STACK);
+    assert_eq!(machine.get_lr(), u32::MAX);
+    assert_eq!(machine.get_xpsr(), 1 << 24);
+    assert!(machine.is_privileged());
+    assert!(!machine.is_handler_mode());
+}
+
+#[test]
+fn adds_set_carry_and_overflow() {
+    let (machine, reason) = run(&[
+        0x43C0, // mvns r0, r0
+        0x0840, // lsrs r0, r0, #1
+        0x2101, // movs r1, #1
+        0x1842, // adds r2, r0, r1
+        0xBE00, // bkpt #0
+    ]);
+    assert_eq!(reason, StopReason::Breakpoint(at(4)));
+    assert_eq!(machine.get_r2(), 0x8000_0000);
+    let apsr = machine.get_apsr();
+    assert!(apsr.n() && !apsr.z() && !apsr.c() && apsr.v());
+
+    let (machine, _) = run(&[
+        0x2080, // movs r0, #0x80
+        0x0600, // lsls r0, r0, #24
+        0x1800, // adds r0, r0, r0
+        0x4149, // adcs r1, r1
+        0xBE00, // bkpt #0
+    ]);
+    assert_eq!(machine.get_r0(), 0);
+    assert_eq!(machine.get_r1(), 1);
+    let apsr = machine.get_apsr();
+    assert!(!apsr.n() && !apsr.z() && !apsr.c() && !apsr.v());
+}
+
+#[test]
+fn loops_on_conditional_branches() {
+    let (machine, reason) = run(&[
+        0x2000, // movs r0, #0
+        0x2105, // movs r1, #5
+        0x1840, // loop: adds r0, r0, r1
+        0x3901, // subs r1, #1
+        0xD1FC, // bne loop
+        0x2805, // cmp r0, #5
+        0xDC00, // bgt skip
+        0x2000, // movs r0, #0
+        0xBE00, // skip: bkpt #0
+    ]);
+    assert_eq!(reason, StopReason::Breakpoint(at(8)));
+    assert_eq!(machine.get_r0(), 15);
+    assert_eq!(machine.get_r1(), 0);
+    let apsr = machine.get_apsr();
+    assert!(!apsr.n() && !apsr.z() && apsr.c() && !apsr.v());
+}
+
+#[test]
+fn shifts_out_into_the_carry() {
+    let (machine, _) = run(&[
+        0x2280, // movs r2, #0x80
+        0x0612, // lsls r2, r2, #24
+        0x1113, // asrs r3, r2, #4
+        0x0FD4, // lsrs r4, r2, #31
+        0x2081, // movs r0, #0x81
+        0x0641, // lsls r1, r0, #25
+        0xBE00, // bkpt #0
+    ]);
+    assert_eq!(machine.get_r1(), 0x0200_0000);
+    assert_eq!(machine.get_r3(), 0xF800_0000);
+    assert_eq!(machine.get_r4(), 1);
+    assert!(machine.get_apsr().c());
+}
+
+#[test]
+fn loads_and_stores_memory() {
+    let (machine, _) = run(&[
+        0x4803, // ldr r0, [pc, #12]
+        0x4904, // ldr r1, [pc, #16]
+        0x6041, // str r1, [r0, #4]
+        0x7201, // strb r1, [r0, #8]
+        0x2208, // movs r2, #8
+        0x5683, // ldrsb r3, [r0, r2]
+        0x8884, // ldrh r4, [r0, #4]
+        0xBE00, // bkpt #0
+        0x0000, 0x2000, // .word 0x20000000
+        0xBEEF, 0xDEAD, // .word 0xdeadbeef
+    ]);
+    assert_eq!(machine.get_r0(), 0x2000_0000);
+    assert_eq!(machine.get_memory().read_u32(0x2000_0004), 0xDEAD_BEEF);
+    assert_eq!(machine.get_memory().read_u32(0x2000_0008), 0xEF);
+    assert_eq!(machine.get_r3(), 0xFFFF_FFEF);
+    assert_eq!(machine.get_r4(), 0xBEEF);
+}
+
+#[test]
+fn calls_and_returns_through_the_stack() {
+    let (machine, reason) = run(&[
+        0x2001, // movs r0, #1
+        0x2403, // movs r4, #3
+        0xF000, 0xF801, // bl func
+        0xBE00, // bkpt #0
+        0xB510, // func: push {r4, lr}
+        0x2407, // movs r4, #7
+        0x1900, // adds r0, r0, r4
+        0xBD10, // pop {r4, pc}
+    ]);
+    assert_eq!(reason, StopReason::Breakpoint(at(4)));
+    assert_eq!(machine.get_r0(), 8);
+    assert_eq!(machine.get_r4(), 3);
+    assert_eq!(machine.get_sp(), STACK);
+    assert_eq!(machine.get_lr(), at(4) | 1);
+    assert_eq!(machine.get_memory().read_u32(STACK - 8), 3);
+    assert_eq!(machine.get_memory().read_u32(STACK - 4), at(4) | 1);
+}
+
+#[test]
+fn transfers_multiple_registers_and_extends() {
+    let (machine, _) = run(&[
+        0x4804, // ldr r0, [pc, #16]
+        0x4905, // ldr r1, [pc, #20]
+        0x1C02, // adds r2, r0, #0
+        0xC202, // stmia r2!, {r1}
+        0xC808, // ldmia r0!, {r3}
+        0xBA1C, // rev r4, r3
+        0xB265, // sxtb r5, r4
+        0xB29E, // uxth r6, r3
+        0xBE00, // bkpt #0
+        0x0000, // .align 2
+        0x0000, 0x2000, // .word 0x20000000
+        0x5680, 0x1234, // .word 0x12345680
+    ]);
+    assert_eq!(machine.get_r0(), 0x2000_0004);
+    assert_eq!(machine.get_r2(), 0x2000_0004);
+    assert_eq!(machine.get_r3(), 0x1234_5680);
+    assert_eq!(machine.get_r4(), 0x8056_3412);
+    assert_eq!(machine.get_r5(), 0x12);
+    assert_eq!(machine.get_r6(), 0x5680);
+}
+
+#[test]
+fn enters_and_returns_from_supervisor_calls() {
+    let mut machine = load(&image(&[
+        0x2029, // movs r0, #41
+        0x2429, // movs r4, #41
+        0xDF00, // svc #0
+        0xBE00, // bkpt #0
+    ]));
+    for _ in 0..3 {
+        machine.step().unwrap();
+    }
+
+    assert!(machine.is_handler_mode());
+    assert_eq!(machine.get_pc(), SV_CALL);
+    assert_eq!(machine.read_register(RegisterId::Ipsr), 11);
+    assert_eq!(machine.get_lr(), EXC_RETURN_THREAD_MAIN);
+    assert_eq!(machine.get_sp(), STACK - 0x20);
+    // NOTE: The stacked return address is the instruction after the SVC
+    assert_eq!(machine.get_memory().read_u32(STACK - 0x20 + 24), at(3));
+
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(at(3)));
+    assert!(!machine.is_handler_mode());
+    // NOTE: R0-R3 are unstacked on return, the handler only changed R4
+    assert_eq!(machine.get_r0(), 41);
+    assert_eq!(machine.get_r4(), 42);
+    assert_eq!(machine.get_sp(), STACK);
+}
+
+#[test]
+fn escalates_unaligned_accesses_to_hard_fault() {
+    let (machine, reason) = run(&[
+        0x2001, // movs r0, #1
+        0x6801, // ldr r1, [r0]
+        0xBE00, // bkpt #0
+    ]);
+    assert_eq!(reason, StopReason::Breakpoint(HARD_FAULT));
+    assert_eq!(machine.read_register(RegisterId::Ipsr), 3);
+    // NOTE: The faulting instruction is stacked, it did not complete
+    let frame = machine.get_sp();
+    assert_eq!(machine.get_memory().read_u32(frame + 24), at(1));
+    assert_eq!(machine.get_r1(), 0);
+}
+
+#[test]
+fn locks_up_on_a_fault_in_the_hard_fault_handler() {
+    let mut bytes = image(&[0xDE00]); // udf #0
+                                      // NOTE: A vector without the Thumb bit faults as soon as the handler runs
+    bytes[12..16].copy_from_slice(&HARD_FAULT.to_le_bytes());
+    let mut machine = load(&bytes);
+
+    assert!(matches!(machine.run(), Err(Error::Lockup(HARD_FAULT))));
+}
+
+#[test]
+fn masks_interrupts_with_primask() {
+    let (machine, _) = run(&[
+        0xB672, // cpsid i
+        0xF3EF, 0x8010, // mrs r0, PRIMASK
+        0xB662, // cpsie i
+        0xF3EF, 0x8110, // mrs r1, PRIMASK
+        0xBE00, // bkpt #0
+    ]);
+    assert_eq!(machine.get_r0(), 1);
+    assert_eq!(machine.get_r1(), 0);
+}
+
+#[test]
+fn records_and_rolls_back_mutations() {
+    let mut machine = load(&image(&[
+        0x4802, // ldr r0, [pc, #8]
+        0x2107, // movs r1, #7
+        0x6001, // str r1, [r0]
+        0xBF00, // nop
+        0xBE00, // bkpt #0
+        0x0000, // .align 2
+        0x0000, 0x2000, // .word 0x20000000
+    ]));
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(at(4)));
+
+    // NOTE: The breakpoint is not executed, every other step moves at least the PC
+    let history = machine.get_mutations_history();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[2].address, at(2));
+    assert_eq!(history[2].memory.len(), 1);
+    assert_eq!(machine.get_memory().read_u32(0x2000_0000), 7);
+    assert_eq!(
+        machine
+            .get_memory_at_mutation(2)
+            .unwrap()
+            .read_u32(0x2000_0000),
+        0
+    );
+    assert_eq!(
+        &machine.get_memory_at_mutation(4).unwrap(),
+        machine.get_memory()
+    );
+    assert!(matches!(
+        machine.get_memory_at_mutation(5),
+        Err(Error::MutationOutOfRange { index: 5, len: 4 })
+    ));
+
+    machine.rollback_last_mutation().unwrap();
+    machine.rollback_last_mutation().unwrap();
+    assert_eq!(machine.get_pc(), at(2));
+    assert_eq!(machine.get_r1(), 7);
+    assert_eq!(machine.get_memory().read_u32(0x2000_0000), 0);
+
+    let replayed = machine.run().unwrap();
+    assert_eq!(replayed, StopReason::Breakpoint(at(4)));
+    assert_eq!(machine.get_memory().read_u32(0x2000_0000), 7);
+}
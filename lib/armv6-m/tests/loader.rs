@@ -0,0 +1,156 @@
+use armv6_m::abi::Runtime;
+use armv6_m::error::Error;
+use armv6_m::Armv6M;
+
+/// Intel HEX record with its checksum
+fn record(kind: u8, offset: u16, data: &[u8]) -> String {
+    let mut bytes = vec![data.len() as u8];
+    bytes.extend(offset.to_be_bytes());
+    bytes.push(kind);
+    bytes.extend(data);
+    let checksum = bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg();
+    bytes.push(checksum);
+
+    let digits: String = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+    format!(":{digits}\n")
+}
+
+/// ELF32 little endian executable with a single `PT_LOAD` segment
+fn elf(vaddr: u32, paddr: u32, data: &[u8]) -> Vec<u8> {
+    const EHSIZE: u16 = 52;
+    const PHENTSIZE: u16 = 32;
+
+    let mut bytes = b"\x7fELF\x01\x01\x01".to_vec();
+    bytes.resize(16, 0);
+    bytes.extend(2u16.to_le_bytes()); // e_type: ET_EXEC
+    bytes.extend(40u16.to_le_bytes()); // e_machine: EM_ARM
+    bytes.extend(1u32.to_le_bytes()); // e_version
+    bytes.extend((vaddr | 1).to_le_bytes()); // e_entry
+    bytes.extend(u32::from(EHSIZE).to_le_bytes()); // e_phoff
+    bytes.extend(0u32.to_le_bytes()); // e_shoff
+    bytes.extend(0x0500_0000u32.to_le_bytes()); // e_flags
+    bytes.extend(EHSIZE.to_le_bytes());
+    bytes.extend(PHENTSIZE.to_le_bytes());
+    bytes.extend(1u16.to_le_bytes()); // e_phnum
+    bytes.extend(40u16.to_le_bytes()); // e_shentsize
+    bytes.extend(0u16.to_le_bytes()); // e_shnum
+    bytes.extend(0u16.to_le_bytes()); // e_shstrndx
+
+    let offset = u32::from(EHSIZE + PHENTSIZE);
+    bytes.extend(1u32.to_le_bytes()); // p_type: PT_LOAD
+    bytes.extend(offset.to_le_bytes());
+    bytes.extend(vaddr.to_le_bytes());
+    bytes.extend(paddr.to_le_bytes());
+    bytes.extend((data.len() as u32).to_le_bytes()); // p_filesz
+    bytes.extend((data.len() as u32).to_le_bytes()); // p_memsz
+    bytes.extend(5u32.to_le_bytes()); // p_flags: R X
+    bytes.extend(4u32.to_le_bytes()); // p_align
+
+    bytes.extend(data);
+    bytes
+}
+
+#[test]
+fn loads_raw_bytes_at_the_start_of_flash() {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&[0x78, 0x56, 0x34, 0x12, 0xAA]).unwrap();
+
+    assert_eq!(machine.get_memory().read_u32(0), 0x1234_5678);
+    assert_eq!(machine.get_memory().read_u8(4), 0xAA);
+    assert_eq!(machine.get_memory().read_u8(5), 0);
+}
+
+#[test]
+fn loads_hex_records_with_extended_addresses() {
+    let hex = [
+        record(0x00, 0x0010, &[0xDE, 0xAD, 0xBE, 0xEF]),
+        record(0x04, 0x0000, &[0x20, 0x00]),
+        record(0x00, 0x0004, &[0x01, 0x02]),
+        record(0x02, 0x0000, &[0x10, 0x00]),
+        record(0x00, 0x0000, &[0x03]),
+        record(0x05, 0x0000, &[0x00, 0x00, 0x00, 0xC1]),
+        record(0x01, 0x0000, &[]),
+        // NOTE: Anything after the End Of File record is ignored, like the MakeCode sources
+        "garbage after the end\n".to_string(),
+    ]
+    .concat();
+
+    let mut machine = Armv6M::init();
+    machine.load_hex(&hex).unwrap();
+
+    let memory = machine.get_memory();
+    assert_eq!(memory.read_u32(0x10), 0xEFBE_ADDE);
+    assert_eq!(memory.read_u16(0x2000_0004), 0x0201);
+    assert_eq!(memory.read_u8(0x0001_0000), 0x03);
+}
+
+#[test]
+fn rejects_malformed_hex_records() {
+    let mut bad_checksum = record(0x00, 0x0000, &[0x01, 0x02]);
+    bad_checksum.replace_range(bad_checksum.len() - 3.., "00\n");
+    let cases = [
+        ("0200000001FD\n".to_string(), "malformed record"),
+        (":0400000001020304\n".to_string(), "record length mismatch"),
+        (bad_checksum, "bad checksum"),
+        (record(0x07, 0x0000, &[]), "unsupported record"),
+    ];
+
+    for (hex, expected) in cases {
+        let mut machine = Armv6M::init();
+        let hex = format!("{}{hex}", record(0x00, 0x0000, &[0xFF]));
+        match machine.load_hex(&hex).map(|_| ()) {
+            Err(Error::Hex { line: 2, reason }) => assert_eq!(reason, expected),
+            other => panic!("{expected}: unexpected {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn loads_elf_segments_at_their_physical_address() {
+    // NOTE: Initialized data runs from RAM but is stored in flash, after the code
+    let bytes = elf(0x2000_0000, 0x0000_0100, &[0x11, 0x22, 0x33, 0x44]);
+    let path = std::env::temp_dir().join(format!("armv6-m-loader-{}.elf", std::process::id()));
+    std::fs::write(&path, bytes).unwrap();
+
+    let mut machine = Armv6M::init();
+    let loaded = machine.load_file(path.to_str().unwrap()).map(|_| ());
+    std::fs::remove_file(&path).unwrap();
+    loaded.unwrap();
+
+    assert_eq!(machine.get_memory().read_u32(0x100), 0x4433_2211);
+    assert_eq!(machine.get_memory().read_u32(0x2000_0000), 0);
+}
+
+#[test]
+fn detects_the_file_format() {
+    let directory = std::env::temp_dir();
+    let hex = directory.join(format!("armv6-m-loader-{}.HEX", std::process::id()));
+    let raw = directory.join(format!("armv6-m-loader-{}.bin", std::process::id()));
+    std::fs::write(
+        &hex,
+        [record(0x00, 0x0020, &[0x42]), record(0x01, 0, &[])].concat(),
+    )
+    .unwrap();
+    std::fs::write(&raw, [0x42]).unwrap();
+
+    let mut from_hex = Armv6M::init();
+    let mut from_raw = Armv6M::init();
+    let loaded = (
+        from_hex.load_file(hex.to_str().unwrap()).map(|_| ()),
+        from_raw.load_file(raw.to_str().unwrap()).map(|_| ()),
+    );
+    std::fs::remove_file(&hex).unwrap();
+    std::fs::remove_file(&raw).unwrap();
+    loaded.0.unwrap();
+    loaded.1.unwrap();
+
+    assert_eq!(from_hex.get_memory().read_u8(0x20), 0x42);
+    assert_eq!(from_raw.get_memory().read_u8(0), 0x42);
+    assert!(matches!(
+        Armv6M::init().load_file("/nonexistent/firmware.hex"),
+        Err(Error::Io(_))
+    ));
+}
@@ -0,0 +1,219 @@
+use armv6_m::abi::{Runtime, RuntimeExtras, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::gpio::Gpio;
+use armv6_m::nrf51::power::{
+    LfclkSource, PowerClock, CALIBRATION, CTIV, EVENTS_CTTO, EVENTS_DONE, GPREGRET, HFCLKSTAT,
+    HFXO_STARTUP, LFCLKSTAT, LFRC_STARTUP, POWER_CLOCK_IRQ, RAMON, RAMSTATUS, RESETREAS,
+    RESETREAS_LOCKUP, RESETREAS_OFF, RESETREAS_SREQ, TASKS_CAL, TASKS_CTSTART, TASKS_LFCLKSTART,
+};
+use armv6_m::nrf51::INTENSET;
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn starts_the_clocks_polled_by_the_startup_code() {
+    let program = firmware!(
+        "main:",
+        "    ldr r0, =0x40000000",
+        "    ldr r4, =0x40000100",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "hfclk:",
+        "    ldr r2, [r4]",
+        "    cmp r2, #0",
+        "    beq hfclk",
+        "    str r1, [r0, #8]",
+        "lfclk:",
+        "    ldr r2, [r4, #4]",
+        "    cmp r2, #0",
+        "    beq lfclk",
+        "started:",
+        "    bkpt #0",
+    );
+    let gpio = Gpio::new();
+    let power_clock = PowerClock::new(gpio.pins());
+    let mut machine = common::load(&program, vec![Box::new(power_clock), Box::new(gpio)]);
+
+    common::run_to(&mut machine, &program, "started");
+    assert!(machine.cycles() > HFXO_STARTUP + LFRC_STARTUP);
+
+    let power_clock = common::peripheral(&machine, "POWER_CLOCK");
+    assert_eq!(power_clock.peek(HFCLKSTAT, 4), Some(0x1_0001));
+    assert_eq!(power_clock.peek(LFCLKSTAT, 4), Some(0x1_0000));
+}
+
+/// Enters System OFF with the button on P0.17 sensed low, stops at `woken` once the button woke
+/// the system up with RESETREAS in r6
+fn system_off() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r0, =0x40000400",
+        "    ldr r6, [r0]",
+        "    cmp r6, #0",
+        "    bne woken",
+        "    ldr r1, =0x50000744",
+        "    ldr r2, =0x3000C",
+        "    str r2, [r1]",
+        "    ldr r1, =0x4000051C",
+        "    movs r2, #7",
+        "    str r2, [r1]",
+        "    ldr r1, =0x40000500",
+        "    movs r2, #1",
+        "    str r2, [r1]",
+        "off:",
+        "    b off",
+        "woken:",
+        "    bkpt #0",
+    )
+}
+
+#[test]
+fn wakes_up_from_system_off_on_sense() {
+    let program = system_off();
+    let gpio = Gpio::new();
+    let pins = gpio.pins();
+    let power_clock = PowerClock::new(gpio.pins());
+    let mut machine = common::load(&program, vec![Box::new(power_clock), Box::new(gpio)]);
+
+    assert_eq!(machine.run().unwrap(), StopReason::SystemOff);
+    let cycles = machine.cycles();
+    assert_eq!(machine.run().unwrap(), StopReason::SystemOff);
+    assert_eq!(machine.cycles(), cycles);
+
+    pins.drive(17, Some(false));
+    common::run_to(&mut machine, &program, "woken");
+    assert_eq!(machine.get_r6(), RESETREAS_OFF);
+    assert_eq!(
+        common::peripheral(&machine, "POWER_CLOCK").peek(GPREGRET, 4),
+        Some(7)
+    );
+}
+
+/// Writes 0x5A to GPREGRET and requests a soft reset, stops at `rebooted` with RESETREAS in r6
+fn soft_reset() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r0, =0x40000400",
+        "    ldr r6, [r0]",
+        "    cmp r6, #0",
+        "    bne rebooted",
+        "    ldr r1, =0x4000051C",
+        "    movs r2, #0x5A",
+        "    str r2, [r1]",
+        "    ldr r0, =0xE000ED0C",
+        "    ldr r1, =0x05FA0004",
+        "    str r1, [r0]",
+        "wait:",
+        "    b wait",
+        "rebooted:",
+        "    bkpt #0",
+    )
+}
+
+#[test]
+fn records_the_soft_resets() {
+    let program = soft_reset();
+    let gpio = Gpio::new();
+    let power_clock = PowerClock::new(gpio.pins());
+    let mut machine = common::load(&program, vec![Box::new(power_clock), Box::new(gpio)]);
+
+    common::run_to(&mut machine, &program, "rebooted");
+    assert_eq!(machine.get_r6(), RESETREAS_SREQ);
+    assert_eq!(
+        common::peripheral(&machine, "POWER_CLOCK").peek(GPREGRET, 4),
+        Some(0x5A)
+    );
+
+    // NOTE: RESETREAS is cleared by writing 1 to its bits, a power-on reset clears everything
+    machine.system_reset(ResetCause::Pin);
+    assert_eq!(
+        common::peripheral(&machine, "POWER_CLOCK").peek(RESETREAS, 4),
+        Some(RESETREAS_SREQ | 1)
+    );
+    machine.system_reset(ResetCause::PowerOn);
+    assert_eq!(
+        common::peripheral(&machine, "POWER_CLOCK").peek(RESETREAS, 4),
+        Some(0)
+    );
+    assert_eq!(
+        common::peripheral(&machine, "POWER_CLOCK").peek(GPREGRET, 4),
+        Some(0)
+    );
+    assert_eq!(machine.get_pc(), program.label("main").unwrap());
+}
+
+#[test]
+fn rolls_back_over_a_system_reset() {
+    let program = soft_reset();
+    let gpio = Gpio::new();
+    let power_clock = PowerClock::new(gpio.pins());
+    let mut machine = common::load(&program, vec![Box::new(power_clock), Box::new(gpio)]);
+
+    common::run_to(&mut machine, &program, "rebooted");
+    let wait = program.label("wait").unwrap();
+    let reset = machine
+        .get_mutations_history()
+        .iter()
+        .find(|mutation| mutation.address == wait)
+        .unwrap();
+    assert_eq!(reset.cycles, 0);
+
+    while machine.get_pc() != wait {
+        machine.rollback_last_mutation().unwrap();
+    }
+    assert_eq!(machine.get_r0(), 0xE000_ED0C);
+    assert_eq!(machine.get_r1(), 0x05FA_0004);
+    assert_eq!(machine.get_r6(), 0);
+}
+
+#[test]
+fn resets_on_lockup() {
+    let program = firmware!(
+        "    .word 0",
+        "    .word hard_fault",
+        "main:",
+        "    ldr r0, =0x40000400",
+        "    ldr r6, [r0]",
+        "    cmp r6, #0",
+        "    bne rebooted",
+        "    udf #0",
+        "rebooted:",
+        "    bkpt #0",
+        "hard_fault:",
+        "    b hard_fault",
+    );
+    let gpio = Gpio::new();
+    let power_clock = PowerClock::new(gpio.pins());
+    let mut machine = common::load(&program, vec![Box::new(power_clock), Box::new(gpio)]);
+    machine.set_reset_on_lockup(true);
+
+    common::run_to(&mut machine, &program, "rebooted");
+    assert_eq!(machine.get_r6(), RESETREAS_LOCKUP);
+}
+
+#[test]
+fn calibrates_the_rc_oscillator() {
+    let mut power_clock = PowerClock::new(Gpio::new().pins());
+    power_clock.write(INTENSET, 4, 1 << 3 | 1 << 4);
+    power_clock.write(TASKS_LFCLKSTART, 4, 1);
+    power_clock.write(TASKS_CAL, 4, 1);
+    power_clock.write(CTIV, 4, 2);
+    power_clock.write(TASKS_CTSTART, 4, 1);
+
+    power_clock.tick(CALIBRATION - 1);
+    assert_eq!(power_clock.lfclk(), Some(LfclkSource::Rc));
+    assert_eq!(power_clock.read(EVENTS_DONE, 4), 0);
+    power_clock.tick(1);
+    assert_eq!(power_clock.read(EVENTS_DONE, 4), 1);
+    assert_eq!(power_clock.interrupt(), Some(POWER_CLOCK_IRQ));
+
+    assert_eq!(power_clock.next_event(), Some(8_000_000 - CALIBRATION));
+    power_clock.tick(8_000_000);
+    assert_eq!(power_clock.read(EVENTS_CTTO, 4), 1);
+    assert_eq!(power_clock.next_event(), None);
+
+    power_clock.write(RAMON, 4, 1);
+    assert_eq!(power_clock.read(RAMSTATUS, 4), 0b1101);
+}
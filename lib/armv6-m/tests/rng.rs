@@ -0,0 +1,136 @@
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::rng::{
+    Rng, CONFIG, CORRECTED_GENERATION, EVENTS_VALRDY, GENERATION, RNG_IRQ, TASKS_START, TASKS_STOP,
+    VALUE,
+};
+use armv6_m::nrf51::{INTENSET, SHORTS};
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Reads two random bytes in r6 and r7
+fn program() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r0, =0x4000D000",
+        "    ldr r4, =0x4000D100",
+        "    ldr r5, =0x4000D508",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "    bl random",
+        "    mov r6, r2",
+        "    bl random",
+        "    mov r7, r2",
+        "done:",
+        "    bkpt #0",
+        "random:",
+        "    ldr r2, [r4]",
+        "    cmp r2, #0",
+        "    beq random",
+        "    movs r2, #0",
+        "    str r2, [r4]",
+        "    ldr r2, [r5]",
+        "    bx lr",
+    )
+}
+
+fn random_bytes(rng: Rng) -> (u32, u32) {
+    let program = program();
+    let mut machine = common::load(&program, vec![Box::new(rng)]);
+    common::run_to(&mut machine, &program, "done");
+    assert!(machine.cycles() > 2 * GENERATION);
+    (machine.get_r6(), machine.get_r7())
+}
+
+#[test]
+fn reproduces_the_bytes_of_a_seed() {
+    let bytes = random_bytes(Rng::new(42));
+    assert_eq!(random_bytes(Rng::new(42)), bytes);
+    assert_ne!(random_bytes(Rng::new(43)), bytes);
+
+    let rng = Rng::from_entropy();
+    let seed = rng.seed();
+    assert_eq!(random_bytes(rng), random_bytes(Rng::new(seed)));
+}
+
+#[test]
+fn generates_a_byte_after_the_delay() {
+    let mut rng = Rng::new(7);
+    rng.write(INTENSET, 4, 1);
+    rng.write(TASKS_START, 4, 1);
+    assert_eq!(rng.next_event(), Some(GENERATION));
+
+    rng.tick(GENERATION - 1);
+    assert_eq!(rng.read(EVENTS_VALRDY, 4), 0);
+    rng.tick(1);
+    assert_eq!(rng.read(EVENTS_VALRDY, 4), 1);
+    assert_eq!(rng.interrupt(), Some(RNG_IRQ));
+    let first = rng.read(VALUE, 4);
+    assert!(first <= 0xFF);
+
+    // NOTE: The error correction slows the generation down
+    rng.write(TASKS_STOP, 4, 1);
+    rng.write(CONFIG, 4, 1);
+    assert!(rng.is_corrected());
+    rng.write(TASKS_START, 4, 1);
+    assert_eq!(rng.next_event(), Some(CORRECTED_GENERATION));
+}
+
+#[test]
+fn stops_after_a_byte_with_the_shortcut() {
+    let mut rng = Rng::new(7);
+    rng.write(SHORTS, 4, 1);
+    rng.write(TASKS_START, 4, 1);
+
+    rng.tick(10 * GENERATION);
+    assert_eq!(rng.read(EVENTS_VALRDY, 4), 1);
+    assert_eq!(rng.next_event(), None);
+    assert_eq!(rng.interrupt(), None);
+
+    // NOTE: A snapshot keeps the generator, the same bytes follow
+    let state = rng.save_state().unwrap();
+    rng.write(TASKS_START, 4, 1);
+    rng.tick(GENERATION);
+    let next = rng.read(VALUE, 4);
+    rng.load_state(&state).unwrap();
+    rng.write(TASKS_START, 4, 1);
+    rng.tick(GENERATION);
+    assert_eq!(rng.read(VALUE, 4), next);
+}
+
+#[test]
+fn goes_on_with_the_bytes_after_a_reset() {
+    let generate = |rng: &mut Rng| {
+        rng.write(TASKS_START, 4, 1);
+        rng.tick(GENERATION);
+        rng.write(TASKS_STOP, 4, 1);
+        rng.read(VALUE, 4)
+    };
+    let mut reference = Rng::new(3);
+    let expected = [generate(&mut reference), generate(&mut reference)];
+
+    let mut rng = Rng::new(3);
+    assert_eq!(generate(&mut rng), expected[0]);
+    rng.write(CONFIG, 4, 1);
+    rng.reset(ResetCause::SoftReset);
+    assert_eq!(rng.read(CONFIG, 4), 0);
+    assert_eq!(rng.read(EVENTS_VALRDY, 4), 0);
+    assert_eq!(generate(&mut rng), expected[1]);
+}
+
+#[test]
+fn holds_a_single_read_only_byte_in_value() {
+    let mut rng = Rng::new(7);
+    // NOTE: CONFIG only has the DERCEN bit
+    rng.write(CONFIG, 4, u32::MAX);
+    assert_eq!(rng.read(CONFIG, 4), 1);
+    assert!(rng.is_corrected());
+
+    rng.write(TASKS_START, 4, 1);
+    rng.tick(CORRECTED_GENERATION);
+    let value = rng.read(VALUE, 4);
+    rng.write(VALUE, 4, !value);
+    assert_eq!(rng.read(VALUE, 4), value);
+    assert_eq!(rng.read(VALUE + 1, 1), 0);
+}
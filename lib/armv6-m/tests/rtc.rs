@@ -0,0 +1,162 @@
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::rtc::{
+    Rtc, CC, COUNTER, EVENTS_COMPARE, EVENTS_OVRFLW, EVENTS_TICK, EVTEN, EVTENCLR, EVTENSET,
+    PRESCALER, RTC0_BASE, RTC1_IRQ, TASKS_START, TASKS_STOP, TASKS_TRIGOVRFLW,
+};
+use armv6_m::nrf51::INTENSET;
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Wakes up every second with COMPARE0 of RTC1, counting the seconds in r7
+fn program() -> Program {
+    firmware!(
+        "    .space 124",
+        "    .word rtc1 + 1",
+        "main:",
+        "    ldr r0, =0x40011000",
+        "    ldr r1, =0x40011540",
+        "    ldr r2, =32768",
+        "    str r2, [r1]",
+        "    ldr r1, =0x40011304",
+        "    ldr r2, =0x10000",
+        "    str r2, [r1]",
+        "    ldr r1, =0xE000E100",
+        "    ldr r2, =0x20000",
+        "    str r2, [r1]",
+        "    movs r7, #0",
+        "    cpsie i",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "sleep:",
+        "    wfi",
+        "    b sleep",
+        "rtc1:",
+        "    ldr r0, =0x40011140",
+        "    movs r1, #0",
+        "    str r1, [r0]",
+        "    ldr r0, =0x40011008",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "    adds r7, #1",
+        "    bx lr",
+    )
+}
+
+#[test]
+fn wakes_up_the_sleeping_core_every_second() {
+    let program = program();
+    let mut machine = common::load(&program, vec![Box::new(Rtc::new(1))]);
+
+    machine.run_for(15_990_000).unwrap();
+    assert_eq!(machine.get_r7(), 0);
+    machine.run_for(3 * 16_000_000).unwrap();
+    assert_eq!(machine.get_r7(), 3);
+
+    let rtc = common::peripheral(&machine, "RTC1");
+    assert_eq!(rtc.peek(CC, 4), Some(32768));
+}
+
+#[test]
+fn counts_the_low_frequency_clock() {
+    let mut rtc = Rtc::new(1);
+    rtc.write(INTENSET, 4, 1);
+    rtc.write(TASKS_START, 4, 1);
+
+    // NOTE: A tick of the 32.768 kHz clock lasts 488.28125 cycles of the core
+    assert_eq!(rtc.next_event(), Some(489));
+    rtc.tick(488);
+    assert_eq!(rtc.read(COUNTER, 4), 0);
+    rtc.tick(1);
+    assert_eq!(rtc.read(COUNTER, 4), 1);
+    assert_eq!(rtc.read(EVENTS_TICK, 4), 1);
+    assert_eq!(rtc.interrupt(), Some(RTC1_IRQ));
+
+    rtc.tick(16_000_000 - 489);
+    assert_eq!(rtc.counter(), 32768);
+
+    rtc.write(TASKS_STOP, 4, 1);
+    rtc.write(PRESCALER, 4, 327);
+    assert!((rtc.frequency() - 99.9).abs() < 0.01);
+    rtc.write(TASKS_START, 4, 1);
+    rtc.tick(16_000_000);
+    assert_eq!(rtc.counter(), 32768 + 99);
+}
+
+#[test]
+fn only_generates_the_enabled_events() {
+    let mut rtc = Rtc::new(1);
+    rtc.write(CC + 4, 4, 10);
+    rtc.write(TASKS_START, 4, 1);
+    assert_eq!(rtc.next_event(), None);
+
+    rtc.tick(20 * 489);
+    assert_eq!(rtc.read(EVENTS_COMPARE + 4, 4), 0);
+    assert_eq!(rtc.read(EVENTS_TICK, 4), 0);
+
+    // NOTE: EVTEN generates the event without the interrupt
+    rtc.write(EVTENSET, 4, 1 << 17);
+    assert!(rtc.next_event().unwrap() > 511 * 16_000_000);
+    rtc.write(CC + 4, 4, 30);
+    rtc.tick(10 * 489);
+    assert_eq!(rtc.read(EVENTS_COMPARE + 4, 4), 1);
+    assert_eq!(rtc.interrupt(), None);
+}
+
+#[test]
+fn overflows_after_24_bits() {
+    let mut rtc = Rtc::new(0);
+    rtc.write(EVTENSET, 4, 1 << 1);
+    rtc.write(TASKS_START, 4, 1);
+    rtc.write(TASKS_TRIGOVRFLW, 4, 1);
+    assert_eq!(rtc.counter(), 0xFF_FFF0);
+
+    rtc.tick(15 * 489);
+    assert_eq!(rtc.read(EVENTS_OVRFLW, 4), 0);
+    rtc.tick(489);
+    assert_eq!(rtc.read(EVENTS_OVRFLW, 4), 1);
+    assert_eq!(rtc.counter(), 0);
+
+    // NOTE: PRESCALER is read-only while the RTC runs, RTC0 has only 3 CC registers
+    rtc.write(PRESCALER, 4, 9);
+    assert_eq!(rtc.read(PRESCALER, 4), 0);
+    rtc.write(CC + 12, 4, 5);
+    assert_eq!(rtc.read(CC + 12, 4), 0);
+    assert_eq!(rtc.base(), RTC0_BASE);
+}
+
+#[test]
+fn resets_to_the_reset_values() {
+    let mut rtc = Rtc::new(1);
+    rtc.write(PRESCALER, 4, 1);
+    rtc.write(CC + 12, 4, 3);
+    rtc.write(TASKS_START, 4, 1);
+    rtc.tick(16_000);
+    assert_ne!(rtc.counter(), 0);
+
+    rtc.reset(ResetCause::Watchdog);
+    assert!(!rtc.is_running());
+    assert_eq!(rtc.read(COUNTER, 4), 0);
+    assert_eq!(rtc.read(PRESCALER, 4), 0);
+    assert_eq!(rtc.read(CC + 12, 4), 0);
+    assert_eq!(rtc.next_event(), None);
+}
+
+#[test]
+fn sets_and_clears_the_enabled_events() {
+    // NOTE: RTC0 only has the COMPARE events of its 3 CC registers
+    let mut rtc = Rtc::new(0);
+    rtc.write(EVTENSET, 4, u32::MAX);
+    assert_eq!(rtc.read(EVTEN, 4), 0x7 << 16 | 0b11);
+    rtc.write(EVTENCLR, 4, 1 << 17);
+    assert_eq!(rtc.read(EVTENSET, 4), 0x5 << 16 | 0b11);
+    rtc.write(EVTEN + 2, 1, 0);
+    assert_eq!(rtc.read(EVTEN, 4), 0b11);
+
+    // NOTE: The CC registers are 24 bits wide, PRESCALER 12 bits wide
+    rtc.write(CC + 4, 4, u32::MAX);
+    assert_eq!(rtc.read(CC + 4, 4), 0xFF_FFFF);
+    rtc.write(PRESCALER, 4, u32::MAX);
+    assert_eq!(rtc.read(PRESCALER, 4), 0xFFF);
+}
@@ -0,0 +1,176 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::error::Error;
+use armv6_m::nrf51::gpio::Gpio;
+use armv6_m::nrf51::rng::Rng;
+use armv6_m::snapshot::{Snapshot, SNAPSHOT_MAGIC, SNAPSHOT_VERSION};
+use armv6_m::structure::RegisterId;
+use armv6_m::timing::WaitStates;
+use armv6_m::{thumb, Armv6M};
+
+/// Sums random bytes in RAM and drives the GPIO with the sum
+fn program() -> Program {
+    thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    ldr r0, =0x4000D000",
+        "    ldr r3, =0x20000000",
+        "    ldr r4, =0x4000D100",
+        "    ldr r5, =0x4000D508",
+        "    ldr r6, =0x50000514",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "    movs r7, #8",
+        "loop:",
+        "    ldr r2, [r4]",
+        "    cmp r2, #0",
+        "    beq loop",
+        "    movs r2, #0",
+        "    str r2, [r4]",
+        "    ldr r2, [r5]",
+        "    ldr r1, [r3]",
+        "    adds r1, r1, r2",
+        "    str r1, [r3]",
+        "    str r1, [r6]",
+        "    subs r7, #1",
+        "    bne loop",
+        "done:",
+        "    bkpt #0",
+    )
+}
+
+fn machine(program: &Program) -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine.add_peripheral(Box::new(Gpio::new()));
+    machine.add_peripheral(Box::new(Rng::new(7)));
+    machine.set_wait_states(WaitStates {
+        start: 0x0000_0000,
+        end: 0x0003_FFFF,
+        cycles: 1,
+    });
+    machine
+}
+
+fn step(machine: &mut Armv6M, steps: usize) {
+    for _ in 0..steps {
+        assert_eq!(machine.step().unwrap(), None);
+    }
+}
+
+/// Everything a restore is allowed to change
+fn state(machine: &Armv6M) -> (Vec<u32>, u64, Vec<Vec<u8>>) {
+    let registers = (0..=12)
+        .map(|n| machine.read_register(RegisterId::ALL[n]))
+        .chain([
+            machine.get_sp(),
+            machine.get_lr(),
+            machine.get_pc(),
+            machine.get_xpsr(),
+        ])
+        .collect();
+    let peripherals = machine
+        .peripherals()
+        .map(|peripheral| peripheral.save_state().unwrap())
+        .collect();
+    (registers, machine.cycles(), peripherals)
+}
+
+#[test]
+fn resumes_where_the_snapshot_was_taken() {
+    let program = program();
+    let done = program.label("done").unwrap();
+    let mut original = machine(&program);
+    step(&mut original, 40);
+    let bytes = original.snapshot().unwrap();
+
+    assert_eq!(original.run().unwrap(), StopReason::Breakpoint(done));
+
+    // NOTE: The wait states come from the snapshot too
+    let mut restored = Armv6M::init();
+    restored.load_bytes(&program.bytes).unwrap();
+    restored.add_peripheral(Box::new(Gpio::new()));
+    restored.add_peripheral(Box::new(Rng::new(7)));
+    restored.restore_snapshot(&bytes).unwrap();
+    assert_eq!(restored.memory_timing(), original.memory_timing());
+    assert!(restored.get_mutations_history().is_empty());
+
+    assert_eq!(restored.run().unwrap(), StopReason::Breakpoint(done));
+    assert_eq!(state(&restored), state(&original));
+    assert_eq!(restored.get_memory(), original.get_memory());
+}
+
+#[test]
+fn round_trips_through_a_file() {
+    let program = program();
+    let mut original = machine(&program);
+    step(&mut original, 25);
+    let path = std::env::temp_dir().join(format!("armv6-m-snapshot-{}", std::process::id()));
+    original.save_snapshot(path.to_str().unwrap()).unwrap();
+
+    let mut restored = machine(&program);
+    let loaded = restored.load_snapshot(path.to_str().unwrap()).map(|_| ());
+    std::fs::remove_file(&path).unwrap();
+    loaded.unwrap();
+
+    assert_eq!(state(&restored), state(&original));
+    assert_eq!(restored.get_memory(), original.get_memory());
+}
+
+#[test]
+fn rejects_other_versions_and_corrupted_snapshots() {
+    let program = program();
+    let mut machine = machine(&program);
+    step(&mut machine, 10);
+    let bytes = machine.snapshot().unwrap();
+
+    let mut version = bytes.clone();
+    version[4..8].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    let header = [&SNAPSHOT_MAGIC[..], &[3]].concat();
+    let truncated = &bytes[..bytes.len() / 2];
+
+    let before = state(&machine);
+    for (corrupted, expected) in [
+        (&version[..], "unsupported version"),
+        (&magic[..], "not a snapshot"),
+        (&header[..], "truncated header"),
+    ] {
+        match machine.restore_snapshot(corrupted).map(|_| ()) {
+            Err(Error::Snapshot(reason)) => assert_eq!(reason, expected),
+            other => panic!("{expected}: unexpected {other:?}"),
+        }
+    }
+    assert!(matches!(
+        machine.restore_snapshot(truncated).map(|_| ()),
+        Err(Error::Bincode(_))
+    ));
+    assert_eq!(state(&machine), before);
+}
+
+#[test]
+fn leaves_the_machine_untouched_when_a_peripheral_fails() {
+    let program = program();
+    let mut machine = machine(&program);
+    step(&mut machine, 30);
+    let mut snapshot = Snapshot::decode(&machine.snapshot().unwrap()).unwrap();
+    // NOTE: The GPIO state is valid and loaded first, the RNG one is not
+    snapshot.peripherals[1].1 = vec![0xFF];
+    let bytes = snapshot.encode().unwrap();
+
+    step(&mut machine, 30);
+    let before = state(&machine);
+    let memory = machine.get_memory().clone();
+    assert!(machine.restore_snapshot(&bytes).is_err());
+    assert_eq!(state(&machine), before);
+    assert_eq!(machine.get_memory(), &memory);
+
+    snapshot.peripherals.push(("UART0".to_owned(), Vec::new()));
+    assert!(matches!(
+        machine.restore_snapshot(&snapshot.encode().unwrap()).map(|_| ()),
+        Err(Error::SnapshotPeripheral(name)) if name == "UART0"
+    ));
+    assert_eq!(state(&machine), before);
+}
@@ -0,0 +1,114 @@
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::temp::{
+    Die, Temp, CONVERSION, DEFAULT_TEMPERATURE, EVENTS_DATARDY, TASKS_START, TASKS_STOP, TEMP,
+    TEMP_IRQ,
+};
+use armv6_m::nrf51::INTENSET;
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Measures the temperature in r6, as `temperature()` of the micro:bit
+fn program() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r0, =0x4000C000",
+        "    ldr r4, =0x4000C100",
+        "    movs r1, #1",
+        "    str r1, [r0]",
+        "wait:",
+        "    ldr r2, [r4]",
+        "    cmp r2, #0",
+        "    beq wait",
+        "    movs r2, #0",
+        "    str r2, [r4]",
+        "    ldr r5, =0x4000C508",
+        "    ldr r6, [r5]",
+        "    str r1, [r0, #4]",
+        "done:",
+        "    bkpt #0",
+    )
+}
+
+fn measure(set: impl FnOnce(&Die)) -> i32 {
+    let program = program();
+    let temp = Temp::new();
+    set(&temp.die());
+    let mut machine = common::load(&program, vec![Box::new(temp)]);
+    common::run_to(&mut machine, &program, "done");
+    assert!(machine.cycles() > CONVERSION);
+    machine.get_r6() as i32
+}
+
+#[test]
+fn measures_the_temperature_set_by_the_host() {
+    assert_eq!(measure(|_| {}), (DEFAULT_TEMPERATURE * 4.0) as i32);
+    assert_eq!(measure(|die| die.set(5.0)), 20);
+    assert_eq!(measure(|die| die.set(40.0)), 160);
+    assert_eq!(measure(|die| die.set(-10.3)), -41);
+}
+
+#[test]
+fn follows_the_scripted_temperature() {
+    let mut temp = Temp::new();
+    let die = temp.die();
+    die.script([(1_600_000, 30.0), (0, 20.0), (3_200_000, 10.0)]);
+    temp.write(INTENSET, 4, 1);
+
+    temp.tick(800_000 - CONVERSION);
+    temp.write(TASKS_START, 4, 1);
+    assert_eq!(temp.next_event(), Some(CONVERSION));
+    temp.tick(CONVERSION);
+    assert_eq!(temp.read(EVENTS_DATARDY, 4), 1);
+    assert_eq!(temp.interrupt(), Some(TEMP_IRQ));
+    assert_eq!(temp.read(TEMP, 4), 100);
+
+    temp.write(EVENTS_DATARDY, 4, 0);
+    temp.tick(1_600_000);
+    assert!((die.celsius() - 20.0).abs() < f64::EPSILON);
+    temp.tick(10_000_000);
+    assert!((die.celsius() - 10.0).abs() < f64::EPSILON);
+
+    // NOTE: A stopped measurement gives no value
+    temp.write(TASKS_START, 4, 1);
+    temp.write(TASKS_STOP, 4, 1);
+    temp.tick(CONVERSION);
+    assert_eq!(temp.read(EVENTS_DATARDY, 4), 0);
+    assert_eq!(temp.read(TEMP, 4), 100);
+}
+
+#[test]
+fn resets_the_result_and_keeps_the_die_temperature() {
+    let mut temp = Temp::new();
+    let die = temp.die();
+    die.set(30.0);
+    temp.write(TASKS_START, 4, 1);
+    temp.tick(CONVERSION);
+    assert_eq!(temp.read(TEMP, 4), 120);
+
+    temp.reset(ResetCause::SoftReset);
+    assert_eq!(temp.read(TEMP, 4), 0);
+    assert_eq!(temp.read(EVENTS_DATARDY, 4), 0);
+    assert!((die.celsius() - 30.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn rounds_the_temperature_to_quarter_degrees() {
+    let mut temp = Temp::new();
+    let die = temp.die();
+    let mut measure = |celsius| {
+        die.set(celsius);
+        temp.write(TASKS_START, 4, 1);
+        temp.tick(CONVERSION);
+        temp.read(TEMP, 4)
+    };
+    assert_eq!(measure(25.1), 100);
+    assert_eq!(measure(25.13), 101);
+    // NOTE: The halves round away from zero, TEMP is sign extended to the whole word
+    assert_eq!(measure(-0.125), -1i32 as u32);
+    assert_eq!(measure(-10.3), 0xFFFF_FFD7);
+
+    temp.write(TEMP, 4, 0);
+    assert_eq!(temp.read(TEMP, 4), 0xFFFF_FFD7);
+}
@@ -0,0 +1,201 @@
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::timer::{
+    Mode, Timer, BITMODE, CC, EVENTS_COMPARE, MODE, PRESCALER, TASKS_CAPTURE, TASKS_CLEAR,
+    TASKS_COUNT, TASKS_START, TIMER0_IRQ, TIMER1_BASE,
+};
+use armv6_m::nrf51::{INTEN, INTENSET, SHORTS};
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Ticks every millisecond with TIMER0 at 1 MHz and COMPARE0 clearing the counter, counting the
+/// ticks in r7
+fn program() -> Program {
+    firmware!(
+        "    .space 88",
+        "    .word timer0 + 1",
+        "main:",
+        "    ldr r0, =0x40008500",
+        "    movs r1, #3",
+        "    str r1, [r0, #8]",
+        "    ldr r1, =1000",
+        "    str r1, [r0, #64]",
+        "    ldr r0, =0x40008000",
+        "    movs r1, #1",
+        "    ldr r2, =0x40008200",
+        "    str r1, [r2]",
+        "    ldr r2, =0x40008304",
+        "    ldr r3, =0x10000",
+        "    str r3, [r2]",
+        "    ldr r2, =0xE000E100",
+        "    ldr r3, =0x100",
+        "    str r3, [r2]",
+        "    movs r7, #0",
+        "    cpsie i",
+        "    str r1, [r0]",
+        "sleep:",
+        "    wfi",
+        "    b sleep",
+        "timer0:",
+        "    ldr r0, =0x40008140",
+        "    movs r1, #0",
+        "    str r1, [r0]",
+        "    adds r7, #1",
+        "    bx lr",
+    )
+}
+
+#[test]
+fn interrupts_every_millisecond() {
+    let program = program();
+    let mut machine = common::load(&program, vec![Box::new(Timer::new(0))]);
+
+    machine.run_for(15_000).unwrap();
+    assert_eq!(machine.get_r7(), 0);
+    machine.run_for(35_000).unwrap();
+    assert_eq!(machine.get_r7(), 3);
+
+    let timer = common::peripheral(&machine, "TIMER0");
+    assert_eq!(timer.peek(CC, 4), Some(1000));
+    assert_eq!(timer.peek(BITMODE, 4), Some(3));
+}
+
+#[test]
+fn raises_compare_events_at_the_prescaled_rate() {
+    let mut timer = Timer::new(0);
+    timer.write(PRESCALER, 4, 0);
+    timer.write(CC + 4, 4, 100);
+    timer.write(INTENSET, 4, 1 << 17);
+    timer.write(TASKS_START, 4, 1);
+
+    assert_eq!(timer.next_event(), Some(100));
+    timer.tick(99);
+    assert_eq!(timer.read(EVENTS_COMPARE + 4, 4), 0);
+    timer.tick(1);
+    assert_eq!(timer.read(EVENTS_COMPARE + 4, 4), 1);
+    assert_eq!(timer.interrupt(), Some(TIMER0_IRQ));
+
+    // NOTE: Without CLEAR the counter wraps at 16 bits to reach the other CC registers at 0
+    assert_eq!(timer.next_event(), Some(0x1_0000 - 100));
+    timer.write(TASKS_CAPTURE + 8, 4, 1);
+    assert_eq!(timer.read(CC + 8, 4), 100);
+
+    timer.write(TASKS_CLEAR, 4, 1);
+    timer.write(PRESCALER, 4, 9);
+    assert_eq!(timer.tick_cycles(), 512);
+    timer.tick(512 * 10 + 5);
+    assert_eq!(timer.counter(), 10);
+    assert_eq!(timer.next_event(), Some(512 * 90 - 5));
+}
+
+#[test]
+fn stops_and_clears_with_the_shortcuts() {
+    let mut timer = Timer::new(0);
+    timer.write(PRESCALER, 4, 0);
+    timer.write(CC, 4, 10);
+    timer.write(CC + 12, 4, 25);
+    timer.write(SHORTS, 4, 1 | 1 << 11);
+    timer.write(TASKS_START, 4, 1);
+
+    timer.tick(35);
+    assert_eq!(timer.read(EVENTS_COMPARE, 4), 1);
+    assert_eq!(timer.counter(), 5);
+    assert_eq!(timer.read(EVENTS_COMPARE + 12, 4), 0);
+
+    // NOTE: COMPARE0 clears the counter before it reaches CC[3]
+    timer.write(CC, 4, 30);
+    timer.tick(20);
+    assert_eq!(timer.read(EVENTS_COMPARE + 12, 4), 1);
+    assert!(!timer.is_running());
+    assert_eq!(timer.counter(), 25);
+    assert_eq!(timer.next_event(), None);
+}
+
+#[test]
+fn counts_the_count_tasks_in_counter_mode() {
+    let mut timer = Timer::new(0);
+    timer.write(MODE, 4, 1);
+    timer.write(BITMODE, 4, 1);
+    timer.write(CC, 4, 2);
+    timer.write(TASKS_START, 4, 1);
+    assert_eq!(timer.mode(), Mode::Counter);
+    assert_eq!(timer.next_event(), None);
+
+    timer.tick(1000);
+    assert_eq!(timer.counter(), 0);
+    for _ in 0..258 {
+        timer.write(TASKS_COUNT, 4, 1);
+    }
+    assert_eq!(timer.counter(), 2);
+    assert_eq!(timer.read(EVENTS_COMPARE, 4), 1);
+}
+
+#[test]
+fn timers_1_and_2_count_on_16_bits() {
+    let mut timer = Timer::new(1);
+    assert_eq!(timer.name(), "TIMER1");
+    assert_eq!(timer.base(), TIMER1_BASE);
+
+    timer.write(BITMODE, 4, 3);
+    assert_eq!(timer.bits(), 16);
+    timer.write(PRESCALER, 4, 0);
+    timer.write(CC, 4, 0x1_0005);
+    timer.write(TASKS_START, 4, 1);
+    for channel in 1..4 {
+        timer.write(CC + 4 * channel, 4, 0x1_0000);
+    }
+    // NOTE: CC registers wider than the counter are never reached
+    assert_eq!(timer.next_event(), None);
+    timer.tick(0x2_0005);
+    assert_eq!(timer.counter(), 5);
+    assert_eq!(timer.read(EVENTS_COMPARE, 4), 0);
+
+    timer.write(CC + 4, 4, 7);
+    assert_eq!(timer.next_event(), Some(2));
+    timer.tick(2);
+    assert_eq!(timer.read(EVENTS_COMPARE + 4, 4), 1);
+}
+
+#[test]
+fn resets_to_the_reset_values() {
+    let mut timer = Timer::new(0);
+    timer.write(PRESCALER, 4, 0);
+    timer.write(CC, 4, 100);
+    timer.write(INTENSET, 4, 1 << 16);
+    timer.write(TASKS_START, 4, 1);
+    timer.tick(100);
+    assert_eq!(timer.interrupt(), Some(TIMER0_IRQ));
+
+    timer.reset(ResetCause::SoftReset);
+    assert!(!timer.is_running());
+    assert_eq!(timer.counter(), 0);
+    assert_eq!(timer.read(CC, 4), 0);
+    assert_eq!(timer.read(PRESCALER, 4), 4);
+    assert_eq!(timer.read(EVENTS_COMPARE, 4), 0);
+    assert_eq!(timer.interrupt(), None);
+}
+
+#[test]
+fn wraps_the_counter_to_the_bit_mode() {
+    let mut timer = Timer::new(0);
+    timer.write(BITMODE, 4, 3);
+    timer.write(PRESCALER, 4, 0);
+    timer.write(TASKS_START, 4, 1);
+    timer.tick(0x1_0102);
+    assert_eq!(timer.counter(), 0x1_0102);
+
+    timer.write(BITMODE, 4, 1);
+    assert_eq!(timer.bits(), 8);
+    assert_eq!(timer.counter(), 0x02);
+    timer.write(BITMODE, 4, 2);
+    timer.tick(0xFF_FFFE);
+    assert_eq!(timer.counter(), 0);
+
+    // NOTE: PRESCALER saturates at 9, the TIMER has no INTEN register
+    timer.write(PRESCALER, 4, 15);
+    assert_eq!(timer.read(PRESCALER, 4), 9);
+    timer.write(INTEN, 4, 1 << 16);
+    assert_eq!(timer.read(INTEN, 4), 0);
+    assert_eq!(timer.read(INTENSET, 4), 0);
+}
@@ -0,0 +1,157 @@
+use armv6_m::abi::{Runtime, RuntimeExtras, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::timing::WaitStates;
+use armv6_m::{thumb, Armv6M};
+
+fn load(program: &Program) -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine
+}
+
+/// Cycles of each step until the breakpoint
+fn step_cycles(machine: &mut Armv6M) -> Vec<u64> {
+    let mut cycles = Vec::new();
+    loop {
+        let before = machine.cycles();
+        let stop = machine.step().unwrap();
+        cycles.push(machine.cycles() - before);
+        if stop.is_some() {
+            return cycles;
+        }
+    }
+}
+
+#[test]
+fn charges_the_cortex_m0_cycles() {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    movs r0, #1",
+        "    ldr r1, =0x20000000",
+        "    str r0, [r1]",
+        "    muls r0, r0, r0",
+        "    cmp r0, #2",
+        "    beq main",
+        "    bne next",
+        "next:",
+        "    stm r1!, {r0, r2, r3}",
+        "    push {r4, lr}",
+        "    bl function",
+        "    pop {r4}",
+        "    mrs r0, primask",
+        "    bkpt #0",
+        "function:",
+        "    push {r4, lr}",
+        "    pop {r4, pc}",
+    );
+
+    let mut machine = load(&program);
+    assert_eq!(
+        step_cycles(&mut machine),
+        [1, 2, 2, 1, 1, 1, 3, 4, 3, 4, 3, 5, 2, 4, 0]
+    );
+    assert_eq!(machine.cycles(), 36);
+}
+
+#[test]
+fn charges_the_exception_entry_and_wait_states() {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "    .space 36",
+        "    .word handler + 1",
+        "main:",
+        "    svc #0",
+        "    ldr r0, =0x20000000",
+        "    ldr r0, [r0]",
+        "    bkpt #0",
+        "handler:",
+        "    bx lr",
+    );
+
+    let mut machine = load(&program);
+    assert_eq!(step_cycles(&mut machine), [16, 3, 2, 2, 0]);
+
+    let mut machine = load(&program);
+    machine
+        .set_wait_states(WaitStates {
+            start: 0,
+            end: 0x3FFFF,
+            cycles: 1,
+        })
+        .set_wait_states(WaitStates {
+            start: 0x2000_0000,
+            end: 0x2000_3FFF,
+            cycles: 2,
+        });
+    // NOTE: Every fetch costs a flash wait state, SVC stacks 8 words in RAM
+    // and the handler returns by reading them back
+    assert_eq!(step_cycles(&mut machine), [33, 20, 4, 5, 1]);
+}
+
+#[test]
+fn sys_tick_wakes_the_core_up() {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "    .space 52",
+        "    .word sys_tick + 1",
+        "main:",
+        "    ldr r0, =0xE000E010",
+        "    ldr r1, =999",
+        "    str r1, [r0, #4]",
+        "    movs r1, #3",
+        "    str r1, [r0]",
+        "    movs r4, #0",
+        "sleep:",
+        "    wfi",
+        "    cmp r4, #3",
+        "    bne sleep",
+        "    bkpt #0",
+        "sys_tick:",
+        "    adds r4, #1",
+        "    bx lr",
+    );
+
+    let mut machine = load(&program);
+    assert_eq!(
+        machine.run().unwrap(),
+        StopReason::Breakpoint(program.label("sleep").unwrap() + 6)
+    );
+    assert_eq!(machine.get_r4(), 3);
+    // NOTE: The third tick happens 3000 cycles after SysTick was enabled
+    assert!((3000..3100).contains(&machine.cycles()));
+}
+
+#[test]
+fn sleeps_forever_without_sys_tick() {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    wfi"
+    );
+
+    let mut machine = load(&program);
+    assert_eq!(machine.run().unwrap(), StopReason::Sleeping);
+}
+
+#[test]
+fn runs_for_a_number_of_cycles_and_rolls_them_back() {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    b main",
+    );
+
+    let mut machine = load(&program);
+    assert_eq!(machine.run_for(160_000).unwrap(), None);
+    assert_eq!(machine.cycles(), 160_002);
+    assert_eq!(machine.elapsed().as_millis(), 10);
+
+    machine.rollback_last_mutation().unwrap();
+    assert_eq!(machine.cycles(), 159_999);
+}
@@ -0,0 +1,149 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::memory::{Access, AccessKind};
+use armv6_m::mutation::RegisterChange;
+use armv6_m::structure::{Exception, RegisterId};
+use armv6_m::trace::{read_binary, BinarySink, JsonLinesSink, TextSink, TraceEntry, Tracer};
+use armv6_m::{thumb, Armv6M};
+
+fn temporary(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("armv6-m-{}-{name}", std::process::id()))
+}
+
+fn run(tracer: Tracer) -> (Armv6M, StopReason) {
+    let program = thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "    .word 0",
+        "    .word hard_fault + 1",
+        "main:",
+        "    movs r0, #2",
+        "loop:",
+        "    subs r0, #1",
+        "    bne loop",
+        "    ldr r1, =0x20000002",
+        "    str r0, [r1]",
+        "hard_fault:",
+        "    bkpt #0",
+    );
+
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine.set_tracer(tracer);
+    let stop = machine.run().unwrap();
+    (machine, stop)
+}
+
+#[test]
+fn records_every_step() {
+    let path = temporary("trace.bin");
+    let sink = BinarySink::new(BufWriter::new(File::create(&path).unwrap()));
+    let (mut machine, stop) = run(Tracer::new(sink));
+    machine.take_tracer().unwrap().flush().unwrap();
+    assert_eq!(stop, StopReason::Breakpoint(0x1A));
+
+    let entries = read_binary(&std::fs::read(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(entries.len(), 8);
+    assert_eq!(
+        entries[0],
+        TraceEntry {
+            address: 0x10,
+            halfwords: vec![0x2002],
+            text: "movs\tr0, #2".to_owned(),
+            exception: None,
+            registers: vec![
+                RegisterChange {
+                    register: RegisterId::R0,
+                    old: 0,
+                    new: 2
+                },
+                RegisterChange {
+                    register: RegisterId::Pc,
+                    old: 0x10,
+                    new: 0x12
+                },
+            ],
+            accesses: Vec::new(),
+            cycles: 1,
+            cycle: 1,
+        }
+    );
+    assert_eq!(entries[5].text, "ldr\tr1, [pc, #4]\t@ (1c)");
+    assert_eq!(
+        entries[5].accesses,
+        [Access {
+            kind: AccessKind::Read,
+            address: 0x1C,
+            size: 4,
+            value: 0x2000_0002
+        }]
+    );
+    assert_eq!(entries[6].exception, Some(Exception::HardFault));
+    assert_eq!(entries[6].cycles, 16);
+    assert_eq!(entries[7].address, 0x1A);
+}
+
+#[test]
+fn keeps_the_last_steps_before_a_fault() {
+    let path = temporary("ring.bin");
+    let sink = BinarySink::new(BufWriter::new(File::create(&path).unwrap()));
+    let (_, _) = run(Tracer::new(sink).with_ring_buffer(2));
+
+    let entries = read_binary(&std::fs::read(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let addresses = entries
+        .iter()
+        .map(|entry| (entry.address, entry.exception))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        addresses,
+        [(0x16, None), (0x18, Some(Exception::HardFault))]
+    );
+}
+
+#[test]
+fn filters_the_addresses() {
+    let (machine, _) = run(Tracer::new(Vec::new())
+        .with_ring_buffer(100)
+        .with_filter(0x12, 0x15));
+
+    let addresses = machine
+        .tracer()
+        .unwrap()
+        .entries()
+        .map(|entry| entry.address)
+        .collect::<Vec<_>>();
+    assert_eq!(addresses, [0x12, 0x14, 0x12, 0x14]);
+}
+
+#[test]
+fn writes_text_and_json_lines() {
+    let text = temporary("trace.txt");
+    let json = temporary("trace.jsonl");
+
+    let sink = TextSink::new(BufWriter::new(File::create(&text).unwrap()));
+    let (mut machine, _) = run(Tracer::new(sink));
+    machine.take_tracer().unwrap().flush().unwrap();
+    let sink = JsonLinesSink::new(BufWriter::new(File::create(&json).unwrap()));
+    let (mut machine, _) = run(Tracer::new(sink));
+    machine.take_tracer().unwrap().flush().unwrap();
+
+    let text = std::fs::read_to_string(&text).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 8);
+    assert_eq!(
+        lines[0],
+        "         1 00000010: 2002       movs r0, #2                  | R0 0x00000000 -> 0x00000002 | Pc 0x00000010 -> 0x00000012"
+    );
+    assert!(lines[6].contains("str r0, [r1, #0] <HardFault exception>"));
+
+    let json = std::fs::read_to_string(&json).unwrap();
+    assert_eq!(
+        json.lines().nth(5).unwrap(),
+        r#"{"address":22,"halfwords":[18689],"text":"ldr\tr1, [pc, #4]\t@ (1c)","exception":null,"registers":[{"register":"R1","old":0,"new":536870914},{"register":"Pc","old":22,"new":24}],"accesses":[{"kind":"read","address":28,"size":4,"value":536870914}],"cycles":2,"cycle":9}"#
+    );
+}
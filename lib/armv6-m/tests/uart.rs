@@ -0,0 +1,197 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use armv6_m::assembler::Program;
+use armv6_m::nrf51::uart::{
+    Serial, Uart, BAUDRATE, BAUD_115200, CONFIG, ENABLE, ENABLE_ENABLED, ERRORSRC,
+    ERRORSRC_OVERRUN, EVENTS_ERROR, EVENTS_RXDRDY, EVENTS_TXDRDY, RXD, RX_FIFO, TASKS_STARTRX,
+    TASKS_STARTTX, TXD, UART_IRQ,
+};
+use armv6_m::nrf51::{INTEN, INTENSET};
+use armv6_m::peripheral::{Peripheral, ResetCause};
+
+#[macro_use]
+mod common;
+
+/// Prints "hi" at 115200 bauds, then echoes the received bytes up to a newline
+fn program() -> Program {
+    firmware!(
+        "main:",
+        "    ldr r4, =0x40002000",
+        "    ldr r5, =0x40002100",
+        "    ldr r6, =0x40002500",
+        "    ldr r1, =0x01D7E000",
+        "    str r1, [r6, #36]",
+        "    movs r1, #4",
+        "    str r1, [r6, #0]",
+        "    movs r1, #1",
+        "    str r1, [r4, #8]",
+        "    str r1, [r4, #0]",
+        "    movs r1, #104",
+        "    bl putc",
+        "    movs r1, #105",
+        "    bl putc",
+        "printed:",
+        "    bkpt #0",
+        "echo:",
+        "    ldr r1, [r5, #8]",
+        "    cmp r1, #0",
+        "    beq echo",
+        "    movs r1, #0",
+        "    str r1, [r5, #8]",
+        "    ldr r1, [r6, #24]",
+        "    bl putc",
+        "    cmp r1, #10",
+        "    bne echo",
+        "done:",
+        "    bkpt #1",
+        "putc:",
+        "    str r1, [r6, #28]",
+        "wait:",
+        "    ldr r2, [r5, #28]",
+        "    cmp r2, #0",
+        "    beq wait",
+        "    movs r2, #0",
+        "    str r2, [r5, #28]",
+        "    bx lr",
+    )
+}
+
+fn enabled() -> (Uart, Serial) {
+    let mut uart = Uart::new();
+    let serial = uart.serial();
+    uart.write(BAUDRATE, 4, BAUD_115200);
+    uart.write(ENABLE, 4, ENABLE_ENABLED);
+    uart.write(TASKS_STARTRX, 4, 1);
+    uart.write(TASKS_STARTTX, 4, 1);
+    (uart, serial)
+}
+
+/// Cycles of a frame of 10 bits at 115200 bauds
+const FRAME: u64 = (10 << 32) / BAUD_115200 as u64;
+
+#[test]
+fn prints_and_echoes_text() {
+    let program = program();
+    let uart = Uart::new();
+    let serial = uart.serial();
+    let mut machine = common::load(&program, vec![Box::new(uart)]);
+
+    common::run_to(&mut machine, &program, "printed");
+    assert_eq!(serial.take_output(), b"hi");
+    assert!(machine.cycles() >= 2 * FRAME);
+
+    serial.feed(b"echo\n");
+    machine.set_pc(machine.get_pc() + 2);
+    common::run_to(&mut machine, &program, "done");
+    assert_eq!(String::from_utf8(serial.take_output()).unwrap(), "echo\n");
+}
+
+#[test]
+fn raises_txdrdy_once_the_frame_is_sent() {
+    let (mut uart, serial) = enabled();
+    uart.write(INTENSET, 4, 1 << 7);
+    // NOTE: The UART has no INTEN register, only INTENSET and INTENCLR
+    uart.write(INTEN, 4, 0);
+    assert_eq!(uart.read(INTEN, 4), 0);
+    assert_eq!(uart.read(INTENSET, 4), 1 << 7);
+
+    uart.write(TXD, 4, u32::from(b'A'));
+    assert_eq!(uart.next_event(), Some(FRAME));
+    uart.tick(FRAME - 1);
+    assert_eq!(uart.read(EVENTS_TXDRDY, 4), 0);
+    assert!(serial.take_output().is_empty());
+
+    uart.tick(1);
+    assert_eq!(uart.read(EVENTS_TXDRDY, 4), 1);
+    assert_eq!(uart.interrupt(), Some(UART_IRQ));
+    assert_eq!(serial.take_output(), b"A");
+
+    // NOTE: The parity bit lengthens the frame
+    uart.write(CONFIG, 4, 0xE);
+    uart.write(TXD, 4, u32::from(b'B'));
+    assert_eq!(uart.next_event(), Some((11 << 32) / u64::from(BAUD_115200)));
+}
+
+#[test]
+fn loses_the_bytes_past_the_fifo_without_flow_control() {
+    let (mut uart, serial) = enabled();
+    serial.feed(b"overflow");
+    uart.tick(8 * FRAME);
+
+    assert_eq!(uart.read(EVENTS_ERROR, 4), 1);
+    assert_eq!(uart.read(ERRORSRC, 4), ERRORSRC_OVERRUN);
+    uart.write(ERRORSRC, 4, ERRORSRC_OVERRUN);
+    assert_eq!(uart.read(ERRORSRC, 4), 0);
+
+    let received: Vec<u8> = (0..RX_FIFO).map(|_| uart.read(RXD, 4) as u8).collect();
+    assert_eq!(received, b"overfl");
+    assert_eq!(uart.read(EVENTS_RXDRDY, 4), 1);
+}
+
+#[test]
+fn holds_the_host_with_rts() {
+    let (mut uart, serial) = enabled();
+    uart.write(CONFIG, 4, 1);
+    serial.feed(b"overflow");
+    uart.tick(8 * FRAME);
+
+    assert!(!serial.rts());
+    assert_eq!(serial.pending_input(), 4);
+    assert_eq!(uart.read(EVENTS_ERROR, 4), 0);
+    assert_eq!(uart.next_event(), None);
+
+    assert_eq!(uart.read(RXD, 4), u32::from(b'o'));
+    assert!(serial.rts());
+    uart.tick(8 * FRAME);
+    assert_eq!(serial.pending_input(), 3);
+
+    // NOTE: The transmitter waits for CTS
+    serial.set_cts(false);
+    uart.tick(0);
+    uart.write(TXD, 4, u32::from(b'!'));
+    uart.tick(2 * FRAME);
+    assert!(serial.take_output().is_empty());
+    serial.set_cts(true);
+    assert_eq!(uart.next_event(), Some(0));
+    uart.tick(0);
+    uart.tick(FRAME);
+    assert_eq!(serial.take_output(), b"!");
+}
+
+#[test]
+fn sends_the_output_to_a_callback_or_a_channel() {
+    let (mut uart, serial) = enabled();
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let bytes = received.clone();
+    serial.on_transmit(move |byte| bytes.borrow_mut().push(byte));
+    uart.write(TXD, 4, u32::from(b'x'));
+    uart.tick(FRAME);
+    assert_eq!(*received.borrow(), b"x");
+
+    let (mut uart, serial) = enabled();
+    let channel = serial.channel();
+    uart.write(TXD, 4, u32::from(b'y'));
+    uart.tick(FRAME);
+    assert_eq!(channel.try_recv(), Ok(b'y'));
+    assert!(serial.take_output().is_empty());
+}
+
+#[test]
+fn resets_the_registers_and_keeps_the_host_bytes() {
+    let mut uart = Uart::new();
+    let serial = uart.serial();
+    uart.write(ENABLE, 4, ENABLE_ENABLED);
+    uart.write(BAUDRATE, 4, BAUD_115200);
+    uart.write(TASKS_STARTTX, 4, 1);
+    uart.write(TXD, 4, u32::from(b'x'));
+    serial.feed(b"ok");
+
+    uart.reset(ResetCause::SoftReset);
+    assert_eq!(uart.read(ENABLE, 4), 0);
+    assert_eq!(uart.read(BAUDRATE, 4), 0x0400_0000);
+    assert_eq!(serial.pending_input(), 2);
+    // NOTE: The byte on the wire is lost
+    uart.tick(100_000);
+    assert!(serial.take_output().is_empty());
+}
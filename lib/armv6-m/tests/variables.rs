@@ -0,0 +1,105 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::breakpoint::Breakpoint;
+use armv6_m::debug_info::Location;
+use armv6_m::variables::Type;
+use armv6_m::Armv6M;
+
+/// Built from `fixtures/variables.ll`, see the commands at its top
+const VARIABLES_ELF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/variables.elf");
+
+/// Stopped on the BKPT of `accumulate`
+fn stopped() -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_file(VARIABLES_ELF).unwrap();
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(0x20));
+    machine
+}
+
+fn shown(value: Option<armv6_m::variables::Value>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+#[test]
+fn reads_the_globals() {
+    let machine = stopped();
+
+    assert_eq!(shown(machine.variable("counter")), "counter = 49");
+    assert_eq!(shown(machine.variable("flags")), "flags = { 1, 2, 3, 4 }");
+    assert_eq!(
+        shown(machine.variable("origin")),
+        "origin = { x = 3, y = -4 }"
+    );
+    assert_eq!(shown(machine.variable("last")), "last = 0x20000000");
+    assert_eq!(machine.variable("missing"), None);
+
+    let origin = machine.variable("origin").unwrap();
+    assert_eq!(origin.address, Some(0x2000_0008));
+    assert_eq!(origin.ty.name(), "point");
+    assert_eq!(origin.ty.size(), 4);
+    let last = machine.variable("last").unwrap();
+    assert_eq!(
+        *last.ty,
+        Type::Pointer {
+            name: "int *".to_owned()
+        }
+    );
+    assert_eq!(
+        machine.variable("flags").unwrap().ty.name(),
+        "unsigned char[4]"
+    );
+}
+
+#[test]
+fn reads_the_locals_relative_to_the_stack() {
+    let machine = stopped();
+    let sp = machine.get_sp();
+
+    let locals = machine.locals();
+    let shown = locals.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(shown, ["step = 5", "total = 49"]);
+    assert_eq!(locals[0].address, Some(sp + 4));
+    assert_eq!(locals[1].address, Some(sp));
+}
+
+#[test]
+fn scopes_the_locals_to_their_function() {
+    let mut machine = stopped();
+    assert_eq!(machine.variable("result"), None);
+
+    machine.set_pc(0x22);
+    machine.add_breakpoint(Breakpoint::new(0x3E));
+    assert_eq!(machine.run().unwrap(), StopReason::Breakpoint(0x3E));
+
+    let shown = machine
+        .locals()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(shown, ["result = 49"]);
+    assert_eq!(machine.variable("step"), None);
+}
+
+#[test]
+fn maps_addresses_and_lines() {
+    let machine = stopped();
+    let debug_info = machine.debug_info();
+
+    assert_eq!(
+        debug_info.location(0x20),
+        Some(&Location {
+            file: "variables.c".to_owned(),
+            line: 9,
+        })
+    );
+    assert_eq!(debug_info.address_of("variables.c", 9), Some(0x20));
+    assert_eq!(debug_info.address_of("variables.c", 7), Some(0x0C));
+    assert_eq!(debug_info.address_of("variables.c", 14), Some(0x38));
+    assert_eq!(debug_info.address_of("other.c", 9), None);
+    assert_eq!(debug_info.address_of("variables.c", 11), None);
+
+    let function = debug_info.function(0x20).unwrap();
+    assert_eq!(function.name, "accumulate");
+    assert_eq!(function.ranges, vec![0x08..0x30]);
+    assert!(function.variables[0].parameter);
+    assert_eq!(debug_info.function(0x04), None);
+}
@@ -0,0 +1,151 @@
+use armv6_m::abi::{Runtime, StopReason};
+use armv6_m::assembler::Program;
+use armv6_m::error::Error;
+use armv6_m::peripheral::Peripheral;
+use armv6_m::watchpoint::{WatchKind, Watchpoint};
+use armv6_m::{thumb, Armv6M};
+
+/// Output register of a GPIO port
+struct Out {
+    value: u32,
+}
+
+impl Peripheral for Out {
+    fn name(&self) -> &str {
+        "OUT"
+    }
+
+    fn base(&self) -> u32 {
+        0x5000_0504
+    }
+
+    fn size(&self) -> u32 {
+        4
+    }
+
+    fn read(&mut self, _: u32, _: u8) -> u32 {
+        self.value
+    }
+
+    fn peek(&self, _: u32, _: u8) -> Option<u32> {
+        Some(self.value)
+    }
+
+    fn write(&mut self, _: u32, _: u8, value: u32) {
+        self.value = value;
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.value.to_le_bytes().to_vec())
+    }
+
+    fn load_state(&mut self, _: &[u8]) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn program() -> Program {
+    thumb!(
+        "    .word 0x20001000",
+        "    .word main + 1",
+        "main:",
+        "    ldr r0, =0x20000000",
+        "    ldr r1, =0x50000504",
+        "    movs r2, #0",
+        "loop:",
+        "    adds r2, #1",
+        "    str r2, [r0, #4]",
+        "    str r2, [r1]",
+        "    cmp r2, #5",
+        "    bne loop",
+        "    ldrb r3, [r0, #6]",
+        "done:",
+        "    bkpt #0",
+    )
+}
+
+fn load(program: &Program) -> Armv6M {
+    let mut machine = Armv6M::init();
+    machine.load_bytes(&program.bytes).unwrap();
+    machine.add_peripheral(Box::new(Out { value: 0 }));
+    machine
+}
+
+#[test]
+fn stops_after_writes_to_ram() {
+    let program = program();
+    let mut machine = load(&program);
+    machine.add_watchpoint(Watchpoint::new(WatchKind::Write, 0x2000_0004, 4));
+    let store = program.label("loop").unwrap() + 2;
+
+    for value in 1..=5 {
+        assert_eq!(
+            machine.run().unwrap(),
+            StopReason::Watchpoint {
+                kind: WatchKind::Write,
+                pc: store,
+                address: 0x2000_0004,
+                old: Some(value - 1),
+                new: value,
+            }
+        );
+        assert_eq!(machine.get_pc(), store + 2);
+    }
+    assert_eq!(
+        machine.run().unwrap(),
+        StopReason::Breakpoint(program.label("done").unwrap())
+    );
+}
+
+#[test]
+fn checks_the_value_condition() {
+    let program = program();
+    let mut machine = load(&program);
+    machine.add_watchpoint(Watchpoint::new(WatchKind::Access, 0x2000_0000, 8).with_value(3));
+
+    match machine.run().unwrap() {
+        StopReason::Watchpoint { old, new, .. } => assert_eq!((old, new), (Some(2), 3)),
+        other => panic!("stopped with {other:?}"),
+    }
+    assert_eq!(machine.get_r2(), 3);
+}
+
+#[test]
+fn stops_after_reads_in_a_range() {
+    let program = program();
+    let mut machine = load(&program);
+    let watchpoint = Watchpoint::new(WatchKind::Read, 0x2000_0000, 0x100);
+    machine.add_watchpoint(watchpoint);
+
+    assert_eq!(
+        machine.run().unwrap(),
+        StopReason::Watchpoint {
+            kind: WatchKind::Read,
+            pc: program.label("done").unwrap() - 2,
+            address: 0x2000_0006,
+            old: Some(0),
+            new: 0,
+        }
+    );
+
+    assert!(machine.remove_watchpoint(&watchpoint));
+    assert!(machine.watchpoints().is_empty());
+}
+
+#[test]
+fn watches_peripheral_registers() {
+    let program = program();
+    let mut machine = load(&program);
+    machine.add_watchpoint(Watchpoint::new(WatchKind::Write, 0x5000_0504, 4).with_value(4));
+
+    assert_eq!(
+        machine.run().unwrap(),
+        StopReason::Watchpoint {
+            kind: WatchKind::Write,
+            pc: program.label("loop").unwrap() + 4,
+            address: 0x5000_0504,
+            old: Some(3),
+            new: 4,
+        }
+    );
+}
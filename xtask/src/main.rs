@@ -34,7 +34,7 @@ enum Command {
 fn main() -> Result<()> {
     let cargo = env::var("CARGO")
         .ok()
-        .and_then(|var| if var.is_empty() { None } else { Some(var) })
+        .filter(|var| !var.is_empty())
         .unwrap_or_else(|| "cargo".to_string());
     let workspace_root = std::env::var("CARGO_WORKSPACE_DIR").unwrap();
     color_eyre::install().unwrap();